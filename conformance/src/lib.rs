@@ -102,6 +102,9 @@ fn check_term(term: &Term, defined: &HashSet<String>) -> bool {
             defined.contains(prefix)
         }
         Term::Collection(spanneds) => spanneds.iter().all(|t| check_term(t, defined)),
+        Term::Quoted(quoted) => [&quoted.subject, &quoted.predicate, &quoted.object]
+            .into_iter()
+            .all(|t| check_term(t, defined)),
         _ => true,
     }
 }
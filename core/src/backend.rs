@@ -4,6 +4,7 @@ use bevy_ecs::{
     bundle::Bundle,
     component::Component,
     entity::Entity,
+    query::Without,
     schedule::ScheduleLabel,
     world::{CommandQueue, World},
 };
@@ -17,13 +18,19 @@ use ropey::Rope;
 use tower_lsp::{jsonrpc::Result, LanguageServer};
 use tracing::info;
 
-use crate::{feature::goto_definition::GotoDefinitionRequest, prelude::*, Startup};
+use crate::{
+    feature::goto_definition::GotoDefinitionRequest,
+    prelude::*,
+    systems::{
+        in_folder, indexed_glob, invalidate_remote_document, refresh_document, IndexWorkspace,
+    },
+    Startup,
+};
 
 #[derive(Debug)]
 pub struct Backend {
     entities: Arc<Mutex<HashMap<String, Entity>>>,
     sender: CommandSender,
-    #[allow(unused)]
     client: tower_lsp::Client,
     semantic_tokens: Vec<SemanticTokenType>,
 }
@@ -115,11 +122,17 @@ impl LanguageServer for Backend {
         })
         .collect();
 
-        self.run(|world| {
-            world.insert_resource(server_config);
-            world.run_schedule(Startup);
-        })
-        .await;
+        let commands = self
+            .run(|world| {
+                world.insert_resource(server_config);
+                world.run_schedule(Startup);
+                world
+                    .get_resource::<ExecutableCommands>()
+                    .map(|x| x.0.iter().copied().map(String::from).collect())
+                    .unwrap_or_default()
+            })
+            .await
+            .unwrap_or_default();
 
         // let triggers = L::TRIGGERS.iter().copied().map(String::from).collect();
         Ok(InitializeResult {
@@ -127,9 +140,18 @@ impl LanguageServer for Backend {
             capabilities: ServerCapabilities {
                 inlay_hint_provider: Some(OneOf::Left(true)),
                 text_document_sync: Some(TextDocumentSyncCapability::Kind(
-                    TextDocumentSyncKind::FULL,
+                    TextDocumentSyncKind::INCREMENTAL,
+                )),
+                code_action_provider: Some(CodeActionProviderCapability::Options(
+                    CodeActionOptions {
+                        code_action_kinds: Some(vec![
+                            CodeActionKind::QUICKFIX,
+                            CodeActionKind::REFACTOR_REWRITE,
+                        ]),
+                        work_done_progress_options: Default::default(),
+                        resolve_provider: Some(false),
+                    },
                 )),
-                code_action_provider: None,
                 completion_provider: Some(CompletionOptions {
                     resolve_provider: Some(false),
                     trigger_characters: Some(vec![String::from(":")]),
@@ -143,6 +165,8 @@ impl LanguageServer for Backend {
                 hover_provider: Some(HoverProviderCapability::Simple(true)),
                 definition_provider: Some(OneOf::Left(true)),
                 document_formatting_provider: Some(OneOf::Left(true)),
+                document_symbol_provider: Some(OneOf::Left(true)),
+                workspace_symbol_provider: Some(OneOf::Left(true)),
                 semantic_tokens_provider: Some(
                     SemanticTokensServerCapabilities::SemanticTokensRegistrationOptions(
                         SemanticTokensRegistrationOptions {
@@ -164,6 +188,18 @@ impl LanguageServer for Backend {
                         },
                     ),
                 ),
+                signature_help_provider: Some(SignatureHelpOptions {
+                    trigger_characters: Some(vec!["(".to_string(), ",".to_string()]),
+                    retrigger_characters: Some(vec![";".to_string()]),
+                    work_done_progress_options: Default::default(),
+                }),
+                code_lens_provider: Some(CodeLensOptions {
+                    resolve_provider: Some(false),
+                }),
+                execute_command_provider: Some(ExecuteCommandOptions {
+                    commands,
+                    work_done_progress_options: Default::default(),
+                }),
                 rename_provider: Some(OneOf::Right(RenameOptions {
                     prepare_provider: Some(true),
                     work_done_progress_options: Default::default(),
@@ -173,19 +209,78 @@ impl LanguageServer for Backend {
         })
     }
 
+    async fn initialized(&self, _: InitializedParams) {
+        let watchers = vec![FileSystemWatcher {
+            glob_pattern: GlobPattern::String(indexed_glob()),
+            kind: None,
+        }];
+        let options = DidChangeWatchedFilesRegistrationOptions { watchers };
+        let registration = Registration {
+            id: String::from("swls/watched-files"),
+            method: String::from("workspace/didChangeWatchedFiles"),
+            register_options: serde_json::to_value(options).ok(),
+        };
+
+        if let Err(e) = self.client.register_capability(vec![registration]).await {
+            info!("Client does not support watching files {:?}", e);
+        }
+    }
+
+    async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
+        let Some(fs) = self.run(|world| world.resource::<Fs>().clone()).await else {
+            return;
+        };
+
+        let mut updates = Vec::new();
+        for change in params.changes {
+            let content = if change.typ == FileChangeType::DELETED {
+                None
+            } else {
+                fs.0.read_file(&change.uri).await
+            };
+            updates.push((change.uri, content));
+        }
+
+        self.run(move |world| {
+            let mut changed = false;
+            for (url, content) in updates {
+                changed |= invalidate_remote_document(world, &url);
+                changed |= refresh_document(world, url, content);
+            }
+
+            if changed {
+                world.run_schedule(ParseLabel);
+                world.run_schedule(SaveLabel);
+            }
+        })
+        .await;
+    }
+
     async fn did_change_workspace_folders(&self, params: DidChangeWorkspaceFoldersParams) -> () {
         self.run(move |world| {
             let mut config = world.resource_mut::<ServerConfig>();
             let WorkspaceFoldersChangeEvent { added, removed } = params.event;
 
-            for r in removed {
-                if let Some(idx) = config.workspaces.iter().position(|x| x == &r) {
+            for r in &removed {
+                if let Some(idx) = config.workspaces.iter().position(|x| x == r) {
                     config.workspaces.remove(idx);
                 }
             }
 
-            // This is nice and all, but we don't bubble this event up in the world
-            config.workspaces.extend(added);
+            config.workspaces.extend(added.iter().cloned());
+
+            // Indexed documents of removed folders are forgotten, open documents stay
+            let forgotten: Vec<_> = world
+                .query_filtered::<(Entity, &Label), Without<Open>>()
+                .iter(world)
+                .filter(|(_, label)| removed.iter().any(|r| in_folder(&label.0, &r.uri)))
+                .map(|(e, _)| e)
+                .collect();
+            for e in forgotten {
+                world.despawn(e);
+            }
+
+            world.trigger(IndexWorkspace(added));
         })
         .await;
         ()
@@ -427,11 +522,209 @@ impl LanguageServer for Backend {
         };
 
         let request = self
-            .run_schedule::<FormatRequest>(entity, FormatLabel, FormatRequest(None))
+            .run_schedule::<FormatRequest>(
+                entity,
+                FormatLabel,
+                (FormatRequest(None), FormatOptions(params.options)),
+            )
+            .await;
+        Ok(request.and_then(|x| x.0))
+    }
+
+    #[tracing::instrument(skip(self, params), fields(uri = %params.text_document.uri.as_str()))]
+    async fn document_symbol(
+        &self,
+        params: DocumentSymbolParams,
+    ) -> Result<Option<DocumentSymbolResponse>> {
+        let entity = {
+            let map = self.entities.lock().await;
+            if let Some(entity) = map.get(params.text_document.uri.as_str()) {
+                entity.clone()
+            } else {
+                return Ok(None);
+            }
+        };
+
+        let request = self
+            .run_schedule::<DocumentSymbolRequest>(
+                entity,
+                DocumentSymbolLabel,
+                DocumentSymbolRequest::default(),
+            )
+            .await;
+
+        Ok(request.map(|x| DocumentSymbolResponse::Nested(x.0)))
+    }
+
+    #[tracing::instrument(skip(self, params), fields(query = %params.query))]
+    async fn symbol(
+        &self,
+        params: WorkspaceSymbolParams,
+    ) -> Result<Option<Vec<SymbolInformation>>> {
+        let symbols = self
+            .run(move |world| {
+                let entity = world.spawn(WorkspaceSymbolRequest::new(params.query)).id();
+                world.run_schedule(WorkspaceSymbolLabel);
+                let request = world.entity_mut(entity).take::<WorkspaceSymbolRequest>();
+                world.despawn(entity);
+                request
+            })
+            .await
+            .flatten();
+
+        Ok(symbols.map(|x| x.symbols))
+    }
+
+    #[tracing::instrument(skip(self, params), fields(uri = %params.text_document.uri.as_str()))]
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        let entity = {
+            let map = self.entities.lock().await;
+            if let Some(entity) = map.get(params.text_document.uri.as_str()) {
+                entity.clone()
+            } else {
+                return Ok(None);
+            }
+        };
+
+        let request = self
+            .run_schedule::<CodeActionRequest>(
+                entity,
+                CodeActionLabel,
+                CodeActionRequest::new(params.range, params.context.diagnostics),
+            )
+            .await;
+
+        Ok(request.map(|x| x.actions))
+    }
+
+    #[tracing::instrument(skip(self, params), fields(uri = %params.text_document_position_params.text_document.uri.as_str()))]
+    async fn signature_help(&self, params: SignatureHelpParams) -> Result<Option<SignatureHelp>> {
+        let entity = {
+            let map = self.entities.lock().await;
+            if let Some(entity) = map.get(
+                params
+                    .text_document_position_params
+                    .text_document
+                    .uri
+                    .as_str(),
+            ) {
+                entity.clone()
+            } else {
+                return Ok(None);
+            }
+        };
+
+        let pos = params.text_document_position_params.position;
+        let request = self
+            .run_schedule::<SignatureHelpRequest>(
+                entity,
+                SignatureHelpLabel,
+                (SignatureHelpRequest::default(), PositionComponent(pos)),
+            )
             .await;
+
         Ok(request.and_then(|x| x.0))
     }
 
+    #[tracing::instrument(skip(self, params), fields(uri = %params.text_document.uri.as_str()))]
+    async fn code_lens(&self, params: CodeLensParams) -> Result<Option<Vec<CodeLens>>> {
+        let entity = {
+            let map = self.entities.lock().await;
+            if let Some(entity) = map.get(params.text_document.uri.as_str()) {
+                entity.clone()
+            } else {
+                return Ok(None);
+            }
+        };
+
+        let request = self
+            .run_schedule::<CodeLensRequest>(entity, CodeLensLabel, CodeLensRequest::default())
+            .await;
+
+        Ok(request.map(|x| x.0))
+    }
+
+    #[tracing::instrument(skip(self, params), fields(command = %params.command))]
+    async fn execute_command(
+        &self,
+        params: ExecuteCommandParams,
+    ) -> Result<Option<serde_json::Value>> {
+        let Some(uri) = params.arguments.first().and_then(|x| x.as_str()) else {
+            info!(
+                "Command {} expects a document as first argument",
+                params.command
+            );
+            return Ok(None);
+        };
+
+        let entity = {
+            let map = self.entities.lock().await;
+            if let Some(entity) = map.get(uri) {
+                entity.clone()
+            } else {
+                info!("Didn't find entity {}", uri);
+                return Ok(None);
+            }
+        };
+
+        let command = params.command.clone();
+        let Some(request) = self
+            .run_schedule::<ExecuteCommandRequest>(
+                entity,
+                ExecuteCommandLabel,
+                ExecuteCommandRequest::new(params.command, params.arguments),
+            )
+            .await
+        else {
+            return Ok(None);
+        };
+
+        if let Some(error) = request.error {
+            self.client.show_message(MessageType::ERROR, error).await;
+            return Ok(None);
+        }
+
+        if command == command::RUN_QUERY {
+            let result = request
+                .result
+                .clone()
+                .and_then(|x| serde_json::from_value::<command::QueryResult>(x).ok());
+            if let Some(result) = result {
+                self.client
+                    .send_notification::<command::QueryResultNotification>(result)
+                    .await;
+            }
+        }
+
+        if command == command::CONVERT || command == command::TO_TURTLE {
+            let conversion = request
+                .result
+                .clone()
+                .and_then(|x| serde_json::from_value::<command::Conversion>(x).ok());
+            if let Some(conversion) = conversion {
+                match self.client.apply_edit(conversion.edit).await {
+                    Ok(resp) if resp.applied => {
+                        if !conversion.replace {
+                            let _ = self
+                                .client
+                                .show_document(ShowDocumentParams {
+                                    uri: conversion.uri,
+                                    external: None,
+                                    take_focus: Some(true),
+                                    selection: None,
+                                })
+                                .await;
+                        }
+                    }
+                    Ok(resp) => info!("Conversion not applied {:?}", resp.failure_reason),
+                    Err(e) => info!("Conversion not applied {:?}", e),
+                }
+            }
+        }
+
+        Ok(request.result)
+    }
+
     #[tracing::instrument(skip(self, params), fields(uri = %params.text_document.uri.as_str()))]
     async fn did_open(&self, params: DidOpenTextDocumentParams) {
         let item = params.text_document;
@@ -487,19 +780,31 @@ impl LanguageServer for Backend {
             }
         };
 
-        let change = {
-            if let Some(c) = params.content_changes.into_iter().next() {
-                c
-            } else {
+        if params.content_changes.is_empty() {
+            return;
+        }
+
+        self.run(move |world| {
+            invalidate_remote_document(world, &params.text_document.uri);
+
+            let mut entity_mut = world.entity_mut(entity);
+            let Some(mut rope) = entity_mut.get_mut::<RopeC>() else {
                 return;
+            };
+
+            let change = apply_changes(&mut rope.0, &params.content_changes);
+
+            let source = Source(rope.0.to_string());
+            entity_mut.insert(source);
+            match change {
+                Some(change) => {
+                    entity_mut.insert(change);
+                }
+                None => {
+                    entity_mut.remove::<SourceChange>();
+                }
             }
-        };
 
-        self.run(move |world| {
-            let rope_c = RopeC(Rope::from_str(&change.text));
-            world
-                .entity_mut(entity)
-                .insert((Source(change.text), rope_c));
             world.run_schedule(ParseLabel);
             world.flush();
             info!("Running diagnostics");
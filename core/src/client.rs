@@ -1,6 +1,6 @@
 use std::{collections::HashMap, fmt::Display, pin::Pin};
 
-use lsp_types::{Diagnostic, MessageType, Url};
+use lsp_types::{Diagnostic, MessageType, NumberOrString, Url, WorkDoneProgress};
 
 #[derive(Debug)]
 pub struct Resp {
@@ -18,6 +18,9 @@ pub trait Client: Clone + ClientSync {
         diags: Vec<Diagnostic>,
         version: Option<i32>,
     ) -> ();
+    /// Asks the client to create a work done progress with this token.
+    async fn create_progress(&self, token: NumberOrString) -> ();
+    async fn progress(&self, token: NumberOrString, value: WorkDoneProgress) -> ();
 }
 
 pub trait ClientSync {
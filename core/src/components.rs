@@ -35,6 +35,47 @@ pub struct Errors<E>(pub Vec<E>);
 #[derive(Component, AsRef, Deref, AsMut, DerefMut, Debug)]
 pub struct Source(pub String);
 
+/// [`Component`] describing the region of [`Source`] that changed during the last incremental
+/// update.
+///
+/// `old` is the replaced byte range in the previous source, `new` the byte range of the
+/// inserted text in the current source. Tokenizers can use this to only re-tokenize the affected
+/// region. It is removed after the source is tokenized.
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct SourceChange {
+    pub old: std::ops::Range<usize>,
+    pub new: std::ops::Range<usize>,
+}
+
+impl SourceChange {
+    pub fn new(old: std::ops::Range<usize>, inserted: usize) -> Self {
+        let new = old.start..old.start + inserted;
+        Self { old, new }
+    }
+
+    /// Merges a change that happened after `self` into one change covering both.
+    pub fn merge(self, other: Self) -> Self {
+        // other is expressed in the coordinates of the source after self
+        let start = self.new.start.min(other.old.start);
+        let end_before = self.new.end.max(other.old.end);
+        let end_after = end_before + other.new.len() - other.old.len();
+
+        // map the region back to the coordinates of the original source,
+        // offsets before self.new.start are not moved by self
+        let old_end = end_before + self.old.len() - self.new.len();
+
+        Self {
+            old: start..old_end,
+            new: start..end_after,
+        }
+    }
+
+    /// Maps a byte offset after the changed region in the previous source to the current source.
+    pub fn shift(&self, offset: usize) -> usize {
+        offset + self.new.end - self.old.end
+    }
+}
+
 /// [`Component`] containing the current source code as [`ropey::Rope`]
 #[derive(Component, AsRef, Deref, AsMut, DerefMut, Debug)]
 pub struct RopeC(pub ropey::Rope);
@@ -77,6 +118,23 @@ pub struct CommandSender(pub UnboundedSender<CommandQueue>);
 #[derive(Component, AsRef, Deref, AsMut, DerefMut, Debug, Clone)]
 pub struct DocumentLinks(pub Vec<(lsp_types::Url, &'static str)>);
 
+/// [`Component`] with the remote documents a document depends on, like JSON-LD contexts.
+///
+/// These documents are fetched into [`RemoteDocumentContents`] by
+/// [`fetch_remote_documents`](crate::systems::fetch_remote_documents), the component is marked as
+/// changed when one of them arrives.
+#[derive(Component, AsRef, Deref, AsMut, DerefMut, Debug, Clone, PartialEq, Default)]
+pub struct RemoteDocuments(pub Vec<lsp_types::Url>);
+
+/// [`Resource`] with the content of fetched [`RemoteDocuments`].
+#[derive(Resource, AsRef, Deref, AsMut, DerefMut, Debug, Default)]
+pub struct RemoteDocumentContents(pub HashMap<lsp_types::Url, String>);
+
+/// [`Resource`] with the [`RemoteDocuments`] that are fetched or being fetched, failed fetches
+/// are removed so that they are tried again.
+#[derive(Resource, AsRef, Deref, AsMut, DerefMut, Debug, Default)]
+pub struct RequestedDocuments(pub HashSet<lsp_types::Url>);
+
 /// [`Component`] used to wrap an incoming [`lsp_types::Position`].
 ///
 /// This component is translated into [`TokenComponent`] and [`TripleComponent`]
@@ -200,6 +258,12 @@ pub struct Config {
     pub turtle: Option<bool>,
     pub jsonld: Option<bool>,
     pub sparql: Option<bool>,
+    pub trig: Option<bool>,
+    pub ntriples: Option<bool>,
+    pub yamlld: Option<bool>,
+    /// Path segments that are skipped when indexing workspace folders, defaults to
+    /// [`DEFAULT_IGNORE`].
+    pub ignore: Option<Vec<String>>,
 }
 impl Default for Config {
     fn default() -> Self {
@@ -208,10 +272,24 @@ impl Default for Config {
             turtle: None,
             jsonld: None,
             sparql: None,
+            trig: None,
+            ntriples: None,
+            yamlld: None,
+            ignore: None,
         }
     }
 }
 
+pub const DEFAULT_IGNORE: &[&str] = &[".git", "node_modules", "target"];
+
+impl Config {
+    pub fn ignore(&self) -> Vec<String> {
+        self.ignore
+            .clone()
+            .unwrap_or_else(|| DEFAULT_IGNORE.iter().map(|x| x.to_string()).collect())
+    }
+}
+
 fn debug() -> String {
     String::from("debug")
 }
@@ -0,0 +1,15 @@
+use bevy_ecs::{component::Component, schedule::ScheduleLabel, world::World};
+use derive_more::{AsMut, AsRef, Deref, DerefMut};
+
+/// [`Component`] indicating that the current document is currently handling a CodeLens request.
+#[derive(Component, AsRef, Deref, AsMut, DerefMut, Debug, Default)]
+pub struct CodeLensRequest(pub Vec<lsp_types::CodeLens>);
+
+/// [`ScheduleLabel`] related to the CodeLens schedule, this is language specific
+#[derive(ScheduleLabel, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Label;
+
+pub fn setup_schedule(world: &mut World) {
+    let code_lens = bevy_ecs::schedule::Schedule::new(Label);
+    world.add_schedule(code_lens);
+}
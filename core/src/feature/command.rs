@@ -0,0 +1,139 @@
+use bevy_ecs::{component::Component, schedule::ScheduleLabel, system::Resource, world::World};
+use serde::{Deserialize, Serialize};
+
+pub use crate::systems::convert_document;
+
+/// Runs the SPARQL query of the document passed as first argument, the result is a
+/// [`QueryResult`].
+pub const RUN_QUERY: &str = "swls.runQuery";
+
+/// Converts the document passed as first argument to the format passed as second argument
+/// (`turtle`, `jsonld` or `ntriples`), the result is a [`Conversion`].
+///
+/// The third argument is `new` to open the result in a new untitled document, or `replace` to
+/// replace the document.
+pub const CONVERT: &str = "swls.convert";
+
+/// Writes the N-Triples document passed as first argument as Turtle with prefixes to a sibling
+/// `.ttl` file, the result is a [`Conversion`].
+pub const TO_TURTLE: &str = "swls.toTurtle";
+
+/// [`Resource`] listing the commands that can be executed, they are advertised to the client on
+/// initialize.
+#[derive(Resource, Debug, Default)]
+pub struct ExecutableCommands(pub Vec<&'static str>);
+
+impl ExecutableCommands {
+    /// Registers `command`, languages call this while setting up the world.
+    pub fn register(world: &mut World, command: &'static str) {
+        let mut commands = world.get_resource_or_insert_with(ExecutableCommands::default);
+        if !commands.0.contains(&command) {
+            commands.0.push(command);
+        }
+    }
+}
+
+/// [`Component`] indicating that the current document is currently handling an ExecuteCommand
+/// request.
+///
+/// The first argument is always the url of the document, systems handling `command` set either
+/// `result` or `error`.
+#[derive(Component, Debug)]
+pub struct ExecuteCommandRequest {
+    pub command: String,
+    pub arguments: Vec<serde_json::Value>,
+    pub result: Option<serde_json::Value>,
+    pub error: Option<String>,
+}
+
+impl ExecuteCommandRequest {
+    pub fn new(command: String, arguments: Vec<serde_json::Value>) -> Self {
+        Self {
+            command,
+            arguments,
+            result: None,
+            error: None,
+        }
+    }
+}
+
+/// Table of solutions returned by [`RUN_QUERY`], also sent with [`QueryResultNotification`].
+///
+/// `table` is the same table rendered as markdown, for clients that only want to show it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct QueryResult {
+    pub uri: lsp_types::Url,
+    pub head: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    pub table: String,
+}
+
+impl QueryResult {
+    pub fn new(uri: lsp_types::Url, head: Vec<String>, rows: Vec<Vec<String>>) -> Self {
+        let escape = |x: &str| x.replace('|', "\\|").replace('\n', " ");
+        let mut table = format!("| {} |\n", head.join(" | "));
+        table += &format!("|{}\n", " --- |".repeat(head.len()));
+        for row in &rows {
+            let cells: Vec<_> = row.iter().map(|x| escape(x)).collect();
+            table += &format!("| {} |\n", cells.join(" | "));
+        }
+
+        Self {
+            uri,
+            head,
+            rows,
+            table,
+        }
+    }
+}
+
+/// Result of [`CONVERT`], `edit` either creates the untitled document `uri` or replaces the
+/// converted document.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Conversion {
+    pub uri: lsp_types::Url,
+    pub language_id: String,
+    pub text: String,
+    pub replace: bool,
+    pub edit: lsp_types::WorkspaceEdit,
+}
+
+/// Custom notification `swls/queryResult` carrying a [`QueryResult`].
+#[derive(Debug)]
+pub enum QueryResultNotification {}
+
+impl lsp_types::notification::Notification for QueryResultNotification {
+    type Params = QueryResult;
+
+    const METHOD: &'static str = "swls/queryResult";
+}
+
+/// [`ScheduleLabel`] related to the ExecuteCommand schedule, this is language specific
+#[derive(ScheduleLabel, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Label;
+
+pub fn setup_schedule(world: &mut World) {
+    let mut command = bevy_ecs::schedule::Schedule::new(Label);
+    command.add_systems(convert_document);
+    world.add_schedule(command);
+    world.init_resource::<ExecutableCommands>();
+    ExecutableCommands::register(world, CONVERT);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::QueryResult;
+
+    #[test]
+    fn query_result_table() {
+        let result = QueryResult::new(
+            lsp_types::Url::parse("file:///query.sq").unwrap(),
+            vec!["s".to_string(), "o".to_string()],
+            vec![vec!["<a>".to_string(), "\"x|y\"".to_string()]],
+        );
+        assert_eq!(
+            result.table,
+            "| s | o |\n| --- | --- |\n| <a> | \"x\\|y\" |\n"
+        );
+    }
+}
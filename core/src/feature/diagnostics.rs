@@ -175,11 +175,14 @@ impl DiagnosticSender {
     }
 }
 
+/// Publishes the syntax errors of documents of language `L`, together with the
+/// [`SimpleDiagnostic`]s that the language found validating the parsed element.
 pub fn publish_diagnostics<L: Lang>(
     query: Query<
         (
             &Errors<L::TokenError>,
             &Errors<L::ElementError>,
+            Option<&Errors<SimpleDiagnostic>>,
             &Wrapped<TextDocumentItem>,
             &RopeC,
             &crate::components::Label,
@@ -188,6 +191,7 @@ pub fn publish_diagnostics<L: Lang>(
             Or<(
                 Changed<Errors<L::TokenError>>,
                 Changed<Errors<L::ElementError>>,
+                Changed<Errors<SimpleDiagnostic>>,
             )>,
             With<Open>,
         ),
@@ -197,7 +201,7 @@ pub fn publish_diagnostics<L: Lang>(
     L::TokenError: 'static + Clone,
     L::ElementError: 'static + Clone,
 {
-    for (token_errors, element_errors, params, rope, label) in &query {
+    for (token_errors, element_errors, validation_errors, params, rope, label) in &query {
         tracing::info!("Publish diagnostics for {}", label.0);
         use std::iter::Iterator as _;
         let token_iter = token_errors
@@ -211,7 +215,17 @@ pub fn publish_diagnostics<L: Lang>(
             .cloned()
             .map(|x| Into::<SimpleDiagnostic>::into(x));
 
+        let validation_iter = validation_errors
+            .into_iter()
+            .flat_map(|x| x.0.iter())
+            .map(|x| SimpleDiagnostic {
+                range: x.range.clone(),
+                msg: x.msg.clone(),
+                severity: x.severity,
+            });
+
         let diagnostics: Vec<_> = Iterator::chain(token_iter, turtle_iter)
+            .chain(validation_iter)
             .flat_map(|item| {
                 let (span, message) = (item.range, item.msg);
                 let start_position = offset_to_position(span.start, &rope.0)?;
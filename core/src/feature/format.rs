@@ -5,6 +5,10 @@ use derive_more::{AsMut, AsRef, Deref, DerefMut};
 #[derive(Component, AsRef, Deref, AsMut, DerefMut, Debug)]
 pub struct FormatRequest(pub Option<Vec<lsp_types::TextEdit>>);
 
+/// [`Component`] with the formatting options of the client, like the indentation.
+#[derive(Component, AsRef, Deref, AsMut, DerefMut, Debug)]
+pub struct FormatOptions(pub lsp_types::FormattingOptions);
+
 /// [`ScheduleLabel`] related to the Format schedule, this is language specific
 #[derive(ScheduleLabel, Clone, Eq, PartialEq, Debug, Hash)]
 pub struct Label;
@@ -25,3 +25,15 @@ pub mod goto_definition;
 pub use goto_definition::Label as GotoDefinitionLabel;
 pub mod goto_type;
 pub use goto_type::Label as GotoTypeLabel;
+pub mod code_action;
+pub use code_action::Label as CodeActionLabel;
+pub mod symbol;
+pub use symbol::Label as DocumentSymbolLabel;
+pub mod workspace_symbol;
+pub use workspace_symbol::Label as WorkspaceSymbolLabel;
+pub mod code_lens;
+pub use code_lens::Label as CodeLensLabel;
+pub mod command;
+pub use command::Label as ExecuteCommandLabel;
+pub mod signature_help;
+pub use signature_help::Label as SignatureHelpLabel;
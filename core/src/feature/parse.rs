@@ -6,7 +6,10 @@ pub use crate::systems::{
 };
 use crate::{
     client::Client,
-    systems::{check_added_ontology_extract, derive_owl_imports_links, open_imports},
+    systems::{
+        check_added_ontology_extract, derive_owl_imports_links, fetch_remote_documents,
+        open_imports,
+    },
 };
 
 /// Parse schedule barrier, after this system, triples should be derived
@@ -33,6 +36,7 @@ pub fn setup_schedule<C: Client + Resource>(world: &mut World) {
         derive_shapes.after(triples),
         check_added_ontology_extract.after(triples),
         open_imports::<C>.after(triples),
+        fetch_remote_documents::<C>.after(triples),
     ));
     world.add_schedule(parse_schedule);
 }
@@ -0,0 +1,15 @@
+use bevy_ecs::{component::Component, schedule::ScheduleLabel, world::World};
+
+/// [`Component`] indicating that the current document is currently handling a SignatureHelp
+/// request.
+#[derive(Component, Debug, Default)]
+pub struct SignatureHelpRequest(pub Option<lsp_types::SignatureHelp>);
+
+/// [`ScheduleLabel`] related to the SignatureHelp schedule, this is language specific
+#[derive(ScheduleLabel, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Label;
+
+pub fn setup_schedule(world: &mut World) {
+    let signature_help = bevy_ecs::schedule::Schedule::new(Label);
+    world.add_schedule(signature_help);
+}
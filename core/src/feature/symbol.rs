@@ -0,0 +1,135 @@
+use std::{collections::HashMap, ops::Range};
+
+use bevy_ecs::{component::Component, schedule::ScheduleLabel, world::World};
+use derive_more::{AsMut, AsRef, Deref, DerefMut};
+use lsp_types::{DocumentSymbol, SymbolKind};
+use ropey::Rope;
+use sophia_api::term::{Term, TermKind};
+
+use crate::{
+    prelude::*,
+    util::{ns::rdf, range_to_range},
+};
+
+/// [`Component`] indicating that the current document is currently handling a DocumentSymbol
+/// request.
+#[derive(Component, AsRef, Deref, AsMut, DerefMut, Debug, Default)]
+pub struct DocumentSymbolRequest(pub Vec<DocumentSymbol>);
+
+/// [`ScheduleLabel`] related to the DocumentSymbol schedule, this is language specific
+#[derive(ScheduleLabel, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Label;
+
+pub fn setup_schedule(world: &mut World) {
+    let symbols = bevy_ecs::schedule::Schedule::new(Label);
+    world.add_schedule(symbols);
+}
+
+fn hull(a: &Range<usize>, b: &Range<usize>) -> Range<usize> {
+    a.start.min(b.start)..a.end.max(b.end)
+}
+
+/// Creates a [`DocumentSymbol`], the selection range is always contained in the full range.
+#[allow(deprecated)]
+pub fn document_symbol(
+    name: impl Into<String>,
+    detail: Option<String>,
+    kind: SymbolKind,
+    range: &Range<usize>,
+    selection: &Range<usize>,
+    children: Vec<DocumentSymbol>,
+    rope: &Rope,
+) -> Option<DocumentSymbol> {
+    let mut name = name.into();
+    if name.trim().is_empty() {
+        // Clients reject symbols without name
+        name = String::from("_");
+    }
+
+    Some(DocumentSymbol {
+        name,
+        detail,
+        kind,
+        tags: None,
+        deprecated: None,
+        range: range_to_range(&hull(range, selection), rope)?,
+        selection_range: range_to_range(selection, rope)?,
+        children: (!children.is_empty()).then_some(children),
+    })
+}
+
+/// Shortens a term with the defined prefixes, when possible.
+pub fn short_term(term: &MyTerm<'_>, prefixes: &Prefixes) -> String {
+    match term.kind() {
+        TermKind::Iri => prefixes
+            .shorten(&term.value)
+            .unwrap_or_else(|| format!("<{}>", term.value)),
+        _ => term.to_string(),
+    }
+}
+
+/// Derives an outline from [`Triples`], each subject is a symbol with its types as detail and its
+/// predicates as children.
+///
+/// Languages without a more specific model (like JSON-LD) use this outline.
+pub fn triples_document_symbols(
+    triples: &Triples,
+    prefixes: &Prefixes,
+    rope: &Rope,
+) -> Vec<DocumentSymbol> {
+    let mut order: Vec<&MyTerm<'_>> = Vec::new();
+    let mut per_subject: HashMap<&MyTerm<'_>, Vec<&MyQuad<'_>>> = HashMap::new();
+    for quad in triples.iter() {
+        let quads = per_subject.entry(&quad.subject).or_insert_with(|| {
+            order.push(&quad.subject);
+            Vec::new()
+        });
+        quads.push(quad);
+    }
+
+    order
+        .into_iter()
+        .flat_map(|subject| {
+            let quads = &per_subject[subject];
+            let types: Vec<_> = quads
+                .iter()
+                .filter(|q| Term::eq(&q.predicate, rdf::type_))
+                .map(|q| short_term(&q.object, prefixes))
+                .collect();
+
+            let mut range = subject.span.clone();
+            let children = quads
+                .iter()
+                .flat_map(|q| {
+                    let child_range = hull(&q.predicate.span, &q.object.span);
+                    range = hull(&range, &child_range);
+                    document_symbol(
+                        short_term(&q.predicate, prefixes),
+                        Some(short_term(&q.object, prefixes)),
+                        SymbolKind::PROPERTY,
+                        &child_range,
+                        &q.predicate.span,
+                        Vec::new(),
+                        rope,
+                    )
+                })
+                .collect();
+
+            let kind = if subject.is_blank_node() {
+                SymbolKind::OBJECT
+            } else {
+                SymbolKind::CLASS
+            };
+
+            document_symbol(
+                short_term(subject, prefixes),
+                (!types.is_empty()).then(|| types.join(", ")),
+                kind,
+                &range,
+                &subject.span,
+                children,
+                rope,
+            )
+        })
+        .collect()
+}
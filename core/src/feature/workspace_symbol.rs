@@ -0,0 +1,198 @@
+use bevy_ecs::{
+    component::Component,
+    schedule::{IntoSystemConfigs, Schedule, ScheduleLabel},
+    world::World,
+};
+
+/// [`Component`] indicating that the world is currently handling a WorkspaceSymbol request.
+///
+/// This component lives on a short lived entity, not on a document.
+#[derive(Component, Debug, Default)]
+pub struct WorkspaceSymbolRequest {
+    pub query: String,
+    pub symbols: Vec<lsp_types::SymbolInformation>,
+}
+
+impl WorkspaceSymbolRequest {
+    pub fn new(query: impl Into<String>) -> Self {
+        Self {
+            query: query.into(),
+            symbols: Vec::new(),
+        }
+    }
+}
+
+/// [`ScheduleLabel`] related to the WorkspaceSymbol schedule
+#[derive(ScheduleLabel, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Label;
+
+pub fn setup_schedule(world: &mut World) {
+    let mut symbols = Schedule::new(Label);
+    symbols.add_systems((
+        system::defined_class_symbols,
+        system::defined_property_symbols.after(system::defined_class_symbols),
+        system::subject_symbols.after(system::defined_property_symbols),
+    ));
+    world.add_schedule(symbols);
+}
+
+mod system {
+    use std::collections::HashSet;
+
+    use bevy_ecs::prelude::*;
+    use lsp_types::{SymbolInformation, SymbolKind};
+    use sophia_api::{
+        ns::rdfs,
+        term::{Term, TermKind},
+    };
+
+    use super::WorkspaceSymbolRequest;
+    use crate::{
+        prelude::*,
+        systems::{DefinedClasses, DefinedProperties},
+        util::token_to_location,
+    };
+
+    /// Upper bound of returned symbols, LOV vocabularies can define a lot of terms.
+    const MAX_SYMBOLS: usize = 512;
+
+    /// Symbols are matched case insensitively on their CURIE and their label.
+    fn matches(query: &str, name: &str, label: Option<&str>) -> bool {
+        let query = query.to_lowercase();
+        name.to_lowercase().contains(&query)
+            || label.is_some_and(|l| l.to_lowercase().contains(&query))
+    }
+
+    fn short(value: &str, prefixes: Option<&Prefixes>) -> String {
+        prefixes
+            .and_then(|p| p.shorten(value))
+            .unwrap_or_else(|| format!("<{}>", value))
+    }
+
+    #[allow(deprecated)]
+    fn push_symbol(
+        req: &mut WorkspaceSymbolRequest,
+        seen: &mut HashSet<(String, String)>,
+        term: &MyTerm<'_>,
+        label: Option<&str>,
+        kind: SymbolKind,
+        location: (&Label, &RopeC, Option<&Prefixes>),
+    ) {
+        let (doc, rope, prefixes) = location;
+        if req.symbols.len() >= MAX_SYMBOLS {
+            return;
+        }
+
+        let name = short(&term.value, prefixes);
+        if !matches(&req.query, &name, label) {
+            return;
+        }
+
+        if !seen.insert((doc.0.to_string(), name.clone())) {
+            return;
+        }
+
+        let Some(location) = token_to_location(&term.span, doc, &rope.0) else {
+            return;
+        };
+
+        req.symbols.push(SymbolInformation {
+            name,
+            kind,
+            tags: None,
+            deprecated: None,
+            location,
+            container_name: label.map(String::from),
+        });
+    }
+
+    fn seen_symbols(req: &WorkspaceSymbolRequest) -> HashSet<(String, String)> {
+        req.symbols
+            .iter()
+            .map(|s| (s.location.uri.to_string(), s.name.clone()))
+            .collect()
+    }
+
+    pub fn defined_class_symbols(
+        mut query: Query<&mut WorkspaceSymbolRequest>,
+        project: Query<(&Wrapped<DefinedClasses>, &Label, &RopeC, Option<&Prefixes>)>,
+    ) {
+        for mut req in &mut query {
+            let mut seen = seen_symbols(&req);
+            for (classes, label, rope, prefixes) in &project {
+                for class in classes.iter() {
+                    push_symbol(
+                        &mut req,
+                        &mut seen,
+                        &class.term,
+                        Some(&class.label),
+                        SymbolKind::CLASS,
+                        (label, rope, prefixes),
+                    );
+                }
+            }
+        }
+    }
+
+    pub fn defined_property_symbols(
+        mut query: Query<&mut WorkspaceSymbolRequest>,
+        project: Query<(
+            &Wrapped<DefinedProperties>,
+            &Label,
+            &RopeC,
+            Option<&Prefixes>,
+        )>,
+    ) {
+        for mut req in &mut query {
+            let mut seen = seen_symbols(&req);
+            for (properties, label, rope, prefixes) in &project {
+                for property in properties.iter() {
+                    push_symbol(
+                        &mut req,
+                        &mut seen,
+                        &property.predicate,
+                        Some(&property.label),
+                        SymbolKind::PROPERTY,
+                        (label, rope, prefixes),
+                    );
+                }
+            }
+        }
+    }
+
+    pub fn subject_symbols(
+        mut query: Query<&mut WorkspaceSymbolRequest>,
+        project: Query<(&Triples, &Label, &RopeC, Option<&Prefixes>)>,
+    ) {
+        for mut req in &mut query {
+            let mut seen = seen_symbols(&req);
+            for (triples, label, rope, prefixes) in &project {
+                let mut visited = HashSet::new();
+                for quad in triples.iter() {
+                    if quad.subject.kind() != TermKind::Iri || !visited.insert(&quad.subject.value)
+                    {
+                        continue;
+                    }
+
+                    let key = (label.0.to_string(), short(&quad.subject.value, prefixes));
+                    if seen.contains(&key) {
+                        continue;
+                    }
+
+                    let rdfs_label = triples
+                        .object([&quad.subject], [rdfs::label])
+                        .map(|x| x.as_str().to_string());
+
+                    push_symbol(
+                        &mut req,
+                        &mut seen,
+                        &quad.subject,
+                        rdfs_label.as_deref(),
+                        SymbolKind::OBJECT,
+                        (label, rope, prefixes),
+                    );
+                }
+            }
+        }
+    }
+}
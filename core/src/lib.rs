@@ -13,7 +13,7 @@
 //! respond to requests.
 //!
 //! Language specific implementations that handle things like tokenizing and parsing are
-//! implemented in separate crates. The binary currently supports [Turtle](../lang_turtle/index.html), [JSON-LD](../lang_jsonld/index.html) and [SPARQL](../lang_sparql/index.html).
+//! implemented in separate crates. The binary currently supports [Turtle](../lang_turtle/index.html), [TriG](../lang_trig/index.html), [JSON-LD](../lang_jsonld/index.html), [YAML-LD](../lang_yamlld/index.html) and [SPARQL](../lang_sparql/index.html).
 //! The goal is that each language at least generates [`Tokens`], [`Triples`] and
 //! [`Prefixes`].
 //! These components are then used to derive properties for autcompletion but also derive
@@ -82,7 +82,9 @@
 
 use bevy_ecs::{prelude::*, schedule::ScheduleLabel};
 use prelude::SemanticTokensDict;
-use systems::{init_onology_extractor, OntologyExtractor};
+use systems::{
+    index_startup_workspaces, index_workspace, init_onology_extractor, OntologyExtractor,
+};
 
 use crate::prelude::*;
 
@@ -120,6 +122,8 @@ pub mod systems;
 pub fn setup_schedule_labels<C: Client + Resource>(world: &mut World) {
     world.init_resource::<SemanticTokensDict>();
     world.init_resource::<TypeHierarchy<'static>>();
+    world.init_resource::<RemoteDocumentContents>();
+    world.init_resource::<RequestedDocuments>();
     world.insert_resource(OntologyExtractor::new());
 
     parse::setup_schedule::<C>(world);
@@ -133,14 +137,22 @@ pub fn setup_schedule_labels<C: Client + Resource>(world: &mut World) {
     inlay::setup_schedule(world);
     goto_definition::setup_schedule(world);
     goto_type::setup_schedule(world);
+    code_action::setup_schedule(world);
+    symbol::setup_schedule(world);
+    workspace_symbol::setup_schedule(world);
+    code_lens::setup_schedule(world);
+    command::setup_schedule(world);
+    signature_help::setup_schedule(world);
 
     semantic::setup_world(world);
 
     world.add_schedule(Schedule::new(Tasks));
 
     let mut schedule = Schedule::new(Startup);
-    schedule.add_systems(init_onology_extractor);
+    schedule.add_systems((init_onology_extractor, index_startup_workspaces));
     world.add_schedule(schedule);
+
+    world.observe(index_workspace::<C>);
 }
 
 /// Event triggers when a document is opened
@@ -5,14 +5,20 @@ pub use crate::{
     components::*,
     feature::{
         self,
+        code_action::CodeActionRequest,
+        code_lens::CodeLensRequest,
+        command::{ExecutableCommands, ExecuteCommandRequest},
         completion::{CompletionRequest, SimpleCompletion},
         diagnostics::{DiagnosticItem, DiagnosticPublisher, DiagnosticSender, SimpleDiagnostic},
-        format::FormatRequest,
+        format::{FormatOptions, FormatRequest},
         hover::HoverRequest,
         inlay::InlayRequest,
         rename::PrepareRenameRequest,
         rename::RenameEdits,
         semantic::{HighlightRequest, SemanticTokensDict},
+        signature_help::SignatureHelpRequest,
+        symbol::DocumentSymbolRequest,
+        workspace_symbol::WorkspaceSymbolRequest,
         *,
     },
     lang::{Lang, LangHelper, TokenTrait},
@@ -20,8 +26,9 @@ pub use crate::{
     systems::prefix::{Prefix, Prefixes},
     systems::spawn_or_insert,
     util::{
-        fs::*, lsp_range_to_range, offset_to_position, offsets_to_range, position_to_offset,
-        range_to_range, spanned, token::*, triple::*, Spanned,
+        apply_change, apply_changes, fs::*, lsp_range_to_range, offset_to_position,
+        offsets_to_range, position_to_offset, range_to_range, spanned, token::*, triple::*,
+        Spanned,
     },
     CreateEvent,
 };
@@ -0,0 +1,630 @@
+use std::collections::HashMap;
+
+use bevy_ecs::prelude::*;
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, Command, CreateFile, CreateFileOptions,
+    DocumentChangeOperation, DocumentChanges, OneOf, OptionalVersionedTextDocumentIdentifier,
+    Position, Range, ResourceOp, TextDocumentEdit, TextDocumentItem, TextEdit, Url, WorkspaceEdit,
+};
+use sophia_api::term::{Term, TermKind};
+use tracing::info;
+
+use crate::{
+    feature::command::{Conversion, CONVERT},
+    prelude::*,
+    util::ns::rdf,
+};
+
+/// Syntaxes a document can be converted to with [`CONVERT`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Turtle,
+    JsonLd,
+    NTriples,
+}
+
+impl Format {
+    pub const ALL: [Format; 3] = [Format::Turtle, Format::JsonLd, Format::NTriples];
+
+    pub fn from_language_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|x| x.language_id() == id)
+    }
+
+    pub fn language_id(&self) -> &'static str {
+        match self {
+            Format::Turtle => "turtle",
+            Format::JsonLd => "jsonld",
+            Format::NTriples => "ntriples",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Turtle => "ttl",
+            Format::JsonLd => "jsonld",
+            Format::NTriples => "nt",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Format::Turtle => "Turtle",
+            Format::JsonLd => "JSON-LD",
+            Format::NTriples => "N-Triples",
+        }
+    }
+}
+
+fn escape_literal(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out += "\\\"",
+            '\\' => out += "\\\\",
+            '\n' => out += "\\n",
+            '\r' => out += "\\r",
+            '\t' => out += "\\t",
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Whether `local` can be written as the local part of a prefixed name.
+fn valid_local(local: &str) -> bool {
+    !local.starts_with(['-', '.'])
+        && !local.ends_with('.')
+        && local
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.')
+}
+
+/// Shortens `iri` with the prefix with the longest matching namespace.
+fn compact(iri: &str, prefixes: &Prefixes) -> Option<String> {
+    prefixes
+        .iter()
+        .filter_map(|prefix| {
+            let local = iri.strip_prefix(prefix.url.as_str())?;
+            valid_local(local).then_some((prefix.url.as_str().len(), prefix, local))
+        })
+        .max_by_key(|(len, _, _)| *len)
+        .map(|(_, prefix, local)| format!("{}:{}", prefix.prefix, local))
+}
+
+/// The triples of the default graph, grouped per subject in document order.
+///
+/// Quads with terms that failed to parse are skipped.
+fn group_subjects<'a>(
+    quads: &'a [MyQuad<'static>],
+) -> Vec<(&'a MyTerm<'static>, Vec<&'a MyQuad<'static>>)> {
+    let mut subjects: Vec<(&MyTerm<'static>, Vec<&MyQuad<'static>>)> = Vec::new();
+    let mut idx = HashMap::new();
+    let valid = |term: &MyTerm| !term.value.is_empty() && term.kind() != TermKind::Triple;
+
+    for quad in quads {
+        if quad.graph.is_some()
+            || !valid(&quad.subject)
+            || !quad.predicate.is_iri()
+            || !valid(&quad.object)
+        {
+            continue;
+        }
+        let i = *idx.entry(&quad.subject).or_insert_with(|| {
+            subjects.push((&quad.subject, Vec::new()));
+            subjects.len() - 1
+        });
+        subjects[i].1.push(quad);
+    }
+    subjects
+}
+
+/// Writes a literal with its language tag or datatype, `datatype` writes the datatype IRI.
+fn literal_term(term: &MyTerm, datatype: impl Fn(&str) -> String) -> String {
+    let value = escape_literal(&term.value);
+    match (term.language(), term.datatype_iri()) {
+        (Some(language), _) => format!("{}@{}", value, language),
+        (None, Some(iri)) if iri != XSD_STRING => format!("{}^^{}", value, datatype(iri)),
+        _ => value,
+    }
+}
+
+/// The formats only hold the default graph, named graphs would be dropped.
+fn has_named_graphs(quads: &[MyQuad<'static>]) -> bool {
+    quads.iter().any(|quad| quad.graph.is_some())
+}
+
+fn ntriples_term(term: &MyTerm) -> String {
+    match term.kind() {
+        TermKind::Iri => format!("<{}>", term.value),
+        TermKind::BlankNode => format!("_:{}", term.value),
+        _ => literal_term(term, |iri| format!("<{}>", iri)),
+    }
+}
+
+/// Writes the default graph as N-Triples.
+pub fn to_ntriples(quads: &[MyQuad<'static>]) -> String {
+    let mut out = String::new();
+    for (_, quads) in group_subjects(quads) {
+        for quad in quads {
+            out += &format!(
+                "{} {} {} .\n",
+                ntriples_term(&quad.subject),
+                ntriples_term(&quad.predicate),
+                ntriples_term(&quad.object)
+            );
+        }
+    }
+    out
+}
+
+fn turtle_iri(iri: &str, prefixes: &Prefixes) -> String {
+    compact(iri, prefixes).unwrap_or_else(|| format!("<{}>", iri))
+}
+
+fn turtle_term(term: &MyTerm, prefixes: &Prefixes) -> String {
+    match term.kind() {
+        TermKind::Iri => turtle_iri(&term.value, prefixes),
+        TermKind::Literal => literal_term(term, |iri| turtle_iri(iri, prefixes)),
+        _ => ntriples_term(term),
+    }
+}
+
+/// Writes the default graph as Turtle, the prefixes become `@prefix` lines and shorten IRIs.
+///
+/// Predicates and objects are grouped per subject.
+pub fn to_turtle(quads: &[MyQuad<'static>], prefixes: &Prefixes) -> String {
+    let mut out = String::new();
+    for prefix in prefixes.iter() {
+        out += &format!("@prefix {}: <{}>.\n", prefix.prefix, prefix.url);
+    }
+
+    for (subject, quads) in group_subjects(quads) {
+        if !out.is_empty() {
+            out += "\n";
+        }
+        out += &turtle_term(subject, prefixes);
+
+        let mut predicates: Vec<(&MyTerm, Vec<String>)> = Vec::new();
+        for quad in quads {
+            let object = turtle_term(&quad.object, prefixes);
+            match predicates.iter_mut().find(|(p, _)| *p == &quad.predicate) {
+                Some((_, objects)) => objects.push(object),
+                None => predicates.push((&quad.predicate, vec![object])),
+            }
+        }
+
+        for (i, (predicate, objects)) in predicates.into_iter().enumerate() {
+            let predicate = if Term::eq(predicate, rdf::type_) {
+                String::from("a")
+            } else {
+                turtle_term(predicate, prefixes)
+            };
+            let sep = if i == 0 { " " } else { ";\n  " };
+            out += &format!("{}{} {}", sep, predicate, objects.join(", "));
+        }
+        out += ".\n";
+    }
+    out
+}
+
+fn json_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| escape_literal(value))
+}
+
+fn jsonld_id(term: &MyTerm, prefixes: &Prefixes) -> String {
+    match term.kind() {
+        TermKind::BlankNode => format!("_:{}", term.value),
+        _ => compact(&term.value, prefixes).unwrap_or_else(|| term.value.to_string()),
+    }
+}
+
+fn jsonld_value(term: &MyTerm, prefixes: &Prefixes) -> String {
+    match term.kind() {
+        TermKind::Iri | TermKind::BlankNode => {
+            format!("{{ \"@id\": {} }}", json_string(&jsonld_id(term, prefixes)))
+        }
+        _ => match (term.language(), term.datatype_iri()) {
+            (Some(language), _) => format!(
+                "{{ \"@value\": {}, \"@language\": {} }}",
+                json_string(&term.value),
+                json_string(language)
+            ),
+            (None, Some(iri)) if iri != XSD_STRING => format!(
+                "{{ \"@value\": {}, \"@type\": {} }}",
+                json_string(&term.value),
+                json_string(&compact(iri, prefixes).unwrap_or_else(|| iri.to_string()))
+            ),
+            _ => json_string(&term.value),
+        },
+    }
+}
+
+/// Writes the values of one entry, a single value is not wrapped in an array.
+fn jsonld_entry(key: &str, values: &[String], indent: &str) -> String {
+    let values = match values {
+        [value] => value.clone(),
+        values => format!("[ {} ]", values.join(", ")),
+    };
+    format!("{}{}: {}", indent, json_string(key), values)
+}
+
+/// Writes the default graph as JSON-LD, the prefixes become the `@context`.
+///
+/// Every subject is a node object in `@graph`, `rdf:type` is written as `@type`. Typed and
+/// language-tagged literals are value objects.
+pub fn to_jsonld(quads: &[MyQuad<'static>], prefixes: &Prefixes) -> String {
+    let context: Vec<_> = prefixes
+        .iter()
+        .filter(|prefix| !prefix.prefix.is_empty())
+        .map(|prefix| jsonld_entry(&prefix.prefix, &[json_string(prefix.url.as_str())], "    "))
+        .collect();
+
+    let mut nodes = Vec::new();
+    for (subject, quads) in group_subjects(quads) {
+        let mut types = Vec::new();
+        let mut properties: Vec<(String, Vec<String>)> = Vec::new();
+        for quad in quads {
+            if Term::eq(&quad.predicate, rdf::type_) && quad.object.is_iri() {
+                types.push(json_string(&jsonld_id(&quad.object, prefixes)));
+                continue;
+            }
+            let key = jsonld_id(&quad.predicate, prefixes);
+            let value = jsonld_value(&quad.object, prefixes);
+            match properties.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, values)) => values.push(value),
+                None => properties.push((key, vec![value])),
+            }
+        }
+
+        let id = json_string(&jsonld_id(subject, prefixes));
+        let mut entries = vec![jsonld_entry("@id", &[id], "      ")];
+        if !types.is_empty() {
+            entries.push(jsonld_entry("@type", &types, "      "));
+        }
+        entries.extend(
+            properties
+                .iter()
+                .map(|(key, values)| jsonld_entry(key, values, "      ")),
+        );
+        nodes.push(format!("    {{\n{}\n    }}", entries.join(",\n")));
+    }
+
+    let mut entries = Vec::new();
+    if !context.is_empty() {
+        entries.push(format!("  \"@context\": {{\n{}\n  }}", context.join(",\n")));
+    }
+    if nodes.is_empty() {
+        entries.push(String::from("  \"@graph\": []"));
+    } else {
+        entries.push(format!("  \"@graph\": [\n{}\n  ]", nodes.join(",\n")));
+    }
+    format!("{{\n{}\n}}\n", entries.join(",\n"))
+}
+
+/// Serializes the triples of a document in `format`.
+pub fn convert(quads: &[MyQuad<'static>], prefixes: &Prefixes, format: Format) -> String {
+    match format {
+        Format::Turtle => to_turtle(quads, prefixes),
+        Format::JsonLd => to_jsonld(quads, prefixes),
+        Format::NTriples => to_ntriples(quads),
+    }
+}
+
+/// Location of the untitled document a conversion is opened in, `data.ttl` becomes
+/// `untitled:data.jsonld`.
+fn untitled_location(url: &Url, format: Format) -> Option<Url> {
+    let name = url.path_segments()?.last()?;
+    let stem = name.rsplit_once('.').map(|(stem, _)| stem).unwrap_or(name);
+    let stem = if stem.is_empty() { "untitled" } else { stem };
+    Url::parse(&format!("untitled:{}.{}", stem, format.extension())).ok()
+}
+
+/// Edit that creates the document `uri`, overwriting an existing one, with `text` as content.
+pub fn new_document_edit(uri: &Url, text: &str) -> WorkspaceEdit {
+    let create = DocumentChangeOperation::Op(ResourceOp::Create(CreateFile {
+        uri: uri.clone(),
+        options: Some(CreateFileOptions {
+            overwrite: Some(true),
+            ignore_if_exists: None,
+        }),
+        annotation_id: None,
+    }));
+    let insert = DocumentChangeOperation::Edit(TextDocumentEdit {
+        text_document: OptionalVersionedTextDocumentIdentifier {
+            uri: uri.clone(),
+            version: None,
+        },
+        edits: vec![OneOf::Left(TextEdit {
+            range: Range::new(Position::new(0, 0), Position::new(0, 0)),
+            new_text: text.to_string(),
+        })],
+    });
+    WorkspaceEdit {
+        document_changes: Some(DocumentChanges::Operations(vec![create, insert])),
+        ..Default::default()
+    }
+}
+
+/// Offers to convert Turtle, JSON-LD and N-Triples documents to the other formats, in a new
+/// untitled document or in place.
+///
+/// The actions run [`CONVERT`], documents with syntax errors or named graphs are skipped.
+/// N-Triples documents are converted to Turtle by
+/// [`TO_TURTLE`](crate::feature::command::TO_TURTLE) instead, which writes a sibling `.ttl` file
+/// with generated prefixes, as N-Triples documents have none.
+pub fn convert_code_action(
+    mut query: Query<
+        (
+            &Label,
+            &Triples,
+            &Wrapped<TextDocumentItem>,
+            &mut CodeActionRequest,
+        ),
+        Without<Dirty>,
+    >,
+) {
+    for (label, triples, item, mut req) in &mut query {
+        let Some(current) = Format::from_language_id(&item.language_id) else {
+            continue;
+        };
+        if triples.is_empty() || has_named_graphs(&triples.0) {
+            continue;
+        }
+
+        let formats = Format::ALL
+            .into_iter()
+            .filter(|x| *x != current && !(current == Format::NTriples && *x == Format::Turtle));
+        for format in formats {
+            for (mode, title) in [
+                (
+                    "new",
+                    format!("Convert to {} in a new document", format.name()),
+                ),
+                ("replace", format!("Replace with {}", format.name())),
+            ] {
+                let arguments = vec![
+                    serde_json::Value::String(label.0.to_string()),
+                    serde_json::Value::String(format.language_id().to_string()),
+                    serde_json::Value::String(mode.to_string()),
+                ];
+                req.actions
+                    .push(CodeActionOrCommand::CodeAction(CodeAction {
+                        title: title.clone(),
+                        kind: Some(CodeActionKind::REFACTOR_REWRITE),
+                        command: Some(Command {
+                            title,
+                            command: CONVERT.to_string(),
+                            arguments: Some(arguments),
+                        }),
+                        ..Default::default()
+                    }));
+            }
+        }
+    }
+}
+
+/// Handles [`CONVERT`], the result is a [`Conversion`] with the edit the client applies.
+///
+/// Documents with syntax errors are not converted, their triples can be stale.
+pub fn convert_document(
+    mut query: Query<(
+        &Label,
+        &Triples,
+        &Prefixes,
+        &RopeC,
+        Has<Dirty>,
+        &mut ExecuteCommandRequest,
+    )>,
+) {
+    for (label, triples, prefixes, rope, dirty, mut request) in &mut query {
+        if request.command != CONVERT {
+            continue;
+        }
+
+        if dirty {
+            request.error = Some(format!("Cannot convert {}, it has syntax errors", label.0));
+            continue;
+        }
+
+        let argument = |i: usize| {
+            let value = request.arguments.get(i)?.as_str()?;
+            Some(value.to_string())
+        };
+        let (format, mode) = (argument(1).unwrap_or_default(), argument(2));
+        let Some(format) = Format::from_language_id(&format) else {
+            request.error = Some(format!("Unknown format {}", format));
+            continue;
+        };
+        let replace = match mode.as_deref() {
+            Some("replace") => true,
+            Some("new") | None => false,
+            Some(mode) => {
+                request.error = Some(format!("Unknown conversion mode {}", mode));
+                continue;
+            }
+        };
+
+        if has_named_graphs(&triples.0) {
+            request.error = Some(format!(
+                "{} has named graphs, {} cannot express them",
+                label.0,
+                format.name()
+            ));
+            continue;
+        }
+
+        let text = convert(&triples.0, prefixes, format);
+        info!("Converted {} to {}", label.0, format.name());
+
+        let (uri, edit) = if replace {
+            let end = offset_to_position(rope.len_chars(), &rope.0).unwrap_or_default();
+            let edit = TextEdit {
+                range: Range::new(Position::new(0, 0), end),
+                new_text: text.clone(),
+            };
+            let changes = HashMap::from([(label.0.clone(), vec![edit])]);
+            let edit = WorkspaceEdit {
+                changes: Some(changes),
+                ..Default::default()
+            };
+            (label.0.clone(), edit)
+        } else {
+            let Some(uri) = untitled_location(&label.0, format) else {
+                request.error = Some(format!("No untitled location for {}", label.0));
+                continue;
+            };
+            let edit = new_document_edit(&uri, &text);
+            (uri, edit)
+        };
+
+        let conversion = Conversion {
+            uri,
+            language_id: format.language_id().to_string(),
+            text,
+            replace,
+            edit,
+        };
+        request.result = serde_json::to_value(conversion).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prefixes() -> Prefixes {
+        let base = Url::parse("http://example.com/data.ttl").unwrap();
+        Prefixes(
+            vec![Prefix {
+                prefix: String::from("foaf"),
+                url: Url::parse("http://xmlns.com/foaf/0.1/").unwrap(),
+            }],
+            base,
+        )
+    }
+
+    fn quads() -> Vec<MyQuad<'static>> {
+        let quad = |s: &str, p: &str, o: MyTerm<'static>| MyQuad {
+            subject: MyTerm::named_node(s.to_string(), 0..0),
+            predicate: MyTerm::named_node(p.to_string(), 0..0),
+            object: o,
+            graph: None,
+            span: 0..0,
+        };
+        vec![
+            quad(
+                "http://example.com/me",
+                "http://www.w3.org/1999/02/22-rdf-syntax-ns#type",
+                MyTerm::named_node("http://xmlns.com/foaf/0.1/Person", 0..0),
+            ),
+            quad(
+                "http://example.com/me",
+                "http://xmlns.com/foaf/0.1/name",
+                MyTerm::literal("Arthur \"A\"", 0..0),
+            ),
+            quad(
+                "http://example.com/me",
+                "http://xmlns.com/foaf/0.1/knows",
+                MyTerm::named_node("http://example.com/you", 0..0),
+            ),
+            quad(
+                "http://example.com/me",
+                "http://xmlns.com/foaf/0.1/knows",
+                MyTerm::blank_node("b0", 0..0),
+            ),
+        ]
+    }
+
+    #[test]
+    fn convert_formats() {
+        let quads = quads();
+        let prefixes = prefixes();
+
+        assert_eq!(
+            convert(&quads, &prefixes, Format::Turtle),
+            r#"@prefix foaf: <http://xmlns.com/foaf/0.1/>.
+
+<http://example.com/me> a foaf:Person;
+  foaf:name "Arthur \"A\"";
+  foaf:knows <http://example.com/you>, _:b0.
+"#
+        );
+
+        let nt = convert(&quads, &prefixes, Format::NTriples);
+        assert_eq!(nt.lines().count(), 4);
+        assert!(nt.contains(
+            "<http://example.com/me> <http://xmlns.com/foaf/0.1/name> \"Arthur \\\"A\\\"\" .\n"
+        ));
+
+        assert_eq!(
+            convert(&quads, &prefixes, Format::JsonLd),
+            r#"{
+  "@context": {
+    "foaf": "http://xmlns.com/foaf/0.1/"
+  },
+  "@graph": [
+    {
+      "@id": "http://example.com/me",
+      "@type": "foaf:Person",
+      "foaf:name": "Arthur \"A\"",
+      "foaf:knows": [ { "@id": "http://example.com/you" }, { "@id": "_:b0" } ]
+    }
+  ]
+}
+"#
+        );
+    }
+
+    #[test]
+    fn convert_keeps_datatypes_and_languages() {
+        let quad = |p: &str, o: MyTerm<'static>| MyQuad {
+            subject: MyTerm::named_node("http://example.com/me", 0..0),
+            predicate: MyTerm::named_node(format!("http://xmlns.com/foaf/0.1/{}", p), 0..0),
+            object: o,
+            graph: None,
+            span: 0..0,
+        };
+        let quads = vec![
+            quad(
+                "age",
+                MyTerm::literal("34", 0..0)
+                    .with_datatype("http://www.w3.org/2001/XMLSchema#integer"),
+            ),
+            quad("name", MyTerm::literal("Arthur", 0..0).with_language("en")),
+        ];
+        let prefixes = prefixes();
+
+        let nt = convert(&quads, &prefixes, Format::NTriples);
+        assert!(nt.contains("\"34\"^^<http://www.w3.org/2001/XMLSchema#integer> .\n"));
+        assert!(nt.contains("\"Arthur\"@en .\n"));
+
+        let turtle = convert(&quads, &prefixes, Format::Turtle);
+        assert!(turtle.contains("foaf:age \"34\"^^<http://www.w3.org/2001/XMLSchema#integer>"));
+        assert!(turtle.contains("foaf:name \"Arthur\"@en"));
+
+        let jsonld = convert(&quads, &prefixes, Format::JsonLd);
+        assert!(jsonld.contains(
+            r#""foaf:age": { "@value": "34", "@type": "http://www.w3.org/2001/XMLSchema#integer" }"#
+        ));
+        assert!(jsonld.contains(r#""foaf:name": { "@value": "Arthur", "@language": "en" }"#));
+    }
+
+    #[test]
+    fn named_graphs_are_detected() {
+        let mut quads = quads();
+        assert!(!has_named_graphs(&quads));
+        quads[0].graph = Some(MyTerm::named_node("http://example.com/graph", 0..0));
+        assert!(has_named_graphs(&quads));
+    }
+
+    #[test]
+    fn untitled_location_keeps_the_name() {
+        let url = Url::parse("file:///tmp/data.ttl").unwrap();
+        assert_eq!(
+            untitled_location(&url, Format::JsonLd).map(|x| x.to_string()),
+            Some(String::from("untitled:data.jsonld"))
+        );
+    }
+}
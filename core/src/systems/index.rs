@@ -0,0 +1,277 @@
+use std::collections::HashMap;
+
+use bevy_ecs::{prelude::*, world::CommandQueue};
+use lsp_types::{
+    NumberOrString, TextDocumentItem, Url, WorkDoneProgress, WorkDoneProgressBegin,
+    WorkDoneProgressEnd, WorkDoneProgressReport, WorkspaceFolder,
+};
+use tracing::{debug, info, instrument};
+
+use crate::prelude::*;
+
+/// Extensions of files that are loaded when indexing a workspace folder.
+pub const INDEXED_EXTENSIONS: &[&str] = &[
+    "ttl", "jsonld", "sq", "rq", "ru", "nt", "nq", "trig", "yamlld",
+];
+
+/// Language id of the documents with each [indexed extension](INDEXED_EXTENSIONS).
+const LANGUAGE_IDS: &[(&str, &str)] = &[
+    ("ttl", "turtle"),
+    ("jsonld", "jsonld"),
+    ("sq", "sparql"),
+    ("rq", "sparql"),
+    ("ru", "sparql"),
+    ("nt", "ntriples"),
+    ("nq", "nquads"),
+    ("trig", "trig"),
+    ("yamlld", "yamlld"),
+];
+
+/// Event triggered to index workspace folders in the background.
+///
+/// All files with an [indexed extension](INDEXED_EXTENSIONS) are spawned as documents that are
+/// not [`Open`].
+#[derive(Event, Debug, Clone)]
+pub struct IndexWorkspace(pub Vec<WorkspaceFolder>);
+
+pub fn index_startup_workspaces(config: Res<ServerConfig>, mut commands: Commands) {
+    if !config.workspaces.is_empty() {
+        commands.trigger(IndexWorkspace(config.workspaces.clone()));
+    }
+}
+
+/// A path is ignored when one of its segments, relative to the workspace folder, is ignored.
+fn is_ignored(root: &Url, url: &Url, ignore: &[String]) -> bool {
+    let relative = url.path().strip_prefix(root.path()).unwrap_or(url.path());
+    relative
+        .split('/')
+        .filter(|x| !x.is_empty())
+        .any(|segment| ignore.iter().any(|i| i == segment))
+}
+
+/// Glob pattern matching all files with an [indexed extension](INDEXED_EXTENSIONS).
+pub fn indexed_glob() -> String {
+    format!("**/*.{{{}}}", INDEXED_EXTENSIONS.join(","))
+}
+
+fn is_indexed(url: &Url) -> bool {
+    url.path()
+        .rsplit_once('.')
+        .is_some_and(|(_, ext)| INDEXED_EXTENSIONS.contains(&ext))
+}
+
+fn language_id(url: &Url) -> Option<&'static str> {
+    let (_, ext) = url.path().rsplit_once('.')?;
+    LANGUAGE_IDS
+        .iter()
+        .find(|(x, _)| *x == ext)
+        .map(|(_, id)| *id)
+}
+
+async fn find_files(root: &Url, ignore: &[String], fs: &Fs) -> Vec<Url> {
+    let mut files = Vec::new();
+    let mut stack = vec![root.clone()];
+
+    while let Some(dir) = stack.pop() {
+        let Some(entries) = fs.0.read_dir(&dir).await else {
+            debug!("Cannot list directory {}", dir);
+            continue;
+        };
+
+        for entry in entries {
+            if is_ignored(root, &entry, ignore) {
+                continue;
+            }
+            if entry.path().ends_with('/') {
+                stack.push(entry);
+            } else if is_indexed(&entry) {
+                files.push(entry);
+            }
+        }
+    }
+
+    files
+}
+
+fn spawn_indexed(url: Url, content: String) -> CommandQueue {
+    let mut command_queue = CommandQueue::default();
+    let language_id = language_id(&url).map(String::from);
+    let item = TextDocumentItem {
+        version: 1,
+        uri: url.clone(),
+        language_id: language_id.clone().unwrap_or_default(),
+        text: content.clone(),
+    };
+
+    let spawn = spawn_or_insert(
+        url.clone(),
+        (
+            RopeC(ropey::Rope::from_str(&content)),
+            Source(content),
+            Label(url.clone()),
+            Wrapped(item),
+            Types(HashMap::new()),
+        ),
+        language_id,
+        (),
+    );
+
+    command_queue.push(move |world: &mut World| {
+        // Documents that are already known are the responsibility of the editor
+        let exists = world.query::<&Label>().iter(world).any(|l| l.0 == url);
+        if !exists {
+            spawn(world);
+        }
+    });
+
+    command_queue
+}
+
+async fn index_folder<C: Client + Resource>(
+    folder: WorkspaceFolder,
+    ignore: Vec<String>,
+    fs: Fs,
+    client: C,
+    sender: CommandSender,
+) {
+    let token = NumberOrString::String(format!("swls/index/{}", folder.uri));
+    client.create_progress(token.clone()).await;
+    client
+        .progress(
+            token.clone(),
+            WorkDoneProgress::Begin(WorkDoneProgressBegin {
+                title: format!("Indexing {}", folder.name),
+                cancellable: Some(false),
+                message: None,
+                percentage: Some(0),
+            }),
+        )
+        .await;
+
+    let files = find_files(&folder.uri, &ignore, &fs).await;
+    info!("Indexing {} files in {}", files.len(), folder.uri);
+
+    let total = files.len().max(1);
+    for (i, file) in files.into_iter().enumerate() {
+        client
+            .progress(
+                token.clone(),
+                WorkDoneProgress::Report(WorkDoneProgressReport {
+                    cancellable: Some(false),
+                    message: Some(file.path().to_string()),
+                    percentage: Some((i * 100 / total) as u32),
+                }),
+            )
+            .await;
+
+        if let Some(content) = fs.0.read_file(&file).await {
+            let _ = sender.0.unbounded_send(spawn_indexed(file, content));
+        }
+    }
+
+    client
+        .progress(
+            token,
+            WorkDoneProgress::End(WorkDoneProgressEnd { message: None }),
+        )
+        .await;
+}
+
+/// Whether `url` is inside `folder`, whole path segments are compared so that `/a/proj` does not
+/// contain `/a/proj2`.
+pub fn in_folder(url: &Url, folder: &Url) -> bool {
+    let folder = folder.as_str().trim_end_matches('/');
+    url.as_str()
+        .strip_prefix(folder)
+        .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Files are only picked up when they are inside a workspace folder and not ignored.
+fn in_workspace(url: &Url, config: &ServerConfig) -> bool {
+    let ignore = config.config.ignore();
+    is_indexed(url)
+        && config
+            .workspaces
+            .iter()
+            .any(|folder| in_folder(url, &folder.uri) && !is_ignored(&folder.uri, url, &ignore))
+}
+
+/// Updates the document at `url` with the content found on disk, `None` removes the document.
+///
+/// Open documents are managed by the editor and are left untouched.
+/// Returns true when the world changed and should be parsed again.
+pub fn refresh_document(world: &mut World, url: Url, content: Option<String>) -> bool {
+    let found = world
+        .query::<(Entity, &Label, Has<Open>)>()
+        .iter(world)
+        .find(|(_, label, _)| label.0 == url)
+        .map(|(e, _, open)| (e, open));
+
+    match (found, content) {
+        (Some((_, true)), _) => false,
+        (Some((e, false)), Some(content)) => {
+            info!("Reloading {} from disk", url);
+            world
+                .entity_mut(e)
+                .insert((RopeC(ropey::Rope::from_str(&content)), Source(content)))
+                .remove::<SourceChange>();
+            true
+        }
+        (Some((e, false)), None) => {
+            info!("Removing {}", url);
+            world.despawn(e);
+            true
+        }
+        (None, Some(content)) if in_workspace(&url, world.resource()) => {
+            spawn_indexed(url, content).apply(world);
+            true
+        }
+        (None, _) => false,
+    }
+}
+
+#[instrument(skip(trigger, config, fs, client, sender))]
+pub fn index_workspace<C: Client + Resource>(
+    trigger: Trigger<IndexWorkspace>,
+    config: Res<ServerConfig>,
+    fs: Res<Fs>,
+    client: Res<C>,
+    sender: Res<CommandSender>,
+) {
+    let ignore = config.config.ignore();
+    for folder in trigger.event().0.iter() {
+        client.spawn(index_folder(
+            folder.clone(),
+            ignore.clone(),
+            fs.clone(),
+            client.as_ref().clone(),
+            sender.clone(),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sibling_folders_are_not_in_the_workspace() {
+        let folder = Url::parse("file:///a/proj").unwrap();
+        let inside = Url::parse("file:///a/proj/x.ttl").unwrap();
+        let sibling = Url::parse("file:///a/proj2/x.ttl").unwrap();
+
+        assert!(in_folder(&inside, &folder));
+        assert!(!in_folder(&sibling, &folder));
+        assert!(in_folder(&inside, &Url::parse("file:///a/proj/").unwrap()));
+
+        let config = ServerConfig {
+            workspaces: vec![WorkspaceFolder {
+                uri: folder,
+                name: String::from("proj"),
+            }],
+            ..Default::default()
+        };
+        assert!(in_workspace(&inside, &config));
+        assert!(!in_workspace(&sibling, &config));
+    }
+}
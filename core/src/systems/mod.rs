@@ -16,6 +16,14 @@ pub use properties::{
     complete_class, complete_properties, derive_classes, derive_properties, hover_class,
     hover_property, DefinedClass, DefinedClasses, DefinedProperties, DefinedProperty,
 };
+mod index;
+pub use index::{
+    in_folder, index_startup_workspaces, index_workspace, IndexWorkspace, INDEXED_EXTENSIONS,
+};
+mod remote;
+pub use remote::{fetch_remote_documents, invalidate_remote_document};
+mod convert;
+pub use convert::{convert, convert_code_action, convert_document, new_document_edit, Format};
 mod lov;
 pub use lov::{
     check_added_ontology_extract, fetch_lov_properties, init_onology_extractor, open_imports,
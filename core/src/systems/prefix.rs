@@ -1,7 +1,13 @@
-use std::{collections::HashSet, ops::Deref};
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Deref,
+};
 
 use bevy_ecs::prelude::*;
-use lsp_types::{CompletionItemKind, Diagnostic, DiagnosticSeverity, TextDocumentItem, TextEdit};
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CompletionItemKind, Diagnostic,
+    DiagnosticSeverity, NumberOrString, TextDocumentItem, TextEdit, WorkspaceEdit,
+};
 use tracing::{debug, instrument};
 
 use crate::prelude::*;
@@ -144,6 +150,73 @@ pub fn prefix_completion_helper(
     );
 }
 
+/// Looks up the namespace of a well known prefix, first in the locally available LOV
+/// vocabularies, then in the prefix.cc list.
+pub fn known_prefix(prefix: &str) -> Option<&'static str> {
+    lov::LOCAL_PREFIXES
+        .iter()
+        .find(|x| x.name == prefix)
+        .map(|x| x.location)
+        .or_else(|| {
+            HASHMAP
+                .iter()
+                .find(|(name, _)| *name == prefix)
+                .map(|(_, location)| *location)
+        })
+}
+
+/// Looks up the name of a well known namespace, the inverse of [`known_prefix`].
+pub fn known_prefix_name(namespace: &str) -> Option<&'static str> {
+    lov::LOCAL_PREFIXES
+        .iter()
+        .find(|x| x.location == namespace)
+        .map(|x| x.name)
+        .or_else(|| {
+            HASHMAP
+                .iter()
+                .find(|(_, location)| *location == namespace)
+                .map(|(name, _)| *name)
+        })
+}
+
+/// Iterates over all prefixed names that use a prefix not defined in `prefixes`.
+/// Yields the used prefix, spanned by the token.
+pub fn undefined_prefixes<'a>(
+    tokens: &'a [Spanned<Token>],
+    prefixes: &'a Prefixes,
+) -> impl Iterator<Item = Spanned<&'a str>> + 'a {
+    tokens.iter().flat_map(|t| match t.value() {
+        Token::PNameLN(x, _) => {
+            let pref = x.as_ref().map(|x| x.as_str()).unwrap_or("");
+            let found = prefixes.0.iter().any(|x| x.prefix == pref);
+            (!found).then(|| spanned(pref, t.span().clone()))
+        }
+        _ => None,
+    })
+}
+
+/// Diagnostic code of undefined prefix diagnostics, the undefined prefix is found in the data of
+/// the diagnostic.
+pub const UNDEFINED_PREFIX: &str = "undefined_prefix";
+
+/// Creates an undefined prefix [`Diagnostic`], with [`UNDEFINED_PREFIX`] as code.
+pub fn undefined_prefix_diagnostic(
+    prefix: &str,
+    range: lsp_types::Range,
+    message: impl Into<String>,
+) -> Diagnostic {
+    Diagnostic {
+        range,
+        severity: Some(DiagnosticSeverity::ERROR),
+        source: Some(String::from("SWLS")),
+        code: Some(NumberOrString::String(String::from(UNDEFINED_PREFIX))),
+        data: Some(serde_json::Value::String(prefix.to_string())),
+        message: message.into(),
+        related_information: None,
+        ..Default::default()
+    }
+}
+
 pub fn undefined_prefix(
     query: Query<
         (&Tokens, &Prefixes, &Wrapped<TextDocumentItem>, &RopeC),
@@ -152,29 +225,110 @@ pub fn undefined_prefix(
     mut client: ResMut<DiagnosticPublisher>,
 ) {
     for (tokens, prefixes, item, rope) in &query {
-        let mut diagnostics: Vec<Diagnostic> = Vec::new();
-        for t in &tokens.0 {
-            match t.value() {
-                Token::PNameLN(x, _) => {
-                    let pref = x.as_ref().map(|x| x.as_str()).unwrap_or("");
-                    let found = prefixes.0.iter().find(|x| x.prefix == pref).is_some();
-                    if !found {
-                        if let Some(range) = range_to_range(t.span(), &rope) {
-                            diagnostics.push(Diagnostic {
-                                range,
-                                severity: Some(DiagnosticSeverity::ERROR),
-                                source: Some(String::from("SWLS")),
-                                message: format!("Undefined prefix {}", pref),
-                                related_information: None,
-                                ..Default::default()
-                            })
-                        }
-                    }
-                }
-                _ => {}
-            }
+        let diagnostics: Vec<Diagnostic> = undefined_prefixes(&tokens.0, prefixes)
+            .flat_map(|pref| {
+                let range = range_to_range(pref.span(), &rope)?;
+                Some(undefined_prefix_diagnostic(
+                    pref.value(),
+                    range,
+                    format!("Undefined prefix {}", pref.value()),
+                ))
+            })
+            .collect();
+        let _ = client.publish(&item.0, diagnostics, UNDEFINED_PREFIX);
+    }
+}
+
+/// Edit that inserts `statement`, a line defining a prefix, after the base statement ending at
+/// `base_end` or at the start of the document.
+pub fn prefix_insert_edit(
+    statement: String,
+    base_end: Option<usize>,
+    rope: &RopeC,
+) -> Option<TextEdit> {
+    let Some(end) = base_end else {
+        let start = lsp_types::Position::new(0, 0);
+        return Some(TextEdit {
+            range: lsp_types::Range::new(start, start),
+            new_text: statement,
+        });
+    };
+
+    let line = rope.try_char_to_line(end).ok()?;
+    let column = end - rope.try_line_to_char(line).ok()?;
+    let rest_is_blank = rope
+        .line(line)
+        .chars()
+        .skip(column)
+        .all(char::is_whitespace);
+    if rest_is_blank && line + 1 < rope.len_lines() {
+        let start = lsp_types::Position::new(line as u32 + 1, 0);
+        return Some(TextEdit {
+            range: lsp_types::Range::new(start, start),
+            new_text: statement,
+        });
+    }
+
+    // Something follows the base statement on the same line, the prefix gets a line of its own
+    let start = offset_to_position(end, &rope.0)?;
+    Some(TextEdit {
+        range: lsp_types::Range::new(start, start),
+        new_text: format!("\n{}", statement),
+    })
+}
+
+/// Adds a quick fix to `request` for each well known prefix that is used but not defined inside
+/// the requested range.
+///
+/// `edits` returns the language specific edits that define the prefix (name, location).
+pub fn prefix_code_action_helper<'a>(
+    undefined: impl Iterator<Item = Spanned<&'a str>>,
+    request: &mut CodeActionRequest,
+    label: &Label,
+    rope: &RopeC,
+    mut edits: impl FnMut(&str, &str) -> Option<Vec<TextEdit>>,
+) {
+    let mut done = HashSet::new();
+    let overlaps =
+        |a: &lsp_types::Range, b: &lsp_types::Range| a.start <= b.end && b.start <= a.end;
+
+    for pref in undefined {
+        let Some(range) = range_to_range(pref.span(), &rope.0) else {
+            continue;
+        };
+        if !overlaps(&range, &request.range) || !done.insert(*pref.value()) {
+            continue;
         }
-        let _ = client.publish(&item.0, diagnostics, "undefined_prefix");
+
+        let Some(location) = known_prefix(pref.value()) else {
+            debug!("No known location for prefix {}", pref.value());
+            continue;
+        };
+        let Some(edits) = edits(pref.value(), location) else {
+            continue;
+        };
+
+        let data = serde_json::Value::String(pref.value().to_string());
+        let diagnostics: Vec<_> = request
+            .diagnostics
+            .iter()
+            .filter(|d| d.code == Some(NumberOrString::String(String::from(UNDEFINED_PREFIX))))
+            .filter(|d| d.data.as_ref() == Some(&data))
+            .cloned()
+            .collect();
+
+        let mut changes = HashMap::new();
+        changes.insert(label.0.clone(), edits);
+        request
+            .actions
+            .push(CodeActionOrCommand::CodeAction(CodeAction {
+                title: format!("Add prefix {}: <{}>", pref.value(), location),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: (!diagnostics.is_empty()).then_some(diagnostics),
+                edit: Some(WorkspaceEdit::new(changes)),
+                is_preferred: Some(true),
+                ..Default::default()
+            }));
     }
 }
 
@@ -218,3 +372,38 @@ pub fn defined_prefix_completion(
         req.0.extend(completions);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::Position;
+    use ropey::Rope;
+
+    use super::*;
+
+    fn insert(source: &str, base_end: Option<usize>) -> (Position, String) {
+        let rope = RopeC(Rope::from_str(source));
+        let edit =
+            prefix_insert_edit(String::from("PREFIX a: <b>\n"), base_end, &rope).expect("an edit");
+        (edit.range.start, edit.new_text)
+    }
+
+    #[test]
+    fn prefix_is_inserted_after_the_base() {
+        assert_eq!(
+            insert("SELECT * {}", None),
+            (Position::new(0, 0), String::from("PREFIX a: <b>\n"))
+        );
+
+        let source = "# comment\nBASE <x>\nSELECT * {}";
+        assert_eq!(
+            insert(source, Some(18)),
+            (Position::new(2, 0), String::from("PREFIX a: <b>\n"))
+        );
+
+        let source = "BASE <x> SELECT * WHERE {\n}";
+        assert_eq!(
+            insert(source, Some(8)),
+            (Position::new(0, 8), String::from("\nPREFIX a: <b>\n"))
+        );
+    }
+}
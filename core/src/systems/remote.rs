@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+use bevy_ecs::{prelude::*, world::CommandQueue};
+use lsp_types::Url;
+use tracing::{error, info};
+
+use crate::prelude::*;
+
+/// Reads `file:` (and other non HTTP) urls with [`Fs`], HTTP urls are fetched with the client.
+async fn fetch_remote<C: Client>(url: &Url, client: &C, fs: &Fs) -> Option<String> {
+    if !url.scheme().starts_with("http") {
+        return fs.0.read_file(url).await;
+    }
+
+    let headers = HashMap::from([(
+        String::from("Accept"),
+        String::from("application/ld+json, application/json"),
+    )]);
+    match client.fetch(url.as_str(), &headers).await {
+        Ok(resp) if resp.status == 200 => Some(resp.body),
+        Ok(resp) => {
+            error!("Fetch ({}) failed status {}", url, resp.status);
+            None
+        }
+        Err(e) => {
+            error!("Fetch ({}) failed {:?}", url, e);
+            None
+        }
+    }
+}
+
+/// Marks the documents that depend on `url` as changed, so that they are parsed again.
+fn touch_dependents(world: &mut World, url: &Url) -> bool {
+    let mut found = false;
+    let mut query = world.query::<&mut RemoteDocuments>();
+    for mut documents in query.iter_mut(world) {
+        if documents.contains(url) {
+            documents.set_changed();
+            found = true;
+        }
+    }
+    found
+}
+
+/// Forgets the fetched content of a `file:` document that changed, documents that depend on it
+/// fetch it again.
+///
+/// Returns true when a document depends on it and the world should be parsed again.
+pub fn invalidate_remote_document(world: &mut World, url: &Url) -> bool {
+    if url.scheme() != "file" || !world.resource::<RequestedDocuments>().contains(url) {
+        return false;
+    }
+
+    world.resource_mut::<RequestedDocuments>().remove(url);
+    world.resource_mut::<RemoteDocumentContents>().remove(url);
+    touch_dependents(world, url)
+}
+
+/// Fetches [`RemoteDocuments`] that are not requested yet into [`RemoteDocumentContents`].
+///
+/// Documents that depend on a fetched document are marked as changed and parsed again when it
+/// arrives. Failed fetches are tried again the next time a dependent document changes.
+pub fn fetch_remote_documents<C: Client + Resource>(
+    query: Query<&RemoteDocuments, Changed<RemoteDocuments>>,
+    mut requested: ResMut<RequestedDocuments>,
+    sender: Res<CommandSender>,
+    fs: Res<Fs>,
+    client: Res<C>,
+) {
+    for url in query.iter().flat_map(|x| x.0.iter()) {
+        if !requested.insert(url.clone()) {
+            continue;
+        }
+
+        let url = url.clone();
+        let fs = fs.clone();
+        let sender = sender.clone();
+        let c = client.as_ref().clone();
+        client.spawn(async move {
+            let content = fetch_remote(&url, &c, &fs).await;
+            info!("Fetched {} (found {})", url, content.is_some());
+
+            let mut command_queue = CommandQueue::default();
+            command_queue.push(move |world: &mut World| {
+                let Some(content) = content else {
+                    world.resource_mut::<RequestedDocuments>().remove(&url);
+                    return;
+                };
+                world
+                    .resource_mut::<RemoteDocumentContents>()
+                    .insert(url.clone(), content);
+
+                touch_dependents(world, &url);
+                world.run_schedule(ParseLabel);
+            });
+            let _ = sender.unbounded_send(command_queue);
+        });
+    }
+}
@@ -18,5 +18,11 @@ pub trait FsTrait: Send + Sync + 'static + std::fmt::Debug {
         Some(url)
     }
     async fn read_file(&self, url: &lsp_types::Url) -> Option<String>;
+    /// Lists the entries of a directory, directories end with a `/`.
+    ///
+    /// Returns `None` when the platform cannot list directories.
+    async fn read_dir(&self, _url: &lsp_types::Url) -> Option<Vec<lsp_types::Url>> {
+        None
+    }
     async fn write_file(&self, url: &lsp_types::Url, content: &str) -> Option<()>;
 }
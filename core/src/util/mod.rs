@@ -6,7 +6,7 @@ use std::{
 use lsp_types::{Location, Position, Range};
 use ropey::Rope;
 
-use crate::Label;
+use crate::{components::SourceChange, Label};
 
 // pub mod cache;
 pub mod fs;
@@ -61,6 +61,82 @@ pub fn position_to_offset(position: Position, rope: &Rope) -> Option<usize> {
         None
     }
 }
+
+fn position_to_char(position: Position, rope: &Rope) -> Option<usize> {
+    let line = position.line as usize;
+    if line == rope.len_lines() {
+        return Some(rope.len_chars());
+    }
+    let line_offset = rope.try_line_to_char(line).ok()?;
+    let line_length = rope.get_line(line)?.len_chars();
+    Some(line_offset + (position.character as usize).min(line_length))
+}
+
+/// Applies an incremental [`lsp_types::TextDocumentContentChangeEvent`] to `rope`.
+///
+/// Returns the replaced byte range (relative to the rope before the change) and the byte length
+/// of the inserted text. A change without range replaces the complete rope.
+pub fn apply_change(
+    rope: &mut Rope,
+    change: &lsp_types::TextDocumentContentChangeEvent,
+) -> Option<(std::ops::Range<usize>, usize)> {
+    let (start, end) = match change.range {
+        Some(range) => (
+            position_to_char(range.start, rope)?,
+            position_to_char(range.end, rope)?,
+        ),
+        None => (0, rope.len_chars()),
+    };
+    if start > end {
+        return None;
+    }
+
+    let byte_range = rope.char_to_byte(start)..rope.char_to_byte(end);
+    rope.remove(start..end);
+    rope.insert(start, &change.text);
+
+    Some((byte_range, change.text.len()))
+}
+
+/// Applies the content changes of a DidChange notification to `rope`.
+///
+/// Returns the merged [`SourceChange`], or [`None`] when the complete source should be tokenized
+/// again. Changes before the last full text change are overwritten by it and skipped. A change
+/// that does not fit the rope means that the client and the server disagree on the text, it is
+/// dropped and logged, and the complete source is tokenized again.
+pub fn apply_changes(
+    rope: &mut Rope,
+    changes: &[lsp_types::TextDocumentContentChangeEvent],
+) -> Option<SourceChange> {
+    let first = changes
+        .iter()
+        .rposition(|x| x.range.is_none())
+        .unwrap_or_default();
+
+    let mut change: Option<SourceChange> = None;
+    let mut full = changes.get(first).is_some_and(|x| x.range.is_none());
+    for content_change in &changes[first..] {
+        let (range, inserted) = match apply_change(rope, content_change) {
+            Some(x) => x,
+            None => {
+                tracing::error!(
+                    "Change {:?} does not fit the document, the document is out of sync",
+                    content_change.range
+                );
+                full = true;
+                continue;
+            }
+        };
+        let this = SourceChange::new(range, inserted);
+        change = Some(match change {
+            Some(prev) => prev.merge(this),
+            None => this,
+        });
+    }
+
+    change.filter(|_| !full)
+}
+
 pub fn offsets_to_range(start: usize, end: usize, rope: &Rope) -> Option<Range> {
     let start = offset_to_position(start, rope)?;
     let end = offset_to_position(end, rope)?;
@@ -178,3 +254,43 @@ pub fn token_to_location(
         uri: label.0.clone(),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::TextDocumentContentChangeEvent;
+
+    use super::*;
+
+    fn change(range: Option<Range>, text: &str) -> TextDocumentContentChangeEvent {
+        TextDocumentContentChangeEvent {
+            range,
+            range_length: None,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn failed_changes_are_dropped() {
+        let mut rope = Rope::from_str("ab\ncd");
+        let out_of_range = Range::new(Position::new(5, 0), Position::new(5, 0));
+        let first = Range::new(Position::new(0, 0), Position::new(0, 1));
+        let changes = [change(Some(out_of_range), "!"), change(Some(first), "x")];
+
+        assert_eq!(apply_changes(&mut rope, &changes), None);
+        assert_eq!(rope.to_string(), "xb\ncd");
+    }
+
+    #[test]
+    fn changes_before_full_text_are_skipped() {
+        let mut rope = Rope::from_str("ab");
+        let first = Range::new(Position::new(0, 0), Position::new(0, 1));
+        let changes = [
+            change(Some(first), "x"),
+            change(None, "cd"),
+            change(Some(first), "e"),
+        ];
+
+        assert_eq!(apply_changes(&mut rope, &changes), None);
+        assert_eq!(rope.to_string(), "ed");
+    }
+}
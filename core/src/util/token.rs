@@ -81,6 +81,95 @@ pub fn get_current_token(
     }
 }
 
+/// Tokenizes only the region of `source` affected by `change`, reusing the `old` tokens outside
+/// of that region.
+///
+/// Returns [`None`] when the region cannot be tokenized on its own (for example when an edit opens
+/// a multiline string), the complete source should then be tokenized again.
+pub fn retokenize<E>(
+    old: &[Spanned<Token>],
+    change: &SourceChange,
+    source: &str,
+    tokenize: impl Fn(&str) -> (Vec<Spanned<Token>>, Vec<E>),
+) -> Option<Vec<Spanned<Token>>> {
+    // Tokens that end before the change, with at least one byte in between, are untouched
+    let before = old
+        .iter()
+        .take_while(|t| t.span().end < change.old.start)
+        .count();
+    // Tokens that start after the change are only moved
+    let after = old[before..]
+        .iter()
+        .position(|t| t.span().start > change.old.end)
+        .map(|x| x + before)
+        .unwrap_or(old.len());
+
+    let start = before
+        .checked_sub(1)
+        .map(|i| old[i].span().end)
+        .unwrap_or_default();
+
+    // The first moved token is tokenized again, it should come out unchanged, otherwise the edit
+    // leaks into the following tokens.
+    let anchor = old.get(after);
+    let end = anchor
+        .map(|t| change.shift(t.span().end))
+        .unwrap_or(source.len());
+
+    let (tokens, errors) = tokenize(source.get(start..end)?);
+    if !errors.is_empty() {
+        return None;
+    }
+
+    if let Some(anchor) = anchor {
+        let last = tokens.last()?;
+        let expected = change.shift(anchor.span().start)..change.shift(anchor.span().end);
+        if last.value() != anchor.value()
+            || last.span().start + start != expected.start
+            || last.span().end + start != expected.end
+        {
+            return None;
+        }
+    }
+
+    let skip = anchor.is_some() as usize;
+    let mut out = Vec::with_capacity(before + tokens.len() + old.len() - after);
+    out.extend(old[..before].iter().cloned());
+    out.extend(
+        tokens
+            .into_iter()
+            .map(|Spanned(t, s)| spanned(t, s.start + start..s.end + start)),
+    );
+    out.extend(old[after + skip..].iter().map(|t| {
+        spanned(
+            t.value().clone(),
+            change.shift(t.span().start)..change.shift(t.span().end),
+        )
+    }));
+
+    Some(out)
+}
+
+/// Tokenizes `source`, only the region of `change` is tokenized again with [`retokenize`] when
+/// the `old` tokens were error free.
+pub fn tokenize_source<E>(
+    source: &str,
+    change: Option<&SourceChange>,
+    old: Option<(&Tokens, &Errors<E>)>,
+    tokenize: impl Fn(&str) -> (Vec<Spanned<Token>>, Vec<E>),
+) -> (Vec<Spanned<Token>>, Vec<E>) {
+    if let (Some(change), Some((tokens, errors))) = (change, old) {
+        if errors.is_empty() {
+            if let Some(tokens) = retokenize(&tokens.0, change, source, &tokenize) {
+                debug!("retokenized {} tokens", tokens.len());
+                return (tokens, Vec::new());
+            }
+        }
+    }
+
+    tokenize(source)
+}
+
 pub trait Membered: Sized + 'static {
     const ITEMS: &'static [Self];
 
@@ -183,7 +272,7 @@ derive_enum!(
 mod tests {
     use std::str::FromStr as _;
 
-    use super::SparqlExpr2;
+    use super::*;
 
     #[test]
     fn test_sparql_expr_2() {
@@ -193,6 +282,63 @@ mod tests {
         let or = SparqlExpr2::from_str("+");
         assert_eq!(or, Ok(SparqlExpr2::Plus));
     }
+
+    fn words(source: &str) -> (Vec<Spanned<Token>>, Vec<()>) {
+        let mut out = Vec::new();
+        let mut start = None;
+        for (i, c) in source.char_indices().chain([(source.len(), ' ')]) {
+            match (c.is_whitespace(), start) {
+                (true, Some(s)) => {
+                    out.push(spanned(Token::Variable(source[s..i].to_string()), s..i));
+                    start = None;
+                }
+                (false, None) => start = Some(i),
+                _ => {}
+            }
+        }
+        (out, vec![])
+    }
+
+    fn change(source: &str, range: std::ops::Range<usize>, text: &str) -> (String, SourceChange) {
+        let mut new = source.to_string();
+        new.replace_range(range.clone(), text);
+        (new, SourceChange::new(range, text.len()))
+    }
+
+    #[test]
+    fn retokenize_equals_full_tokenize() {
+        let source = "foo bar baz\nqux quux";
+        let (old, _) = words(source);
+
+        for (range, text) in [
+            (5..5, "x"),
+            (4..7, "b"),
+            (3..4, ""),
+            (0..0, "pre "),
+            (source.len()..source.len(), " end"),
+            (8..16, "a b c"),
+        ] {
+            let (new, change) = change(source, range, text);
+            let expected = words(&new).0;
+            let tokens = retokenize(&old, &change, &new, words).expect("retokenizes");
+            assert_eq!(tokens, expected);
+            let spans: Vec<_> = tokens.iter().map(|x| x.span().clone()).collect();
+            let expected_spans: Vec<_> = expected.iter().map(|x| x.span().clone()).collect();
+            assert_eq!(spans, expected_spans);
+        }
+    }
+
+    #[test]
+    fn merge_source_changes() {
+        let source = "foo bar baz";
+        let (first, c1) = change(source, 4..7, "b");
+        let (second, c2) = change(&first, 0..1, "xy");
+        let merged = c1.merge(c2);
+
+        let mut expected = source.to_string();
+        expected.replace_range(merged.old.clone(), &second[merged.new.clone()]);
+        assert_eq!(expected, second);
+    }
 }
 
 pub mod semantic_token {
@@ -219,6 +365,9 @@ derive_enum!(
         Times @ "*",
         Divide @ "/",
         Exclamation @ "!",
+        Pipe @ "|",
+        Hat @ "^",
+        Question @ "?",
     }
 );
 
@@ -335,6 +484,8 @@ derive_enum!(
         Add => "ADD",
         Move => "MOVE",
         Copy => "COPY",
+        Into => "INTO",
+        To => "TO",
         Insert => "INSERT",
         Data => "DATA",
         Delete => "DELETE",
@@ -348,9 +499,139 @@ derive_enum!(
         Undef => "UNDEF",
         Minus => "MINUS",
         Filter => "FILTER",
+        Separator => "SEPARATOR",
     }
 );
 
+/// Argument list and description of a SPARQL built-in, as written in the SPARQL specification.
+///
+/// Optional parameters end with `?` and variadic parameters with `...`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct BuiltinSignature {
+    pub parameters: &'static [&'static str],
+    pub documentation: &'static str,
+}
+
+impl BuiltinSignature {
+    const fn new(parameters: &'static [&'static str], documentation: &'static str) -> Self {
+        Self {
+            parameters,
+            documentation,
+        }
+    }
+
+    /// The signature as it is written, for example `STRSTARTS(str, prefix)`.
+    pub fn label(&self, name: &str) -> String {
+        format!("{}({})", name, self.parameters.join(", "))
+    }
+
+    /// Index of the parameter that the argument at `index` is bound to, variadic parameters
+    /// accept all remaining arguments.
+    pub fn active_parameter(&self, index: usize) -> Option<usize> {
+        if index < self.parameters.len() {
+            return Some(index);
+        }
+        self.parameters
+            .last()
+            .filter(|x| x.ends_with("..."))
+            .map(|_| self.parameters.len() - 1)
+    }
+}
+
+impl SparqlCall {
+    pub fn signature(&self) -> BuiltinSignature {
+        use SparqlCall::*;
+        let (parameters, documentation): (&'static [&'static str], _) = match self {
+            Str => (&["term"], "Returns the lexical form of a literal or the codepoint representation of an IRI."),
+            Lang => (&["literal"], "Returns the language tag of a literal, or the empty string when it has none."),
+            LangMatches => (&["tag", "range"], "Returns true if the language tag matches the language range, as defined by RFC 4647 basic filtering. The range `*` matches any non-empty tag."),
+            LangDir => (&["literal"], "Returns the base direction (`ltr` or `rtl`) of a directional language-tagged literal, or the empty string."),
+            Datatype => (&["literal"], "Returns the datatype IRI of a literal, `rdf:langString` for literals with a language tag."),
+            Bound => (&["?var"], "Returns true if the variable is bound to a value in the current solution."),
+            Iri | Uri => (&["str"], "Returns an IRI built from a string, resolved against the base IRI. IRIs are returned unchanged."),
+            Bnode => (&["str?"], "Returns a fresh blank node, or the same blank node for the same string within a solution."),
+            Rand => (&[], "Returns a pseudo-random number between 0 (inclusive) and 1 (exclusive)."),
+            Abs => (&["number"], "Returns the absolute value of a number."),
+            Ceil => (&["number"], "Returns the smallest number without a fractional part that is not less than the argument."),
+            Floor => (&["number"], "Returns the largest number without a fractional part that is not greater than the argument."),
+            Round => (&["number"], "Returns the number without a fractional part that is closest to the argument, halves are rounded towards positive infinity."),
+            Concat => (&["str..."], "Returns the concatenation of the string arguments, keeping a language tag that all arguments share."),
+            StrLen => (&["str"], "Returns the number of characters in a string."),
+            Ucase => (&["str"], "Returns the string converted to upper case, keeping its language tag or datatype."),
+            Lcase => (&["str"], "Returns the string converted to lower case, keeping its language tag or datatype."),
+            EncodeForUri => (&["str"], "Returns the string with all characters that are not unreserved in IRIs percent-encoded."),
+            Contains => (&["str", "substring"], "Returns true if the substring occurs in the string."),
+            StrStarts => (&["str", "prefix"], "Returns true if the string starts with the prefix."),
+            StrEnds => (&["str", "suffix"], "Returns true if the string ends with the suffix."),
+            StrBefore => (&["str", "separator"], "Returns the part of the string before the first occurrence of the separator, or the empty string."),
+            StrAfter => (&["str", "separator"], "Returns the part of the string after the first occurrence of the separator, or the empty string."),
+            Year => (&["dateTime"], "Returns the year of an `xsd:dateTime` as an integer."),
+            Month => (&["dateTime"], "Returns the month of an `xsd:dateTime` as an integer."),
+            Day => (&["dateTime"], "Returns the day of an `xsd:dateTime` as an integer."),
+            Hours => (&["dateTime"], "Returns the hours of an `xsd:dateTime` as an integer."),
+            Minutes => (&["dateTime"], "Returns the minutes of an `xsd:dateTime` as an integer."),
+            Seconds => (&["dateTime"], "Returns the seconds of an `xsd:dateTime` as a decimal."),
+            Timezone => (&["dateTime"], "Returns the timezone of an `xsd:dateTime` as an `xsd:dayTimeDuration`, an error when there is none."),
+            Tz => (&["dateTime"], "Returns the timezone of an `xsd:dateTime` as a string, the empty string when there is none."),
+            Now => (&[], "Returns the `xsd:dateTime` at which the query is executed, the same for the whole query."),
+            Uuid => (&[], "Returns a fresh IRI from the UUID URN scheme."),
+            StrUuid => (&[], "Returns a string containing a fresh UUID."),
+            Md5 => (&["str"], "Returns the MD5 checksum of the string as a hex encoded string."),
+            Sha1 => (&["str"], "Returns the SHA1 checksum of the string as a hex encoded string."),
+            Sha256 => (&["str"], "Returns the SHA256 checksum of the string as a hex encoded string."),
+            Sha384 => (&["str"], "Returns the SHA384 checksum of the string as a hex encoded string."),
+            Sha512 => (&["str"], "Returns the SHA512 checksum of the string as a hex encoded string."),
+            Coalesce => (&["expr..."], "Returns the value of the first expression that evaluates without error."),
+            If => (&["condition", "then", "else"], "Evaluates `then` when the condition is true and `else` otherwise."),
+            StrLang => (&["str", "tag"], "Returns a literal with the lexical form of the string and the language tag."),
+            StrLangDir => (&["str", "tag", "direction"], "Returns a literal with the lexical form of the string, the language tag and the base direction."),
+            StrDt => (&["str", "datatype"], "Returns a literal with the lexical form of the string and the datatype IRI."),
+            SameTerm => (&["term", "term"], "Returns true if both arguments are the same RDF term."),
+            IsIri | IsUri => (&["term"], "Returns true if the term is an IRI."),
+            IsBlank => (&["term"], "Returns true if the term is a blank node."),
+            IsLiteral => (&["term"], "Returns true if the term is a literal."),
+            IsNumeric => (&["term"], "Returns true if the term is a numeric literal with a valid lexical form."),
+            HasLang => (&["literal"], "Returns true if the literal has a language tag."),
+            HasLangDir => (&["literal"], "Returns true if the literal has a base direction."),
+            IsTriple => (&["term"], "Returns true if the term is a triple term."),
+            Triple => (&["subject", "predicate", "object"], "Returns the triple term with the given subject, predicate and object."),
+            Subject => (&["triple"], "Returns the subject of a triple term."),
+            Predicate => (&["triple"], "Returns the predicate of a triple term."),
+            Object => (&["triple"], "Returns the object of a triple term."),
+        };
+        BuiltinSignature::new(parameters, documentation)
+    }
+}
+
+impl SparqlAggregate {
+    pub fn signature(&self) -> BuiltinSignature {
+        use SparqlAggregate::*;
+        let (parameters, documentation): (&'static [&'static str], _) = match self {
+            Count => (&["DISTINCT? expr | *"], "Counts the solutions in the group where the expression is bound, or all solutions with `*`."),
+            Sum => (&["DISTINCT? expr"], "Returns the sum of the numeric values in the group."),
+            Min => (&["DISTINCT? expr"], "Returns the smallest value in the group."),
+            Max => (&["DISTINCT? expr"], "Returns the largest value in the group."),
+            Avg => (&["DISTINCT? expr"], "Returns the average of the numeric values in the group."),
+            Sample => (&["DISTINCT? expr"], "Returns an arbitrary value from the group."),
+            GroupConcat => (&["DISTINCT? expr", "SEPARATOR = separator?"], "Returns the string values in the group joined by the separator, a single space by default. The separator follows a `;`."),
+        };
+        BuiltinSignature::new(parameters, documentation)
+    }
+}
+
+impl SparqlKeyword {
+    /// Signature of the keywords that are called like functions, `REGEX`, `SUBSTR` and `REPLACE`.
+    pub fn signature(&self) -> Option<BuiltinSignature> {
+        let (parameters, documentation): (&'static [&'static str], _) = match self {
+            SparqlKeyword::Regex => (&["text", "pattern", "flags?"], "Returns true if the text matches the XPath regular expression pattern, with flags like `i` for case insensitive matching."),
+            SparqlKeyword::Substr => (&["str", "start", "length?"], "Returns the part of the string starting at the 1-based start position, of the given length or until the end."),
+            SparqlKeyword::Replace => (&["text", "pattern", "replacement", "flags?"], "Returns the text where every match of the XPath regular expression pattern is replaced, `$1` refers to a captured group."),
+            _ => return None,
+        };
+        Some(BuiltinSignature::new(parameters, documentation))
+    }
+}
+
 #[derive(
     Clone, PartialEq, Ord, PartialOrd, Eq, Hash, Debug, EnumIntoGetters, EnumIsA, EnumToGetters,
 )]
@@ -390,6 +671,14 @@ pub enum Token {
     BracketOpen,
     /// )
     BracketClose,
+    /// <<
+    TripleOpen,
+    /// >>
+    TripleClose,
+    /// {|
+    AnnotationOpen,
+    /// |}
+    AnnotationClose,
 
     /// ^^
     DataTypeDelim,
@@ -427,6 +716,8 @@ pub enum Token {
     Colon,
     /// null
     Null,
+    /// - (YAML block sequence entry)
+    Dash,
 
     Invalid(String),
 }
@@ -464,6 +755,10 @@ impl TokenTrait for Token {
             Token::Str(_, _) => Some(lsp_types::SemanticTokenType::STRING),
             Token::Comment(_) => Some(lsp_types::SemanticTokenType::COMMENT),
             Token::Variable(_) => Some(lsp_types::SemanticTokenType::VARIABLE),
+            Token::TripleOpen
+            | Token::TripleClose
+            | Token::AnnotationOpen
+            | Token::AnnotationClose => Some(lsp_types::SemanticTokenType::OPERATOR),
             _ => None,
         }
     }
@@ -519,6 +814,8 @@ pub enum StringStyle {
     SingleLong,
     /// '...'
     Single,
+    /// Unquoted, YAML plain scalars
+    Plain,
 }
 
 impl StringStyle {
@@ -528,6 +825,7 @@ impl StringStyle {
             StringStyle::Double => "\"",
             StringStyle::SingleLong => "'''",
             StringStyle::Single => "'",
+            StringStyle::Plain => "",
         }
     }
 }
@@ -549,6 +847,10 @@ impl std::fmt::Display for Token {
             Token::SqClose => write!(f, "']'"),
             Token::BracketOpen => write!(f, "'('"),
             Token::BracketClose => write!(f, "')'"),
+            Token::TripleOpen => write!(f, "'<<'"),
+            Token::TripleClose => write!(f, "'>>'"),
+            Token::AnnotationOpen => write!(f, "'{{|'"),
+            Token::AnnotationClose => write!(f, "'|}}'"),
             Token::DataTypeDelim => write!(f, "'^^'"),
             Token::Stop => write!(f, "'.'"),
             Token::PredicateSplit => write!(f, "';'"),
@@ -568,6 +870,7 @@ impl std::fmt::Display for Token {
             Token::CurlClose => write!(f, "'}}'"),
             Token::Colon => write!(f, "':'"),
             Token::Null => write!(f, "'null'"),
+            Token::Dash => write!(f, "'-'"),
             Token::SparqlExpr(_) => write!(f, "sparql expr token"),
             Token::SparqlKeyword(_) => write!(f, "sparql keyword"),
             Token::SparqlCall(_) => write!(f, "sparql call"),
@@ -5,7 +5,7 @@ use derive_more::{AsMut, AsRef, Deref, DerefMut};
 use sophia_api::{
     prelude::{Any, Dataset},
     quad::Quad,
-    term::{matcher::TermMatcher, BnodeId, GraphName, IriRef, Term, TermKind},
+    term::{matcher::TermMatcher, BnodeId, GraphName, IriRef, LanguageTag, Term, TermKind},
     MownStr,
 };
 use tracing::{debug, instrument};
@@ -147,15 +147,17 @@ pub struct MyQuad<'a> {
     pub subject: MyTerm<'a>,
     pub predicate: MyTerm<'a>,
     pub object: MyTerm<'a>,
+    /// Name of the graph this quad belongs to, `None` is the default graph.
+    pub graph: Option<MyTerm<'a>>,
     pub span: std::ops::Range<usize>,
 }
 impl<'a> std::fmt::Display for MyQuad<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{} {} {}. # {:?}",
-            self.subject, self.predicate, self.object, self.span
-        )
+        write!(f, "{} {} {}", self.subject, self.predicate, self.object)?;
+        if let Some(graph) = &self.graph {
+            write!(f, " {}", graph)?;
+        }
+        write!(f, ". # {:?}", self.span)
     }
 }
 
@@ -165,6 +167,7 @@ impl<'a> MyQuad<'a> {
             subject: self.subject.to_owned(),
             predicate: self.predicate.to_owned(),
             object: self.object.to_owned(),
+            graph: self.graph.as_ref().map(|g| g.to_owned()),
             span: self.span.clone(),
         }
     }
@@ -186,19 +189,28 @@ impl<'a> Quad for MyQuad<'a> {
     }
 
     fn g(&self) -> GraphName<sophia_api::quad::QBorrowTerm<Self>> {
-        None
+        self.graph.as_ref().map(|g| g.borrow_term())
     }
 
     fn to_spog(self) -> sophia_api::quad::Spog<Self::Term> {
-        ([self.subject, self.predicate, self.object], None)
+        ([self.subject, self.predicate, self.object], self.graph)
     }
 }
 // pub type MyQuad<'a> = ([MyTerm<'a>; 3], GraphName<MyTerm<'a>>);
 
+/// Datatype of literals without datatype or language tag.
+pub const XSD_STRING: &str = "http://www.w3.org/2001/XMLSchema#string";
+/// Datatype of literals with a language tag.
+pub const RDF_LANG_STRING: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#langString";
+
 #[derive(Debug, Clone, Eq)]
 pub struct MyTerm<'a> {
     pub value: Cow<'a, str>,
     ty: Option<TermKind>,
+    /// Datatype of a literal, `None` for `xsd:string` and language-tagged strings.
+    datatype: Option<Cow<'a, str>>,
+    /// Language tag of a literal.
+    language: Option<Cow<'a, str>>,
     pub span: std::ops::Range<usize>,
 }
 
@@ -207,13 +219,18 @@ impl Hash for MyTerm<'_> {
         // Ignore span
         self.value.hash(state);
         self.ty.hash(state);
+        self.datatype.hash(state);
+        self.language.hash(state);
     }
 }
 
 impl PartialEq for MyTerm<'_> {
     fn eq(&self, other: &Self) -> bool {
         // Ignore span
-        other.value == self.value && other.ty == self.ty
+        other.value == self.value
+            && other.ty == self.ty
+            && other.datatype == self.datatype
+            && other.language == self.language
     }
 }
 
@@ -221,7 +238,11 @@ impl<'a> std::fmt::Display for MyTerm<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self.kind() {
             TermKind::Iri => write!(f, "<{}>", self.value),
-            TermKind::Literal => write!(f, "\"{}\"", self.value),
+            TermKind::Literal => match (&self.language, &self.datatype) {
+                (Some(language), _) => write!(f, "\"{}\"@{}", self.value, language),
+                (None, Some(datatype)) => write!(f, "\"{}\"^^<{}>", self.value, datatype),
+                (None, None) => write!(f, "\"{}\"", self.value),
+            },
             TermKind::BlankNode => write!(f, "_:{}", self.value),
             TermKind::Triple => write!(f, "<{}>", self.value),
             TermKind::Variable => write!(f, "?{}", self.value),
@@ -235,6 +256,8 @@ impl<'a> MyTerm<'a> {
         MyTerm {
             value,
             ty: self.ty.clone(),
+            datatype: self.datatype.as_ref().map(|x| Cow::Owned(x.to_string())),
+            language: self.language.as_ref().map(|x| Cow::Owned(x.to_string())),
             span: self.span.clone(),
         }
     }
@@ -242,6 +265,8 @@ impl<'a> MyTerm<'a> {
         Self {
             value: value.into(),
             ty: TermKind::Variable.into(),
+            datatype: None,
+            language: None,
             span,
         }
     }
@@ -249,6 +274,8 @@ impl<'a> MyTerm<'a> {
         Self {
             value: value.into(),
             ty: TermKind::Iri.into(),
+            datatype: None,
+            language: None,
             span,
         }
     }
@@ -256,6 +283,8 @@ impl<'a> MyTerm<'a> {
         Self {
             value: value.into(),
             ty: TermKind::BlankNode.into(),
+            datatype: None,
+            language: None,
             span,
         }
     }
@@ -263,6 +292,8 @@ impl<'a> MyTerm<'a> {
         Self {
             value: value.into(),
             ty: TermKind::Literal.into(),
+            datatype: None,
+            language: None,
             span,
         }
     }
@@ -271,13 +302,46 @@ impl<'a> MyTerm<'a> {
         Self {
             value: Cow::default(),
             ty: None,
+            datatype: None,
+            language: None,
             span,
         }
     }
 
+    /// Sets the datatype of a literal, `xsd:string` is the default and is not stored.
+    pub fn with_datatype<T: Into<Cow<'a, str>>>(mut self, datatype: T) -> Self {
+        let datatype = datatype.into();
+        self.datatype = (datatype != XSD_STRING).then_some(datatype);
+        self
+    }
+
+    /// Sets the language tag of a literal.
+    pub fn with_language<T: Into<Cow<'a, str>>>(mut self, language: T) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
     pub fn as_str(&'a self) -> &'a str {
         &self.value
     }
+
+    /// Datatype IRI of a literal, `rdf:langString` for language-tagged strings and `xsd:string`
+    /// for simple literals.
+    pub fn datatype_iri(&self) -> Option<&str> {
+        if !self.is_literal() {
+            return None;
+        }
+        Some(match (&self.language, &self.datatype) {
+            (Some(_), _) => RDF_LANG_STRING,
+            (None, Some(datatype)) => datatype,
+            (None, None) => XSD_STRING,
+        })
+    }
+
+    /// Language tag of a literal.
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
 }
 
 impl<'a> Term for MyTerm<'a> {
@@ -309,11 +373,13 @@ impl<'a> Term for MyTerm<'a> {
     }
 
     fn datatype(&self) -> Option<sophia_api::term::IriRef<sophia_api::MownStr>> {
-        None
+        self.datatype_iri()
+            .map(|x| IriRef::new_unchecked(MownStr::from_str(x)))
     }
 
     fn language_tag(&self) -> Option<sophia_api::term::LanguageTag<sophia_api::MownStr>> {
-        None
+        self.language()
+            .map(|x| LanguageTag::new_unchecked(MownStr::from_str(x)))
     }
 
     fn variable(&self) -> Option<sophia_api::term::VarName<sophia_api::MownStr>> {
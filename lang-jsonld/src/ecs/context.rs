@@ -0,0 +1,208 @@
+use std::{collections::HashMap, ops::Range, sync::Arc};
+
+use bevy_ecs::prelude::*;
+use lsp_core::{components::*, feature::goto_definition::GotoDefinitionRequest, prelude::*};
+use lsp_types::{CompletionItemKind, Location, TextEdit};
+use ropey::Rope;
+
+use crate::lang::{
+    context::{members, ActiveContext, RemoteContexts, TermDefinition, KEYWORDS},
+    parser::{parse, Json},
+    tokenizer::tokenize,
+};
+
+/// [`Component`] with the context of a JSON-LD document, the context of its top-level nodes.
+#[derive(Component, Debug)]
+pub struct JsonLdContext(pub ActiveContext);
+
+/// [`Resource`] with the remote contexts parsed from [`RemoteDocumentContents`], with the source
+/// of each document to locate the terms it defines.
+#[derive(Resource, Debug, Default)]
+pub struct LoadedContexts {
+    pub contexts: Arc<RemoteContexts>,
+    pub sources: HashMap<String, Rope>,
+}
+
+/// Parses fetched remote documents, keeping their `@context` entry.
+pub fn load_remote_contexts(
+    contents: Res<RemoteDocumentContents>,
+    mut loaded: ResMut<LoadedContexts>,
+) {
+    if !contents.is_changed() {
+        return;
+    }
+
+    // Documents that are no longer fetched changed and are loaded again when they arrive
+    let fetched = |url: &String| contents.keys().any(|x| x.as_str() == url);
+    let mut contexts = loaded.contexts.as_ref().clone();
+    contexts.0.retain(|url, _| fetched(url));
+    loaded.sources.retain(|url, _| fetched(url));
+
+    for (url, content) in contents.iter() {
+        if loaded.sources.contains_key(url.as_str()) {
+            continue;
+        }
+
+        let (tokens, _) = tokenize(content);
+        let (json, _) = parse(content, tokens);
+        if let Json::Object(ms) = json.value() {
+            let context = members(ms)
+                .find(|(key, _)| *key.value() == "@context")
+                .and_then(|(_, value)| value);
+            if let Some(context) = context {
+                contexts.0.insert(url.to_string(), context.clone());
+            }
+        }
+        loaded
+            .sources
+            .insert(url.to_string(), Rope::from_str(content));
+    }
+    loaded.contexts = Arc::new(contexts);
+}
+
+/// Whether `span` is the key of an object member in `json`.
+fn is_key(json: &Spanned<Json>, span: &Range<usize>) -> bool {
+    match json.value() {
+        Json::Array(xs) => xs.iter().any(|x| is_key(x, span)),
+        Json::Object(ms) => ms
+            .iter()
+            .any(|m| m.field().span() == span || m.json_value().is_some_and(|v| is_key(v, span))),
+        _ => false,
+    }
+}
+
+fn describe(term: &str, definition: &TermDefinition) -> String {
+    let mut out = format!("{}: {}", term, definition.iri.as_deref().unwrap_or("null"));
+    if let Some(ty) = &definition.type_mapping {
+        out += &format!(", @type {}", ty);
+    }
+    if !definition.container.is_empty() {
+        out += &format!(", @container {}", definition.container.join(" "));
+    }
+    if definition.reverse {
+        out += ", @reverse";
+    }
+    if let Some(source) = &definition.source {
+        out += &format!(" (from {})", source);
+    }
+    out
+}
+
+/// The term definition of the key under the cursor.
+fn current_term<'a>(
+    token: &TokenComponent,
+    element: &Spanned<Json>,
+    context: &'a JsonLdContext,
+) -> Option<(&'a String, &'a TermDefinition)> {
+    if !matches!(token.token.value(), Token::Str(_, _)) || !is_key(element, token.token.span()) {
+        return None;
+    }
+    context.0.terms.get_key_value(&token.text)
+}
+
+/// Completes the terms of the context in key position, and keywords when the key starts with
+/// `@`. Prefixes are completed as compact IRIs.
+pub fn complete_terms<L: Lang<Element = Json> + Component>(
+    mut query: Query<
+        (
+            &TokenComponent,
+            &Element<L>,
+            &JsonLdContext,
+            &mut CompletionRequest,
+        ),
+        With<L>,
+    >,
+) {
+    for (word, element, context, mut req) in &mut query {
+        if !matches!(word.token.value(), Token::Str(_, _)) || !is_key(element, word.token.span()) {
+            continue;
+        }
+
+        if word.text.starts_with('@') {
+            for keyword in KEYWORDS {
+                req.push(SimpleCompletion::new(
+                    CompletionItemKind::KEYWORD,
+                    keyword.to_string(),
+                    TextEdit {
+                        range: word.range.clone(),
+                        new_text: keyword.to_string(),
+                    },
+                ));
+            }
+        }
+
+        for (term, definition) in &context.0.terms {
+            if definition.prefix || definition.iri.is_none() {
+                continue;
+            }
+            req.push(
+                SimpleCompletion::new(
+                    CompletionItemKind::PROPERTY,
+                    term.clone(),
+                    TextEdit {
+                        range: word.range.clone(),
+                        new_text: term.clone(),
+                    },
+                )
+                .documentation(describe(term, definition)),
+            );
+        }
+    }
+}
+
+/// Shows the definition of the term under the cursor.
+pub fn hover_term<L: Lang<Element = Json> + Component>(
+    mut query: Query<
+        (
+            &TokenComponent,
+            &Element<L>,
+            &JsonLdContext,
+            &mut HoverRequest,
+        ),
+        With<L>,
+    >,
+) {
+    for (token, element, context, mut request) in &mut query {
+        if let Some((term, definition)) = current_term(token, element, context) {
+            request.0.push(describe(term, definition));
+            request.1 = Some(token.range.clone());
+        }
+    }
+}
+
+/// Goes to the definition of the term under the cursor, also when it is defined in a remote
+/// context.
+pub fn goto_term_definition<L: Lang<Element = Json> + Component>(
+    mut query: Query<
+        (
+            &TokenComponent,
+            &Element<L>,
+            &JsonLdContext,
+            &Label,
+            &RopeC,
+            &mut GotoDefinitionRequest,
+        ),
+        With<L>,
+    >,
+    loaded: Res<LoadedContexts>,
+) {
+    for (token, element, context, label, rope, mut request) in &mut query {
+        let Some((_, definition)) = current_term(token, element, context) else {
+            continue;
+        };
+
+        let location = match &definition.source {
+            None => range_to_range(&definition.span, &rope.0).map(|range| Location {
+                uri: label.0.clone(),
+                range,
+            }),
+            Some(source) => loaded.sources.get(source).and_then(|rope| {
+                Some(Location {
+                    uri: lsp_types::Url::parse(source).ok()?,
+                    range: range_to_range(&definition.span, rope)?,
+                })
+            }),
+        };
+        request.0.extend(location);
+    }
+}
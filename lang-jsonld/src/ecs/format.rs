@@ -0,0 +1,49 @@
+use bevy_ecs::prelude::*;
+use lsp_core::{components::*, prelude::*};
+use lsp_types::{Position, Range};
+use tracing::info;
+
+use crate::{lang::parser::format_jsonld, JsonLd};
+
+/// Formats JSON-LD documents with the indentation of the client, `@context`, `@id` and `@type`
+/// are written first in every object.
+pub fn format_jsonld_system(
+    mut query: Query<
+        (
+            &RopeC,
+            &Element<JsonLd>,
+            Option<&FormatOptions>,
+            &mut FormatRequest,
+        ),
+        Without<Dirty>,
+    >,
+) {
+    for (source, json, options, mut request) in &mut query {
+        if request.0.is_some() {
+            info!("Didn't format with the jsonld format system, already formatted");
+            continue;
+        }
+
+        let formatted = match options {
+            Some(options) => format_jsonld(json.value(), &options.0),
+            None => format_jsonld(
+                json.value(),
+                &lsp_types::FormattingOptions {
+                    tab_size: 2,
+                    insert_spaces: true,
+                    ..Default::default()
+                },
+            ),
+        };
+
+        request.0 = formatted.map(|x| {
+            vec![lsp_types::TextEdit::new(
+                Range::new(
+                    Position::new(0, 0),
+                    Position::new(source.0.len_lines() as u32 + 1, 0),
+                ),
+                x,
+            )]
+        });
+    }
+}
@@ -1,28 +1,98 @@
-use bevy_ecs::{schedule::IntoSystemConfigs as _, world::World};
+use bevy_ecs::{component::Component, schedule::IntoSystemConfigs as _, world::World};
 use lsp_core::prelude::*;
+
+use crate::{lang::parser::Json, JsonLd};
+
+mod context;
+pub use context::{
+    complete_terms, goto_term_definition, hover_term, load_remote_contexts, JsonLdContext,
+    LoadedContexts,
+};
+mod format;
+pub use format::format_jsonld_system;
+
 mod highlight;
 pub use highlight::*;
 
 mod parse;
-use parse::derive_triples;
-pub use parse::{parse_jsonld_system, parse_source};
+pub use parse::{derive_triples, parse_jsonld_system, parse_source};
+
+mod prefix;
+pub use prefix::{jsonld_undefined_prefix_code_action, undefined_compact_iri};
+
+mod symbol;
+pub use symbol::jsonld_document_symbols;
+
+/// Loads the remote contexts that documents refer to, once for all languages that share the
+/// JSON-LD systems.
+pub fn setup_remote_contexts(world: &mut World) {
+    if world.contains_resource::<LoadedContexts>() {
+        return;
+    }
+    world.init_resource::<LoadedContexts>();
+    world.schedule_scope(ParseLabel, |_, schedule| {
+        schedule.add_systems(load_remote_contexts);
+    });
+}
 
 pub fn setup_parse(world: &mut World) {
     use lsp_core::prelude::parse::*;
+    setup_remote_contexts(world);
     world.schedule_scope(ParseLabel, |_, schedule| {
         schedule.add_systems((
             parse_source,
             parse_jsonld_system.after(parse_source),
-            derive_triples
+            derive_triples::<JsonLd>
                 .after(parse_jsonld_system)
+                .after(load_remote_contexts)
                 .before(triples)
                 .before(prefixes),
         ));
     });
 }
 
+pub fn setup_completion<L: Lang<Element = Json> + Component>(world: &mut World) {
+    use lsp_core::feature::completion::*;
+    world.schedule_scope(CompletionLabel, |_, schedule| {
+        schedule.add_systems(complete_terms::<L>.after(get_current_token));
+    });
+}
+
+pub fn setup_hover<L: Lang<Element = Json> + Component>(world: &mut World) {
+    use lsp_core::feature::hover::*;
+    world.schedule_scope(Label, |_, schedule| {
+        schedule.add_systems(hover_term::<L>.after(get_current_token));
+    });
+}
+
+pub fn setup_goto_definition<L: Lang<Element = Json> + Component>(world: &mut World) {
+    use lsp_core::feature::goto_definition::*;
+    world.schedule_scope(Label, |_, schedule| {
+        schedule.add_systems(goto_term_definition::<L>.after(get_current_token));
+    });
+}
+
+pub fn setup_format(world: &mut World) {
+    world.schedule_scope(FormatLabel, |_, schedule| {
+        schedule.add_systems(format_jsonld_system);
+    });
+}
+
+pub fn setup_code_action(world: &mut World) {
+    world.schedule_scope(CodeActionLabel, |_, schedule| {
+        schedule.add_systems(jsonld_undefined_prefix_code_action);
+    });
+}
+
+pub fn setup_symbols(world: &mut World) {
+    world.schedule_scope(DocumentSymbolLabel, |_, schedule| {
+        schedule.add_systems(jsonld_document_symbols);
+    });
+}
+
 #[cfg(test)]
 mod tests {
+    use bevy_ecs::change_detection::DetectChangesMut as _;
     use completion::CompletionRequest;
     use futures::executor::block_on;
     use lsp_core::{components::*, prelude::*, util::lsp_range_to_range, Tasks};
@@ -278,4 +348,287 @@ mod tests {
 
         assert_eq!(comppletions.len(), 63);
     }
+
+    #[test]
+    fn remote_context_is_loaded() {
+        let mut client = TestClient::new();
+        client.add_res(
+            "http://example.com/context.jsonld",
+            r#"{ "@context": { "name": "http://xmlns.com/foaf/0.1/name" } }"#,
+        );
+        let (mut world, _) = setup_world(client, crate::setup_world);
+
+        let t1 = r#"{
+    "@context": "context.jsonld",
+    "@id": "http://example.com/ns#me",
+    "name": "Arthur"
+}"#;
+        let entity = create_file(&mut world, t1, "http://example.com/ns", "jsonld", Open);
+
+        let links = world
+            .entity(entity)
+            .get::<RemoteDocuments>()
+            .expect("remote documents");
+        assert_eq!(links.0[0].as_str(), "http://example.com/context.jsonld");
+
+        let c = world.resource::<TestClient>().clone();
+        block_on(c.await_futures(|| world.run_schedule(Tasks)));
+
+        let triples = world
+            .entity(entity)
+            .get::<Triples>()
+            .expect("triples exists");
+        assert_eq!(triples.0.len(), 1);
+        assert_eq!(
+            triples.0[0].predicate.as_str(),
+            "http://xmlns.com/foaf/0.1/name"
+        );
+
+        // hover on the term key
+        world.entity_mut(entity).insert((
+            HoverRequest::default(),
+            PositionComponent(lsp_types::Position::new(3, 6)),
+        ));
+        world.run_schedule(HoverLabel);
+        let hover = world
+            .entity_mut(entity)
+            .take::<HoverRequest>()
+            .expect("hover request");
+        assert!(hover.0.contains(&String::from(
+            "name: http://xmlns.com/foaf/0.1/name (from http://example.com/context.jsonld)"
+        )));
+    }
+
+    #[test]
+    fn remote_context_is_fetched_again() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = r#"{
+    "@context": ["missing.jsonld", "context.jsonld"],
+    "name": "Arthur"
+}"#;
+        let entity = create_file(&mut world, t1, "file:///tmp/doc.jsonld", "jsonld", Open);
+        let c = world.resource::<TestClient>().clone();
+        block_on(c.await_futures(|| world.run_schedule(Tasks)));
+
+        // Failed fetches are not remembered
+        let missing = lsp_types::Url::parse("file:///tmp/missing.jsonld").unwrap();
+        assert!(!world.resource::<RequestedDocuments>().contains(&missing));
+
+        let url = lsp_types::Url::parse("file:///tmp/context.jsonld").unwrap();
+        world
+            .resource_mut::<RequestedDocuments>()
+            .insert(url.clone());
+        world.resource_mut::<RemoteDocumentContents>().insert(
+            url.clone(),
+            String::from(r#"{ "@context": { "name": "http://xmlns.com/foaf/0.1/name" } }"#),
+        );
+        world
+            .get_mut::<RemoteDocuments>(entity)
+            .expect("remote documents")
+            .set_changed();
+        world.run_schedule(ParseLabel);
+
+        let triples = world.entity(entity).get::<Triples>().expect("triples");
+        assert_eq!(
+            triples.0[0].predicate.as_str(),
+            "http://xmlns.com/foaf/0.1/name"
+        );
+
+        // The changed context is forgotten, and loaded again when it is fetched
+        assert!(systems::invalidate_remote_document(&mut world, &url));
+        world.run_schedule(ParseLabel);
+        assert!(!world
+            .resource::<RemoteDocumentContents>()
+            .contains_key(&url));
+        assert!(!world
+            .resource::<crate::ecs::LoadedContexts>()
+            .sources
+            .contains_key(url.as_str()));
+    }
+
+    #[test_log::test]
+    fn complete_keywords_and_terms() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = r#"{
+    "@context" : { "name": "http://xmlns.com/foaf/0.1/name" },
+    "@i"
+}"#;
+        let entity = create_file(&mut world, t1, "http://example.com/ns#", "jsonld", Open);
+
+        world.entity_mut(entity).insert((
+            CompletionRequest(vec![]),
+            PositionComponent(lsp_types::Position::new(2, 6)),
+        ));
+        world.run_schedule(CompletionLabel);
+
+        let completions = world
+            .entity_mut(entity)
+            .take::<CompletionRequest>()
+            .expect("completion request")
+            .0;
+
+        let id = completions
+            .iter()
+            .find(|x| x.label == "@id")
+            .expect("@id completion");
+        assert_eq!(id.edits[0].new_text, "@id");
+
+        let name = completions
+            .iter()
+            .find(|x| x.label == "name")
+            .expect("term completion");
+        assert_eq!(name.edits[0].new_text, "name");
+    }
+
+    #[test_log::test]
+    fn complete_type_classes() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+        lang_turtle::setup_world(&mut world);
+
+        let t1 = r#"{
+    "@context" : { "foaf": "http://xmlns.com/foaf/0.1/" },
+    "@type": "foaf:"
+}"#;
+        let entity = create_file(&mut world, t1, "http://example.com/ns#", "jsonld", Open);
+
+        let c = world.resource::<TestClient>().clone();
+        block_on(c.await_futures(|| world.run_schedule(Tasks)));
+
+        world.entity_mut(entity).insert((
+            CompletionRequest(vec![]),
+            PositionComponent(lsp_types::Position::new(2, 16)),
+        ));
+        world.run_schedule(CompletionLabel);
+
+        let completions = world
+            .entity_mut(entity)
+            .take::<CompletionRequest>()
+            .expect("completion request")
+            .0;
+
+        let person = completions
+            .iter()
+            .find(|x| x.label == "foaf:Person")
+            .expect("class completion");
+        assert_eq!(person.edits[0].new_text, "foaf:Person");
+    }
+
+    #[test]
+    fn undefined_prefix_code_action() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = r#"{
+    "@context" : { "ex": "http://example.com/" },
+    "@id": "ex:me",
+    "foaf:name": "Arthur"
+}"#;
+        let entity = create_file(&mut world, t1, "http://example.com/ns#", "jsonld", Open);
+
+        let range = lsp_types::Range::new(
+            lsp_types::Position::new(0, 0),
+            lsp_types::Position::new(4, 0),
+        );
+        world
+            .entity_mut(entity)
+            .insert(CodeActionRequest::new(range, vec![]));
+        world.run_schedule(CodeActionLabel);
+
+        let req = world
+            .entity_mut(entity)
+            .take::<CodeActionRequest>()
+            .expect("code action request");
+        // Conversions to the other formats are commands, they are offered as well
+        let actions: Vec<_> = req
+            .actions
+            .iter()
+            .filter(|x| !matches!(x, lsp_types::CodeActionOrCommand::CodeAction(a) if a.command.is_some()))
+            .collect();
+        assert_eq!(actions.len(), 1);
+
+        let lsp_types::CodeActionOrCommand::CodeAction(action) = actions[0] else {
+            panic!("expected a code action");
+        };
+        let edits = action
+            .edit
+            .as_ref()
+            .and_then(|x| x.changes.as_ref())
+            .and_then(|x| x.values().next())
+            .expect("edits");
+        assert_eq!(edits.len(), 1);
+        assert_eq!(
+            edits[0].new_text,
+            " \"foaf\": \"http://xmlns.com/foaf/0.1/\", "
+        );
+        assert_eq!(edits[0].range.start, lsp_types::Position::new(1, 18));
+    }
+
+    #[test]
+    fn format_uses_client_options() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = r#"{"foaf:name": "me", "@id": "http://example.com/ns#me", "@context": {"foaf": "http://xmlns.com/foaf/0.1/"}}"#;
+        let entity = create_file(&mut world, t1, "http://example.com/ns#", "jsonld", Open);
+
+        world.entity_mut(entity).insert((
+            FormatRequest(None),
+            FormatOptions(lsp_types::FormattingOptions {
+                tab_size: 4,
+                insert_spaces: true,
+                ..Default::default()
+            }),
+        ));
+        world.run_schedule(FormatLabel);
+        let formatted = world
+            .entity_mut(entity)
+            .take::<FormatRequest>()
+            .and_then(|x| x.0)
+            .expect("formatted");
+
+        assert_eq!(
+            formatted[0].new_text,
+            r#"{
+    "@context": {
+        "foaf": "http://xmlns.com/foaf/0.1/"
+    },
+    "@id": "http://example.com/ns#me",
+    "foaf:name": "me"
+}
+"#
+        );
+    }
+
+    #[test]
+    fn jsonld_diagnostics_are_published() {
+        let (mut world, mut rx) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = r#"{
+  "@context": {
+    "foaf": "http://xmlns.com/foaf/0.1/",
+    "knows": { "@id": "foaf:knows", "@container": ["@list", "@set"] }
+  },
+  "@id": "http://example.com/ns#me",
+  "@tpye": "foaf:Person"
+}"#;
+        create_file(&mut world, t1, "http://example.com/ns#", "jsonld", Open);
+        world.run_schedule(DiagnosticsLabel);
+
+        let mut diagnostics = Vec::new();
+        while let Ok(Some(item)) = rx.try_next() {
+            diagnostics = item.diagnostics;
+        }
+
+        let messages: Vec<_> = diagnostics
+            .iter()
+            .map(|x| (x.message.as_str(), x.severity))
+            .collect();
+        assert!(messages.contains(&(
+            "Unknown keyword @tpye, the entry is ignored",
+            Some(lsp_types::DiagnosticSeverity::WARNING)
+        )));
+        assert!(messages.iter().any(|(msg, severity)| msg
+            .starts_with("Invalid container mapping")
+            && *severity == Some(lsp_types::DiagnosticSeverity::ERROR)));
+    }
 }
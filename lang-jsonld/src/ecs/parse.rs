@@ -1,12 +1,19 @@
 use bevy_ecs::prelude::*;
 use lsp_core::{
     components::*,
-    prelude::{Tokens, Triples},
+    prelude::{Lang, SimpleDiagnostic, Spanned, Tokens, Triples},
 };
+use lsp_types::DiagnosticSeverity;
 use tracing::{info, instrument};
 
+use super::{JsonLdContext, LoadedContexts};
 use crate::{
-    lang::{parser::parse, tokenizer::tokenize, triples},
+    lang::{
+        context::ActiveContext,
+        parser::{parse, Json},
+        tokenizer::tokenize,
+        triples,
+    },
     JsonLd,
 };
 
@@ -47,14 +54,45 @@ pub fn parse_jsonld_system(
     }
 }
 
-#[instrument(skip(query, commands))]
-pub fn derive_triples(
-    query: Query<(Entity, &Label, &Element<JsonLd>), Changed<Element<JsonLd>>>,
+/// Expands the document into triples, with the errors and warnings found on the way. Shared with
+/// the languages that parse into the same [`Json`] tree.
+#[instrument(skip(query, loaded, commands))]
+pub fn derive_triples<L: Lang<Element = Json> + Component>(
+    query: Query<
+        (Entity, &Label, &Element<L>, Option<&RemoteDocuments>),
+        Or<(Changed<Element<L>>, Changed<RemoteDocuments>)>,
+    >,
+    loaded: Res<LoadedContexts>,
     mut commands: Commands,
 ) {
-    for (e, l, el) in &query {
-        let prefix = triples::derive_prefixes(&el, &l.0);
-        let triples = triples::derive_triples(&el, &prefix);
-        commands.entity(e).insert((Triples(triples), prefix));
+    for (e, l, el, remote) in &query {
+        let references = triples::context_references(&el, &l.0, &loaded.contexts);
+        if remote.map(|x| &x.0) != Some(&references) {
+            commands.entity(e).insert(RemoteDocuments(references));
+        }
+
+        let initial = ActiveContext::new(&l.0).with_remote(loaded.contexts.clone());
+        let (context, _) = triples::document_context(&el, &initial);
+        let prefix = triples::context_prefixes(&context, &l.0);
+        let (triples, errors, warnings) = triples::expand(&el, &initial);
+
+        let diagnostics = errors
+            .into_iter()
+            .map(|x| (x, DiagnosticSeverity::ERROR))
+            .chain(
+                warnings
+                    .into_iter()
+                    .map(|x| (x, DiagnosticSeverity::WARNING)),
+            )
+            .map(|(Spanned(msg, span), severity)| {
+                SimpleDiagnostic::new_severity(span, msg, severity)
+            })
+            .collect();
+        commands.entity(e).insert((
+            Triples(triples),
+            prefix,
+            JsonLdContext(context),
+            Errors(diagnostics),
+        ));
     }
 }
@@ -0,0 +1,154 @@
+use bevy_ecs::prelude::*;
+use lsp_core::{
+    components::*,
+    prelude::*,
+    systems::prefix::{prefix_code_action_helper, undefined_prefix_diagnostic},
+};
+use lsp_types::{DiagnosticSeverity, TextDocumentItem, TextEdit};
+
+use crate::{
+    lang::parser::{Json, ObjectMember},
+    JsonLd,
+};
+
+/// Prefixes of absolute IRIs that look like compact IRIs
+const IRI_SCHEMES: &[&str] = &[
+    "_", "http", "https", "urn", "mailto", "file", "tag", "did", "data",
+];
+
+/// Returns the prefix of a string that looks like a compact IRI ("foaf:name").
+fn compact_iri_prefix(st: &str) -> Option<&str> {
+    let (prefix, local) = st.split_once(':')?;
+    let mut chars = prefix.chars();
+    if !chars.next()?.is_alphabetic()
+        || !chars.all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == '.')
+    {
+        return None;
+    }
+
+    if local.starts_with("//")
+        || local.chars().any(char::is_whitespace)
+        || IRI_SCHEMES.contains(&prefix)
+    {
+        return None;
+    }
+
+    Some(prefix)
+}
+
+/// Iterates over all strings that look like compact IRIs, but use an undefined prefix.
+fn undefined_prefixes<'a>(
+    tokens: &'a [Spanned<Token>],
+    prefixes: &'a Prefixes,
+) -> impl Iterator<Item = Spanned<&'a str>> + 'a {
+    tokens.iter().flat_map(|t| match t.value() {
+        Token::Str(st, _) => {
+            let prefix = compact_iri_prefix(st)?;
+            let found = prefixes.0.iter().any(|x| x.prefix == prefix);
+            (!found).then(|| spanned(prefix, t.span().clone()))
+        }
+        _ => None,
+    })
+}
+
+pub fn undefined_compact_iri(
+    query: Query<
+        (&Tokens, &Prefixes, &Wrapped<TextDocumentItem>, &RopeC),
+        (Or<(Changed<Prefixes>, Changed<Tokens>)>, With<JsonLd>),
+    >,
+    mut client: ResMut<DiagnosticPublisher>,
+) {
+    for (tokens, prefixes, item, rope) in &query {
+        let diagnostics: Vec<_> = undefined_prefixes(&tokens.0, prefixes)
+            .flat_map(|pref| {
+                let range = range_to_range(pref.span(), &rope)?;
+                let mut diagnostic = undefined_prefix_diagnostic(
+                    pref.value(),
+                    range,
+                    format!(
+                        "Undefined prefix {}, this value is interpreted as an absolute IRI",
+                        pref.value()
+                    ),
+                );
+                diagnostic.severity = Some(DiagnosticSeverity::WARNING);
+                Some(diagnostic)
+            })
+            .collect();
+        let _ = client.publish(&item.0, diagnostics, "undefined_compact_iri");
+    }
+}
+
+fn insert(offset: usize, text: String, rope: &RopeC) -> Option<TextEdit> {
+    let position = offset_to_position(offset, &rope.0)?;
+    Some(TextEdit {
+        range: lsp_types::Range::new(position, position),
+        new_text: text,
+    })
+}
+
+/// Creates the edits that add `"name": "location"` to the root `@context`.
+fn add_to_context(
+    json: &Spanned<Json>,
+    rope: &RopeC,
+    name: &str,
+    location: &str,
+) -> Option<Vec<TextEdit>> {
+    let entry = format!("\"{}\": \"{}\"", name, location);
+    let comma = |empty: bool| if empty { "" } else { ", " };
+
+    let Json::Object(members) = json.value() else {
+        return None;
+    };
+
+    let context = members
+        .iter()
+        .find(|m| matches!(m.field().value(), Token::Str(x, _) if x == "@context"));
+
+    let Some(context) = context else {
+        let text = format!("\"@context\": {{ {} }}{}", entry, comma(members.is_empty()));
+        return Some(vec![insert(json.span().start + 1, text, rope)?]);
+    };
+
+    let value = match context.value() {
+        ObjectMember::Full(_, value) => value,
+        ObjectMember::Partial(_, _, value) => value.as_ref()?,
+    };
+
+    let edits = match value.value() {
+        Json::Object(entries) => vec![insert(
+            value.span().start + 1,
+            format!(" {}{}", entry, comma(entries.is_empty())),
+            rope,
+        )?],
+        Json::Array(items) => vec![insert(
+            value.span().start + 1,
+            format!("{{ {} }}{}", entry, comma(items.is_empty())),
+            rope,
+        )?],
+        Json::Token(Token::Str(_, _)) => vec![
+            insert(value.span().start, String::from("["), rope)?,
+            insert(value.span().end, format!(", {{ {} }}]", entry), rope)?,
+        ],
+        _ => return None,
+    };
+
+    Some(edits)
+}
+
+pub fn jsonld_undefined_prefix_code_action(
+    mut query: Query<(
+        &Tokens,
+        &Element<JsonLd>,
+        &Prefixes,
+        &Label,
+        &RopeC,
+        &mut CodeActionRequest,
+    )>,
+) {
+    for (tokens, element, prefixes, label, rope, mut req) in &mut query {
+        let undefined = undefined_prefixes(&tokens.0, prefixes);
+        prefix_code_action_helper(undefined, &mut req, label, rope, |name, location| {
+            add_to_context(&element.0, rope, name, location)
+        });
+    }
+}
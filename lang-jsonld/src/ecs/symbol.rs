@@ -0,0 +1,13 @@
+use bevy_ecs::prelude::*;
+use lsp_core::{components::*, feature::symbol::triples_document_symbols, prelude::*};
+
+use crate::JsonLd;
+
+pub fn jsonld_document_symbols(
+    mut query: Query<(&Triples, &Prefixes, &RopeC, &mut DocumentSymbolRequest), With<JsonLd>>,
+) {
+    for (triples, prefixes, rope, mut req) in &mut query {
+        req.0
+            .extend(triples_document_symbols(triples, prefixes, &rope.0));
+    }
+}
@@ -0,0 +1,997 @@
+//! Context processing, following the context processing and create term definition algorithms
+//! of JSON-LD 1.1 API.
+//!
+//! Errors don't stop processing, they are collected with the span of the offending entry and the
+//! entry is skipped.
+//!
+//! Remote contexts are not loaded here, they are looked up in the [`RemoteContexts`] of the
+//! active context and skipped when they are not loaded (yet).
+use std::{collections::HashMap, ops::Range, sync::Arc};
+
+use lsp_core::prelude::{Spanned, Token};
+
+use crate::lang::parser::{Json, ObjectMember};
+
+/// All JSON-LD 1.1 keywords.
+pub const KEYWORDS: &[&str] = &[
+    "@base",
+    "@container",
+    "@context",
+    "@direction",
+    "@graph",
+    "@id",
+    "@import",
+    "@included",
+    "@index",
+    "@json",
+    "@language",
+    "@list",
+    "@nest",
+    "@none",
+    "@prefix",
+    "@propagate",
+    "@protected",
+    "@reverse",
+    "@set",
+    "@type",
+    "@value",
+    "@version",
+    "@vocab",
+];
+
+/// Values allowed in `@container`.
+pub const CONTAINERS: &[&str] = &[
+    "@list",
+    "@set",
+    "@index",
+    "@language",
+    "@graph",
+    "@id",
+    "@type",
+];
+
+/// Entries of a context that are not term definitions.
+pub(crate) const CONTEXT_ENTRIES: &[&str] = &[
+    "@base",
+    "@direction",
+    "@import",
+    "@language",
+    "@propagate",
+    "@protected",
+    "@version",
+    "@vocab",
+];
+
+/// Entries allowed in an expanded term definition.
+const DEFINITION_ENTRIES: &[&str] = &[
+    "@id",
+    "@reverse",
+    "@container",
+    "@context",
+    "@direction",
+    "@index",
+    "@language",
+    "@nest",
+    "@prefix",
+    "@protected",
+    "@type",
+];
+
+pub type ContextError = Spanned<String>;
+
+pub(crate) fn get_str(json: &Json) -> Option<&str> {
+    match json {
+        Json::Token(Token::Str(x, _)) => Some(x),
+        _ => None,
+    }
+}
+
+pub(crate) fn get_bool(json: &Json) -> Option<bool> {
+    match json {
+        Json::Token(Token::True) => Some(true),
+        Json::Token(Token::False) => Some(false),
+        _ => None,
+    }
+}
+
+pub(crate) fn is_null(json: &Json) -> bool {
+    matches!(json, Json::Token(Token::Null))
+}
+
+/// Key and value of every member of an object, members without a string key are skipped.
+pub(crate) fn members(
+    members: &[Spanned<ObjectMember>],
+) -> impl Iterator<Item = (Spanned<&str>, Option<&Spanned<Json>>)> {
+    members.iter().flat_map(|m| {
+        let field = m.field();
+        match field.value() {
+            Token::Str(key, _) => {
+                Some((Spanned(key.as_str(), field.span().clone()), m.json_value()))
+            }
+            _ => None,
+        }
+    })
+}
+
+fn member<'a>(ms: &'a [Spanned<ObjectMember>], key: &str) -> Option<&'a Spanned<Json>> {
+    members(ms)
+        .find(|(k, _)| k.value() == &key)
+        .and_then(|(_, v)| v)
+}
+
+/// Whether `value` starts with a scheme, `foaf:name` is absolute when `foaf` is not a prefix.
+pub fn is_absolute(value: &str) -> bool {
+    let Some((scheme, _)) = value.split_once(':') else {
+        return false;
+    };
+    let mut chars = scheme.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+/// Whether `value` has the form of a keyword, these are reserved and ignored when unknown.
+pub fn is_keyword_like(value: &str) -> bool {
+    value
+        .strip_prefix('@')
+        .is_some_and(|x| !x.is_empty() && x.chars().all(|c| c.is_ascii_alphabetic()))
+}
+
+/// Whether the values of `@container` form a valid container mapping, `@set` can be combined
+/// with any container but `@list`, `@graph` with `@id` or `@index`.
+fn valid_container(values: &[Option<&str>]) -> bool {
+    let Some(mut values) = values
+        .iter()
+        .map(|x| x.filter(|x| CONTAINERS.contains(x)))
+        .collect::<Option<Vec<_>>>()
+    else {
+        return false;
+    };
+    values.sort();
+    let len = values.len();
+    values.dedup();
+    if values.len() != len {
+        return false;
+    }
+
+    values.retain(|x| *x != "@set");
+    match values.as_slice() {
+        [] => true,
+        ["@list"] => len == 1,
+        [_] => true,
+        ["@graph", "@id"] | ["@graph", "@index"] => true,
+        _ => false,
+    }
+}
+
+fn resolve(base: Option<&str>, value: &str) -> String {
+    base.and_then(|base| lsp_types::Url::parse(base).ok())
+        .and_then(|base| base.join(value).ok())
+        .map(|x| x.to_string())
+        .unwrap_or_else(|| value.to_string())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TermDefinition {
+    /// Expanded IRI or keyword, `None` when the term is mapped to `null`
+    pub iri: Option<String>,
+    pub reverse: bool,
+    /// `@id`, `@vocab`, `@json`, `@none` or a datatype IRI
+    pub type_mapping: Option<String>,
+    /// Default language of string values, `Some(None)` when it is explicitly `null`
+    pub language: Option<Option<String>>,
+    pub container: Vec<String>,
+    pub nest: Option<String>,
+    pub index: Option<String>,
+    /// Scoped context, applied to the values of this term or to nodes of this type
+    pub context: Option<Spanned<Json>>,
+    pub protected: bool,
+    /// The term can be used as prefix of a compact IRI
+    pub prefix: bool,
+    /// Span of the term in the context that defines it
+    pub span: Range<usize>,
+    /// IRI of the remote context that defines the term, `None` for the document itself
+    pub source: Option<String>,
+}
+
+impl TermDefinition {
+    fn new(span: Range<usize>, protected: bool) -> Self {
+        Self {
+            iri: None,
+            reverse: false,
+            type_mapping: None,
+            language: None,
+            container: Vec::new(),
+            nest: None,
+            index: None,
+            context: None,
+            protected,
+            prefix: false,
+            span,
+            source: None,
+        }
+    }
+
+    /// Definitions are the same when they only differ in where they are defined.
+    pub(crate) fn same_as(&self, other: &TermDefinition) -> bool {
+        TermDefinition {
+            span: other.span.clone(),
+            source: other.source.clone(),
+            protected: other.protected,
+            context: other
+                .context
+                .as_ref()
+                .map(|x| Spanned(x.value().clone(), 0..0)),
+            ..self.clone()
+        } == TermDefinition {
+            context: other
+                .context
+                .as_ref()
+                .map(|x| Spanned(x.value().clone(), 0..0)),
+            ..other.clone()
+        }
+    }
+}
+
+/// Loaded remote contexts, keyed by their IRI, with the `@context` entry of each document.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RemoteContexts(pub HashMap<String, Spanned<Json>>);
+
+/// The result of processing contexts, used to expand terms and compact IRIs.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ActiveContext {
+    pub terms: HashMap<String, TermDefinition>,
+    pub base: Option<String>,
+    /// Base IRI of the document, restored when the context is set to `null`
+    pub original_base: Option<String>,
+    pub vocab: Option<String>,
+    pub language: Option<String>,
+    /// Remote contexts that string contexts refer to
+    pub remote: Arc<RemoteContexts>,
+}
+
+/// The local context that is being processed, terms are defined on demand when another
+/// definition depends on them.
+struct LocalContext<'a> {
+    members: &'a [Spanned<ObjectMember>],
+    defined: HashMap<String, bool>,
+    protected: bool,
+    override_protected: bool,
+    source: Option<&'a str>,
+}
+
+impl ActiveContext {
+    pub fn new(base: &lsp_types::Url) -> Self {
+        Self {
+            base: Some(base.to_string()),
+            original_base: Some(base.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Resolves string contexts with `remote`.
+    pub fn with_remote(mut self, remote: Arc<RemoteContexts>) -> Self {
+        self.remote = remote;
+        self
+    }
+
+    /// The keyword that `key` is, or is an alias of.
+    pub fn keyword<'a>(&'a self, key: &'a str) -> Option<&'a str> {
+        if key.starts_with('@') {
+            return KEYWORDS.contains(&key).then_some(key);
+        }
+        self.terms
+            .get(key)
+            .and_then(|t| t.iri.as_deref())
+            .filter(|x| KEYWORDS.contains(x))
+    }
+
+    /// IRI expansion, `vocab` expands terms and uses `@vocab`, `document_relative` resolves
+    /// relative IRIs against `@base`.
+    ///
+    /// Returns `None` for terms mapped to `null` and unknown keywords, relative IRIs that are not
+    /// resolved are returned as is.
+    pub fn expand_iri(&self, value: &str, vocab: bool, document_relative: bool) -> Option<String> {
+        if is_keyword_like(value) {
+            return KEYWORDS.contains(&value).then(|| value.to_string());
+        }
+        if vocab {
+            if let Some(term) = self.terms.get(value) {
+                return term.iri.clone();
+            }
+        }
+        if let Some((prefix, suffix)) = value.split_once(':') {
+            if prefix == "_" || suffix.starts_with("//") {
+                return Some(value.to_string());
+            }
+            if let Some(iri) = self
+                .terms
+                .get(prefix)
+                .filter(|t| t.prefix)
+                .and_then(|t| t.iri.as_ref())
+            {
+                return Some(format!("{}{}", iri, suffix));
+            }
+            if is_absolute(value) {
+                return Some(value.to_string());
+            }
+        }
+        if vocab {
+            if let Some(v) = &self.vocab {
+                return Some(format!("{}{}", v, value));
+            }
+        }
+        if document_relative {
+            return Some(resolve(self.base.as_deref(), value));
+        }
+        Some(value.to_string())
+    }
+
+    /// Processes `local` on top of this context.
+    pub fn process(&self, local: &Spanned<Json>, errors: &mut Vec<ContextError>) -> ActiveContext {
+        self.process_with(local, errors, false)
+    }
+
+    /// Processes a scoped context, `override_protected` is set for property-scoped contexts which
+    /// may redefine protected terms.
+    pub fn process_with(
+        &self,
+        local: &Spanned<Json>,
+        errors: &mut Vec<ContextError>,
+        override_protected: bool,
+    ) -> ActiveContext {
+        let mut result = self.clone();
+        result.process_items(local, None, errors, override_protected, &mut Vec::new());
+        result
+    }
+
+    /// Processes a local context, `source` is the remote context it comes from. `loading` holds
+    /// the remote contexts that are being processed, to detect recursive inclusion.
+    fn process_items(
+        &mut self,
+        local: &Spanned<Json>,
+        source: Option<&str>,
+        errors: &mut Vec<ContextError>,
+        override_protected: bool,
+        loading: &mut Vec<String>,
+    ) {
+        let items: Vec<&Spanned<Json>> = match local.value() {
+            Json::Array(xs) => xs.iter().collect(),
+            _ => vec![local],
+        };
+
+        for ctx in items {
+            match ctx.value() {
+                Json::Token(Token::Null) => {
+                    if !override_protected && self.terms.values().any(|t| t.protected) {
+                        errors.push(Spanned(
+                            String::from("Invalid context nullification, the active context has protected terms"),
+                            ctx.span().clone(),
+                        ));
+                        continue;
+                    }
+                    *self = ActiveContext {
+                        base: self.original_base.clone(),
+                        original_base: self.original_base.clone(),
+                        remote: self.remote.clone(),
+                        ..Default::default()
+                    };
+                }
+                Json::Token(Token::Str(reference, _)) => {
+                    let iri = resolve(source.or(self.original_base.as_deref()), reference);
+                    self.process_remote(ctx.span(), iri, errors, override_protected, loading);
+                }
+                Json::Object(ms) => {
+                    self.process_object(ms, source, errors, override_protected, loading)
+                }
+                _ => errors.push(Spanned(
+                    String::from("Invalid local context, expected an object, a string or null"),
+                    ctx.span().clone(),
+                )),
+            }
+        }
+    }
+
+    /// Processes the remote context `iri` when it is loaded, its errors are reported on the
+    /// reference.
+    fn process_remote(
+        &mut self,
+        span: &Range<usize>,
+        iri: String,
+        errors: &mut Vec<ContextError>,
+        override_protected: bool,
+        loading: &mut Vec<String>,
+    ) {
+        if loading.contains(&iri) {
+            errors.push(Spanned(
+                format!("Recursive context inclusion of {}", iri),
+                span.clone(),
+            ));
+            return;
+        }
+        let remote = self.remote.clone();
+        let Some(context) = remote.0.get(&iri) else {
+            return;
+        };
+
+        let mut remote_errors = Vec::new();
+        loading.push(iri.clone());
+        self.process_items(
+            context,
+            Some(&iri),
+            &mut remote_errors,
+            override_protected,
+            loading,
+        );
+        loading.pop();
+
+        errors.extend(
+            remote_errors
+                .into_iter()
+                .map(|Spanned(msg, _)| Spanned(format!("{} (in {})", msg, iri), span.clone())),
+        );
+    }
+
+    fn process_object(
+        &mut self,
+        ms: &[Spanned<ObjectMember>],
+        source: Option<&str>,
+        errors: &mut Vec<ContextError>,
+        override_protected: bool,
+        loading: &mut Vec<String>,
+    ) {
+        // Definitions of the imported context are overridden by those of this context
+        if let Some(import) = member(ms, "@import") {
+            match import.value() {
+                Json::Token(Token::Str(reference, _)) => {
+                    let iri = resolve(source.or(self.original_base.as_deref()), reference);
+                    self.process_remote(import.span(), iri, errors, override_protected, loading);
+                }
+                _ => errors.push(Spanned(
+                    String::from("Invalid @import value, expected a string"),
+                    import.span().clone(),
+                )),
+            }
+        }
+
+        if let Some(version) = member(ms, "@version") {
+            if !matches!(version.value(), Json::Token(Token::Number(x)) if x == "1.1") {
+                errors.push(Spanned(
+                    String::from("Invalid @version value, expected 1.1"),
+                    version.span().clone(),
+                ));
+            }
+        }
+
+        if let Some(base) = member(ms, "@base") {
+            match base.value() {
+                x if is_null(x) => self.base = None,
+                Json::Token(Token::Str(x, _)) => {
+                    self.base = Some(resolve(self.base.as_deref(), x));
+                }
+                _ => errors.push(Spanned(
+                    String::from("Invalid base IRI, expected a string or null"),
+                    base.span().clone(),
+                )),
+            }
+        }
+
+        if let Some(vocab) = member(ms, "@vocab") {
+            match vocab.value() {
+                x if is_null(x) => self.vocab = None,
+                Json::Token(Token::Str(x, _)) => {
+                    self.vocab = self.expand_iri(x, true, true);
+                }
+                _ => errors.push(Spanned(
+                    String::from("Invalid vocab mapping, expected a string or null"),
+                    vocab.span().clone(),
+                )),
+            }
+        }
+
+        if let Some(language) = member(ms, "@language") {
+            match language.value() {
+                x if is_null(x) => self.language = None,
+                Json::Token(Token::Str(x, _)) => self.language = Some(x.to_lowercase()),
+                _ => errors.push(Spanned(
+                    String::from("Invalid default language, expected a string or null"),
+                    language.span().clone(),
+                )),
+            }
+        }
+
+        let protected = match member(ms, "@protected") {
+            Some(x) => get_bool(x.value()).unwrap_or_else(|| {
+                errors.push(Spanned(
+                    String::from("Invalid @protected value, expected a boolean"),
+                    x.span().clone(),
+                ));
+                false
+            }),
+            None => false,
+        };
+
+        let mut local = LocalContext {
+            members: ms,
+            defined: HashMap::new(),
+            protected,
+            override_protected,
+            source,
+        };
+        for (key, _) in members(ms) {
+            if !CONTEXT_ENTRIES.contains(key.value()) {
+                self.create_term(&mut local, key.value(), errors);
+            }
+        }
+    }
+
+    /// Expands `value` while defining the terms of the local context it depends on.
+    fn expand_local(
+        &mut self,
+        local: &mut LocalContext,
+        value: &str,
+        vocab: bool,
+        errors: &mut Vec<ContextError>,
+    ) -> Option<String> {
+        if members(local.members).any(|(k, _)| k.value() == &value) {
+            self.create_term(local, value, errors);
+        }
+        if let Some((prefix, _)) = value.split_once(':') {
+            if members(local.members).any(|(k, _)| k.value() == &prefix) {
+                self.create_term(local, prefix, errors);
+            }
+        }
+        self.expand_iri(value, vocab, false)
+    }
+
+    fn create_term(
+        &mut self,
+        local: &mut LocalContext,
+        term: &str,
+        errors: &mut Vec<ContextError>,
+    ) {
+        let Some((key, value)) = members(local.members).find(|(k, _)| k.value() == &term) else {
+            return;
+        };
+        match local.defined.get(term) {
+            Some(true) => return,
+            Some(false) => {
+                errors.push(Spanned(
+                    format!("Cyclic IRI mapping for {}", term),
+                    key.span().clone(),
+                ));
+                return;
+            }
+            None => {}
+        }
+        local.defined.insert(term.to_string(), false);
+
+        if let Some(definition) = self.term_definition(local, &key, value, errors) {
+            match self.terms.get(term) {
+                Some(previous)
+                    if previous.protected
+                        && !local.override_protected
+                        && !previous.same_as(&definition) =>
+                {
+                    errors.push(Spanned(
+                        format!("Protected term {} cannot be redefined", term),
+                        key.span().clone(),
+                    ));
+                }
+                _ => {
+                    let source = local.source.map(String::from);
+                    self.terms.insert(
+                        term.to_string(),
+                        TermDefinition {
+                            source,
+                            ..definition
+                        },
+                    );
+                }
+            }
+        }
+
+        local.defined.insert(term.to_string(), true);
+    }
+
+    fn term_definition(
+        &mut self,
+        local: &mut LocalContext,
+        key: &Spanned<&str>,
+        value: Option<&Spanned<Json>>,
+        errors: &mut Vec<ContextError>,
+    ) -> Option<TermDefinition> {
+        let term = *key.value();
+        let mut error = |msg: String, span: &Range<usize>| errors.push(Spanned(msg, span.clone()));
+
+        if term.is_empty() {
+            error(
+                String::from("Invalid term definition, the term is empty"),
+                key.span(),
+            );
+            return None;
+        }
+        if term == "@type" {
+            // @type can only be given a container and be protected
+        } else if KEYWORDS.contains(&term) {
+            error(format!("Keyword {} cannot be redefined", term), key.span());
+            return None;
+        } else if is_keyword_like(term) {
+            return None;
+        }
+
+        let value = value?;
+        let mut definition = TermDefinition::new(key.span().clone(), local.protected);
+        let (ms, simple): (&[Spanned<ObjectMember>], bool) = match value.value() {
+            x if is_null(x) => return Some(definition),
+            Json::Token(Token::Str(_, _)) => (&[], true),
+            Json::Object(ms) => (ms, false),
+            _ => {
+                error(
+                    format!(
+                        "Invalid term definition for {}, expected a string, an object or null",
+                        term
+                    ),
+                    value.span(),
+                );
+                return None;
+            }
+        };
+
+        let mut error = |msg: String, span: &Range<usize>| errors.push(Spanned(msg, span.clone()));
+        for (entry, _) in members(ms) {
+            if !DEFINITION_ENTRIES.contains(entry.value()) {
+                error(
+                    format!("Invalid term definition, unknown entry {}", entry.value()),
+                    entry.span(),
+                );
+            }
+        }
+
+        if let Some(protected) = member(ms, "@protected") {
+            match get_bool(protected.value()) {
+                Some(x) => definition.protected = x,
+                None => error(
+                    String::from("Invalid @protected value, expected a boolean"),
+                    protected.span(),
+                ),
+            }
+        }
+
+        if let Some(ty) = member(ms, "@type") {
+            match get_str(ty.value()) {
+                Some(x) => {
+                    let expanded = self.expand_local(local, x, true, errors);
+                    match expanded {
+                        Some(x) if ["@id", "@json", "@none", "@vocab"].contains(&x.as_str()) => {
+                            definition.type_mapping = Some(x)
+                        }
+                        Some(x) if is_absolute(&x) => definition.type_mapping = Some(x),
+                        _ => errors.push(Spanned(
+                            format!("Invalid type mapping {}, expected an absolute IRI", x),
+                            ty.span().clone(),
+                        )),
+                    }
+                }
+                None => errors.push(Spanned(
+                    String::from("Invalid type mapping, expected a string"),
+                    ty.span().clone(),
+                )),
+            }
+        }
+
+        let id = if simple {
+            Some(value)
+        } else {
+            member(ms, "@id")
+        };
+
+        if let Some(reverse) = member(ms, "@reverse") {
+            if id.is_some() || member(ms, "@nest").is_some() {
+                errors.push(Spanned(
+                    String::from("Invalid reverse property, @id and @nest are not allowed"),
+                    reverse.span().clone(),
+                ));
+                return None;
+            }
+            let Some(x) = get_str(reverse.value()) else {
+                errors.push(Spanned(
+                    String::from("Invalid IRI mapping, @reverse expects a string"),
+                    reverse.span().clone(),
+                ));
+                return None;
+            };
+            match self.expand_local(local, x, true, errors) {
+                Some(iri) if iri.contains(':') => definition.iri = Some(iri),
+                _ => {
+                    errors.push(Spanned(
+                        format!("Invalid IRI mapping, {} is not an absolute IRI", x),
+                        reverse.span().clone(),
+                    ));
+                    return None;
+                }
+            }
+            definition.reverse = true;
+        } else if let Some(id) = id.filter(|x| get_str(x.value()) != Some(term)) {
+            match id.value() {
+                x if is_null(x) => return Some(definition),
+                Json::Token(Token::Str(x, _)) => {
+                    if !x.starts_with('@') || !is_keyword_like(x) || KEYWORDS.contains(&x.as_str())
+                    {
+                        let iri = self.expand_local(local, x, true, errors)?;
+                        if !KEYWORDS.contains(&iri.as_str()) && !iri.contains(':') {
+                            errors.push(Spanned(
+                                format!("Invalid IRI mapping, {} is not an absolute IRI", x),
+                                id.span().clone(),
+                            ));
+                            return None;
+                        }
+                        definition.prefix = simple
+                            && !term.contains([':', '/'])
+                            && (iri.starts_with("_:")
+                                || iri.ends_with([':', '/', '?', '#', '[', ']', '@']));
+                        definition.iri = Some(iri);
+                    } else {
+                        return None;
+                    }
+                }
+                _ => {
+                    errors.push(Spanned(
+                        String::from("Invalid IRI mapping, @id expects a string or null"),
+                        id.span().clone(),
+                    ));
+                    return None;
+                }
+            }
+        } else if let Some((prefix, suffix)) =
+            term.split_once(':').filter(|(_, s)| !s.starts_with("//"))
+        {
+            if members(local.members).any(|(k, _)| k.value() == &prefix) {
+                self.create_term(local, prefix, errors);
+            }
+            definition.iri = match self.terms.get(prefix).and_then(|t| t.iri.as_ref()) {
+                Some(iri) => Some(format!("{}{}", iri, suffix)),
+                None => Some(term.to_string()),
+            };
+        } else if term.contains(':') || term == "@type" {
+            definition.iri = Some(term.to_string());
+        } else if term.contains('/') {
+            definition.iri = self.expand_iri(term, true, true);
+        } else if let Some(vocab) = &self.vocab {
+            definition.iri = Some(format!("{}{}", vocab, term));
+        } else {
+            errors.push(Spanned(
+                format!(
+                    "Invalid IRI mapping, {} cannot be expanded without @vocab",
+                    term
+                ),
+                key.span().clone(),
+            ));
+            return None;
+        }
+
+        let mut error = |msg: String, span: &Range<usize>| errors.push(Spanned(msg, span.clone()));
+        if let Some(container) = member(ms, "@container") {
+            let values: Vec<_> = match container.value() {
+                Json::Array(xs) => xs.iter().map(|x| get_str(x.value())).collect(),
+                x => vec![get_str(x)],
+            };
+            if valid_container(&values) {
+                definition.container = values.into_iter().flatten().map(String::from).collect();
+            } else {
+                error(
+                    format!(
+                        "Invalid container mapping, expected one of {}",
+                        CONTAINERS.join(", ")
+                    ),
+                    container.span(),
+                );
+            }
+            if definition.reverse
+                && !definition
+                    .container
+                    .iter()
+                    .all(|x| x == "@set" || x == "@index")
+            {
+                error(
+                    String::from("Invalid reverse property, the container must be @set or @index"),
+                    container.span(),
+                );
+            }
+        }
+
+        if let Some(index) = member(ms, "@index") {
+            match get_str(index.value()) {
+                Some(x) if definition.container.iter().any(|c| c == "@index") => {
+                    definition.index = Some(x.to_string())
+                }
+                _ => error(
+                    String::from("Invalid term definition, @index requires an @index container"),
+                    index.span(),
+                ),
+            }
+        }
+
+        if let Some(language) = member(ms, "@language") {
+            match language.value() {
+                x if is_null(x) => definition.language = Some(None),
+                Json::Token(Token::Str(x, _)) => definition.language = Some(Some(x.to_lowercase())),
+                _ => error(
+                    String::from("Invalid language mapping, expected a string or null"),
+                    language.span(),
+                ),
+            }
+        }
+
+        if let Some(nest) = member(ms, "@nest") {
+            match get_str(nest.value()) {
+                Some(x) if x == "@nest" || !x.starts_with('@') => {
+                    definition.nest = Some(x.to_string())
+                }
+                _ => error(String::from("Invalid @nest value"), nest.span()),
+            }
+        }
+
+        if let Some(prefix) = member(ms, "@prefix") {
+            match get_bool(prefix.value()) {
+                Some(_) if term.contains([':', '/']) => error(
+                    String::from(
+                        "Invalid term definition, compact IRIs and IRIs cannot be prefixes",
+                    ),
+                    prefix.span(),
+                ),
+                Some(x) => definition.prefix = x,
+                None => error(
+                    String::from("Invalid @prefix value, expected a boolean"),
+                    prefix.span(),
+                ),
+            }
+        }
+
+        if let Some(context) = member(ms, "@context") {
+            definition.context = Some(context.clone());
+        }
+
+        Some(definition)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use lsp_core::prelude::Spanned;
+
+    use super::{ActiveContext, RemoteContexts};
+    use crate::lang::{
+        parser::{parse, Json},
+        tokenizer::tokenize,
+    };
+
+    fn parse_json(st: &str) -> Spanned<Json> {
+        let (tok, _) = tokenize(st);
+        parse(st, tok).0
+    }
+
+    fn process(st: &str) -> (ActiveContext, Vec<String>) {
+        let json = parse_json(st);
+        let base = lsp_types::Url::parse("http://example.com/doc").unwrap();
+        let mut errors = Vec::new();
+        let ctx = ActiveContext::new(&base).process(&json, &mut errors);
+        (ctx, errors.into_iter().map(|Spanned(x, _)| x).collect())
+    }
+
+    fn iri<'a>(ctx: &'a ActiveContext, term: &str) -> Option<&'a str> {
+        ctx.terms.get(term).and_then(|x| x.iri.as_deref())
+    }
+
+    #[test]
+    fn expanded_term_definitions() {
+        let (ctx, errors) = process(
+            r#"{
+            "@vocab": "http://schema.org/",
+            "foaf": "http://xmlns.com/foaf/0.1/",
+            "knows": { "@id": "foaf:knows", "@type": "@id", "@container": "@set" },
+            "name": { "@language": "en" },
+            "parent": { "@reverse": "foaf:child" }
+        }"#,
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(iri(&ctx, "knows"), Some("http://xmlns.com/foaf/0.1/knows"));
+        assert_eq!(ctx.terms["knows"].type_mapping.as_deref(), Some("@id"));
+        assert_eq!(ctx.terms["knows"].container, vec!["@set".to_string()]);
+        assert_eq!(iri(&ctx, "name"), Some("http://schema.org/name"));
+        assert_eq!(ctx.terms["name"].language, Some(Some("en".to_string())));
+        assert!(ctx.terms["parent"].reverse);
+        assert!(ctx.terms["foaf"].prefix);
+        assert_eq!(
+            ctx.expand_iri("foaf:name", true, false).as_deref(),
+            Some("http://xmlns.com/foaf/0.1/name")
+        );
+        assert_eq!(
+            ctx.expand_iri("other", false, true).as_deref(),
+            Some("http://example.com/other")
+        );
+    }
+
+    #[test]
+    fn protected_and_invalid_definitions() {
+        let (_, errors) = process(
+            r#"[{
+            "@protected": true,
+            "name": "http://schema.org/name"
+        }, {
+            "name": "http://xmlns.com/foaf/0.1/name",
+            "bad": { "@id": "relative", "@container": "@unknown" },
+            "@id": "http://example.com/"
+        }]"#,
+        );
+        assert_eq!(
+            errors,
+            vec![
+                "Protected term name cannot be redefined".to_string(),
+                "Invalid IRI mapping, relative is not an absolute IRI".to_string(),
+                "Keyword @id cannot be redefined".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn null_context_resets() {
+        let (ctx, _) = process(r#"[{ "foaf": "http://xmlns.com/foaf/0.1/" }, null]"#);
+        assert!(ctx.terms.is_empty());
+        assert_eq!(ctx.base.as_deref(), Some("http://example.com/doc"));
+    }
+
+    #[test]
+    fn remote_contexts() {
+        let mut remote = RemoteContexts::default();
+        remote.0.insert(
+            String::from("http://example.com/context.jsonld"),
+            parse_json(r#"{ "name": "http://xmlns.com/foaf/0.1/name" }"#),
+        );
+        remote.0.insert(
+            String::from("http://example.com/loop.jsonld"),
+            parse_json(r#""./loop.jsonld""#),
+        );
+        let base = lsp_types::Url::parse("http://example.com/doc").unwrap();
+        let active = ActiveContext::new(&base).with_remote(Arc::new(remote));
+
+        let mut errors = Vec::new();
+        let ctx = active.process(
+            &parse_json(r#"["./context.jsonld", "http://example.com/missing.jsonld"]"#),
+            &mut errors,
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(
+            ctx.terms["name"].iri.as_deref(),
+            Some("http://xmlns.com/foaf/0.1/name")
+        );
+        assert_eq!(
+            ctx.terms["name"].source.as_deref(),
+            Some("http://example.com/context.jsonld")
+        );
+
+        active.process(&parse_json(r#""loop.jsonld""#), &mut errors);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].value().starts_with("Recursive context inclusion"));
+    }
+
+    #[test]
+    fn container_mappings() {
+        let (ctx, errors) = process(
+            r#"{
+            "list": { "@id": "http://example.com/list", "@container": "@list" },
+            "graphs": { "@id": "http://example.com/graphs", "@container": ["@graph", "@id", "@set"] },
+            "listSet": { "@id": "http://example.com/listSet", "@container": ["@list", "@set"] },
+            "idType": { "@id": "http://example.com/idType", "@container": ["@id", "@type"] },
+            "twice": { "@id": "http://example.com/twice", "@container": ["@set", "@set"] }
+        }"#,
+        );
+        assert_eq!(ctx.terms["list"].container, vec!["@list".to_string()]);
+        assert_eq!(ctx.terms["graphs"].container.len(), 3);
+        assert_eq!(errors.len(), 3);
+        assert!(errors
+            .iter()
+            .all(|x| x.starts_with("Invalid container mapping")));
+    }
+}
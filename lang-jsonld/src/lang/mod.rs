@@ -1,3 +1,4 @@
+pub mod context;
 pub mod parser;
 pub mod tokenizer;
 pub mod triples;
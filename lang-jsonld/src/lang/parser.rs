@@ -203,11 +203,22 @@ impl Json {
     }
 }
 
+/// Writes [`Json`] following JSON-LD conventions: `@context`, `@id` and `@type` come first in
+/// every object.
 pub struct JsonFormatter {
     pub indent: String,
     pub inc: usize,
 }
 impl JsonFormatter {
+    pub fn new(options: &lsp_types::FormattingOptions) -> Self {
+        let indent = if options.insert_spaces {
+            " ".repeat(options.tab_size as usize)
+        } else {
+            String::from("\t")
+        };
+        Self { indent, inc: 0 }
+    }
+
     pub fn inc(&mut self) {
         self.inc += 1;
     }
@@ -224,13 +235,35 @@ impl JsonFormatter {
         Ok(())
     }
 
+    fn key_rank(key: &Token) -> usize {
+        match key {
+            Str(x, _) if x == "@context" => 0,
+            Str(x, _) if x == "@id" => 1,
+            Str(x, _) if x == "@type" => 2,
+            _ => 3,
+        }
+    }
+
+    fn token(&mut self, token: &Token, writer: &mut impl Write) -> io::Result<()> {
+        use std::io::{Error, ErrorKind};
+        match token {
+            Str(x, _) => write!(writer, "{}", serde_json::Value::String(x.clone())),
+            Number(x) => write!(writer, "{}", x),
+            True => write!(writer, "true"),
+            False => write!(writer, "false"),
+            Null => write!(writer, "null"),
+            _ => Result::Err(Error::new(ErrorKind::Other, "cannot format invalid json")),
+        }
+    }
+
     pub fn format(&mut self, json: &Json, writer: &mut impl Write) -> io::Result<()> {
         use std::io::{Error, ErrorKind};
         match json {
             Json::Invalid => {
                 return Result::Err(Error::new(ErrorKind::Other, "cannot format invalid json"))
             }
-            Json::Token(t) => write!(writer, "{}", t)?,
+            Json::Token(t) => self.token(t, writer)?,
+            Json::Array(xs) if xs.is_empty() => write!(writer, "[]")?,
             Json::Array(xs) => {
                 write!(writer, "[")?;
                 self.inc();
@@ -248,19 +281,24 @@ impl JsonFormatter {
                 self.line(writer)?;
                 write!(writer, "]")?;
             }
+            Json::Object(xs) if xs.is_empty() => write!(writer, "{{}}")?,
             Json::Object(xs) => {
+                let mut members: Vec<_> = xs.iter().collect();
+                members.sort_by_key(|x| Self::key_rank(x.field().value()));
+
                 write!(writer, "{{")?;
                 self.inc();
                 self.line(writer)?;
                 let mut first = true;
-                for t in xs {
+                for t in members {
                     if !first {
                         write!(writer, ",")?;
                         self.line(writer)?;
                     }
                     match &t.0 {
                         ObjectMember::Full(x, y) => {
-                            write!(writer, "{}: ", x.0)?;
+                            self.token(x, writer)?;
+                            write!(writer, ": ")?;
                             self.format(y, writer)?;
                         }
                         ObjectMember::Partial(_, _, _) => {
@@ -281,6 +319,14 @@ impl JsonFormatter {
     }
 }
 
+/// Formats a JSON-LD document, `None` when the document is not valid JSON.
+pub fn format_jsonld(json: &Json, options: &lsp_types::FormattingOptions) -> Option<String> {
+    let mut out = Vec::new();
+    JsonFormatter::new(options).format(json, &mut out).ok()?;
+    out.push(b'\n');
+    String::from_utf8(out).ok()
+}
+
 impl Default for Json {
     fn default() -> Self {
         Self::Invalid
@@ -569,4 +615,37 @@ mod tests {
         assert!(token_errors.is_empty());
         assert_eq!(json_errors.len(), 0);
     }
+
+    #[test]
+    fn format_jsonld_orders_keywords() {
+        let source = r#"{"name": "Arthur \"A\"", "@type": "Person", "knows": [], "@context": {"@vocab": "http://schema.org/"}, "@id": "me", "age": [1, true, null]}"#;
+        let (tokens, _) = tokenize(source);
+        let (json, json_errors) = parse(source, tokens);
+        assert!(json_errors.is_empty());
+
+        let options = lsp_types::FormattingOptions {
+            tab_size: 2,
+            insert_spaces: true,
+            ..Default::default()
+        };
+        let formatted = format_jsonld(json.value(), &options).expect("valid json");
+        assert_eq!(
+            formatted,
+            r#"{
+  "@context": {
+    "@vocab": "http://schema.org/"
+  },
+  "@id": "me",
+  "@type": "Person",
+  "name": "Arthur \"A\"",
+  "knows": [],
+  "age": [
+    1,
+    true,
+    null
+  ]
+}
+"#
+        );
+    }
 }
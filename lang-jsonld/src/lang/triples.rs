@@ -1,8 +1,17 @@
-use std::{borrow::Cow, ops::Range};
+use std::ops::Range;
 
 use lsp_core::prelude::*;
 
-use crate::lang::parser::{Json, ObjectMember};
+use crate::lang::{
+    context::{
+        get_str, is_absolute, is_keyword_like, members, ActiveContext, ContextError,
+        RemoteContexts, TermDefinition, CONTEXT_ENTRIES, KEYWORDS,
+    },
+    parser::{Json, ObjectMember},
+};
+
+const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
 
 fn visit_obj(json: &Spanned<Json>, f: &mut dyn FnMut(&[Spanned<ObjectMember>], &Range<usize>)) {
     match json.value() {
@@ -16,196 +25,697 @@ fn visit_obj(json: &Spanned<Json>, f: &mut dyn FnMut(&[Spanned<ObjectMember>], &
     }
 }
 
-fn get_str(tok: &Token) -> Option<&str> {
-    match tok {
-        Token::Str(x, _) => Some(x),
-        _ => None,
+/// The member of `mems` that is `keyword`, or an alias of it.
+fn find_keyword<'a>(
+    ctx: &ActiveContext,
+    mems: &'a [Spanned<ObjectMember>],
+    keyword: &str,
+) -> Option<&'a Spanned<Json>> {
+    members(mems)
+        .find(|(k, _)| ctx.keyword(k.value()) == Some(keyword))
+        .and_then(|(_, v)| v)
+}
+
+/// The context of the document, built from the contexts of the top-level nodes in order on top
+/// of `initial`.
+pub fn document_context(
+    json: &Spanned<Json>,
+    initial: &ActiveContext,
+) -> (ActiveContext, Vec<ContextError>) {
+    let mut errors = Vec::new();
+    let mut ctx = initial.clone();
+    visit_obj(json, &mut |mems, _| {
+        if let Some(local) = find_keyword(&ctx, mems, "@context") {
+            ctx = ctx.process(local, &mut errors);
+        }
+    });
+    (ctx, errors)
+}
+
+fn collect_references<'a>(json: &'a Spanned<Json>, context: bool, out: &mut Vec<&'a str>) {
+    match json.value() {
+        Json::Token(Token::Str(x, _)) if context => out.push(x),
+        Json::Array(xs) => {
+            for x in xs {
+                collect_references(x, context, out);
+            }
+        }
+        Json::Object(ms) => {
+            for (key, value) in members(ms) {
+                if let Some(value) = value {
+                    let context = matches!(*key.value(), "@context" | "@import");
+                    collect_references(value, context, out);
+                }
+            }
+        }
+        _ => {}
     }
 }
 
-fn correct_field(obj: &ObjectMember, field: &str) -> bool {
-    let f = obj.field();
-    match f.value() {
-        Token::Str(st, _) => st == field,
-        _ => false,
+/// IRIs of the remote contexts that the document references, including the contexts that
+/// loaded remote contexts reference.
+pub fn context_references(
+    json: &Spanned<Json>,
+    base: &lsp_types::Url,
+    remote: &RemoteContexts,
+) -> Vec<lsp_types::Url> {
+    let mut out: Vec<lsp_types::Url> = Vec::new();
+    let mut todo = vec![(json, base.clone(), false)];
+
+    while let Some((json, base, context)) = todo.pop() {
+        let mut references = Vec::new();
+        collect_references(json, context, &mut references);
+
+        for reference in references {
+            let Ok(url) = base.join(reference) else {
+                continue;
+            };
+            if out.contains(&url) {
+                continue;
+            }
+            if let Some(loaded) = remote.0.get(url.as_str()) {
+                todo.push((loaded, url.clone(), true));
+            }
+            out.push(url);
+        }
     }
+
+    out
 }
 
-fn find_field<'a>(
-    mem: &'a [Spanned<ObjectMember>],
-    field: &str,
-) -> Option<(&'a Spanned<Json>, &'a Range<usize>)> {
-    mem.iter()
-        .find(|x| correct_field(x, field))
-        .and_then(|x| x.json_value().map(|y| (y, x.field().span())))
+/// Every term of the context that expands to an IRI is a prefix.
+pub fn context_prefixes(ctx: &ActiveContext, base: &lsp_types::Url) -> Prefixes {
+    let mut out: Vec<_> = ctx
+        .terms
+        .iter()
+        .flat_map(|(prefix, term)| {
+            let url = lsp_types::Url::parse(term.iri.as_ref()?).ok()?;
+            Some((
+                term.span.start,
+                Prefix {
+                    prefix: prefix.clone(),
+                    url,
+                },
+            ))
+        })
+        .collect();
+    out.sort_by_key(|(start, _)| *start);
+
+    Prefixes(out.into_iter().map(|(_, x)| x).collect(), base.clone())
 }
 
 pub fn derive_prefixes(json: &Spanned<Json>, base: &lsp_types::Url) -> Prefixes {
-    let mut options: Vec<(Cow<str>, Cow<str>)> = Vec::new();
+    let (ctx, _) = document_context(json, &ActiveContext::new(base));
+    context_prefixes(&ctx, base)
+}
 
-    // Extract prefixes
-    visit_obj(json, &mut |mem, _| {
-        let Some((ctx, _)) = find_field(mem, "@context") else {
-            return;
-        };
+fn shorten_span(span: &Range<usize>) -> Range<usize> {
+    span.start + 1..span.end - 1
+}
+
+/// Turns node objects into quads, like JSON-LD expansion followed by RDF serialization.
+#[derive(Default)]
+struct Expander {
+    out: Vec<MyQuad<'static>>,
+    errors: Vec<ContextError>,
+    /// Entries that are valid JSON-LD but that processors ignore or override
+    warnings: Vec<ContextError>,
+    bn_count: usize,
+}
+
+impl Expander {
+    /// Blank node values are labels without `_:`, like in Turtle, and generated labels are named
+    /// like Turtle's so they do not clash with labels in the document.
+    fn blank_node(&mut self, span: Range<usize>) -> MyTerm<'static> {
+        let out = MyTerm::blank_node(format!("internal_bnode_{}", self.bn_count), span);
+        self.bn_count += 1;
+        out
+    }
+
+    fn iri(value: Option<String>, span: Range<usize>) -> MyTerm<'static> {
+        match value {
+            Some(x) if x.starts_with("_:") => MyTerm::blank_node(x[2..].to_string(), span),
+            Some(x) => MyTerm::named_node(x, span),
+            None => MyTerm::invalid(span),
+        }
+    }
 
-        visit_obj(ctx, &mut |mems, _| {
-            for mem in mems {
-                let Some(key) = get_str(mem.field()) else {
-                    continue;
-                };
-
-                let Some(value) = mem
-                    .json_value()
-                    .and_then(|x| x.as_ref().try_map(|x| x.token()))
-                    .and_then(|x| x.try_map(|x| get_str(x)))
-                else {
-                    continue;
-                };
-
-                options.push((Cow::Owned(key.to_string()), Cow::Owned(value.to_string())));
+    fn unknown_keyword(&mut self, key: &Spanned<&str>) {
+        self.warnings.push(Spanned(
+            format!("Unknown keyword {}, the entry is ignored", key.value()),
+            key.span().clone(),
+        ));
+    }
+
+    /// Checks the entries of an embedded context and the terms it redefines with respect to the
+    /// context it is nested in.
+    fn nested_context(
+        &mut self,
+        local: &Spanned<Json>,
+        outer: &ActiveContext,
+        inner: &ActiveContext,
+    ) {
+        let items: Vec<&Spanned<Json>> = match local.value() {
+            Json::Array(xs) => xs.iter().collect(),
+            _ => vec![local],
+        };
+        for item in items {
+            if let Json::Object(ms) = item.value() {
+                for (key, _) in members(ms) {
+                    if is_keyword_like(key.value())
+                        && !KEYWORDS.contains(key.value())
+                        && !CONTEXT_ENTRIES.contains(key.value())
+                    {
+                        self.unknown_keyword(&key);
+                    }
+                }
             }
-        });
-    });
+        }
 
-    // Expand prefixes
-    let mut changed = true;
-    let mut steps = 0;
-    while changed && steps < 5 {
-        changed = false;
-        for i in 0..options.len() {
-            let new_expaned = if let Some(pref) = options[i].1.find(':') {
-                let prefix = &options[i].1[..pref];
-                let Some((_, expaned)) = options.iter().find(|x| x.0 == prefix) else {
-                    continue;
-                };
-                format!("{}{}", expaned, &options[i].1[pref + 1..])
-            } else {
+        for (term, definition) in &inner.terms {
+            let defined_here =
+                definition.source.is_none() && local.span().contains(&definition.span.start);
+            let Some(previous) = outer.terms.get(term) else {
                 continue;
             };
-            options[i].1 = Cow::from(new_expaned);
-            changed = true;
+            if defined_here && !previous.same_as(definition) {
+                self.warnings.push(Spanned(
+                    format!(
+                        "Term {} conflicts with its definition in an outer context",
+                        term
+                    ),
+                    definition.span.clone(),
+                ));
+            }
         }
-        steps += 1;
     }
-    let mut out = Vec::new();
-
-    for (k, v) in options {
-        if let Some(url) = lsp_types::Url::parse(&v).ok() {
-            out.push(Prefix {
-                prefix: k.into_owned(),
-                url,
-            });
-        }
+
+    fn push(
+        &mut self,
+        subject: MyTerm<'static>,
+        predicate: MyTerm<'static>,
+        object: MyTerm<'static>,
+        graph: &Option<MyTerm<'static>>,
+        span: &Range<usize>,
+    ) {
+        self.out.push(MyQuad {
+            subject,
+            predicate,
+            object,
+            graph: graph.clone(),
+            span: span.clone(),
+        });
     }
 
-    Prefixes(out, base.clone())
-}
+    fn nodes(
+        &mut self,
+        json: &Spanned<Json>,
+        ctx: &ActiveContext,
+        graph: &Option<MyTerm<'static>>,
+    ) {
+        match json.value() {
+            Json::Array(xs) => {
+                for x in xs {
+                    self.nodes(x, ctx, graph);
+                }
+            }
+            Json::Object(ms) => {
+                self.node(ms, json.span(), ctx, graph, None);
+            }
+            _ => {}
+        }
+    }
 
-fn shorten_span(span: &Range<usize>) -> Range<usize> {
-    span.start + 1..span.end - 1
-}
+    /// Derives the quads of a node object and returns its subject, `id` is set for the values of
+    /// an `@id` map.
+    fn node(
+        &mut self,
+        ms: &[Spanned<ObjectMember>],
+        span: &Range<usize>,
+        ctx: &ActiveContext,
+        graph: &Option<MyTerm<'static>>,
+        id: Option<MyTerm<'static>>,
+    ) -> MyTerm<'static> {
+        let propagated = match find_keyword(ctx, ms, "@context") {
+            Some(local) => {
+                let processed = ctx.process(local, &mut self.errors);
+                self.nested_context(local, ctx, &processed);
+                processed
+            }
+            None => ctx.clone(),
+        };
 
-fn derive_triples_sub(
-    json: &Spanned<Json>,
-    prefixes: &Prefixes,
-    out: &mut Vec<MyQuad<'static>>,
-    bn_f: &mut dyn FnMut(Range<usize>) -> MyTerm<'static>,
-) -> Option<MyTerm<'static>> {
-    let mut subj_out = None;
-    visit_obj(json, &mut |mems, span| {
-        let subj = find_field(mems, "@id")
-            .and_then(|x| x.0.as_ref().try_map(|x| x.token()))
-            .and_then(|x| x.try_map(|x| prefixes.expand_json(x)))
-            .map(|Spanned(v, r)| MyTerm::named_node(v, shorten_span(&r)))
-            .unwrap_or_else(|| bn_f(span.clone()));
-
-        if let Some((ctx, _)) = find_field(mems, "@graph") {
-            derive_triples_sub(ctx, prefixes, out, bn_f);
+        // Type-scoped contexts only apply to this node, not to nested nodes
+        let mut types: Vec<&str> = match find_keyword(&propagated, ms, "@type").map(|x| x.value()) {
+            Some(Json::Array(xs)) => xs.iter().flat_map(|x| get_str(x.value())).collect(),
+            Some(x) => get_str(x).into_iter().collect(),
+            None => Vec::new(),
+        };
+        types.sort();
+        let mut node_ctx = propagated.clone();
+        for ty in types {
+            if let Some(scoped) = propagated.terms.get(ty).and_then(|t| t.context.as_ref()) {
+                node_ctx = node_ctx.process(scoped, &mut self.errors);
+            }
         }
-        if let Some((mem, span)) = find_field(mems, "@type") {
-            let object = match mem {
-                Spanned(Json::Token(tok), span) => {
-                    if let Some(st) = prefixes.expand_json(&tok) {
-                        MyTerm::named_node(st, span.clone())
+
+        let subject = id
+            .or_else(|| {
+                let id = find_keyword(&node_ctx, ms, "@id")?;
+                let value = get_str(id.value())?;
+                Some(Self::iri(
+                    node_ctx.expand_iri(value, false, true),
+                    shorten_span(id.span()),
+                ))
+            })
+            .unwrap_or_else(|| self.blank_node(span.clone()));
+
+        self.properties(ms, &subject, &node_ctx, &propagated, graph);
+        subject
+    }
+
+    fn properties(
+        &mut self,
+        ms: &[Spanned<ObjectMember>],
+        subject: &MyTerm<'static>,
+        ctx: &ActiveContext,
+        propagated: &ActiveContext,
+        graph: &Option<MyTerm<'static>>,
+    ) {
+        for mem in ms {
+            let field = mem.field();
+            let Token::Str(key, _) = field.value() else {
+                continue;
+            };
+            let value = mem.json_value();
+
+            match ctx.keyword(key) {
+                Some("@type") => {
+                    let Some(value) = value else {
+                        continue;
+                    };
+                    let predicate =
+                        MyTerm::named_node(format!("{}type", RDF), field.span().clone());
+                    let values: Vec<&Spanned<Json>> = match value.value() {
+                        Json::Array(xs) => xs.iter().collect(),
+                        _ => vec![value],
+                    };
+                    for ty in values {
+                        let object = match ty.value() {
+                            Json::Token(Token::Str(x, _)) => {
+                                let iri = ctx.expand_iri(x, true, true);
+                                let valid = iri
+                                    .as_deref()
+                                    .is_some_and(|x| is_absolute(x) || x.starts_with("_:"));
+                                if !valid || x.contains(char::is_whitespace) {
+                                    self.errors.push(Spanned(
+                                        format!("Invalid @type value {}, expected an IRI", x),
+                                        ty.span().clone(),
+                                    ));
+                                }
+                                Self::iri(iri, ty.span().clone())
+                            }
+                            Json::Object(ms) => self.node(ms, ty.span(), propagated, graph, None),
+                            _ => {
+                                self.errors.push(Spanned(
+                                    String::from("Invalid @type value, expected an IRI"),
+                                    ty.span().clone(),
+                                ));
+                                MyTerm::invalid(ty.span().clone())
+                            }
+                        };
+                        self.push(
+                            subject.clone(),
+                            predicate.clone(),
+                            object,
+                            graph,
+                            mem.span(),
+                        );
+                    }
+                }
+                Some("@graph") => {
+                    // A node with an @id or properties names the graph
+                    let named = members(ms).any(|(k, _)| match ctx.keyword(k.value()) {
+                        Some(kwd) => kwd == "@id",
+                        None => !is_keyword_like(k.value()),
+                    });
+                    let graph = if named {
+                        Some(subject.clone())
                     } else {
-                        MyTerm::invalid(span.clone())
+                        graph.clone()
+                    };
+                    if let Some(value) = value {
+                        self.nodes(value, propagated, &graph);
                     }
                 }
-                json => derive_triples_sub(json, prefixes, out, bn_f)
-                    .unwrap_or_else(|| MyTerm::invalid(json.span().clone())),
-            };
-
-            out.push(MyQuad {
-                subject: subj.clone(),
-                predicate: MyTerm::named_node(
-                    "http://www.w3.org/1999/02/22-rdf-syntax-ns#type",
-                    span.clone(),
-                ),
-                object,
-                span: mem.span().clone(),
-            });
+                Some("@reverse") => {
+                    if let Some(Spanned(Json::Object(rms), _)) = value {
+                        for rm in rms {
+                            let Some(key) = get_str_token(rm.field()) else {
+                                continue;
+                            };
+                            let Some(iri) = ctx.expand_iri(key, true, false) else {
+                                continue;
+                            };
+                            let predicate =
+                                MyTerm::named_node(iri, shorten_span(rm.field().span()));
+                            let Some(value) = rm.json_value() else {
+                                continue;
+                            };
+                            for object in self.values(value, None, propagated, graph) {
+                                self.push(
+                                    object,
+                                    predicate.clone(),
+                                    subject.clone(),
+                                    graph,
+                                    rm.span(),
+                                );
+                            }
+                        }
+                    }
+                }
+                Some("@nest") => {
+                    if let Some(Spanned(Json::Object(nms), _)) = value {
+                        self.properties(nms, subject, ctx, propagated, graph);
+                    }
+                }
+                Some("@included") => {
+                    if let Some(value) = value {
+                        self.nodes(value, propagated, graph);
+                    }
+                }
+                Some(_) => {}
+                None if is_keyword_like(key) => {
+                    self.unknown_keyword(&Spanned(key.as_str(), field.span().clone()))
+                }
+                None => {
+                    let definition = ctx.terms.get(key.as_str());
+                    let Some(iri) = ctx.expand_iri(key, true, false) else {
+                        continue;
+                    };
+                    // Keys that don't expand to an IRI are resolved against the base, so that a
+                    // key that is still being typed keeps its triple
+                    let iri = if iri.contains(':') {
+                        iri
+                    } else {
+                        self.warnings.push(Spanned(
+                            format!(
+                                "Term {} does not expand to an absolute IRI, the entry is dropped",
+                                key
+                            ),
+                            field.span().clone(),
+                        ));
+                        ctx.expand_iri(&iri, false, true).unwrap_or(iri)
+                    };
+                    let predicate = MyTerm::named_node(iri, shorten_span(field.span()));
+
+                    let value_ctx = match definition.and_then(|t| t.context.as_ref()) {
+                        Some(scoped) => propagated.process_with(scoped, &mut self.errors, true),
+                        None => propagated.clone(),
+                    };
+                    let objects = match value {
+                        None => vec![MyTerm::invalid(0..0)],
+                        Some(value) => self.values(value, definition, &value_ctx, graph),
+                    };
+
+                    let reverse = definition.is_some_and(|t| t.reverse);
+                    for object in objects {
+                        if reverse {
+                            self.push(
+                                object,
+                                predicate.clone(),
+                                subject.clone(),
+                                graph,
+                                mem.span(),
+                            );
+                        } else {
+                            self.push(
+                                subject.clone(),
+                                predicate.clone(),
+                                object,
+                                graph,
+                                mem.span(),
+                            );
+                        }
+                    }
+                }
+            }
         }
-        for mem in mems {
-            let field = mem.field();
-            let st = get_str(&field);
-            if st.map(|x| x.starts_with("@")).unwrap_or(false) {
-                continue;
+    }
+
+    /// The objects that a property value stands for, following the container and type mapping of
+    /// its term.
+    fn values(
+        &mut self,
+        json: &Spanned<Json>,
+        definition: Option<&TermDefinition>,
+        ctx: &ActiveContext,
+        graph: &Option<MyTerm<'static>>,
+    ) -> Vec<MyTerm<'static>> {
+        let container = definition
+            .map(|t| t.container.as_slice())
+            .unwrap_or_default();
+        let has = |x: &str| container.iter().any(|c| c == x);
+        // Values of a map or list are not maps or lists themselves
+        let inner = definition.map(|t| TermDefinition {
+            container: Vec::new(),
+            ..t.clone()
+        });
+        let inner = inner.as_ref();
+
+        match json.value() {
+            Json::Array(xs) if has("@list") => vec![self.list(xs, inner, ctx, graph, json.span())],
+            Json::Array(xs) => {
+                let mut out = Vec::new();
+                for x in xs {
+                    out.extend(self.values(x, definition, ctx, graph));
+                }
+                out
+            }
+            Json::Object(ms) if has("@language") => {
+                let mut out = Vec::new();
+                for (key, value) in members(ms) {
+                    let Some(value) = value else {
+                        continue;
+                    };
+                    // The keys of a language map are the language tags of its strings
+                    let language = (*key.value() != "@none").then(|| key.value().to_lowercase());
+                    let definition = inner.map(|t| TermDefinition {
+                        language: Some(language),
+                        ..t.clone()
+                    });
+                    out.extend(self.values(value, definition.as_ref(), ctx, graph));
+                }
+                out
+            }
+            Json::Object(ms) if has("@id") || has("@type") || has("@index") => {
+                let mut out = Vec::new();
+                for (key, value) in members(ms) {
+                    let Some(value) = value else {
+                        continue;
+                    };
+                    match value.value() {
+                        Json::Object(nms) if has("@id") && *key.value() != "@none" => {
+                            let id = Self::iri(
+                                ctx.expand_iri(key.value(), false, true),
+                                shorten_span(key.span()),
+                            );
+                            out.push(self.node(nms, value.span(), ctx, graph, Some(id)));
+                        }
+                        _ if has("@type") => {
+                            let nodes = match value.value() {
+                                Json::Token(Token::Str(x, _)) => {
+                                    vec![Self::iri(
+                                        ctx.expand_iri(x, false, true),
+                                        value.span().clone(),
+                                    )]
+                                }
+                                _ => self.values(value, inner, ctx, graph),
+                            };
+                            if *key.value() != "@none" {
+                                let ty = Self::iri(
+                                    ctx.expand_iri(key.value(), true, true),
+                                    shorten_span(key.span()),
+                                );
+                                let predicate =
+                                    MyTerm::named_node(format!("{}type", RDF), key.span().clone());
+                                for node in &nodes {
+                                    self.push(
+                                        node.clone(),
+                                        predicate.clone(),
+                                        ty.clone(),
+                                        graph,
+                                        value.span(),
+                                    );
+                                }
+                            }
+                            out.extend(nodes);
+                        }
+                        _ => out.extend(self.values(value, inner, ctx, graph)),
+                    }
+                }
+                out
             }
+            Json::Object(ms) => {
+                if let Some(value) = find_keyword(ctx, ms, "@value") {
+                    let datatype = find_keyword(ctx, ms, "@type")
+                        .and_then(|x| get_str(x.value()))
+                        .and_then(|x| ctx.expand_iri(x, true, true))
+                        .filter(|x| !x.starts_with('@'));
+                    let language = find_keyword(ctx, ms, "@language")
+                        .and_then(|x| get_str(x.value()))
+                        .map(|x| x.to_lowercase());
+                    return literal(value, datatype, language);
+                }
+                if let Some(list) = find_keyword(ctx, ms, "@list") {
+                    let xs = match list.value() {
+                        Json::Array(xs) => xs.as_slice(),
+                        _ => std::slice::from_ref(list),
+                    };
+                    return vec![self.list(xs, inner, ctx, graph, list.span())];
+                }
+                if let Some(set) = find_keyword(ctx, ms, "@set") {
+                    return self.values(set, inner, ctx, graph);
+                }
+                vec![self.node(ms, json.span(), ctx, graph, None)]
+            }
+            Json::Token(token) => {
+                let ty = definition.and_then(|t| t.type_mapping.as_deref());
+                match (ty, token) {
+                    (Some("@id"), Token::Str(x, _)) => vec![Self::iri(
+                        ctx.expand_iri(x, false, true),
+                        shorten_span(json.span()),
+                    )],
+                    (Some("@vocab"), Token::Str(x, _)) => vec![Self::iri(
+                        ctx.expand_iri(x, true, true),
+                        shorten_span(json.span()),
+                    )],
+                    _ => {
+                        let datatype = ty.filter(|x| !x.starts_with('@')).map(String::from);
+                        // The language of the term takes precedence over the default language
+                        let language = match definition.and_then(|t| t.language.clone()) {
+                            Some(language) => language,
+                            None => ctx.language.clone(),
+                        };
+                        literal(json, datatype, language)
+                    }
+                }
+            }
+            _ => vec![MyTerm::invalid(json.span().clone())],
+        }
+    }
 
-            let Some(pred) = prefixes
-                .expand_json(&field)
-                .or_else(|| st.map(|x| x.to_string()))
-            else {
-                continue;
-            };
-            let pred = MyTerm::named_node(pred, shorten_span(field.span()));
-
-            // get value
-            let object = match mem.json_value() {
-                None => MyTerm::invalid(0..0),
-                // TODO: this might not be a literal, should look it up in the context
-                Some(Spanned(Json::Token(tok), span)) => match tok {
-                    Token::Str(x, _) => MyTerm::literal(x.clone(), span.clone()),
-                    _ => MyTerm::invalid(span.clone()),
-                },
-                Some(json) => derive_triples_sub(json, prefixes, out, bn_f)
-                    .unwrap_or_else(|| MyTerm::invalid(json.span().clone())),
-            };
+    /// Builds an `rdf:List` of the values, returns its head.
+    fn list(
+        &mut self,
+        xs: &[Spanned<Json>],
+        definition: Option<&TermDefinition>,
+        ctx: &ActiveContext,
+        graph: &Option<MyTerm<'static>>,
+        span: &Range<usize>,
+    ) -> MyTerm<'static> {
+        let mut items = Vec::new();
+        for x in xs {
+            items.extend(self.values(x, definition, ctx, graph));
+        }
 
-            out.push(MyQuad {
-                subject: subj.clone(),
-                predicate: pred,
-                object,
-                span: mem.span().clone(),
-            });
+        let mut head = MyTerm::named_node(format!("{}nil", RDF), span.clone());
+        for item in items.into_iter().rev() {
+            let node = self.blank_node(span.clone());
+            let first = MyTerm::named_node(format!("{}first", RDF), span.clone());
+            let rest = MyTerm::named_node(format!("{}rest", RDF), span.clone());
+            self.push(node.clone(), first, item, graph, span);
+            self.push(node.clone(), rest, head, graph, span);
+            head = node;
+        }
+        head
+    }
+}
+
+/// The literal of a scalar value. Numbers and booleans get their XSD datatype unless `datatype`
+/// is set, `language` only applies to strings without a datatype.
+fn literal(
+    json: &Spanned<Json>,
+    datatype: Option<String>,
+    language: Option<String>,
+) -> Vec<MyTerm<'static>> {
+    let (value, default) = match json.value() {
+        Json::Token(Token::Str(x, _)) => (x.clone(), None),
+        Json::Token(Token::Number(x)) => {
+            let ty = if x.contains(['.', 'e', 'E']) {
+                "double"
+            } else {
+                "integer"
+            };
+            (x.clone(), Some(format!("{}{}", XSD, ty)))
         }
+        Json::Token(Token::True) => (String::from("true"), Some(format!("{}boolean", XSD))),
+        Json::Token(Token::False) => (String::from("false"), Some(format!("{}boolean", XSD))),
+        Json::Token(Token::Null) => return Vec::new(),
+        _ => return vec![MyTerm::invalid(json.span().clone())],
+    };
+
+    let term = MyTerm::literal(value, json.span().clone());
+    let term = match (datatype.or(default), language) {
+        (Some(datatype), _) => term.with_datatype(datatype),
+        (None, Some(language)) => term.with_language(language),
+        (None, None) => term,
+    };
+    vec![term]
+}
+
+fn get_str_token(tok: &Token) -> Option<&str> {
+    match tok {
+        Token::Str(x, _) => Some(x),
+        _ => None,
+    }
+}
 
-        subj_out = Some(subj);
+/// Sorts problems in document order, dropping those that are reported more than once (scoped
+/// contexts are processed for every use of their term).
+fn in_document_order(mut problems: Vec<ContextError>) -> Vec<ContextError> {
+    problems.sort_by(|a, b| {
+        (a.span().start, a.span().end, a.value()).cmp(&(b.span().start, b.span().end, b.value()))
     });
-    subj_out
+    problems.dedup_by(|a, b| a.span() == b.span() && a.value() == b.value());
+    problems
+}
+
+/// Derives the quads of the document together with the errors and warnings found while processing
+/// contexts and node objects, starting from the `initial` context.
+///
+/// Literals carry their datatype and language, numbers keep their lexical form instead of the
+/// canonical form of expansion, and `@json` and `@direction` values become plain strings.
+pub fn expand(
+    json: &Spanned<Json>,
+    initial: &ActiveContext,
+) -> (Vec<MyQuad<'static>>, Vec<ContextError>, Vec<ContextError>) {
+    let mut expander = Expander::default();
+    expander.nodes(json, initial, &None);
+    (
+        expander.out,
+        in_document_order(expander.errors),
+        in_document_order(expander.warnings),
+    )
 }
 
+/// Derives the quads of the document, relative IRIs are resolved against the base of `prefixes`.
 pub fn derive_triples(json: &Spanned<Json>, prefixes: &Prefixes) -> Vec<MyQuad<'static>> {
-    let mut bn_count = 0;
-    let mut bn = move |span| {
-        let out = MyTerm::blank_node(format!("_:{}", bn_count), span);
-        bn_count += 1;
-        out
-    };
-    let mut out = Vec::new();
-    derive_triples_sub(json, prefixes, &mut out, &mut bn);
-    out
+    expand(json, &ActiveContext::new(&prefixes.1)).0
 }
 
 #[cfg(test)]
 mod tests {
 
-    use lsp_core::prelude::{MyQuad, Spanned};
+    use lsp_core::{
+        prelude::{MyQuad, Spanned},
+        systems::{convert, Format},
+    };
     use sophia_api::term::{Term, TermKind};
 
-    use super::{derive_prefixes, derive_triples};
+    use super::{derive_prefixes, derive_triples, expand};
     use crate::lang::{
+        context::ActiveContext,
         parser::{parse, Json},
         tokenizer::tokenize,
     };
@@ -516,4 +1026,206 @@ mod tests {
         assert_eq!(friend_friend[0].object.as_str(), "Julian");
         assert_eq!(friend_friend[0].object.kind(), TermKind::Literal);
     }
+
+    #[test]
+    fn derive_triples_expanded_definitions() {
+        let st = r#" {
+            "@context": {
+                "@vocab": "http://schema.org/",
+                "knows": {"@type": "@id"},
+                "parent": {"@reverse": "http://schema.org/children"},
+                "steps": {"@container": "@list"}
+            },
+            "@id": "http://example.com/ns#me",
+            "knows": "http://example.com/ns#you",
+            "parent": {"@id": "http://example.com/ns#dad"},
+            "steps": ["a"]
+        } "#;
+        let url = lsp_types::Url::parse("memory://test.jsonld").unwrap();
+
+        let json = parse_json(st).expect("valid json");
+        let prefixes = derive_prefixes(&json, &url);
+        let triples = derive_triples(&json, &prefixes);
+
+        let knows = triples
+            .iter()
+            .find(|x| x.predicate.as_str() == "http://schema.org/knows")
+            .expect("knows triple");
+        assert_eq!(knows.object.as_str(), "http://example.com/ns#you");
+        assert_eq!(knows.object.kind(), TermKind::Iri);
+
+        let children = triples
+            .iter()
+            .find(|x| x.predicate.as_str() == "http://schema.org/children")
+            .expect("reverse triple");
+        assert_eq!(children.subject.as_str(), "http://example.com/ns#dad");
+        assert_eq!(children.object.as_str(), "http://example.com/ns#me");
+
+        let steps = triples
+            .iter()
+            .find(|x| x.predicate.as_str() == "http://schema.org/steps")
+            .expect("list triple");
+        assert_eq!(steps.object.kind(), TermKind::BlankNode);
+        assert_eq!(triples.len(), 5);
+    }
+
+    #[test]
+    fn derive_triples_type_scoped_context() {
+        let st = r#" {
+            "@context": {
+                "@vocab": "http://schema.org/",
+                "Person": {"@context": {"name": "http://xmlns.com/foaf/0.1/name"}}
+            },
+            "@type": "Person",
+            "name": "Arthur",
+            "knows": {"name": "Julian"}
+        } "#;
+        let url = lsp_types::Url::parse("memory://test.jsonld").unwrap();
+
+        let json = parse_json(st).expect("valid json");
+        let prefixes = derive_prefixes(&json, &url);
+        let triples = derive_triples(&json, &prefixes);
+
+        let names: Vec<_> = triples
+            .iter()
+            .filter(|x| x.predicate.as_str().ends_with("name"))
+            .map(|x| (x.predicate.as_str(), x.object.as_str()))
+            .collect();
+        assert!(names.contains(&("http://xmlns.com/foaf/0.1/name", "Arthur")));
+        assert!(names.contains(&("http://schema.org/name", "Julian")));
+    }
+
+    #[test]
+    fn converted_blank_nodes_have_one_prefix() {
+        let st = r#" {
+            "@context": { "@vocab": "http://schema.org/" },
+            "@id": "http://example.com/me",
+            "address": { "city": "Ghent" },
+            "knows": { "@id": "_:friend" }
+        } "#;
+        let url = lsp_types::Url::parse("memory://test.jsonld").unwrap();
+
+        let json = parse_json(st).expect("valid json");
+        let prefixes = derive_prefixes(&json, &url);
+        let triples = derive_triples(&json, &prefixes);
+
+        let ntriples = convert(&triples, &prefixes, Format::NTriples);
+        assert!(
+            ntriples.contains("<http://schema.org/address> _:internal_bnode_0 ."),
+            "{}",
+            ntriples
+        );
+        assert!(
+            ntriples.contains("_:internal_bnode_0 <http://schema.org/city> \"Ghent\" ."),
+            "{}",
+            ntriples
+        );
+        assert!(
+            ntriples.contains("<http://schema.org/knows> _:friend ."),
+            "{}",
+            ntriples
+        );
+
+        let turtle = convert(&triples, &prefixes, Format::Turtle);
+        assert!(turtle.contains("_:internal_bnode_0 "), "{}", turtle);
+        assert!(turtle.contains("_:friend"), "{}", turtle);
+        assert!(!turtle.contains("_:_:"), "{}", turtle);
+    }
+
+    #[test]
+    fn derive_triples_typed_literals() {
+        let st = r#" {
+            "@context": {
+                "@vocab": "http://schema.org/",
+                "@language": "en",
+                "xsd": "http://www.w3.org/2001/XMLSchema#",
+                "born": {"@type": "xsd:date"},
+                "nick": {"@language": null},
+                "label": {"@container": "@language"}
+            },
+            "name": "Arthur",
+            "nick": "Art",
+            "born": "1990-01-01",
+            "age": 34,
+            "height": 1.8,
+            "alive": true,
+            "title": {"@value": "Herr", "@language": "DE"},
+            "count": {"@value": "3", "@type": "xsd:integer"},
+            "label": {"nl": "Artur"}
+        } "#;
+        let url = lsp_types::Url::parse("memory://test.jsonld").unwrap();
+
+        let json = parse_json(st).expect("valid json");
+        let prefixes = derive_prefixes(&json, &url);
+        let triples = derive_triples(&json, &prefixes);
+
+        let object = |name: &str| {
+            let triple = triples
+                .iter()
+                .find(|x| x.predicate.as_str() == format!("http://schema.org/{}", name))
+                .expect("triple");
+            (
+                triple.object.as_str(),
+                triple.object.datatype_iri().map(String::from),
+                triple.object.language().map(String::from),
+            )
+        };
+        let xsd = |x: &str| Some(format!("http://www.w3.org/2001/XMLSchema#{}", x));
+        let lang_string = Some(String::from(
+            "http://www.w3.org/1999/02/22-rdf-syntax-ns#langString",
+        ));
+
+        assert_eq!(
+            object("name"),
+            ("Arthur", lang_string.clone(), Some("en".into()))
+        );
+        assert_eq!(object("nick"), ("Art", xsd("string"), None));
+        assert_eq!(object("born"), ("1990-01-01", xsd("date"), None));
+        assert_eq!(object("age"), ("34", xsd("integer"), None));
+        assert_eq!(object("height"), ("1.8", xsd("double"), None));
+        assert_eq!(object("alive"), ("true", xsd("boolean"), None));
+        assert_eq!(
+            object("title"),
+            ("Herr", lang_string.clone(), Some("de".into()))
+        );
+        assert_eq!(object("count"), ("3", xsd("integer"), None));
+        assert_eq!(object("label"), ("Artur", lang_string, Some("nl".into())));
+    }
+
+    #[test]
+    fn expand_reports_problems() {
+        let st = r#" {
+            "@context": {"foaf": "http://xmlns.com/foaf/0.1/", "name": "foaf:name"},
+            "@id": "http://example.com/ns#me",
+            "@type": ["foaf:Person", "not an iri", 42],
+            "@tpye": "foaf:Agent",
+            "nick": "Arthur",
+            "foaf:knows": {
+                "@context": {"name": "http://schema.org/name"},
+                "name": "Julian"
+            }
+        } "#;
+        let json = parse_json(st).expect("valid json");
+        let url = lsp_types::Url::parse("http://example.com/doc").unwrap();
+        let (_, errors, warnings) = expand(&json, &ActiveContext::new(&url));
+
+        let errors: Vec<_> = errors.iter().map(|x| x.value().as_str()).collect();
+        assert_eq!(
+            errors,
+            vec![
+                "Invalid @type value not an iri, expected an IRI",
+                "Invalid @type value, expected an IRI",
+            ]
+        );
+
+        let warnings: Vec<_> = warnings.iter().map(|x| x.value().as_str()).collect();
+        assert_eq!(
+            warnings,
+            vec![
+                "Unknown keyword @tpye, the entry is ignored",
+                "Term nick does not expand to an absolute IRI, the entry is dropped",
+                "Term name conflicts with its definition in an outer context",
+            ]
+        );
+    }
 }
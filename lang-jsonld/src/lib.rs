@@ -16,7 +16,11 @@ use ropey::Rope;
 pub mod ecs;
 pub mod lang;
 use crate::{
-    ecs::{highlight_named_nodes, keyword_highlight, setup_parse},
+    ecs::{
+        highlight_named_nodes, keyword_highlight, setup_code_action, setup_completion,
+        setup_format, setup_goto_definition, setup_hover, setup_parse, setup_symbols,
+        undefined_compact_iri,
+    },
     lang::parser::Json,
 };
 
@@ -63,10 +67,16 @@ pub fn setup_world(world: &mut World) {
 
     world.schedule_scope(DiagnosticsLabel, |_, schedule| {
         use diagnostics::*;
-        schedule.add_systems(publish_diagnostics::<JsonLd>);
+        schedule.add_systems((publish_diagnostics::<JsonLd>, undefined_compact_iri));
     });
 
     setup_parse(world);
+    setup_code_action(world);
+    setup_format(world);
+    setup_symbols(world);
+    setup_completion::<JsonLd>(world);
+    setup_hover::<JsonLd>(world);
+    setup_goto_definition::<JsonLd>(world);
 }
 
 #[derive(Debug, Component)]
@@ -84,7 +94,7 @@ impl Lang for JsonLd {
     const PATTERN: Option<&'static str> = None;
 
     const LANG: &'static str = "jsonld";
-    const CODE_ACTION: bool = false;
+    const CODE_ACTION: bool = true;
     const HOVER: bool = true;
 
     const TRIGGERS: &'static [&'static str] = &["@", "\""];
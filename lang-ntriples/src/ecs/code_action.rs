@@ -0,0 +1,93 @@
+use bevy_ecs::prelude::*;
+use lsp_core::{
+    components::*,
+    feature::command::{Conversion, TO_TURTLE},
+    prelude::*,
+    systems::{convert, new_document_edit, Format},
+};
+use lsp_types::{CodeAction, CodeActionKind, CodeActionOrCommand, Command};
+use tracing::info;
+
+use crate::{lang::convert::turtle_prefixes, NTriplesLang};
+
+/// Location of the Turtle file next to the N-Triples document, `data.nt` becomes `data.ttl`.
+fn turtle_location(url: &lsp_types::Url) -> Option<lsp_types::Url> {
+    let name = url.path_segments()?.last()?;
+    let stem = name
+        .strip_suffix(".nt")
+        .or_else(|| name.strip_suffix(".nq"))
+        .unwrap_or(name);
+    url.join(&format!("{}.ttl", stem)).ok()
+}
+
+/// Offers to write the document as Turtle with prefixes to a sibling `.ttl` file.
+///
+/// The action only carries the [`TO_TURTLE`] command, the document is converted when the command
+/// is executed. Documents with graph names are skipped, Turtle cannot express them.
+pub fn ntriples_to_turtle_code_action(
+    mut query: Query<(&Element<NTriplesLang>, &Label, &mut CodeActionRequest)>,
+) {
+    for (nt, label, mut req) in &mut query {
+        if nt.statements.is_empty() || nt.has_graphs() {
+            continue;
+        }
+
+        let title = String::from("Convert to Turtle with prefixes");
+        req.actions
+            .push(CodeActionOrCommand::CodeAction(CodeAction {
+                title: title.clone(),
+                kind: Some(CodeActionKind::REFACTOR_REWRITE),
+                command: Some(Command {
+                    title,
+                    command: TO_TURTLE.to_string(),
+                    arguments: Some(vec![serde_json::Value::String(label.0.to_string())]),
+                }),
+                ..Default::default()
+            }));
+    }
+}
+
+/// Handles [`TO_TURTLE`], the result is a [`Conversion`] creating the sibling `.ttl` file.
+///
+/// The triples are written by the [`convert`] serializer with prefixes from
+/// [`turtle_prefixes`].
+pub fn ntriples_to_turtle(
+    mut query: Query<(
+        &Element<NTriplesLang>,
+        &Label,
+        &Triples,
+        &mut ExecuteCommandRequest,
+    )>,
+) {
+    for (nt, label, triples, mut request) in &mut query {
+        if request.command != TO_TURTLE {
+            continue;
+        }
+
+        if nt.has_graphs() {
+            request.error = Some(format!(
+                "{} has named graphs, Turtle cannot express them",
+                label.0
+            ));
+            continue;
+        }
+
+        let Some(target) = turtle_location(&label.0) else {
+            request.error = Some(format!("No turtle location for {}", label.0));
+            continue;
+        };
+
+        let prefixes = turtle_prefixes(&triples.0, &label.0);
+        let text = convert(&triples.0, &prefixes, Format::Turtle);
+        info!("Converted {} to {}", label.0, target);
+
+        let conversion = Conversion {
+            edit: new_document_edit(&target, &text),
+            uri: target,
+            language_id: Format::Turtle.language_id().to_string(),
+            text,
+            replace: false,
+        };
+        request.result = serde_json::to_value(conversion).ok();
+    }
+}
@@ -0,0 +1,150 @@
+use bevy_ecs::{prelude::*, world::World};
+use code_action::{ntriples_to_turtle, ntriples_to_turtle_code_action};
+use lsp_core::prelude::*;
+use parse::{derive_prefixes, derive_triples, parse_ntriples_system, parse_source};
+
+mod code_action;
+mod parse;
+
+pub fn setup_parsing(world: &mut World) {
+    use lsp_core::feature::parse::*;
+    world.schedule_scope(ParseLabel, |_, schedule| {
+        schedule.add_systems((
+            parse_source,
+            parse_ntriples_system.after(parse_source),
+            derive_prefixes
+                .after(parse_ntriples_system)
+                .before(prefixes),
+            derive_triples.after(parse_ntriples_system).before(triples),
+        ));
+    });
+}
+
+pub fn setup_code_action(world: &mut World) {
+    world.schedule_scope(CodeActionLabel, |_, schedule| {
+        schedule.add_systems(ntriples_to_turtle_code_action);
+    });
+    world.schedule_scope(ExecuteCommandLabel, |_, schedule| {
+        schedule.add_systems(ntriples_to_turtle);
+    });
+    ExecutableCommands::register(world, lsp_core::feature::command::TO_TURTLE);
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_core::{
+        components::*,
+        prelude::{diagnostics::DiagnosticItem, *},
+    };
+    use test_log::test;
+    use test_utils::{create_file, setup_world, TestClient};
+
+    use crate::NTriplesLang;
+
+    #[test]
+    fn errors_do_not_hide_other_lines() {
+        let (mut world, mut rx) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = "<http://example.com/a> <http://example.com/p> <http://example.com/b> .
+<http://example.com/a> <http://example.com/p>
+<http://example.com/a> <http://example.com/p> \"c\" .
+";
+
+        let entity = create_file(&mut world, t1, "http://example.com/data.nt", "", Open);
+        world.run_schedule(ParseLabel);
+        world.run_schedule(DiagnosticsLabel);
+
+        assert!(world.entity(entity).contains::<NTriplesLang>());
+
+        let triples = world.entity(entity).get::<Triples>().expect("triples");
+        assert_eq!(triples.0.len(), 2);
+
+        let prefixes = world.entity(entity).get::<Prefixes>().expect("prefixes");
+        assert!(prefixes.0.is_empty());
+
+        let mut items: Vec<DiagnosticItem> = Vec::new();
+        while let Ok(Some(x)) = rx.try_next() {
+            items.push(x);
+        }
+        assert_eq!(items[0].diagnostics.len(), 1);
+        assert_eq!(items[0].diagnostics[0].range.start.line, 1);
+    }
+
+    #[test]
+    fn convert_to_turtle_code_action() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = "<http://data.example/a> <http://xmlns.com/foaf/0.1/name> \"A\" .\n";
+        let entity = create_file(
+            &mut world,
+            t1,
+            "http://example.com/data.nt",
+            "ntriples",
+            Open,
+        );
+        world.run_schedule(ParseLabel);
+
+        let range = lsp_types::Range::new(
+            lsp_types::Position::new(0, 0),
+            lsp_types::Position::new(0, 0),
+        );
+        world
+            .entity_mut(entity)
+            .insert(CodeActionRequest::new(range, vec![]));
+        world.run_schedule(CodeActionLabel);
+
+        let req = world
+            .entity_mut(entity)
+            .take::<CodeActionRequest>()
+            .expect("code action request");
+        // Only one action converts to Turtle, the other formats are offered by the core
+        let turtle: Vec<_> = req
+            .actions
+            .iter()
+            .filter_map(|x| match x {
+                lsp_types::CodeActionOrCommand::CodeAction(a) if a.title.contains("Turtle") => {
+                    Some(a)
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(turtle.len(), 1);
+        assert!(req.actions.len() > 1);
+
+        let action = turtle[0];
+        assert!(action.edit.is_none());
+        let command = action.command.clone().expect("command");
+        assert_eq!(command.command, lsp_core::feature::command::TO_TURTLE);
+
+        world.entity_mut(entity).insert(ExecuteCommandRequest::new(
+            command.command,
+            command.arguments.unwrap_or_default(),
+        ));
+        world.run_schedule(ExecuteCommandLabel);
+
+        let request = world
+            .entity_mut(entity)
+            .take::<ExecuteCommandRequest>()
+            .expect("execute command request");
+        let conversion: lsp_core::feature::command::Conversion =
+            serde_json::from_value(request.result.expect("result")).expect("conversion");
+        assert_eq!(conversion.uri.as_str(), "http://example.com/data.ttl");
+        let Some(lsp_types::DocumentChanges::Operations(ops)) = conversion.edit.document_changes
+        else {
+            panic!("expected document changes");
+        };
+        let lsp_types::DocumentChangeOperation::Edit(edit) = &ops[1] else {
+            panic!("expected a text edit");
+        };
+        assert_eq!(
+            edit.text_document.uri.as_str(),
+            "http://example.com/data.ttl"
+        );
+        let lsp_types::OneOf::Left(edit) = &edit.edits[0] else {
+            panic!("expected a plain text edit");
+        };
+        assert!(edit
+            .new_text
+            .starts_with("@prefix ns1: <http://data.example/>.\n@prefix foaf:"));
+    }
+}
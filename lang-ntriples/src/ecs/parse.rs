@@ -0,0 +1,81 @@
+use bevy_ecs::prelude::*;
+use chumsky::prelude::Simple;
+use lang_turtle::lang::tokenizer::parse_tokens_str;
+use lsp_core::prelude::*;
+use tracing::{info, instrument};
+
+use crate::{lang::parser::parse_ntriples, NTriplesLang};
+
+#[instrument(skip(query, commands), name = "parse_source")]
+pub fn parse_source(
+    query: Query<
+        (
+            Entity,
+            &Source,
+            Option<&SourceChange>,
+            Option<&Tokens>,
+            Option<&Errors<Simple<char>>>,
+        ),
+        (Changed<Source>, With<NTriplesLang>),
+    >,
+    mut commands: Commands,
+) {
+    for (entity, source, change, old_tokens, old_errors) in &query {
+        commands.entity(entity).remove::<SourceChange>();
+        let old = old_tokens.zip(old_errors);
+        let (tok, es) = tokenize_source(&source.0, change, old, parse_tokens_str);
+        info!("tokenized  {} tokens ({} errors)", tok.len(), es.len());
+        commands.entity(entity).insert((Tokens(tok), Errors(es)));
+    }
+}
+
+#[instrument(skip(query, commands), name = "parse_ntriples")]
+pub fn parse_ntriples_system(
+    query: Query<(Entity, &Source, &Tokens, &Label), (Changed<Tokens>, With<NTriplesLang>)>,
+    mut commands: Commands,
+    config: Res<ServerConfig>,
+) {
+    if !config.config.ntriples.unwrap_or(true) {
+        return;
+    }
+    for (entity, source, tokens, label) in &query {
+        let (nt, es) = parse_ntriples(&label.0, &tokens.0, &source.0);
+
+        let es: Vec<_> = es.into_iter().map(|e| (e.map(|PToken(t, _)| t))).collect();
+        info!("{} statements ({} errors)", nt.statements.len(), es.len());
+
+        let element = Element::<NTriplesLang>(spanned(nt, 0..source.0.len()));
+        if es.is_empty() {
+            commands
+                .entity(entity)
+                .insert((element, Errors(es)))
+                .remove::<Dirty>();
+        } else {
+            commands.entity(entity).insert((Errors(es), element, Dirty));
+        }
+    }
+}
+
+pub fn derive_triples(
+    query: Query<(Entity, &Element<NTriplesLang>), Changed<Element<NTriplesLang>>>,
+    mut commands: Commands,
+) {
+    for (entity, nt) in &query {
+        if let Ok(triples) = nt.0.get_simple_triples() {
+            let triples: Vec<_> = triples.iter().map(|x| x.to_owned()).collect();
+            commands.entity(entity).insert(Triples(triples));
+        }
+    }
+}
+
+/// N-Triples has no prefixes, the document location is the base.
+pub fn derive_prefixes(
+    query: Query<(Entity, &Label), Changed<Element<NTriplesLang>>>,
+    mut commands: Commands,
+) {
+    for (entity, url) in &query {
+        commands
+            .entity(entity)
+            .insert(Prefixes(Vec::new(), url.0.clone()));
+    }
+}
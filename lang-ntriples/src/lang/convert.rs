@@ -0,0 +1,105 @@
+use std::collections::HashSet;
+
+use lsp_core::{prelude::*, systems::prefix::known_prefix_name};
+use sophia_api::term::Term as _;
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+
+/// Splits an IRI in a namespace and a local name that is valid in a prefixed name.
+fn split_iri(iri: &str) -> Option<(&str, &str)> {
+    let idx = iri.rfind(|c| c == '#' || c == '/')? + 1;
+    let (ns, local) = iri.split_at(idx);
+    let valid = !local.starts_with('-')
+        && local
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+    valid.then_some((ns, local))
+}
+
+/// Prefixes that shorten the IRIs of the triples when they are written as Turtle, N-Triples
+/// documents have none of their own.
+///
+/// Well known namespaces keep their usual name, other namespaces are called `ns1`, `ns2`, ... in
+/// the order they are first used.
+pub fn turtle_prefixes(quads: &[MyQuad<'_>], base: &lsp_types::Url) -> Prefixes {
+    let mut seen = HashSet::new();
+    let mut prefixes: Vec<Prefix> = Vec::new();
+    let taken = |name: &str, prefixes: &[Prefix]| prefixes.iter().any(|x| x.prefix == name);
+
+    // rdf:type is written as `a`, plain and language-tagged strings have no datatype
+    let iris = quads.iter().flat_map(|quad| {
+        let predicate = (quad.predicate.value != RDF_TYPE).then_some(&quad.predicate);
+        [Some(&quad.subject), predicate, Some(&quad.object)]
+            .into_iter()
+            .flatten()
+            .filter_map(|term| match term.datatype_iri() {
+                Some(XSD_STRING) | Some(RDF_LANG_STRING) => None,
+                Some(datatype) => Some(datatype),
+                None => term.is_iri().then_some(term.value.as_ref()),
+            })
+    });
+
+    for iri in iris {
+        let Some((ns, _)) = split_iri(iri) else {
+            continue;
+        };
+        if !seen.insert(ns) {
+            continue;
+        }
+        let Ok(url) = lsp_types::Url::parse(ns) else {
+            continue;
+        };
+
+        let prefix = known_prefix_name(ns)
+            .filter(|name| !taken(name, &prefixes))
+            .map(String::from)
+            .unwrap_or_else(|| {
+                (1..)
+                    .map(|i| format!("ns{}", i))
+                    .find(|name| !taken(name, &prefixes))
+                    .unwrap()
+            });
+        prefixes.push(Prefix { prefix, url });
+    }
+
+    Prefixes(prefixes, base.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use lang_turtle::lang::tokenizer::parse_tokens_str;
+    use lsp_core::systems::{convert, Format};
+
+    use super::*;
+    use crate::lang::parser::parse_ntriples;
+
+    #[test]
+    fn convert_groups_subjects_and_prefixes() {
+        let txt = r#"<http://data.example/a> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://xmlns.com/foaf/0.1/Person> .
+<http://data.example/a> <http://xmlns.com/foaf/0.1/name> "A" .
+<http://data.example/a> <http://xmlns.com/foaf/0.1/name> "B" .
+<http://data.example/b> <http://xmlns.com/foaf/0.1/knows> <http://data.example/a> .
+"#;
+        let url = lsp_types::Url::from_str("http://example.com/data.nt").unwrap();
+        let (tokens, _) = parse_tokens_str(txt);
+        let (nt, errors) = parse_ntriples(&url, &tokens, txt);
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let quads: Vec<_> = nt
+            .get_simple_triples()
+            .expect("triples")
+            .iter()
+            .map(|x| x.to_owned())
+            .collect();
+        let prefixes = turtle_prefixes(&quads, &url);
+        let names: Vec<_> = prefixes.iter().map(|x| x.prefix.as_str()).collect();
+        assert_eq!(names, vec!["ns1", "foaf"]);
+
+        let output = convert(&quads, &prefixes, Format::Turtle);
+        assert!(output.contains("@prefix foaf: <http://xmlns.com/foaf/0.1/>."));
+        assert!(output.contains("ns1:a a foaf:Person;\n  foaf:name \"A\", \"B\"."));
+        assert!(output.contains("ns1:b foaf:knows ns1:a."));
+    }
+}
@@ -0,0 +1,3 @@
+pub mod convert;
+pub mod model;
+pub mod parser;
@@ -0,0 +1,68 @@
+use lang_turtle::lang::model::{
+    Based, Term, Triple, TriplesBuilder, TurtlePrefix, TurtleSimpleError,
+};
+use lsp_core::prelude::{MyTerm, Spanned, Triples2};
+use sophia_iri::resolve::BaseIri;
+use tracing::debug;
+
+/// One line of an N-Triples or N-Quads document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Statement {
+    /// Triple with exactly one predicate and one object.
+    pub triple: Spanned<Triple>,
+    /// Graph name, only present in N-Quads.
+    pub graph: Option<Spanned<Term>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NTriples {
+    pub set_base: lsp_types::Url,
+    pub statements: Vec<Statement>,
+}
+
+impl Based for NTriples {
+    fn get_base(&self) -> &lsp_types::Url {
+        &self.set_base
+    }
+
+    fn prefixes(&self) -> &[Spanned<TurtlePrefix>] {
+        &[]
+    }
+}
+
+impl NTriples {
+    pub fn new(statements: Vec<Statement>, location: &lsp_types::Url) -> Self {
+        Self {
+            set_base: location.clone(),
+            statements,
+        }
+    }
+
+    /// True when at least one statement is part of a named graph.
+    pub fn has_graphs(&self) -> bool {
+        self.statements.iter().any(|st| st.graph.is_some())
+    }
+
+    /// Creates quads for all statements, statements that cannot be converted are skipped.
+    pub fn get_simple_triples<'a>(&'a self) -> Result<Triples2<'a>, TurtleSimpleError> {
+        let base =
+            BaseIri::new(self.set_base.as_str().to_string()).map_err(TurtleSimpleError::Parse)?;
+        let mut builder = TriplesBuilder::new(self, base);
+
+        for st in &self.statements {
+            let res = match &st.graph {
+                Some(graph) => builder.ingest_graph(Some(graph), std::slice::from_ref(&st.triple)),
+                None => builder.ingest(&st.triple),
+            };
+            if let Err(e) = res {
+                debug!("Skipping statement {}: {:?}", st.triple.value(), e);
+            }
+        }
+
+        Ok(Triples2 {
+            triples: builder.triples,
+            base: Some(MyTerm::named_node(self.set_base.as_str().to_string(), 0..0)),
+            base_url: self.set_base.to_string(),
+        })
+    }
+}
@@ -0,0 +1,169 @@
+use chumsky::prelude::*;
+use lang_turtle::lang::{
+    model::{BlankNode, NamedNode, Term, Triple, PO},
+    parser::{expect_token, literal, named_node},
+};
+use lsp_core::prelude::*;
+
+use crate::lang::model::{NTriples, Statement};
+
+type S = std::ops::Range<usize>;
+
+fn iri() -> impl Parser<PToken, Term, Error = Simple<PToken>> + Clone {
+    named_node().validate(|nn, span: S, emit| {
+        if !matches!(nn, NamedNode::Full(_, _)) {
+            emit(Simple::custom(
+                span,
+                "Expected an absolute IRI like <http://...>",
+            ));
+        }
+        Term::NamedNode(nn)
+    })
+}
+
+fn blank_node() -> impl Parser<PToken, Term, Error = Simple<PToken>> + Clone {
+    select! {
+        PToken(Token::BlankNodeLabel(x), idx) => Term::BlankNode(BlankNode::Named(x, idx)),
+    }
+}
+
+/// Parses exactly one statement, `subject predicate object graph? .`
+fn statement() -> impl Parser<PToken, Statement, Error = Simple<PToken>> {
+    let subject = iri().or(blank_node()).labelled("subject");
+    let predicate = iri().labelled("predicate");
+    let object = iri()
+        .or(blank_node())
+        .or(literal().map(Term::Literal))
+        .labelled("object");
+    let graph = iri().or(blank_node()).labelled("graph");
+
+    subject
+        .map_with_span(spanned)
+        .then(predicate.map_with_span(spanned))
+        .then(object.map_with_span(spanned))
+        .then(graph.map_with_span(spanned).or_not())
+        .then_ignore(expect_token(Token::Stop, |_| true))
+        .then_ignore(end())
+        .map_with_span(|(((subject, predicate), object), graph), span: S| {
+            let po_span = predicate.span().start..object.span().end;
+            let triple = Triple {
+                subject,
+                po: vec![spanned(PO::new(predicate, vec![object]), po_span)],
+            };
+            Statement {
+                triple: spanned(triple, span),
+                graph,
+            }
+        })
+}
+
+fn parse_line(
+    tokens: Vec<PToken>,
+    spans: Vec<S>,
+    eol: usize,
+    statements: &mut Vec<Statement>,
+    errors: &mut Vec<Simple<PToken>>,
+) {
+    let stream = chumsky::Stream::from_iter(eol..eol, tokens.into_iter().zip(spans));
+    let (st, es) = statement().parse_recovery(stream);
+    statements.extend(st);
+    errors.extend(es);
+}
+
+/// Parses N-Triples and N-Quads, each line is parsed on its own.
+///
+/// An error on one line never influences the statements on other lines.
+pub fn parse_ntriples(
+    location: &lsp_types::Url,
+    tokens: &[Spanned<Token>],
+    source: &str,
+) -> (NTriples, Vec<Simple<PToken>>) {
+    let mut statements = Vec::new();
+    let mut errors = Vec::new();
+
+    let mut line = Vec::new();
+    let mut spans = Vec::new();
+    let mut eol = 0;
+
+    for (i, Spanned(token, span)) in tokens.iter().enumerate() {
+        if token.is_comment() {
+            continue;
+        }
+
+        if !line.is_empty() && span.start > eol {
+            parse_line(
+                std::mem::take(&mut line),
+                std::mem::take(&mut spans),
+                eol,
+                &mut statements,
+                &mut errors,
+            );
+        }
+
+        if line.is_empty() {
+            eol = source[span.start..]
+                .find('\n')
+                .map(|x| x + span.start)
+                .unwrap_or(source.len());
+        }
+
+        line.push(PToken(token.clone(), i));
+        spans.push(span.clone());
+    }
+
+    if !line.is_empty() {
+        parse_line(line, spans, eol, &mut statements, &mut errors);
+    }
+
+    (NTriples::new(statements, location), errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use lang_turtle::lang::tokenizer::parse_tokens_str;
+
+    use super::*;
+
+    fn parse(txt: &str) -> (NTriples, Vec<Simple<PToken>>) {
+        let url = lsp_types::Url::from_str("http://example.com/data.nt").unwrap();
+        let (tokens, _) = parse_tokens_str(txt);
+        parse_ntriples(&url, &tokens, txt)
+    }
+
+    #[test]
+    fn parse_statements() {
+        let (nt, errors) = parse(
+            r#"<http://ex.com/a> <http://ex.com/p> <http://ex.com/b> .
+# a comment
+_:b0 <http://ex.com/p> "test"@en .
+<http://ex.com/a> <http://ex.com/p> "5"^^<http://www.w3.org/2001/XMLSchema#integer> <http://ex.com/g> .
+"#,
+        );
+
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(nt.statements.len(), 3);
+        assert!(nt.statements[0].graph.is_none());
+        assert!(nt.statements[2].graph.is_some());
+        assert!(nt.has_graphs());
+    }
+
+    #[test]
+    fn errors_are_recovered_per_line() {
+        let (nt, errors) = parse(
+            r#"<http://ex.com/a> <http://ex.com/p> <http://ex.com/b> .
+<http://ex.com/a> <http://ex.com/p>
+<http://ex.com/a> ex:p <http://ex.com/c> .
+<http://ex.com/a> <http://ex.com/p> <http://ex.com/d> .
+"#,
+        );
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(nt.statements.len(), 3);
+
+        let triples = nt.get_simple_triples().expect("triples");
+        assert_eq!(triples.len(), 3);
+        assert_eq!(triples[2].object.as_str(), "http://ex.com/d");
+    }
+}
@@ -0,0 +1,91 @@
+#![doc(
+    html_logo_url = "https://ajuvercr.github.io/semantic-web-lsp/assets/icons/favicon.png",
+    html_favicon_url = "https://ajuvercr.github.io/semantic-web-lsp/assets/icons/favicon.ico"
+)]
+//! [N-Triples](https://www.w3.org/TR/n-triples/) and [N-Quads](https://www.w3.org/TR/n-quads/)
+//! support, built on top of [`lang_turtle`].
+//!
+//! Documents are tokenized with the Turtle tokenizer, every line is parsed on its own so a syntax
+//! error never hides the statements on other lines. Documents without graph names can be
+//! converted to Turtle with a code action.
+use bevy_ecs::{component::Component, observer::Trigger, system::Commands, world::World};
+use chumsky::prelude::Simple;
+use lang_turtle::TurtleLang;
+use lsp_core::{
+    feature::diagnostics::publish_diagnostics,
+    lang::{Lang, LangHelper},
+    prelude::*,
+    CreateEvent,
+};
+
+pub mod ecs;
+pub mod lang;
+
+use crate::ecs::{setup_code_action, setup_parsing};
+
+#[derive(Component)]
+pub struct NTriplesLang;
+
+#[derive(Debug)]
+pub struct NTriplesHelper;
+impl LangHelper for NTriplesHelper {
+    fn keyword(&self) -> &[&'static str] {
+        &[]
+    }
+}
+
+pub fn setup_world(world: &mut World) {
+    let mut semantic_token_dict = world.resource_mut::<SemanticTokensDict>();
+    NTriplesLang::LEGEND_TYPES.iter().for_each(|lt| {
+        if !semantic_token_dict.contains_key(lt) {
+            let l = semantic_token_dict.0.len();
+            semantic_token_dict.insert(lt.clone(), l);
+        }
+    });
+
+    world.observe(|trigger: Trigger<CreateEvent>, mut commands: Commands| {
+        match &trigger.event().language_id {
+            Some(x) if x == "ntriples" || x == "nquads" => {
+                commands
+                    .entity(trigger.entity())
+                    .insert((NTriplesLang, DynLang(Box::new(NTriplesHelper))));
+                return;
+            }
+            _ => {}
+        }
+        let url = trigger.event().url.as_str();
+        if url.ends_with(".nt") || url.ends_with(".nq") {
+            commands
+                .entity(trigger.entity())
+                .insert((NTriplesLang, DynLang(Box::new(NTriplesHelper))));
+            return;
+        }
+    });
+
+    world.schedule_scope(lsp_core::feature::DiagnosticsLabel, |_, schedule| {
+        schedule.add_systems(publish_diagnostics::<NTriplesLang>);
+    });
+
+    setup_parsing(world);
+    setup_code_action(world);
+}
+
+impl Lang for NTriplesLang {
+    type Token = Token;
+
+    type TokenError = Simple<char>;
+
+    type Element = crate::lang::model::NTriples;
+
+    type ElementError = Simple<Token>;
+
+    const LANG: &'static str = "ntriples";
+
+    const TRIGGERS: &'static [&'static str] = &[];
+    const CODE_ACTION: bool = true;
+    const HOVER: bool = true;
+
+    const LEGEND_TYPES: &'static [lsp_types::SemanticTokenType] = TurtleLang::LEGEND_TYPES;
+
+    const PATTERN: Option<&'static str> = None;
+}
@@ -0,0 +1,355 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy_ecs::prelude::*;
+use lsp_core::{components::*, prelude::*};
+use lsp_types::{Diagnostic, DiagnosticSeverity, TextDocumentItem};
+
+use crate::{
+    lang::model::{
+        variable_name, Expression, GroupCondition, GroupGraphPattern, GroupGraphPatternSub,
+        InlineData, Modifier, Query as SparqlQuery, QueryClause, SelectClause, Solution,
+        WhereClause,
+    },
+    Sparql,
+};
+
+fn arity_diagnostic(expr: &Spanned<Expression>, rope: &RopeC) -> Option<Diagnostic> {
+    let Expression::Call { function, args, .. } = expr.value() else {
+        return None;
+    };
+    let arity = function.arity()?;
+    if arity.contains(&args.len()) {
+        return None;
+    }
+
+    let expected = match (arity.start(), arity.end()) {
+        (start, end) if start == end => format!("{}", start),
+        (start, &usize::MAX) => format!("at least {}", start),
+        (start, end) => format!("{} to {}", start, end),
+    };
+
+    Some(Diagnostic {
+        range: range_to_range(args.span(), &rope)?,
+        severity: Some(DiagnosticSeverity::ERROR),
+        source: Some(String::from("SWLS")),
+        message: format!(
+            "{} expects {} arguments, found {}",
+            function.value(),
+            expected,
+            args.len()
+        ),
+        ..Default::default()
+    })
+}
+
+/// Publishes diagnostics for built-in functions that are called with the wrong number of
+/// arguments.
+pub fn expression_diagnostics(
+    query: Query<(&Element<Sparql>, &Wrapped<TextDocumentItem>, &RopeC), Changed<Element<Sparql>>>,
+    mut client: ResMut<DiagnosticPublisher>,
+) {
+    for (element, item, rope) in &query {
+        let diagnostics: Vec<_> = element
+            .expressions()
+            .into_iter()
+            .flat_map(|expr| arity_diagnostic(expr, rope))
+            .collect();
+        let _ = client.publish(&item.0, diagnostics, "sparql_expression");
+    }
+}
+
+struct VariableDiagnostic<'a> {
+    var: Spanned<&'a str>,
+    severity: DiagnosticSeverity,
+    message: String,
+}
+
+impl<'a> VariableDiagnostic<'a> {
+    fn new(var: Spanned<&'a str>, severity: DiagnosticSeverity, message: String) -> Self {
+        Self {
+            var,
+            severity,
+            message,
+        }
+    }
+}
+
+/// Variables of `expr` that are not inside an aggregate.
+fn ungrouped<'a>(expr: &'a Spanned<Expression>, out: &mut Vec<Spanned<&'a str>>) {
+    match expr.value() {
+        Expression::Variable(var) => out.push(Spanned(var.0.as_str(), expr.span().clone())),
+        Expression::Aggregate { .. } | Expression::Exists { .. } => {}
+        x => x.children().into_iter().for_each(|x| ungrouped(x, out)),
+    }
+}
+
+fn select_diagnostics<'a>(
+    select: &'a SelectClause,
+    where_clause: &'a WhereClause,
+    modifiers: &'a [Spanned<Modifier>],
+    values: Option<&'a Spanned<InlineData>>,
+    projected: &mut HashSet<&'a str>,
+    out: &mut Vec<VariableDiagnostic<'a>>,
+) {
+    let mut bound: HashSet<&str> = where_clause
+        .ggp
+        .in_scope()
+        .into_iter()
+        .map(|x| variable_name(x.value()))
+        .collect();
+    bound.extend(
+        values
+            .into_iter()
+            .flat_map(|v| v.vars.iter().map(|x| x.name())),
+    );
+
+    let group_by = modifiers.iter().find_map(|m| match m.value() {
+        Modifier::GroupBy(_, _, conditions) => Some(conditions),
+        _ => None,
+    });
+    let mut grouped: HashSet<&str> = HashSet::new();
+    for condition in group_by.into_iter().flatten() {
+        match condition.value() {
+            GroupCondition::Expression(Spanned(Expression::Variable(var), _)) => {
+                grouped.insert(var.name());
+            }
+            GroupCondition::Bind(bind) => {
+                grouped.insert(bind.var.name());
+                bound.insert(bind.var.name());
+            }
+            _ => {}
+        }
+    }
+
+    for solution in &select.solutions {
+        match solution.value() {
+            // `*` projects every variable that is in scope
+            Solution::All => projected.extend(
+                where_clause
+                    .ggp
+                    .in_scope()
+                    .into_iter()
+                    .map(|x| variable_name(x.value())),
+            ),
+            Solution::Var(var) => {
+                let spanned = Spanned(var.0.as_str(), solution.span().clone());
+                if !bound.contains(var.name()) {
+                    out.push(VariableDiagnostic::new(
+                        spanned.clone(),
+                        DiagnosticSeverity::WARNING,
+                        format!("{} is projected but never bound", var.0),
+                    ));
+                }
+                if group_by.is_some() && !grouped.contains(var.name()) {
+                    out.push(VariableDiagnostic::new(
+                        spanned,
+                        DiagnosticSeverity::ERROR,
+                        format!(
+                            "{} is not grouped, use it in GROUP BY or in an aggregate",
+                            var.0
+                        ),
+                    ));
+                }
+            }
+            Solution::VarAs(bind) => {
+                if group_by.is_some() {
+                    let mut vars = Vec::new();
+                    ungrouped(&bind.expr, &mut vars);
+                    for var in vars {
+                        if !grouped.contains(variable_name(var.value())) {
+                            let message = format!(
+                                "{} is not grouped, use it in GROUP BY or in an aggregate",
+                                var.value()
+                            );
+                            out.push(VariableDiagnostic::new(
+                                var,
+                                DiagnosticSeverity::ERROR,
+                                message,
+                            ));
+                        }
+                    }
+                }
+                // Later projections can use this variable
+                grouped.insert(bind.var.name());
+                bound.insert(bind.var.name());
+                projected.insert(bind.var.name());
+            }
+        }
+    }
+}
+
+fn ggp_diagnostics<'a>(
+    ggp: &'a GroupGraphPattern,
+    projected: &mut HashSet<&'a str>,
+    out: &mut Vec<VariableDiagnostic<'a>>,
+) {
+    match ggp {
+        GroupGraphPattern::SubSelect(sub) => {
+            select_diagnostics(
+                &sub.select,
+                &sub.where_clause,
+                &sub.modifier,
+                sub.values.as_ref(),
+                projected,
+                out,
+            );
+            ggp_diagnostics(sub.where_clause.ggp.value(), projected, out);
+        }
+        GroupGraphPattern::GroupGraph(subs) => {
+            let mut in_scope: HashSet<&str> = HashSet::new();
+            for sub in subs {
+                if let GroupGraphPatternSub::Bind(_, _, bind, _) = sub.value() {
+                    if in_scope.contains(bind.var.name()) {
+                        out.push(VariableDiagnostic::new(
+                            Spanned(bind.var.0 .0.as_str(), bind.var.span().clone()),
+                            DiagnosticSeverity::ERROR,
+                            format!(
+                                "{} is already in scope and cannot be bound again",
+                                bind.var.0 .0
+                            ),
+                        ));
+                    }
+                }
+
+                for ggp in sub.patterns() {
+                    ggp_diagnostics(ggp.value(), projected, out);
+                }
+
+                in_scope.extend(sub.in_scope().into_iter().map(|x| variable_name(x.value())));
+            }
+        }
+        GroupGraphPattern::Invalid => {}
+    }
+}
+
+/// Finds projected variables that are never bound, variables that are not grouped, BINDs to
+/// variables that are already in scope and variables that are only used once.
+///
+/// Projected variables are used by the result, so `*` and `(expr AS ?x)` count as a use.
+fn variable_diagnostics<'a>(query: &'a SparqlQuery) -> Vec<VariableDiagnostic<'a>> {
+    let mut out = Vec::new();
+    let mut projected = HashSet::new();
+
+    if let Some(where_clause) = &query.where_clause {
+        if let QueryClause::Select(select) = &query.kwds {
+            select_diagnostics(
+                select,
+                where_clause,
+                &query.modifier,
+                query.values.as_ref(),
+                &mut projected,
+                &mut out,
+            );
+        }
+        ggp_diagnostics(where_clause.ggp.value(), &mut projected, &mut out);
+    }
+
+    // Unbound projections are already reported
+    let reported: HashSet<_> = out.iter().map(|x| x.var.span().clone()).collect();
+    let variables = query.variables();
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for var in &variables {
+        *counts.entry(variable_name(var.value())).or_default() += 1;
+    }
+    for var in variables {
+        let name = variable_name(var.value());
+        if counts[name] == 1 && !projected.contains(name) && !reported.contains(var.span()) {
+            let message = format!("{} is only used once", var.value());
+            out.push(VariableDiagnostic::new(
+                var,
+                DiagnosticSeverity::WARNING,
+                message,
+            ));
+        }
+    }
+
+    out
+}
+
+/// Publishes diagnostics for common mistakes with variables, see [`variable_diagnostics`].
+pub fn sparql_variable_diagnostics(
+    query: Query<(&Element<Sparql>, &Wrapped<TextDocumentItem>, &RopeC), Changed<Element<Sparql>>>,
+    mut client: ResMut<DiagnosticPublisher>,
+) {
+    for (element, item, rope) in &query {
+        let diagnostics: Vec<_> = variable_diagnostics(element)
+            .into_iter()
+            .flat_map(|diagnostic| {
+                Some(Diagnostic {
+                    range: range_to_range(diagnostic.var.span(), &rope)?,
+                    severity: Some(diagnostic.severity),
+                    source: Some(String::from("SWLS")),
+                    message: diagnostic.message,
+                    ..Default::default()
+                })
+            })
+            .collect();
+        let _ = client.publish(&item.0, diagnostics, "sparql_variables");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lang_turtle::lang::context::Context;
+
+    use super::*;
+    use crate::lang::{parsing::parse, tokenizer::parse_tokens_str};
+
+    fn messages(inp: &str) -> Vec<String> {
+        let context = Context::new();
+        let (tokens, _) = parse_tokens_str(inp);
+        let (query, _) = parse(
+            inp,
+            lsp_types::Url::parse("memory::myFile.sq").unwrap(),
+            tokens,
+            context.ctx(),
+        );
+        let mut out: Vec<_> = variable_diagnostics(&query.0)
+            .into_iter()
+            .map(|x| x.message)
+            .collect();
+        out.sort();
+        out
+    }
+
+    #[test]
+    fn unbound_projection_and_single_use() {
+        let out = messages("SELECT ?s ?x WHERE { ?s ?p ?s. ?p ?p ?s. }");
+        assert_eq!(out, vec!["?x is projected but never bound".to_string()]);
+
+        let out = messages("SELECT ?s WHERE { ?s <a> ?o. }");
+        assert_eq!(out, vec!["?o is only used once".to_string()]);
+    }
+
+    #[test]
+    fn star_projects_every_variable() {
+        let out = messages("SELECT * WHERE { ?s <a> ?o. BIND(1 AS ?x) }");
+        assert!(out.is_empty(), "{:?}", out);
+
+        let out = messages("SELECT ?s WHERE { ?s <a> ?o. { SELECT * WHERE { ?s <b> ?y. } } }");
+        assert_eq!(out, vec!["?o is only used once".to_string()]);
+    }
+
+    #[test]
+    fn ungrouped_projection() {
+        let out = messages(
+            "SELECT ?s ?o (COUNT(?p) AS ?c) (?c + ?o AS ?d) WHERE { ?s ?p ?o. } GROUP BY ?s",
+        );
+        assert_eq!(
+            out,
+            vec![
+                "?o is not grouped, use it in GROUP BY or in an aggregate".to_string(),
+                "?o is not grouped, use it in GROUP BY or in an aggregate".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn bind_in_scope_variable() {
+        let out = messages("SELECT ?s WHERE { ?s ?p ?o. BIND(?o AS ?p) BIND(?o AS ?x) ?x ?x ?s. }");
+        assert_eq!(
+            out,
+            vec!["?p is already in scope and cannot be bound again".to_string()]
+        );
+    }
+}
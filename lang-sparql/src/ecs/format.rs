@@ -0,0 +1,53 @@
+use bevy_ecs::prelude::*;
+use lsp_core::{components::*, prelude::*};
+use lsp_types::{Position, Range};
+use tracing::info;
+
+use crate::{lang::formatter::format_sparql, Sparql};
+
+/// Formats SPARQL queries with the indentation of the client, two spaces when it sent none.
+pub fn format_sparql_system(
+    mut query: Query<
+        (
+            &RopeC,
+            &Element<Sparql>,
+            &Tokens,
+            Option<&FormatOptions>,
+            &mut FormatRequest,
+        ),
+        Without<Dirty>,
+    >,
+) {
+    for (source, element, tokens, options, mut request) in &mut query {
+        if request.0.is_some() {
+            info!("Didn't format with the sparql format system, already formatted");
+            continue;
+        }
+        let comments: Vec<_> = tokens
+            .iter()
+            .filter(|x| x.is_comment())
+            .cloned()
+            .map(|Spanned(x, span)| Spanned(x.into_comment(), span))
+            .collect();
+
+        let options = match options {
+            Some(options) => options.0.clone(),
+            None => lsp_types::FormattingOptions {
+                tab_size: 2,
+                insert_spaces: true,
+                ..Default::default()
+            },
+        };
+        let formatted = format_sparql(&element.0, options, &comments, &source.0);
+
+        request.0 = formatted.map(|x| {
+            vec![lsp_types::TextEdit::new(
+                Range::new(
+                    Position::new(0, 0),
+                    Position::new(source.0.len_lines() as u32 + 1, 0),
+                ),
+                x,
+            )]
+        });
+    }
+}
@@ -0,0 +1,39 @@
+use bevy_ecs::prelude::*;
+use lsp_core::{components::Element, feature::semantic::TokenTypesComponent, prelude::*};
+use lsp_types::SemanticTokenType;
+
+use crate::{lang::model::Expression, Sparql};
+
+fn expression_tokens(expr: &Expression, ttc: &mut Vec<Spanned<SemanticTokenType>>) {
+    match expr {
+        Expression::Unary(op, _) | Expression::Binary(_, op, _) => {
+            ttc.push(Spanned(SemanticTokenType::OPERATOR, op.span().clone()));
+        }
+        Expression::In { not, .. } => {
+            if let Some(not) = not {
+                ttc.push(Spanned(SemanticTokenType::OPERATOR, not.span().clone()));
+            }
+        }
+        Expression::Call { function, .. } => {
+            ttc.push(Spanned(
+                SemanticTokenType::FUNCTION,
+                function.span().clone(),
+            ));
+        }
+        Expression::Aggregate { name, .. } => {
+            ttc.push(Spanned(SemanticTokenType::FUNCTION, name.span().clone()));
+        }
+        _ => {}
+    }
+}
+
+/// Highlights operators and function names found in the expressions of the query.
+pub fn highlight_expressions(
+    mut query: Query<(&Element<Sparql>, &mut TokenTypesComponent), With<HighlightRequest>>,
+) {
+    for (element, mut ttc) in &mut query {
+        for expr in element.expressions() {
+            expression_tokens(expr.value(), &mut ttc.0);
+        }
+    }
+}
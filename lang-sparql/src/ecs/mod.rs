@@ -1,12 +1,19 @@
 use std::collections::{HashMap, HashSet};
 
 use bevy_ecs::{prelude::*, world::World};
+use chumsky::prelude::Simple;
 use completion::{CompletionRequest, SimpleCompletion};
 use lang_turtle::lang::{
     context::{Context, TokenIdx},
     model::TriplesBuilder,
 };
-use lsp_core::{components::*, prelude::*, systems::prefix::prefix_completion_helper};
+use lsp_core::{
+    components::*,
+    prelude::*,
+    systems::prefix::{
+        prefix_code_action_helper, prefix_completion_helper, prefix_insert_edit, undefined_prefixes,
+    },
+};
 use lsp_types::CompletionItemKind;
 use sophia_iri::resolve::BaseIri;
 
@@ -15,6 +22,21 @@ use crate::{
     Sparql,
 };
 
+mod diagnostics;
+mod format;
+mod highlight;
+mod references;
+mod run;
+mod signature;
+mod symbol;
+use diagnostics::{expression_diagnostics, sparql_variable_diagnostics};
+use format::format_sparql_system;
+use highlight::highlight_expressions;
+use references::variable_references;
+use run::{query_code_lens, run_query};
+use signature::{hover_builtin, sparql_signature_help};
+use symbol::sparql_document_symbols;
+
 pub fn setup_parse(world: &mut World) {
     use lsp_core::feature::parse::*;
     world.schedule_scope(Label, |_, schedule| {
@@ -29,6 +51,71 @@ pub fn setup_parse(world: &mut World) {
     });
 }
 
+pub fn setup_code_action(world: &mut World) {
+    world.schedule_scope(CodeActionLabel, |_, schedule| {
+        schedule.add_systems(sparql_undefined_prefix_code_action);
+    });
+}
+
+pub fn setup_symbols(world: &mut World) {
+    world.schedule_scope(DocumentSymbolLabel, |_, schedule| {
+        schedule.add_systems(sparql_document_symbols);
+    });
+}
+
+pub fn setup_references(world: &mut World) {
+    use lsp_core::feature::references::*;
+    world.schedule_scope(Label, |_, schedule| {
+        schedule.add_systems(variable_references.after(get_current_token));
+    });
+}
+
+pub fn setup_semantic(world: &mut World) {
+    use lsp_core::feature::semantic::*;
+    world.schedule_scope(Label, |_, schedule| {
+        schedule.add_systems(
+            highlight_expressions
+                .after(basic_semantic_tokens)
+                .before(semantic_tokens_system),
+        );
+    });
+}
+
+pub fn setup_diagnostics(world: &mut World) {
+    world.schedule_scope(DiagnosticsLabel, |_, schedule| {
+        schedule.add_systems((expression_diagnostics, sparql_variable_diagnostics));
+    });
+}
+
+pub fn setup_formatting(world: &mut World) {
+    world.schedule_scope(FormatLabel, |_, schedule| {
+        schedule.add_systems(format_sparql_system);
+    });
+}
+
+pub fn setup_hover(world: &mut World) {
+    use lsp_core::feature::hover::*;
+    world.schedule_scope(Label, |_, schedule| {
+        schedule.add_systems(hover_builtin.after(get_current_token));
+    });
+}
+
+pub fn setup_signature_help(world: &mut World) {
+    world.schedule_scope(SignatureHelpLabel, |_, schedule| {
+        schedule.add_systems(sparql_signature_help);
+    });
+}
+
+pub fn setup_commands(world: &mut World) {
+    world.schedule_scope(CodeLensLabel, |_, schedule| {
+        schedule.add_systems(query_code_lens);
+    });
+    world.schedule_scope(ExecuteCommandLabel, |_, schedule| {
+        schedule.add_systems(run_query);
+    });
+    ExecutableCommands::register(world, lsp_core::feature::command::RUN_QUERY);
+}
+
 pub fn setup_completion(world: &mut World) {
     use lsp_core::feature::completion::*;
     world.schedule_scope(Label, |_, schedule| {
@@ -41,11 +128,22 @@ pub fn setup_completion(world: &mut World) {
 
 #[instrument(skip(query, commands))]
 fn parse_source(
-    query: Query<(Entity, &Source), (Changed<Source>, With<Sparql>)>,
+    query: Query<
+        (
+            Entity,
+            &Source,
+            Option<&SourceChange>,
+            Option<&Tokens>,
+            Option<&Errors<Simple<char>>>,
+        ),
+        (Changed<Source>, With<Sparql>),
+    >,
     mut commands: Commands,
 ) {
-    for (entity, source) in &query {
-        let (tok, es) = parse_tokens_str(source.0.as_str());
+    for (entity, source, change, old_tokens, old_errors) in &query {
+        commands.entity(entity).remove::<SourceChange>();
+        let old = old_tokens.zip(old_errors);
+        let (tok, es) = tokenize_source(&source.0, change, old, parse_tokens_str);
         info!("tokenized  {} tokens ({} errors)", tok.len(), es.len());
         commands.entity(entity).insert((Tokens(tok), Errors(es)));
     }
@@ -166,22 +264,35 @@ pub fn sparql_lov_undefined_prefix_completion(
         &TokenComponent,
         &Element<Sparql>,
         &Prefixes,
+        &RopeC,
         &mut CompletionRequest,
     )>,
 ) {
-    for (word, turtle, prefixes, mut req) in &mut query {
-        let mut start = Position::new(0, 0);
-
-        if turtle.base_statement.is_some() {
-            start = Position::new(1, 0);
-        }
-
-        use lsp_types::{Position, Range};
+    for (word, sparql, prefixes, rope, mut req) in &mut query {
+        let base_end = sparql.base_statement.as_ref().map(|base| base.span().end);
         prefix_completion_helper(word, prefixes, &mut req.0, |name, location| {
-            Some(vec![lsp_types::TextEdit {
-                range: Range::new(start.clone(), start),
-                new_text: format!("PREFIX {}: <{}>\n", name, location),
-            }])
+            let statement = format!("PREFIX {}: <{}>\n", name, location);
+            Some(vec![prefix_insert_edit(statement, base_end, rope)?])
+        });
+    }
+}
+
+pub fn sparql_undefined_prefix_code_action(
+    mut query: Query<(
+        &Tokens,
+        &Element<Sparql>,
+        &Prefixes,
+        &Label,
+        &RopeC,
+        &mut CodeActionRequest,
+    )>,
+) {
+    for (tokens, element, prefixes, label, rope, mut req) in &mut query {
+        let base_end = element.base_statement.as_ref().map(|base| base.span().end);
+        let undefined = undefined_prefixes(&tokens.0, prefixes);
+        prefix_code_action_helper(undefined, &mut req, label, rope, |name, location| {
+            let statement = format!("PREFIX {}: <{}>\n", name, location);
+            Some(vec![prefix_insert_edit(statement, base_end, rope)?])
         });
     }
 }
@@ -0,0 +1,36 @@
+use bevy_ecs::prelude::*;
+use lsp_core::{
+    components::Element, feature::references::ReferencesRequest, prelude::*,
+    util::token_to_location,
+};
+
+use crate::{lang::model::variable_name, Sparql};
+
+/// Adds every occurrence of the variable under the cursor, `?x` and `$x` are the same variable.
+pub fn variable_references(
+    mut query: Query<
+        (
+            &TokenComponent,
+            &Element<Sparql>,
+            &Label,
+            &RopeC,
+            &mut ReferencesRequest,
+        ),
+        With<Sparql>,
+    >,
+) {
+    for (token, element, label, rope, mut req) in &mut query {
+        let Token::Variable(var) = token.token.value() else {
+            continue;
+        };
+        let name = variable_name(var);
+
+        req.0.extend(
+            element
+                .variables()
+                .into_iter()
+                .filter(|x| variable_name(x.value()) == name)
+                .flat_map(|x| token_to_location(x.span(), label, &rope)),
+        );
+    }
+}
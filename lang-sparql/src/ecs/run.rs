@@ -0,0 +1,127 @@
+use bevy_ecs::prelude::*;
+use lsp_core::{
+    components::*,
+    feature::command::{QueryResult, RUN_QUERY},
+    prelude::*,
+};
+use lsp_types::{CodeLens, Command};
+use tracing::info;
+
+use crate::{
+    lang::{
+        eval::{dataset, execute},
+        model::QueryClause,
+    },
+    Sparql,
+};
+
+/// Adds a "Run query" code lens on the query form of SPARQL queries, updates are not run.
+pub fn query_code_lens(
+    mut query: Query<(&Label, &RopeC, &Element<Sparql>, &mut CodeLensRequest), Without<Dirty>>,
+) {
+    for (label, rope, element, mut request) in &mut query {
+        let span = match &element.0.value().kwds {
+            QueryClause::Select(select) => select.kwd.span(),
+            QueryClause::Construct(construct) => construct.kwd.span(),
+            QueryClause::Describe(describe) => describe.kwd.span(),
+            QueryClause::Ask(kwd) => kwd.span(),
+            QueryClause::Update(_) | QueryClause::Invalid => continue,
+        };
+        let Some(range) = range_to_range(span, &rope.0) else {
+            continue;
+        };
+
+        request.0.push(CodeLens {
+            range,
+            command: Some(Command {
+                title: String::from("Run query"),
+                command: RUN_QUERY.to_string(),
+                arguments: Some(vec![serde_json::Value::String(label.0.to_string())]),
+            }),
+            data: None,
+        });
+    }
+}
+
+/// Whether `link` points to the document `label`, namespace links like `ex#` drop their fragment.
+fn links_to(link: &lsp_types::Url, label: &lsp_types::Url) -> bool {
+    let mut link = link.clone();
+    link.set_fragment(None);
+    link == *label
+}
+
+/// Runs the query against the `FROM` documents when present, otherwise against the linked and
+/// open documents. Queries with syntax errors are not run.
+pub fn run_query(
+    mut query: Query<(
+        &Label,
+        &Element<Sparql>,
+        Option<&DocumentLinks>,
+        Has<Dirty>,
+        &mut ExecuteCommandRequest,
+    )>,
+    documents: Query<(&Label, &Triples, Has<Open>), Without<Sparql>>,
+) {
+    for (label, element, links, dirty, mut request) in &mut query {
+        if request.command != RUN_QUERY {
+            continue;
+        }
+
+        if dirty {
+            request.error = Some(format!("Cannot run {}, it has syntax errors", label.0));
+            continue;
+        }
+
+        let sparql = element.0.value();
+        let data =
+            match dataset(sparql) {
+                Ok(ds) if !ds.is_empty() => {
+                    let mut data = Vec::new();
+                    for (iri, named) in ds {
+                        let Some((_, triples, _)) = documents
+                            .iter()
+                            .find(|(label, _, _)| label.0.as_str() == iri)
+                        else {
+                            request.error = Some(format!("No document found for {}", iri));
+                            break;
+                        };
+                        let graph = named.then(|| MyTerm::named_node(iri.clone(), 0..0));
+                        data.extend(triples.0.iter().filter(|q| q.graph.is_none()).map(|q| {
+                            MyQuad {
+                                graph: graph.clone(),
+                                ..q.clone()
+                            }
+                        }));
+                    }
+                    data
+                }
+                Ok(_) => documents
+                    .iter()
+                    .filter(|(other, _, open)| {
+                        *open
+                            || links.is_some_and(|links| {
+                                links.iter().any(|link| links_to(&link.0, &other.0))
+                            })
+                    })
+                    .flat_map(|(_, triples, _)| triples.0.iter().cloned())
+                    .collect(),
+                Err(e) => {
+                    request.error = Some(e);
+                    Vec::new()
+                }
+            };
+        if request.error.is_some() {
+            continue;
+        }
+
+        info!("Running query {} against {} quads", label.0, data.len());
+        match execute(sparql, &data) {
+            Ok(results) => {
+                let (head, rows) = results.table();
+                let result = QueryResult::new(label.0.clone(), head, rows);
+                request.result = serde_json::to_value(result).ok();
+            }
+            Err(e) => request.error = Some(e),
+        }
+    }
+}
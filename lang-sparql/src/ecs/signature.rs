@@ -0,0 +1,152 @@
+use bevy_ecs::prelude::*;
+use lsp_core::{components::*, prelude::*};
+use lsp_types::{
+    Documentation, ParameterInformation, ParameterLabel, SignatureHelp, SignatureInformation,
+};
+
+use crate::Sparql;
+
+/// Name and signature of the built-in that `token` calls, if any.
+fn builtin(token: &Token) -> Option<(&'static str, BuiltinSignature)> {
+    match token {
+        Token::SparqlCall(call) => Some((call.complete(), call.signature())),
+        Token::SparqlAggregate(aggregate) => Some((aggregate.complete(), aggregate.signature())),
+        Token::SparqlKeyword(kwd) => Some((kwd.complete(), kwd.signature()?)),
+        _ => None,
+    }
+}
+
+/// The innermost built-in call that is open at `offset`, with the index of the argument that
+/// contains `offset`.
+fn active_call(tokens: &[Spanned<Token>], offset: usize) -> Option<(&Token, usize)> {
+    // Every open bracket, with the built-in it calls and the current argument index
+    let mut stack: Vec<Option<(&Token, usize)>> = Vec::new();
+    let mut previous: Option<&Token> = None;
+    for token in tokens.iter().take_while(|x| x.span().end <= offset) {
+        match token.value() {
+            Token::BracketOpen => {
+                let call = previous.filter(|x| builtin(x).is_some()).map(|x| (x, 0));
+                stack.push(call);
+            }
+            Token::BracketClose => {
+                stack.pop();
+            }
+            Token::Comma => {
+                if let Some(Some((_, idx))) = stack.last_mut() {
+                    *idx += 1;
+                }
+            }
+            // The separator of GROUP_CONCAT follows a ';'
+            Token::PredicateSplit => {
+                if let Some(Some((Token::SparqlAggregate(_), idx))) = stack.last_mut() {
+                    *idx += 1;
+                }
+            }
+            _ => {}
+        }
+        if !token.is_comment() {
+            previous = Some(token.value());
+        }
+    }
+    stack.into_iter().rev().find_map(|x| x)
+}
+
+/// Shows the signature of the built-in function or aggregate around the cursor, highlighting the
+/// current argument.
+pub fn sparql_signature_help(
+    mut query: Query<
+        (
+            &Tokens,
+            &PositionComponent,
+            &RopeC,
+            &mut SignatureHelpRequest,
+        ),
+        With<Sparql>,
+    >,
+) {
+    for (tokens, position, rope, mut request) in &mut query {
+        let Some(offset) = position_to_offset(position.0, &rope.0) else {
+            continue;
+        };
+        let Some((token, idx)) = active_call(&tokens.0, offset) else {
+            continue;
+        };
+        let Some((name, signature)) = builtin(token) else {
+            continue;
+        };
+
+        let parameters = signature
+            .parameters
+            .iter()
+            .map(|x| ParameterInformation {
+                label: ParameterLabel::Simple(x.to_string()),
+                documentation: None,
+            })
+            .collect();
+        let active_parameter = signature.active_parameter(idx).map(|x| x as u32);
+
+        request.0 = Some(SignatureHelp {
+            signatures: vec![SignatureInformation {
+                label: signature.label(name),
+                documentation: Some(Documentation::String(signature.documentation.to_string())),
+                parameters: Some(parameters),
+                active_parameter,
+            }],
+            active_signature: Some(0),
+            active_parameter,
+        });
+    }
+}
+
+/// Shows the signature and description of the built-in function or aggregate under the cursor.
+pub fn hover_builtin(mut query: Query<(&TokenComponent, &mut HoverRequest), With<Sparql>>) {
+    for (token, mut request) in &mut query {
+        if let Some((name, signature)) = builtin(token.token.value()) {
+            request.0.push(format!(
+                "{}: {}",
+                signature.label(name),
+                signature.documentation
+            ));
+            request.1 = Some(token.range.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_core::prelude::{SparqlAggregate, SparqlCall, Token};
+
+    use super::active_call;
+    use crate::lang::tokenizer::parse_tokens_str;
+
+    fn call_at(inp: &str) -> Option<(Token, usize)> {
+        let offset = inp.find('|').expect("cursor");
+        let inp = inp.replace('|', "");
+        let (tokens, _) = parse_tokens_str(&inp);
+        active_call(&tokens, offset).map(|(t, i)| (t.clone(), i))
+    }
+
+    #[test]
+    fn tracks_active_argument() {
+        assert_eq!(
+            call_at("SELECT * WHERE { FILTER(STRSTARTS(?x, |\"a\")) }"),
+            Some((Token::SparqlCall(SparqlCall::StrStarts), 1))
+        );
+        assert_eq!(
+            call_at("SELECT * WHERE { FILTER(STRSTARTS(STR(|?x), \"a\")) }"),
+            Some((Token::SparqlCall(SparqlCall::Str), 0))
+        );
+        assert_eq!(
+            call_at("SELECT * WHERE { FILTER(STRSTARTS(STR(?x), \"a\") && |?y) }"),
+            None
+        );
+    }
+
+    #[test]
+    fn group_concat_separator() {
+        assert_eq!(
+            call_at("SELECT (GROUP_CONCAT(?x; |SEPARATOR=\",\") AS ?y) WHERE { }"),
+            Some((Token::SparqlAggregate(SparqlAggregate::GroupConcat), 1))
+        );
+    }
+}
@@ -0,0 +1,384 @@
+use std::ops::Range;
+
+use bevy_ecs::prelude::*;
+use lang_turtle::lang::symbol::{prefixes_symbol, triple_symbol};
+use lsp_core::{components::*, feature::symbol::document_symbol, prelude::*};
+use lsp_types::{DocumentSymbol, SymbolKind};
+use ropey::Rope;
+
+use crate::{
+    lang::model::{
+        ConstructClause, DescribeClause, DescribeTarget, GroupGraphPattern, GroupGraphPatternSub,
+        QuadPattern, Quads, Query as SparqlQuery, QueryClause, SelectClause, Solution, Update,
+    },
+    Sparql,
+};
+
+fn select_symbol(select: &SelectClause, rope: &Rope) -> Option<DocumentSymbol> {
+    let end = select
+        .solutions
+        .last()
+        .map(|x| x.span().end)
+        .unwrap_or(select.kwd.span().end);
+
+    let children = select
+        .solutions
+        .iter()
+        .flat_map(|solution| match solution.value() {
+            Solution::All => None,
+            Solution::Var(var) => document_symbol(
+                var.0.clone(),
+                None,
+                SymbolKind::VARIABLE,
+                solution.span(),
+                solution.span(),
+                Vec::new(),
+                rope,
+            ),
+            Solution::VarAs(bind) => document_symbol(
+                bind.var.0.clone(),
+                None,
+                SymbolKind::VARIABLE,
+                solution.span(),
+                bind.var.span(),
+                Vec::new(),
+                rope,
+            ),
+        })
+        .collect();
+
+    document_symbol(
+        select.kwd.complete(),
+        select.modifier.as_ref().map(|x| x.complete().to_string()),
+        SymbolKind::FUNCTION,
+        &(select.kwd.span().start..end),
+        select.kwd.span(),
+        children,
+        rope,
+    )
+}
+
+fn construct_symbol(
+    construct: &ConstructClause,
+    query: &SparqlQuery,
+    rope: &Rope,
+) -> Option<DocumentSymbol> {
+    let (end, children) = match &construct.template {
+        Some((_, subs, close)) => (
+            close.span().end,
+            subs.iter()
+                .flat_map(|sub| ggp_sub_symbol(sub, query, rope))
+                .collect(),
+        ),
+        None => (construct.kwd.span().end, Vec::new()),
+    };
+
+    document_symbol(
+        construct.kwd.complete(),
+        None,
+        SymbolKind::FUNCTION,
+        &(construct.kwd.span().start..end),
+        construct.kwd.span(),
+        children,
+        rope,
+    )
+}
+
+fn describe_symbol(describe: &DescribeClause, rope: &Rope) -> Option<DocumentSymbol> {
+    let end = describe
+        .targets
+        .last()
+        .map(|x| x.span().end)
+        .unwrap_or(describe.kwd.span().end);
+
+    let children = describe
+        .targets
+        .iter()
+        .flat_map(|target| match target.value() {
+            DescribeTarget::All => None,
+            DescribeTarget::Var(var) => document_symbol(
+                var.0.clone(),
+                None,
+                SymbolKind::VARIABLE,
+                target.span(),
+                target.span(),
+                Vec::new(),
+                rope,
+            ),
+            DescribeTarget::NamedNode(nn) => document_symbol(
+                nn.to_string(),
+                None,
+                SymbolKind::OBJECT,
+                target.span(),
+                target.span(),
+                Vec::new(),
+                rope,
+            ),
+        })
+        .collect();
+
+    document_symbol(
+        describe.kwd.complete(),
+        None,
+        SymbolKind::FUNCTION,
+        &(describe.kwd.span().start..end),
+        describe.kwd.span(),
+        children,
+        rope,
+    )
+}
+
+fn quads_symbols(quads: &Quads, query: &SparqlQuery, rope: &Rope) -> Vec<DocumentSymbol> {
+    quads
+        .iter()
+        .flat_map(|quad| match quad.value() {
+            QuadPattern::Triple(triple) => triple_symbol(triple, query, rope),
+            QuadPattern::Graph(kwd, name, triples) => document_symbol(
+                format!("{} {}", kwd.complete(), name.value()),
+                None,
+                SymbolKind::NAMESPACE,
+                quad.span(),
+                name.span(),
+                triples
+                    .iter()
+                    .flat_map(|t| triple_symbol(t, query, rope))
+                    .collect(),
+                rope,
+            ),
+        })
+        .collect()
+}
+
+fn update_symbol(
+    update: &Spanned<Update>,
+    query: &SparqlQuery,
+    rope: &Rope,
+) -> Option<DocumentSymbol> {
+    let (name, selection, children) = match update.value() {
+        Update::Load { kwd, .. } | Update::Graph { kwd, .. } | Update::Transfer { kwd, .. } => {
+            (kwd.complete().to_string(), kwd.span(), Vec::new())
+        }
+        Update::Quads { kwd, second, quads } => (
+            format!("{} {}", kwd.complete(), second.complete()),
+            kwd.span(),
+            quads_symbols(quads, query, rope),
+        ),
+        Update::Modify(modify) => {
+            let mut children = Vec::new();
+            for (kwd, quads) in modify.delete.iter().chain(modify.insert.iter()) {
+                children.extend(document_symbol(
+                    kwd.complete(),
+                    None,
+                    SymbolKind::NAMESPACE,
+                    &(kwd.span().start..quads.span().end),
+                    kwd.span(),
+                    quads_symbols(quads, query, rope),
+                    rope,
+                ));
+            }
+            children.extend(where_symbol(
+                modify.where_clause.kwd.as_ref(),
+                &modify.where_clause.ggp,
+                query,
+                rope,
+            ));
+
+            let kwds: Vec<_> = modify
+                .delete
+                .iter()
+                .chain(modify.insert.iter())
+                .map(|(kwd, _)| kwd)
+                .collect();
+            let name = kwds
+                .iter()
+                .map(|kwd| kwd.complete())
+                .collect::<Vec<_>>()
+                .join("/");
+            let selection = kwds
+                .first()
+                .map(|kwd| kwd.span())
+                .unwrap_or(modify.where_clause.span());
+            (name, selection, children)
+        }
+    };
+
+    document_symbol(
+        name,
+        None,
+        SymbolKind::FUNCTION,
+        update.span(),
+        selection,
+        children,
+        rope,
+    )
+}
+
+fn ggp_symbols(ggp: &GroupGraphPattern, query: &SparqlQuery, rope: &Rope) -> Vec<DocumentSymbol> {
+    match ggp {
+        GroupGraphPattern::SubSelect(sub) => {
+            let mut out: Vec<_> = select_symbol(&sub.select, rope).into_iter().collect();
+            out.extend(where_symbol(
+                sub.where_clause.kwd.as_ref(),
+                &sub.where_clause.ggp,
+                query,
+                rope,
+            ));
+            out
+        }
+        GroupGraphPattern::GroupGraph(subs) => subs
+            .iter()
+            .flat_map(|sub| ggp_sub_symbol(sub, query, rope))
+            .collect(),
+        GroupGraphPattern::Invalid => Vec::new(),
+    }
+}
+
+fn group_symbol(
+    name: String,
+    kind: SymbolKind,
+    range: &Range<usize>,
+    selection: &Range<usize>,
+    ggps: &[&Spanned<GroupGraphPattern>],
+    query: &SparqlQuery,
+    rope: &Rope,
+) -> Option<DocumentSymbol> {
+    let children = ggps
+        .iter()
+        .flat_map(|ggp| ggp_symbols(ggp.value(), query, rope))
+        .collect();
+    document_symbol(name, None, kind, range, selection, children, rope)
+}
+
+fn ggp_sub_symbol(
+    sub: &Spanned<GroupGraphPatternSub>,
+    query: &SparqlQuery,
+    rope: &Rope,
+) -> Option<DocumentSymbol> {
+    match sub.value() {
+        GroupGraphPatternSub::Triple(triple) => triple_symbol(triple, query, rope),
+        GroupGraphPatternSub::Optional(kwd, ggp) | GroupGraphPatternSub::Minus(kwd, ggp) => {
+            group_symbol(
+                kwd.complete().to_string(),
+                SymbolKind::NAMESPACE,
+                sub.span(),
+                kwd.span(),
+                &[ggp],
+                query,
+                rope,
+            )
+        }
+        GroupGraphPatternSub::Union(first, others) => {
+            let selection = others.first().map(|x| x.0.span()).unwrap_or(first.span());
+            let mut ggps = vec![first];
+            ggps.extend(others.iter().map(|(_, x)| x));
+            group_symbol(
+                String::from("UNION"),
+                SymbolKind::NAMESPACE,
+                sub.span(),
+                selection,
+                &ggps,
+                query,
+                rope,
+            )
+        }
+        GroupGraphPatternSub::Graph(kwd, name, ggp)
+        | GroupGraphPatternSub::Service(kwd, _, name, ggp) => group_symbol(
+            format!("{} {}", kwd.complete(), name.value()),
+            SymbolKind::NAMESPACE,
+            sub.span(),
+            name.span(),
+            &[ggp],
+            query,
+            rope,
+        ),
+        GroupGraphPatternSub::Bind(kwd, _, bind, _) => document_symbol(
+            format!("{} {}", kwd.complete(), bind.var.0),
+            None,
+            SymbolKind::VARIABLE,
+            sub.span(),
+            bind.var.span(),
+            Vec::new(),
+            rope,
+        ),
+        GroupGraphPatternSub::Filter(kwd, _) => document_symbol(
+            kwd.complete(),
+            None,
+            SymbolKind::OPERATOR,
+            sub.span(),
+            kwd.span(),
+            Vec::new(),
+            rope,
+        ),
+        GroupGraphPatternSub::Values(values) => {
+            let vars: Vec<_> = values.vars.iter().map(|x| x.0 .0.as_str()).collect();
+            document_symbol(
+                format!("{} {}", values.kwd.complete(), vars.join(" ")),
+                None,
+                SymbolKind::ARRAY,
+                sub.span(),
+                values.kwd.span(),
+                Vec::new(),
+                rope,
+            )
+        }
+    }
+}
+
+fn where_symbol(
+    kwd: Option<&Spanned<SparqlKeyword>>,
+    ggp: &Spanned<GroupGraphPattern>,
+    query: &SparqlQuery,
+    rope: &Rope,
+) -> Option<DocumentSymbol> {
+    let selection = kwd.map(|x| x.span()).unwrap_or(ggp.span());
+    group_symbol(
+        String::from("WHERE"),
+        SymbolKind::NAMESPACE,
+        &(selection.start..ggp.span().end),
+        selection,
+        &[ggp],
+        query,
+        rope,
+    )
+}
+
+pub fn sparql_document_symbols(
+    mut query: Query<(&Element<Sparql>, &RopeC, &mut DocumentSymbolRequest)>,
+) {
+    for (element, rope, mut req) in &mut query {
+        let sparql = element.0.value();
+        req.0.extend(prefixes_symbol(&sparql.prefixes, &rope.0));
+
+        match &sparql.kwds {
+            QueryClause::Select(select) => req.0.extend(select_symbol(select, &rope.0)),
+            QueryClause::Construct(construct) => {
+                req.0.extend(construct_symbol(construct, sparql, &rope.0))
+            }
+            QueryClause::Describe(describe) => req.0.extend(describe_symbol(describe, &rope.0)),
+            QueryClause::Ask(kwd) => req.0.extend(document_symbol(
+                kwd.complete(),
+                None,
+                SymbolKind::FUNCTION,
+                kwd.span(),
+                kwd.span(),
+                Vec::new(),
+                &rope.0,
+            )),
+            QueryClause::Update(updates) => req.0.extend(
+                updates
+                    .iter()
+                    .flat_map(|update| update_symbol(update, sparql, &rope.0)),
+            ),
+            QueryClause::Invalid => {}
+        }
+
+        if let Some(where_clause) = &sparql.where_clause {
+            req.0.extend(where_symbol(
+                where_clause.kwd.as_ref(),
+                &where_clause.ggp,
+                sparql,
+                &rope.0,
+            ));
+        }
+    }
+}
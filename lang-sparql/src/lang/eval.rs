@@ -0,0 +1,1399 @@
+//! Evaluates queries against an in-memory set of quads, used to try queries on fixture data
+//! without a triple store.
+//!
+//! `=` compares numbers by value when both literals have a numeric datatype and other terms by
+//! lexical form, datatype and language. Where this differs from the specification:
+//! - `<`, `>`, ORDER BY and arithmetic also parse literals without a numeric datatype when they
+//!   look like numbers, other literals are ordered by their lexical form;
+//! - the effective boolean value of any literal `"true"` or `"false"` is that boolean.
+//!
+//! `SERVICE`, custom functions and regular expressions beyond plain text with `^` and `$` are not
+//! supported, running a query that uses them is an error instead of dropping solutions.
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap, HashSet},
+};
+
+use lang_turtle::lang::model::{
+    NamedNode, PathModifier, PropertyPath, Term, Triple, TriplesBuilder,
+};
+use lsp_core::prelude::{
+    MyQuad, MyTerm, Spanned, SparqlAggregate, SparqlCall, SparqlExpr, SparqlKeyword,
+};
+use sophia_api::term::{Term as _, TermKind};
+use sophia_iri::resolve::BaseIri;
+
+use crate::lang::model::{
+    variable_name, DataValue, DescribeTarget, Expression, Function, GroupCondition,
+    GroupGraphPattern, GroupGraphPatternSub, InlineData, Modifier, Query, QueryClause,
+    SelectClause, Solution, WhereClause,
+};
+
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+
+/// Values of the variables of one solution, keyed without `?`.
+///
+/// Blank nodes in patterns behave like variables and are keyed as `_:name`.
+pub type Binding = BTreeMap<String, MyTerm<'static>>;
+
+/// The graph patterns are matched against, `None` is the default graph.
+type ActiveGraph = Option<MyTerm<'static>>;
+
+#[derive(Debug)]
+pub enum QueryResults {
+    Solutions {
+        vars: Vec<String>,
+        rows: Vec<Binding>,
+    },
+    Boolean(bool),
+    Graph(Vec<MyQuad<'static>>),
+}
+
+impl QueryResults {
+    /// Column names and rows of cells, unbound variables are empty cells.
+    pub fn table(&self) -> (Vec<String>, Vec<Vec<String>>) {
+        match self {
+            QueryResults::Solutions { vars, rows } => {
+                let rows = rows
+                    .iter()
+                    .map(|row| {
+                        vars.iter()
+                            .map(|v| row.get(v).map(|x| x.to_string()).unwrap_or_default())
+                            .collect()
+                    })
+                    .collect();
+                (vars.clone(), rows)
+            }
+            QueryResults::Boolean(x) => (vec![String::from("result")], vec![vec![x.to_string()]]),
+            QueryResults::Graph(quads) => {
+                let head = ["subject", "predicate", "object"]
+                    .into_iter()
+                    .map(String::from)
+                    .collect();
+                let rows = quads
+                    .iter()
+                    .map(|q| {
+                        vec![
+                            q.subject.to_string(),
+                            q.predicate.to_string(),
+                            q.object.to_string(),
+                        ]
+                    })
+                    .collect();
+                (head, rows)
+            }
+        }
+    }
+}
+
+fn literal(value: impl Into<String>) -> MyTerm<'static> {
+    MyTerm::literal(value.into(), 0..0)
+}
+
+fn boolean(value: bool) -> MyTerm<'static> {
+    literal(value.to_string()).with_datatype(format!("{}boolean", XSD))
+}
+
+fn number(value: f64) -> MyTerm<'static> {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        literal(format!("{}", value as i64)).with_datatype(format!("{}integer", XSD))
+    } else {
+        literal(format!("{}", value)).with_datatype(format!("{}decimal", XSD))
+    }
+}
+
+fn numeric(term: &MyTerm<'_>) -> Option<f64> {
+    term.is_literal()
+        .then(|| term.value.parse::<f64>().ok())
+        .flatten()
+}
+
+/// Effective boolean value of a term.
+fn ebv(term: &MyTerm<'_>) -> Result<bool, ()> {
+    if !term.is_literal() {
+        return Err(());
+    }
+    match term.value.as_ref() {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        x => Ok(numeric(term).map(|n| n != 0.0).unwrap_or(!x.is_empty())),
+    }
+}
+
+/// Value of a literal with a numeric XSD datatype.
+fn typed_numeric(term: &MyTerm<'_>) -> Option<f64> {
+    let datatype = term.datatype_iri()?.strip_prefix(XSD)?;
+    let numeric = matches!(
+        datatype,
+        "integer"
+            | "decimal"
+            | "double"
+            | "float"
+            | "long"
+            | "int"
+            | "short"
+            | "byte"
+            | "nonNegativeInteger"
+            | "positiveInteger"
+            | "nonPositiveInteger"
+            | "negativeInteger"
+            | "unsignedLong"
+            | "unsignedInt"
+            | "unsignedShort"
+            | "unsignedByte"
+    );
+    numeric.then(|| term.value.parse::<f64>().ok()).flatten()
+}
+
+/// Numbers are equal by value when both literals have a numeric datatype, other terms only when
+/// their lexical form, datatype and language are the same.
+fn term_equal(a: &MyTerm<'_>, b: &MyTerm<'_>) -> bool {
+    match (typed_numeric(a), typed_numeric(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+fn compare(a: &MyTerm<'_>, b: &MyTerm<'_>) -> Option<Ordering> {
+    match (numeric(a), numeric(b)) {
+        (Some(a), Some(b)) => a.partial_cmp(&b),
+        _ if a.is_literal() && b.is_literal() => Some(a.value.cmp(&b.value)),
+        _ => None,
+    }
+}
+
+/// Total order used by ORDER BY, unbound values come first, then blank nodes, IRIs and literals.
+fn order(a: Option<&MyTerm<'_>>, b: Option<&MyTerm<'_>>) -> Ordering {
+    let rank = |x: Option<&MyTerm<'_>>| match x.map(|x| x.kind()) {
+        None => 0,
+        Some(TermKind::BlankNode) => 1,
+        Some(TermKind::Iri) => 2,
+        Some(_) => 3,
+    };
+    match (a, b) {
+        (Some(a), Some(b)) if rank(Some(a)) == rank(Some(b)) => {
+            compare(a, b).unwrap_or_else(|| a.value.cmp(&b.value))
+        }
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+/// The key of a pattern term that binds values, `None` for constants.
+fn key(term: &MyTerm<'_>) -> Option<String> {
+    match term.kind() {
+        TermKind::Variable => Some(variable_name(&term.value).to_string()),
+        TermKind::BlankNode => Some(format!("_:{}", term.value)),
+        _ => None,
+    }
+}
+
+/// Binds `pattern` to `value`, returns false when `pattern` is bound to something else.
+fn bind(binding: &mut Binding, pattern: &MyTerm<'_>, value: &MyTerm<'static>) -> bool {
+    match key(pattern) {
+        Some(k) => match binding.get(&k) {
+            Some(v) => v == value,
+            None => {
+                binding.insert(k, value.clone());
+                true
+            }
+        },
+        None => pattern == value,
+    }
+}
+
+fn compatible(a: &Binding, b: &Binding) -> bool {
+    a.iter()
+        .all(|(k, v)| b.get(k).map(|w| v == w).unwrap_or(true))
+}
+
+fn join(left: Vec<Binding>, right: &[Binding]) -> Vec<Binding> {
+    let mut out = Vec::new();
+    for l in &left {
+        for r in right {
+            if compatible(l, r) {
+                let mut b = l.clone();
+                b.extend(r.iter().map(|(k, v)| (k.clone(), v.clone())));
+                out.push(b);
+            }
+        }
+    }
+    out
+}
+
+/// Only matches plain text, optionally anchored with `^` and `$`, `None` for other patterns.
+fn simple_regex(text: &str, pattern: &str, flags: &str) -> Option<bool> {
+    let (text, pattern) = if flags.contains('i') {
+        (text.to_lowercase(), pattern.to_lowercase())
+    } else {
+        (text.to_string(), pattern.to_string())
+    };
+    let start = pattern.starts_with('^');
+    let end = pattern.ends_with('$') && !pattern.ends_with("\\$");
+    let inner = &pattern[start as usize..pattern.len() - end as usize];
+    if inner.contains([
+        '.', '*', '+', '?', '(', ')', '[', ']', '{', '}', '|', '\\', '^', '$',
+    ]) {
+        return None;
+    }
+    Some(match (start, end) {
+        (true, true) => text == inner,
+        (true, false) => text.starts_with(inner),
+        (false, true) => text.ends_with(inner),
+        (false, false) => text.contains(inner),
+    })
+}
+
+struct Evaluator<'a> {
+    data: &'a [MyQuad<'static>],
+    builder: TriplesBuilder<'a, Query>,
+    /// First unsupported feature that was evaluated, expressions fail with a type error but the
+    /// query itself fails with this message.
+    unsupported: Option<String>,
+}
+
+impl<'a> Evaluator<'a> {
+    fn unsupported<T>(&mut self, what: String) -> Result<T, ()> {
+        self.unsupported.get_or_insert(what);
+        Err(())
+    }
+
+    fn quads<'s>(
+        &'s self,
+        graph: &'s ActiveGraph,
+    ) -> impl Iterator<Item = &'a MyQuad<'static>> + 's {
+        self.data.iter().filter(move |q| &q.graph == graph)
+    }
+
+    fn graph_names(&self) -> Vec<MyTerm<'static>> {
+        let mut seen = HashSet::new();
+        self.data
+            .iter()
+            .flat_map(|q| q.graph.clone())
+            .filter(|g| seen.insert(g.clone()))
+            .collect()
+    }
+
+    fn named_node(&self, node: &NamedNode) -> Result<MyTerm<'static>, String> {
+        self.builder
+            .resolve(node)
+            .map(|x| MyTerm::named_node(x, 0..0))
+            .ok_or_else(|| format!("Cannot resolve {}", node))
+    }
+
+    fn term(&self, term: &Term) -> Result<MyTerm<'static>, String> {
+        match term {
+            Term::NamedNode(node) => self.named_node(node),
+            Term::Literal(lit) => Ok(self.builder.literal(lit, 0..0).to_owned()),
+            x => Err(format!("Expected an IRI or a literal, found {}", x)),
+        }
+    }
+
+    fn match_quads(
+        &self,
+        patterns: &[MyQuad<'_>],
+        graph: &ActiveGraph,
+        input: Vec<Binding>,
+    ) -> Vec<Binding> {
+        let mut out = input;
+        for pattern in patterns {
+            let mut next = Vec::new();
+            for b in &out {
+                for quad in self.quads(graph) {
+                    let mut b = b.clone();
+                    if bind(&mut b, &pattern.subject, &quad.subject)
+                        && bind(&mut b, &pattern.predicate, &quad.predicate)
+                        && bind(&mut b, &pattern.object, &quad.object)
+                    {
+                        next.push(b);
+                    }
+                }
+            }
+            out = next;
+        }
+        out
+    }
+
+    fn nodes(&self, graph: &ActiveGraph) -> HashSet<MyTerm<'static>> {
+        self.quads(graph)
+            .flat_map(|q| [q.subject.clone(), q.object.clone()])
+            .collect()
+    }
+
+    /// All subject and object pairs connected by `path`.
+    fn path_pairs(
+        &self,
+        path: &PropertyPath,
+        graph: &ActiveGraph,
+    ) -> Result<HashSet<(MyTerm<'static>, MyTerm<'static>)>, String> {
+        let out = match path {
+            PropertyPath::Iri(iri) => {
+                let predicate = self.named_node(iri)?;
+                self.quads(graph)
+                    .filter(|q| q.predicate == predicate)
+                    .map(|q| (q.subject.clone(), q.object.clone()))
+                    .collect()
+            }
+            PropertyPath::Inverse(path) => self
+                .path_pairs(path, graph)?
+                .into_iter()
+                .map(|(s, o)| (o, s))
+                .collect(),
+            PropertyPath::Alternative(paths) => {
+                let mut out = HashSet::new();
+                for path in paths {
+                    out.extend(self.path_pairs(path, graph)?);
+                }
+                out
+            }
+            PropertyPath::Sequence(paths) => {
+                let mut out: Option<HashSet<(MyTerm<'static>, MyTerm<'static>)>> = None;
+                for path in paths {
+                    let pairs = self.path_pairs(path, graph)?;
+                    out = Some(match out {
+                        None => pairs,
+                        Some(prev) => prev
+                            .iter()
+                            .flat_map(|(s, m)| {
+                                pairs
+                                    .iter()
+                                    .filter(move |(m2, _)| m == m2)
+                                    .map(move |(_, o)| (s.clone(), o.clone()))
+                            })
+                            .collect(),
+                    });
+                }
+                out.unwrap_or_default()
+            }
+            PropertyPath::Modified(path, modifier) => {
+                let pairs = self.path_pairs(path, graph)?;
+                let mut out = pairs.clone();
+                if !matches!(modifier.value(), PathModifier::ZeroOrOne) {
+                    loop {
+                        let next: HashSet<_> = out
+                            .iter()
+                            .flat_map(|(s, m)| {
+                                pairs
+                                    .iter()
+                                    .filter(move |(m2, _)| m == m2)
+                                    .map(move |(_, o)| (s.clone(), o.clone()))
+                            })
+                            .filter(|x| !out.contains(x))
+                            .collect();
+                        if next.is_empty() {
+                            break;
+                        }
+                        out.extend(next);
+                    }
+                }
+                if !matches!(modifier.value(), PathModifier::OneOrMore) {
+                    out.extend(self.nodes(graph).into_iter().map(|n| (n.clone(), n)));
+                }
+                out
+            }
+            PropertyPath::Negated(paths) => {
+                let mut forward = Vec::new();
+                let mut inverse = Vec::new();
+                for path in paths {
+                    match path.value() {
+                        PropertyPath::Iri(iri) => forward.push(self.named_node(iri)?),
+                        PropertyPath::Inverse(inner) => match inner.value() {
+                            PropertyPath::Iri(iri) => inverse.push(self.named_node(iri)?),
+                            _ => return Err(String::from("Unexpected path in negated set")),
+                        },
+                        _ => return Err(String::from("Unexpected path in negated set")),
+                    }
+                }
+                let mut out = HashSet::new();
+                if !forward.is_empty() || inverse.is_empty() {
+                    out.extend(
+                        self.quads(graph)
+                            .filter(|q| !forward.contains(&q.predicate))
+                            .map(|q| (q.subject.clone(), q.object.clone())),
+                    );
+                }
+                if !inverse.is_empty() {
+                    out.extend(
+                        self.quads(graph)
+                            .filter(|q| !inverse.contains(&q.predicate))
+                            .map(|q| (q.object.clone(), q.subject.clone())),
+                    );
+                }
+                out
+            }
+        };
+        Ok(out)
+    }
+
+    fn match_triple(
+        &mut self,
+        triple: &'a Spanned<Triple>,
+        graph: &ActiveGraph,
+        input: Vec<Binding>,
+    ) -> Result<Vec<Binding>, String> {
+        self.builder
+            .ingest(triple)
+            .map_err(|e| format!("Cannot run triple pattern: {:?}", e))?;
+        let patterns = std::mem::take(&mut self.builder.triples);
+        let paths = std::mem::take(&mut self.builder.paths);
+
+        let mut out = self.match_quads(&patterns, graph, input);
+        for (subject, path, object) in paths {
+            let pairs = self.path_pairs(path, graph)?;
+            let mut next = Vec::new();
+            for b in &out {
+                for (s, o) in &pairs {
+                    let mut b = b.clone();
+                    if bind(&mut b, &subject, s) && bind(&mut b, &object, o) {
+                        next.push(b);
+                    }
+                }
+            }
+            out = next;
+        }
+        Ok(out)
+    }
+
+    fn inline_rows(&self, data: &InlineData) -> Result<Vec<Binding>, String> {
+        let mut out = Vec::new();
+        for row in &data.rows {
+            let mut b = Binding::new();
+            for (var, value) in data.vars.iter().zip(row.iter()) {
+                if let DataValue::Term(term) = value.value() {
+                    b.insert(var.name().to_string(), self.term(term)?);
+                }
+            }
+            out.push(b);
+        }
+        Ok(out)
+    }
+
+    fn eval_ggp(
+        &mut self,
+        ggp: &'a GroupGraphPattern,
+        graph: &ActiveGraph,
+        input: Vec<Binding>,
+    ) -> Result<Vec<Binding>, String> {
+        let (out, filters) = self.eval_group(ggp, graph, input)?;
+        Ok(self.filter(out, &filters, graph))
+    }
+
+    /// Keeps the solutions for which every filter is true.
+    fn filter(
+        &mut self,
+        mut out: Vec<Binding>,
+        filters: &[&'a Spanned<Expression>],
+        graph: &ActiveGraph,
+    ) -> Vec<Binding> {
+        for &filter in filters {
+            let mut kept = Vec::new();
+            for b in out {
+                let value = self.eval_expr(filter, &b, None, graph);
+                if value.and_then(|x| ebv(&x)).unwrap_or(false) {
+                    kept.push(b);
+                }
+            }
+            out = kept;
+        }
+        out
+    }
+
+    /// Solutions of a group before its filters are applied, returned next to them so OPTIONAL can
+    /// apply them to the joined solutions.
+    fn eval_group(
+        &mut self,
+        ggp: &'a GroupGraphPattern,
+        graph: &ActiveGraph,
+        input: Vec<Binding>,
+    ) -> Result<(Vec<Binding>, Vec<&'a Spanned<Expression>>), String> {
+        let subs = match ggp {
+            GroupGraphPattern::SubSelect(sub) => {
+                let (_, rows) = self.eval_select(
+                    Some(&sub.select),
+                    &sub.where_clause,
+                    &sub.modifier,
+                    sub.values.as_ref().map(|x| x.value()),
+                    graph,
+                )?;
+                return Ok((join(input, &rows), Vec::new()));
+            }
+            GroupGraphPattern::GroupGraph(subs) => subs,
+            GroupGraphPattern::Invalid => {
+                return Err(String::from("Cannot run a query with syntax errors"))
+            }
+        };
+
+        let mut out = input;
+        let mut filters = Vec::new();
+        for sub in subs {
+            match sub.value() {
+                GroupGraphPatternSub::Triple(triple) => {
+                    out = self.match_triple(triple, graph, out)?;
+                }
+                GroupGraphPatternSub::Optional(_, ggp) => {
+                    let (right, filters) = self.eval_group(ggp, graph, vec![Binding::new()])?;
+                    let mut next = Vec::new();
+                    for b in out {
+                        let found = join(vec![b.clone()], &right);
+                        let found = self.filter(found, &filters, graph);
+                        if found.is_empty() {
+                            next.push(b);
+                        } else {
+                            next.extend(found);
+                        }
+                    }
+                    out = next;
+                }
+                GroupGraphPatternSub::Minus(_, ggp) => {
+                    let minus = self.eval_ggp(ggp, graph, vec![Binding::new()])?;
+                    out.retain(|b| {
+                        !minus
+                            .iter()
+                            .any(|m| compatible(b, m) && m.keys().any(|k| b.contains_key(k)))
+                    });
+                }
+                GroupGraphPatternSub::Filter(_, expr) => filters.push(expr),
+                GroupGraphPatternSub::Union(first, rest) => {
+                    let mut right = self.eval_ggp(first, graph, vec![Binding::new()])?;
+                    for (_, ggp) in rest {
+                        right.extend(self.eval_ggp(ggp, graph, vec![Binding::new()])?);
+                    }
+                    out = join(out, &right);
+                }
+                GroupGraphPatternSub::Graph(_, name, ggp) => {
+                    let names = match name.value() {
+                        Term::Variable(_) => self.graph_names(),
+                        x => vec![self.term(x)?],
+                    };
+                    let var = match name.value() {
+                        Term::Variable(var) => Some(MyTerm::variable(var.0.as_str(), 0..0)),
+                        _ => None,
+                    };
+                    let mut right = Vec::new();
+                    for g in &names {
+                        for mut b in self.eval_ggp(ggp, &Some(g.clone()), vec![Binding::new()])? {
+                            if var.as_ref().map(|v| bind(&mut b, v, g)).unwrap_or(true) {
+                                right.push(b);
+                            }
+                        }
+                    }
+                    out = join(out, &right);
+                }
+                GroupGraphPatternSub::Service(..) => {
+                    return Err(String::from(
+                        "SERVICE is not supported when running offline",
+                    ))
+                }
+                GroupGraphPatternSub::Bind(_, _, bind, _) => {
+                    for b in &mut out {
+                        if let Ok(value) = self.eval_expr(&bind.expr, b, None, graph) {
+                            b.insert(bind.var.name().to_string(), value);
+                        }
+                    }
+                }
+                GroupGraphPatternSub::Values(data) => {
+                    out = join(out, &self.inline_rows(data)?);
+                }
+            }
+        }
+
+        Ok((out, filters))
+    }
+
+    fn eval_args(
+        &mut self,
+        args: &'a [Spanned<Expression>],
+        b: &Binding,
+        group: Option<&[Binding]>,
+        graph: &ActiveGraph,
+    ) -> Result<Vec<MyTerm<'static>>, ()> {
+        args.iter()
+            .map(|arg| self.eval_expr(arg, b, group, graph))
+            .collect()
+    }
+
+    fn eval_aggregate(
+        &mut self,
+        expr: &'a Expression,
+        group: &[Binding],
+        graph: &ActiveGraph,
+    ) -> Result<MyTerm<'static>, ()> {
+        let Expression::Aggregate {
+            name,
+            distinct,
+            star,
+            args,
+            separator,
+        } = expr
+        else {
+            return Err(());
+        };
+        let distinct = distinct.is_some();
+        if *star {
+            let count = if distinct {
+                group.iter().collect::<HashSet<_>>().len()
+            } else {
+                group.len()
+            };
+            return Ok(number(count as f64));
+        }
+
+        let mut values = Vec::new();
+        for b in group {
+            if let Ok(value) = self.eval_expr(args.first().ok_or(())?, b, None, graph) {
+                if !distinct || !values.contains(&value) {
+                    values.push(value);
+                }
+            }
+        }
+
+        let numbers = || {
+            values
+                .iter()
+                .map(numeric)
+                .collect::<Option<Vec<_>>>()
+                .ok_or(())
+        };
+        match name.value() {
+            SparqlAggregate::Count => Ok(number(values.len() as f64)),
+            SparqlAggregate::Sum => Ok(number(numbers()?.into_iter().sum())),
+            SparqlAggregate::Avg => {
+                let numbers = numbers()?;
+                if numbers.is_empty() {
+                    return Ok(number(0.0));
+                }
+                Ok(number(numbers.iter().sum::<f64>() / numbers.len() as f64))
+            }
+            SparqlAggregate::Min => values
+                .into_iter()
+                .min_by(|a, b| order(Some(a), Some(b)))
+                .ok_or(()),
+            SparqlAggregate::Max => values
+                .into_iter()
+                .max_by(|a, b| order(Some(a), Some(b)))
+                .ok_or(()),
+            SparqlAggregate::Sample => values.into_iter().next().ok_or(()),
+            SparqlAggregate::GroupConcat => {
+                let values: Vec<_> = values.iter().map(|x| x.value.as_ref()).collect();
+                Ok(literal(
+                    values.join(
+                        separator
+                            .as_ref()
+                            .map(|x| x.value().0.as_str())
+                            .unwrap_or(" "),
+                    ),
+                ))
+            }
+        }
+    }
+
+    fn eval_call(
+        &mut self,
+        function: &Function,
+        args: &'a [Spanned<Expression>],
+        b: &Binding,
+        group: Option<&[Binding]>,
+        graph: &ActiveGraph,
+    ) -> Result<MyTerm<'static>, ()> {
+        use SparqlCall::*;
+        let call = match function {
+            Function::Call(call) => call,
+            Function::Keyword(kwd) => {
+                let values = self.eval_args(args, b, group, graph)?;
+                let text = values.first().ok_or(())?.value.to_string();
+                let arg = |i: usize| values.get(i).map(|x| x.value.as_ref());
+                return match kwd {
+                    SparqlKeyword::Regex => {
+                        let pattern = arg(1).ok_or(())?;
+                        match simple_regex(&text, pattern, arg(2).unwrap_or("")) {
+                            Some(x) => Ok(boolean(x)),
+                            None => self.unsupported(format!("Unsupported regex {}", pattern)),
+                        }
+                    }
+                    SparqlKeyword::Substr => {
+                        let start = numeric(values.get(1).ok_or(())?).ok_or(())? as usize;
+                        let chars = text.chars().skip(start.saturating_sub(1));
+                        Ok(literal(match values.get(2).and_then(numeric) {
+                            Some(len) => chars.take(len as usize).collect::<String>(),
+                            None => chars.collect(),
+                        }))
+                    }
+                    SparqlKeyword::Replace => {
+                        let pattern = arg(1).ok_or(())?;
+                        if pattern.contains(['.', '*', '+', '?', '(', '[', '\\', '^', '$']) {
+                            return self.unsupported(format!("Unsupported regex {}", pattern));
+                        }
+                        Ok(literal(text.replace(pattern, arg(2).ok_or(())?)))
+                    }
+                    _ => self.unsupported(format!("Unsupported function {}", function)),
+                };
+            }
+            Function::Iri(_) => {
+                return self.unsupported(format!("Unsupported function {}", function))
+            }
+        };
+
+        match call {
+            Bound => {
+                return match args.first().map(|x| x.value()) {
+                    Some(Expression::Variable(var)) => Ok(boolean(b.contains_key(var.name()))),
+                    _ => Err(()),
+                }
+            }
+            If => {
+                let cond = self.eval_expr(args.first().ok_or(())?, b, group, graph)?;
+                let branch = if ebv(&cond)? { 1 } else { 2 };
+                return self.eval_expr(args.get(branch).ok_or(())?, b, group, graph);
+            }
+            Coalesce => {
+                return args
+                    .iter()
+                    .find_map(|arg| self.eval_expr(arg, b, group, graph).ok())
+                    .ok_or(())
+            }
+            _ => {}
+        }
+
+        let values = self.eval_args(args, b, group, graph)?;
+        let first = values.first();
+        let text = || first.map(|x| x.value.to_string()).ok_or(());
+        let second = || values.get(1).map(|x| x.value.to_string()).ok_or(());
+        let num = || first.and_then(numeric).ok_or(());
+        let out = match call {
+            Str => literal(text()?),
+            Lang => {
+                let first = first.filter(|x| x.is_literal()).ok_or(())?;
+                literal(first.language().unwrap_or_default().to_string())
+            }
+            Datatype => {
+                let ty = first.and_then(|x| x.datatype_iri()).ok_or(())?;
+                MyTerm::named_node(ty.to_string(), 0..0)
+            }
+            Iri | Uri => MyTerm::named_node(text()?, 0..0),
+            Abs => number(num()?.abs()),
+            Ceil => number(num()?.ceil()),
+            Floor => number(num()?.floor()),
+            Round => number(num()?.round()),
+            Concat => literal(values.iter().map(|x| x.value.as_ref()).collect::<String>()),
+            StrLen => number(text()?.chars().count() as f64),
+            Ucase => literal(text()?.to_uppercase()),
+            Lcase => literal(text()?.to_lowercase()),
+            Contains => boolean(text()?.contains(&second()?)),
+            StrStarts => boolean(text()?.starts_with(&second()?)),
+            StrEnds => boolean(text()?.ends_with(&second()?)),
+            StrBefore => literal(text()?.split_once(&second()?).map(|x| x.0).unwrap_or("")),
+            StrAfter => literal(text()?.split_once(&second()?).map(|x| x.1).unwrap_or("")),
+            SameTerm => boolean(first.ok_or(())? == values.get(1).ok_or(())?),
+            IsIri | IsUri => boolean(first.ok_or(())?.is_iri()),
+            IsBlank => boolean(first.ok_or(())?.is_blank_node()),
+            IsLiteral => boolean(first.ok_or(())?.is_literal()),
+            IsNumeric => boolean(first.and_then(numeric).is_some()),
+            _ => return self.unsupported(format!("Unsupported function {}", function)),
+        };
+        Ok(out)
+    }
+
+    fn eval_expr(
+        &mut self,
+        expr: &'a Expression,
+        b: &Binding,
+        group: Option<&[Binding]>,
+        graph: &ActiveGraph,
+    ) -> Result<MyTerm<'static>, ()> {
+        match expr {
+            Expression::Variable(var) => b.get(var.name()).cloned().ok_or(()),
+            Expression::NamedNode(node) => self.named_node(node).map_err(|_| ()),
+            Expression::Literal(lit) => Ok(self.builder.literal(lit, 0..0).to_owned()),
+            Expression::Bracketted(expr) => self.eval_expr(expr, b, group, graph),
+            Expression::Unary(op, expr) => {
+                let value = self.eval_expr(expr, b, group, graph)?;
+                match op.value() {
+                    SparqlExpr::Exclamation => Ok(boolean(!ebv(&value)?)),
+                    SparqlExpr::Minus => Ok(number(-numeric(&value).ok_or(())?)),
+                    SparqlExpr::Plus => Ok(number(numeric(&value).ok_or(())?)),
+                    _ => Err(()),
+                }
+            }
+            Expression::Binary(left, op, right) => {
+                let left = self.eval_expr(left, b, group, graph);
+                let right = self.eval_expr(right, b, group, graph);
+                match op.value() {
+                    SparqlExpr::Or => {
+                        let (l, r) = (left.and_then(|x| ebv(&x)), right.and_then(|x| ebv(&x)));
+                        match (l, r) {
+                            (Ok(true), _) | (_, Ok(true)) => Ok(boolean(true)),
+                            (Ok(false), Ok(false)) => Ok(boolean(false)),
+                            _ => Err(()),
+                        }
+                    }
+                    SparqlExpr::And => {
+                        let (l, r) = (left.and_then(|x| ebv(&x)), right.and_then(|x| ebv(&x)));
+                        match (l, r) {
+                            (Ok(false), _) | (_, Ok(false)) => Ok(boolean(false)),
+                            (Ok(true), Ok(true)) => Ok(boolean(true)),
+                            _ => Err(()),
+                        }
+                    }
+                    op => {
+                        let (left, right) = (left?, right?);
+                        let cmp = || compare(&left, &right).ok_or(());
+                        let nums =
+                            || Ok::<_, ()>((numeric(&left).ok_or(())?, numeric(&right).ok_or(())?));
+                        match op {
+                            SparqlExpr::Equal => Ok(boolean(term_equal(&left, &right))),
+                            SparqlExpr::NotEqual => Ok(boolean(!term_equal(&left, &right))),
+                            SparqlExpr::Lt => Ok(boolean(cmp()? == Ordering::Less)),
+                            SparqlExpr::Gt => Ok(boolean(cmp()? == Ordering::Greater)),
+                            SparqlExpr::Lte => Ok(boolean(cmp()? != Ordering::Greater)),
+                            SparqlExpr::Gte => Ok(boolean(cmp()? != Ordering::Less)),
+                            SparqlExpr::Plus => nums().map(|(l, r)| number(l + r)),
+                            SparqlExpr::Minus => nums().map(|(l, r)| number(l - r)),
+                            SparqlExpr::Times => nums().map(|(l, r)| number(l * r)),
+                            SparqlExpr::Divide => match nums()? {
+                                (_, r) if r == 0.0 => Err(()),
+                                (l, r) => Ok(number(l / r)),
+                            },
+                            _ => Err(()),
+                        }
+                    }
+                }
+            }
+            Expression::In { expr, not, list } => {
+                let value = self.eval_expr(expr, b, group, graph)?;
+                let mut found = false;
+                for item in list.iter() {
+                    if let Ok(item) = self.eval_expr(item, b, group, graph) {
+                        found |= term_equal(&value, &item);
+                    }
+                }
+                Ok(boolean(found != not.is_some()))
+            }
+            Expression::Call { function, args, .. } => {
+                self.eval_call(function, args, b, group, graph)
+            }
+            Expression::Aggregate { .. } => self.eval_aggregate(expr, group.ok_or(())?, graph),
+            Expression::Exists { not, ggp, .. } => {
+                let found = self.eval_ggp(ggp, graph, vec![b.clone()]).map_err(|_| ())?;
+                Ok(boolean(found.is_empty() == not.is_some()))
+            }
+            Expression::Invalid => Err(()),
+        }
+    }
+
+    /// Evaluates the where clause followed by grouping, projection and the solution modifiers.
+    ///
+    /// Without `select` all variables in scope are projected.
+    fn eval_select(
+        &mut self,
+        select: Option<&'a SelectClause>,
+        where_clause: &'a WhereClause,
+        modifiers: &'a [Spanned<Modifier>],
+        values: Option<&'a InlineData>,
+        graph: &ActiveGraph,
+    ) -> Result<(Vec<String>, Vec<Binding>), String> {
+        let mut rows = self.eval_ggp(&where_clause.ggp, graph, vec![Binding::new()])?;
+        if let Some(values) = values {
+            rows = join(rows, &self.inline_rows(values)?);
+        }
+
+        let names = select
+            .and_then(|s| s.projected())
+            .unwrap_or_else(|| where_clause.ggp.in_scope());
+        let mut vars: Vec<String> = Vec::new();
+        for var in names {
+            let var = variable_name(var.value());
+            if !vars.iter().any(|x| x == var) {
+                vars.push(var.to_string());
+            }
+        }
+
+        let group_by = modifiers.iter().find_map(|m| match m.value() {
+            Modifier::GroupBy(_, _, conditions) => Some(conditions),
+            _ => None,
+        });
+        let has_aggregate = select
+            .map(|s| {
+                s.solutions.iter().any(|s| match s.value() {
+                    Solution::VarAs(bind) => contains_aggregate(&bind.expr),
+                    _ => false,
+                })
+            })
+            .unwrap_or(false);
+        let grouped = group_by.is_some()
+            || has_aggregate
+            || modifiers
+                .iter()
+                .any(|m| matches!(m.value(), Modifier::Having(..)));
+
+        // Every row is a representative binding and, when grouped, the members of its group
+        let mut groups: Vec<(Binding, Option<Vec<Binding>>)> = if grouped {
+            let mut index: HashMap<Vec<Option<MyTerm<'static>>>, usize> = HashMap::new();
+            let mut groups: Vec<(Binding, Vec<Binding>)> = Vec::new();
+            if group_by.is_none() {
+                groups.push((Binding::new(), Vec::new()));
+            }
+            for row in rows {
+                let mut keys = Vec::new();
+                let mut repr = Binding::new();
+                for condition in group_by.into_iter().flatten() {
+                    let (expr, var) = match condition.value() {
+                        GroupCondition::Expression(expr) => match expr.value() {
+                            Expression::Variable(var) => (expr, Some(var.name())),
+                            _ => (expr, None),
+                        },
+                        GroupCondition::Bind(bind) => (&bind.expr, Some(bind.var.name())),
+                    };
+                    let value = self.eval_expr(expr, &row, None, graph).ok();
+                    if let (Some(var), Some(value)) = (var, &value) {
+                        repr.insert(var.to_string(), value.clone());
+                    }
+                    keys.push(value);
+                }
+                let i = *index.entry(keys).or_insert_with(|| {
+                    groups.push((repr, Vec::new()));
+                    groups.len() - 1
+                });
+                groups[i].1.push(row);
+            }
+            groups.into_iter().map(|(r, m)| (r, Some(m))).collect()
+        } else {
+            rows.into_iter().map(|r| (r, None)).collect()
+        };
+
+        for modifier in modifiers {
+            if let Modifier::Having(_, constraints) = modifier.value() {
+                let mut kept = Vec::new();
+                for (repr, members) in groups {
+                    let mut keep = true;
+                    for constraint in constraints {
+                        let value = self.eval_expr(constraint, &repr, members.as_deref(), graph);
+                        keep &= value.and_then(|x| ebv(&x)).unwrap_or(false);
+                    }
+                    if keep {
+                        kept.push((repr, members));
+                    }
+                }
+                groups = kept;
+            }
+        }
+
+        if let Some(select) = select {
+            for solution in &select.solutions {
+                if let Solution::VarAs(bind) = solution.value() {
+                    for (repr, members) in &mut groups {
+                        if let Ok(value) =
+                            self.eval_expr(&bind.expr, repr, members.as_deref(), graph)
+                        {
+                            repr.insert(bind.var.name().to_string(), value);
+                        }
+                    }
+                }
+            }
+        }
+
+        for modifier in modifiers {
+            if let Modifier::OrderBy(_, _, conditions) = modifier.value() {
+                let mut keyed = Vec::new();
+                for (repr, members) in groups {
+                    let mut keys = Vec::new();
+                    for condition in conditions {
+                        let desc = condition
+                            .direction
+                            .as_ref()
+                            .is_some_and(|d| matches!(d.value(), SparqlKeyword::Desc));
+                        let value = self
+                            .eval_expr(&condition.expr, &repr, members.as_deref(), graph)
+                            .ok();
+                        keys.push((value, desc));
+                    }
+                    keyed.push((keys, (repr, members)));
+                }
+                keyed.sort_by(|(a, _), (b, _)| {
+                    a.iter()
+                        .zip(b.iter())
+                        .map(|((a, desc), (b, _))| {
+                            let ord = order(a.as_ref(), b.as_ref());
+                            if *desc {
+                                ord.reverse()
+                            } else {
+                                ord
+                            }
+                        })
+                        .find(|x| x.is_ne())
+                        .unwrap_or(Ordering::Equal)
+                });
+                groups = keyed.into_iter().map(|(_, g)| g).collect();
+            }
+        }
+
+        let mut rows: Vec<Binding> = groups
+            .into_iter()
+            .map(|(repr, _)| repr.into_iter().filter(|(k, _)| vars.contains(k)).collect())
+            .collect();
+
+        if select.is_some_and(|s| s.modifier.is_some()) {
+            let mut seen = HashSet::new();
+            rows.retain(|row| seen.insert(row.clone()));
+        }
+
+        let mut offset = 0;
+        let mut limit = usize::MAX;
+        for modifier in modifiers {
+            if let Modifier::LimitOffset(kwd, value) = modifier.value() {
+                let value: usize = value
+                    .parse()
+                    .map_err(|_| format!("Expected a number, found {}", value.value()))?;
+                match kwd.value() {
+                    SparqlKeyword::Limit => limit = value,
+                    _ => offset = value,
+                }
+            }
+        }
+        let rows = rows.into_iter().skip(offset).take(limit).collect();
+
+        Ok((vars, rows))
+    }
+
+    fn instantiate(&self, template: &[MyQuad<'_>], rows: &[Binding]) -> Vec<MyQuad<'static>> {
+        let mut out: Vec<MyQuad<'static>> = Vec::new();
+        for (i, row) in rows.iter().enumerate() {
+            let term = |t: &MyTerm<'_>| match (t.kind(), key(t)) {
+                (TermKind::BlankNode, _) => {
+                    Some(MyTerm::blank_node(format!("b{}_{}", i, t.value), 0..0))
+                }
+                (_, Some(k)) => row.get(&k).cloned(),
+                _ => Some(t.to_owned()),
+            };
+            for pattern in template {
+                let (Some(subject), Some(predicate), Some(object)) = (
+                    term(&pattern.subject),
+                    term(&pattern.predicate),
+                    term(&pattern.object),
+                ) else {
+                    continue;
+                };
+                let quad = MyQuad {
+                    subject,
+                    predicate,
+                    object,
+                    graph: None,
+                    span: 0..0,
+                };
+                if !out.iter().any(|q| {
+                    q.subject == quad.subject
+                        && q.predicate == quad.predicate
+                        && q.object == quad.object
+                }) {
+                    out.push(quad);
+                }
+            }
+        }
+        out
+    }
+}
+
+fn contains_aggregate(expr: &Spanned<Expression>) -> bool {
+    match expr.value() {
+        Expression::Aggregate { .. } => true,
+        x => x.children().into_iter().any(contains_aggregate),
+    }
+}
+
+/// The documents of the `FROM` and `FROM NAMED` clauses, with whether they are named graphs.
+pub fn dataset(query: &Query) -> Result<Vec<(String, bool)>, String> {
+    let base = BaseIri::new(query.base.to_string()).map_err(|e| e.to_string())?;
+    let builder = TriplesBuilder::new(query, base);
+    query
+        .datasets
+        .iter()
+        .map(|d| {
+            builder
+                .resolve(&d.iri)
+                .map(|iri| (iri, d.named.is_some()))
+                .ok_or_else(|| format!("Cannot resolve {}", d.iri.value()))
+        })
+        .collect()
+}
+
+/// Runs `query` against `data`.
+pub fn execute(query: &Query, data: &[MyQuad<'static>]) -> Result<QueryResults, String> {
+    let base = BaseIri::new(query.base.to_string()).map_err(|e| e.to_string())?;
+    let mut eval = Evaluator {
+        data,
+        builder: TriplesBuilder::new(query, base).keep_paths(),
+        unsupported: None,
+    };
+    let results = run(&mut eval, query, data);
+    match eval.unsupported {
+        Some(unsupported) => Err(unsupported),
+        None => results,
+    }
+}
+
+fn run<'a>(
+    eval: &mut Evaluator<'a>,
+    query: &'a Query,
+    data: &'a [MyQuad<'static>],
+) -> Result<QueryResults, String> {
+    let where_clause = query.where_clause.as_ref().map(|x| x.value());
+    let values = query.values.as_ref().map(|x| x.value());
+    let missing_where = || String::from("Expected a WHERE clause");
+
+    match &query.kwds {
+        QueryClause::Select(select) => {
+            let where_clause = where_clause.ok_or_else(missing_where)?;
+            let (vars, rows) =
+                eval.eval_select(Some(select), where_clause, &query.modifier, values, &None)?;
+            Ok(QueryResults::Solutions { vars, rows })
+        }
+        QueryClause::Ask(_) => {
+            let where_clause = where_clause.ok_or_else(missing_where)?;
+            let (_, rows) = eval.eval_select(None, where_clause, &query.modifier, values, &None)?;
+            Ok(QueryResults::Boolean(!rows.is_empty()))
+        }
+        QueryClause::Construct(construct) => {
+            let where_clause = where_clause.ok_or_else(missing_where)?;
+            let subs = match (&construct.template, where_clause.ggp.value()) {
+                (Some((_, subs, _)), _) => subs,
+                (None, GroupGraphPattern::GroupGraph(subs)) => subs,
+                _ => return Err(String::from("Expected a construct template")),
+            };
+            let (_, rows) = eval.eval_select(None, where_clause, &query.modifier, values, &None)?;
+
+            for sub in subs {
+                match sub.value() {
+                    GroupGraphPatternSub::Triple(triple) => eval
+                        .builder
+                        .ingest(triple)
+                        .map_err(|e| format!("Cannot build template: {:?}", e))?,
+                    _ => return Err(String::from("Only triples are allowed in a template")),
+                }
+            }
+            let template = std::mem::take(&mut eval.builder.triples);
+            Ok(QueryResults::Graph(eval.instantiate(&template, &rows)))
+        }
+        QueryClause::Describe(describe) => {
+            let rows = match where_clause {
+                Some(where_clause) => {
+                    eval.eval_select(None, where_clause, &query.modifier, values, &None)?
+                        .1
+                }
+                None => vec![Binding::new()],
+            };
+            let mut resources = Vec::new();
+            for target in &describe.targets {
+                match target.value() {
+                    DescribeTarget::NamedNode(node) => resources.push(eval.named_node(node)?),
+                    DescribeTarget::Var(var) => {
+                        resources.extend(rows.iter().flat_map(|row| row.get(var.name()).cloned()))
+                    }
+                    DescribeTarget::All => {
+                        resources.extend(rows.iter().flat_map(|row| row.values().cloned()))
+                    }
+                }
+            }
+            let quads = data
+                .iter()
+                .filter(|q| resources.contains(&q.subject))
+                .map(|q| MyQuad {
+                    graph: None,
+                    ..q.clone()
+                })
+                .collect();
+            Ok(QueryResults::Graph(quads))
+        }
+        QueryClause::Update(_) => Err(String::from(
+            "Only queries can be executed, updates would change the documents",
+        )),
+        QueryClause::Invalid => Err(String::from("Cannot run a query with syntax errors")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lang_turtle::lang::context::Context;
+    use lsp_core::prelude::{MyQuad, MyTerm};
+
+    use super::{execute, QueryResults};
+    use crate::lang::{parsing::parse, tokenizer::parse_tokens_str};
+
+    fn quad(s: &str, p: &str, o: MyTerm<'static>) -> MyQuad<'static> {
+        MyQuad {
+            subject: MyTerm::named_node(format!("http://example.org/{}", s), 0..0),
+            predicate: MyTerm::named_node(format!("http://example.org/{}", p), 0..0),
+            object: o,
+            graph: None,
+            span: 0..0,
+        }
+    }
+
+    fn node(x: &str) -> MyTerm<'static> {
+        MyTerm::named_node(format!("http://example.org/{}", x), 0..0)
+    }
+
+    fn data() -> Vec<MyQuad<'static>> {
+        vec![
+            quad("alice", "knows", node("bob")),
+            quad("bob", "knows", node("carol")),
+            quad("alice", "age", MyTerm::literal("30", 0..0)),
+            quad("bob", "age", MyTerm::literal("25", 0..0)),
+            quad("carol", "age", MyTerm::literal("40", 0..0)),
+            quad("alice", "name", MyTerm::literal("Alice", 0..0)),
+            quad(
+                "alice",
+                "label",
+                MyTerm::literal("Alice", 0..0).with_language("en"),
+            ),
+        ]
+    }
+
+    fn try_run(inp: &str) -> Result<QueryResults, String> {
+        let context = Context::new();
+        let (tokens, _) = parse_tokens_str(inp);
+        let (query, errors) = parse(
+            inp,
+            lsp_types::Url::parse("memory::myFile.sq").unwrap(),
+            tokens,
+            context.ctx(),
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+        execute(&query.0, &data())
+    }
+
+    fn run(inp: &str) -> QueryResults {
+        try_run(inp).expect("query runs")
+    }
+
+    fn cells(results: &QueryResults) -> Vec<Vec<String>> {
+        results.table().1
+    }
+
+    #[test]
+    fn select_with_optional_filter_and_order() {
+        let results = run("PREFIX ex: <http://example.org/>
+SELECT ?p ?name WHERE { ?p ex:age ?age. OPTIONAL { ?p ex:name ?name } FILTER(?age > 26) }
+ORDER BY DESC(?age)");
+        assert_eq!(
+            cells(&results),
+            vec![
+                vec!["<http://example.org/carol>".to_string(), String::new()],
+                vec![
+                    "<http://example.org/alice>".to_string(),
+                    "\"Alice\"".to_string()
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn optional_filters_are_scoped_like_a_left_join() {
+        let expected = vec![
+            vec!["<http://example.org/alice>".to_string(), String::new()],
+            vec!["<http://example.org/carol>".to_string(), String::new()],
+        ];
+
+        // A filter of the optional group is the condition of the left join, it sees ?age.
+        let results = run("PREFIX ex: <http://example.org/>
+SELECT ?p ?name WHERE { ?p ex:age ?age. OPTIONAL { ?p ex:name ?name FILTER(?age > 35) } FILTER(?age > 26) }
+ORDER BY ?p");
+        assert_eq!(cells(&results), expected);
+
+        // A nested group is evaluated on its own, ?age is not bound there.
+        let results = run("PREFIX ex: <http://example.org/>
+SELECT ?p ?name WHERE { ?p ex:age ?age. OPTIONAL { ?p ex:name ?name { FILTER(BOUND(?age)) } } FILTER(?age > 26) }
+ORDER BY ?p");
+        assert_eq!(cells(&results), expected);
+    }
+
+    #[test]
+    fn typed_literals_are_equal_by_value_and_datatype() {
+        let results = run("PREFIX ex: <http://example.org/>
+PREFIX xsd: <http://www.w3.org/2001/XMLSchema#>
+SELECT (1 = 1.0 AS ?numbers) (\"1\"^^xsd:integer = \"1\" AS ?string)
+(\"01\"^^xsd:integer = 1 AS ?lexical) (\"Alice\"@en = \"Alice\" AS ?lang)
+WHERE {}");
+        assert_eq!(
+            cells(&results),
+            vec![["true", "false", "true", "false"]
+                .iter()
+                .map(|x| format!("\"{}\"^^<http://www.w3.org/2001/XMLSchema#boolean>", x))
+                .collect::<Vec<_>>()]
+        );
+    }
+
+    #[test]
+    fn property_paths_and_aggregates() {
+        let results = run("PREFIX ex: <http://example.org/>
+SELECT (COUNT(?o) AS ?c) WHERE { ex:alice ex:knows+ ?o }");
+        assert_eq!(
+            cells(&results),
+            vec![vec![
+                "\"2\"^^<http://www.w3.org/2001/XMLSchema#integer>".to_string()
+            ]]
+        );
+
+        let results = run("PREFIX ex: <http://example.org/>
+SELECT ?p (SUM(?age) AS ?total) WHERE { ?p ex:knows/ex:age ?age } GROUP BY ?p ORDER BY ?p");
+        assert_eq!(
+            cells(&results),
+            vec![
+                vec![
+                    "<http://example.org/alice>".to_string(),
+                    "\"25\"^^<http://www.w3.org/2001/XMLSchema#integer>".to_string()
+                ],
+                vec![
+                    "<http://example.org/bob>".to_string(),
+                    "\"40\"^^<http://www.w3.org/2001/XMLSchema#integer>".to_string()
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn ask_and_construct() {
+        let results = run("PREFIX ex: <http://example.org/> ASK { ex:bob ^ex:knows ex:alice }");
+        assert!(matches!(results, QueryResults::Boolean(true)));
+
+        let results = run("PREFIX ex: <http://example.org/>
+CONSTRUCT { ?b ex:knownBy ?a } WHERE { ?a ex:knows ?b } LIMIT 1");
+        assert_eq!(
+            cells(&results),
+            vec![vec![
+                "<http://example.org/bob>".to_string(),
+                "<http://example.org/knownBy>".to_string(),
+                "<http://example.org/alice>".to_string(),
+            ]]
+        );
+    }
+
+    #[test]
+    fn lang_and_datatype() {
+        let results = run("PREFIX ex: <http://example.org/>
+SELECT (LANG(?l) AS ?lang) (DATATYPE(?l) AS ?ty) (DATATYPE(1.5) AS ?d)
+WHERE { ex:alice ex:label ?l }");
+        assert_eq!(
+            cells(&results),
+            vec![vec![
+                "\"en\"".to_string(),
+                "<http://www.w3.org/1999/02/22-rdf-syntax-ns#langString>".to_string(),
+                "<http://www.w3.org/2001/XMLSchema#decimal>".to_string(),
+            ]]
+        );
+    }
+
+    #[test]
+    fn unsupported_functions_fail_the_query() {
+        let err = try_run(
+            "PREFIX ex: <http://example.org/>
+SELECT ?p WHERE { ?p ex:name ?n FILTER(REGEX(?n, \"^A.*\")) }",
+        )
+        .expect_err("regex is unsupported");
+        assert_eq!(err, "Unsupported regex ^A.*");
+
+        let err = try_run(
+            "PREFIX ex: <http://example.org/>
+SELECT ?p WHERE { ?p ex:name ?n FILTER(ex:custom(?n)) }",
+        )
+        .expect_err("custom functions are unsupported");
+        assert!(err.starts_with("Unsupported function"), "{}", err);
+    }
+}
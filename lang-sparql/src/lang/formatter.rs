@@ -0,0 +1,703 @@
+use std::io;
+
+use lang_turtle::lang::formatter::FormatState;
+use lsp_core::prelude::*;
+use lsp_types::FormattingOptions;
+use ropey::Rope;
+use tracing::info;
+
+use crate::lang::model::{
+    Bind, ConstructClause, DataValue, DescribeClause, DescribeTarget, Expression, GraphTarget,
+    GroupCondition, GroupGraphPattern, GroupGraphPatternSub, InlineData, Modifier, Modify,
+    QuadPattern, Quads, Query, QueryClause, SelectClause, Solution, SubSelect, Update, WhereClause,
+};
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Other,
+        format!("cannot format sparql with {}", msg),
+    )
+}
+
+fn operator(expr: &SparqlExpr) -> &'static str {
+    match expr {
+        SparqlExpr::Or => "||",
+        SparqlExpr::And => "&&",
+        SparqlExpr::Equal => "=",
+        SparqlExpr::NotEqual => "!=",
+        SparqlExpr::Lt => "<",
+        SparqlExpr::Gt => ">",
+        SparqlExpr::Lte => "<=",
+        SparqlExpr::Gte => ">=",
+        SparqlExpr::In => "IN",
+        SparqlExpr::Not => "NOT",
+        SparqlExpr::Plus => "+",
+        SparqlExpr::Minus => "-",
+        SparqlExpr::Times => "*",
+        SparqlExpr::Divide => "/",
+        SparqlExpr::Exclamation => "!",
+        SparqlExpr::Pipe => "|",
+        SparqlExpr::Hat => "^",
+        SparqlExpr::Question => "?",
+    }
+}
+
+fn write_kwd(state: &mut FormatState, kwd: &SparqlKeyword) -> io::Result<()> {
+    state.write_str(kwd.complete())
+}
+
+/// Writes `items` separated by spaces, putting every item on its own line when they don't fit
+/// on the current line.
+fn write_wrapped<T>(
+    state: &mut FormatState,
+    items: &[Spanned<T>],
+    mut write: impl FnMut(&mut FormatState, &T) -> io::Result<()>,
+) -> io::Result<()> {
+    let checkpoint = state.checkpoint();
+    let mut should_indent = false;
+    for item in items {
+        state.write_str(" ")?;
+        write(state, item.value())?;
+        if state.current_line_length() > 80 {
+            should_indent = true;
+            break;
+        }
+    }
+
+    if should_indent {
+        state.rewind(checkpoint);
+        state.inc();
+        for item in items {
+            state.new_line()?;
+            state.check_comments(item.span())?;
+            write(state, item.value())?;
+        }
+        state.decr();
+    }
+    Ok(())
+}
+
+fn write_args(state: &mut FormatState, args: &[Spanned<Expression>]) -> io::Result<()> {
+    for (i, arg) in args.iter().enumerate() {
+        if i > 0 {
+            state.write_str(", ")?;
+        }
+        write_expr(state, arg)?;
+    }
+    Ok(())
+}
+
+fn write_expr(state: &mut FormatState, expr: &Expression) -> io::Result<()> {
+    match expr {
+        Expression::Variable(var) => state.write_str(&var.0),
+        Expression::NamedNode(node) => state.write_str(&node.to_string()),
+        Expression::Literal(lit) => state.write_str(&lit.to_string()),
+        Expression::Bracketted(expr) => {
+            state.write_str("(")?;
+            write_expr(state, expr)?;
+            state.write_str(")")
+        }
+        Expression::Unary(op, expr) => {
+            state.write_str(operator(op))?;
+            write_expr(state, expr)
+        }
+        Expression::Binary(left, op, right) => {
+            write_expr(state, left)?;
+            state.write_str(&format!(" {} ", operator(op)))?;
+            write_expr(state, right)
+        }
+        Expression::In { expr, not, list } => {
+            write_expr(state, expr)?;
+            if not.is_some() {
+                state.write_str(" NOT")?;
+            }
+            state.write_str(" IN (")?;
+            write_args(state, list)?;
+            state.write_str(")")
+        }
+        Expression::Call {
+            function,
+            distinct,
+            args,
+        } => {
+            state.write_str(&format!("{}(", function.value()))?;
+            if distinct.is_some() {
+                state.write_str("DISTINCT ")?;
+            }
+            write_args(state, args)?;
+            state.write_str(")")
+        }
+        Expression::Aggregate {
+            name,
+            distinct,
+            star,
+            args,
+            separator,
+        } => {
+            state.write_str(&format!("{}(", name.complete()))?;
+            if distinct.is_some() {
+                state.write_str("DISTINCT ")?;
+            }
+            if *star {
+                state.write_str("*")?;
+            } else {
+                write_args(state, args)?;
+            }
+            if let Some(separator) = separator {
+                let Spanned((value, style), _) = separator;
+                let quote = style.quote();
+                state.write_str(&format!("; SEPARATOR = {}{}{}", quote, value, quote))?;
+            }
+            state.write_str(")")
+        }
+        Expression::Exists { not, ggp, .. } => {
+            if not.is_some() {
+                state.write_str("NOT ")?;
+            }
+            state.write_str("EXISTS ")?;
+            write_ggp(state, ggp)
+        }
+        Expression::Invalid => Err(invalid("invalid expressions")),
+    }
+}
+
+fn write_bind(state: &mut FormatState, bind: &Bind) -> io::Result<()> {
+    write_expr(state, &bind.expr)?;
+    state.write_str(&format!(" AS {}", bind.var.0))
+}
+
+fn write_inline_data(state: &mut FormatState, data: &InlineData) -> io::Result<()> {
+    write_kwd(state, &SparqlKeyword::Values)?;
+    let vars: Vec<_> = data.vars.iter().map(|x| x.0.as_str()).collect();
+    if data.bracketted {
+        state.write_str(&format!(" ({}) ", vars.join(" ")))?;
+    } else {
+        state.write_str(&format!(" {} ", vars.join(" ")))?;
+    }
+
+    if data.rows.is_empty() {
+        return state.write_str("{ }");
+    }
+
+    let write_row = |state: &mut FormatState, row: &[Spanned<DataValue>]| -> io::Result<()> {
+        for (i, value) in row.iter().enumerate() {
+            if i > 0 {
+                state.write_str(" ")?;
+            }
+            match value.value() {
+                DataValue::Term(term) => state.write_term(term)?,
+                DataValue::Undef => write_kwd(state, &SparqlKeyword::Undef)?,
+            }
+        }
+        Ok(())
+    };
+
+    state.write_str("{")?;
+    if !data.bracketted {
+        for row in &data.rows {
+            state.write_str(" ")?;
+            write_row(state, row)?;
+        }
+        return state.write_str(" }");
+    }
+
+    state.inc();
+    for row in &data.rows {
+        state.new_line()?;
+        state.check_comments(row.span())?;
+        state.write_str("(")?;
+        write_row(state, row)?;
+        state.write_str(")")?;
+    }
+    state.decr();
+    state.new_line()?;
+    state.write_str("}")
+}
+
+fn write_sub(state: &mut FormatState, sub: &GroupGraphPatternSub) -> io::Result<()> {
+    match sub {
+        GroupGraphPatternSub::Triple(triple) => state.write_triple(triple),
+        GroupGraphPatternSub::Optional(kwd, ggp) | GroupGraphPatternSub::Minus(kwd, ggp) => {
+            write_kwd(state, kwd)?;
+            state.write_str(" ")?;
+            write_ggp(state, ggp)
+        }
+        GroupGraphPatternSub::Filter(kwd, expr) => {
+            write_kwd(state, kwd)?;
+            state.write_str(" ")?;
+            write_expr(state, expr)
+        }
+        GroupGraphPatternSub::Union(first, rest) => {
+            write_ggp(state, first)?;
+            for (kwd, ggp) in rest {
+                state.write_str(" ")?;
+                write_kwd(state, kwd)?;
+                state.write_str(" ")?;
+                write_ggp(state, ggp)?;
+            }
+            Ok(())
+        }
+        GroupGraphPatternSub::Graph(kwd, name, ggp) => {
+            write_kwd(state, kwd)?;
+            state.write_str(" ")?;
+            state.write_term(name)?;
+            state.write_str(" ")?;
+            write_ggp(state, ggp)
+        }
+        GroupGraphPatternSub::Service(kwd, silent, name, ggp) => {
+            write_kwd(state, kwd)?;
+            if silent.is_some() {
+                state.write_str(" SILENT")?;
+            }
+            state.write_str(" ")?;
+            state.write_term(name)?;
+            state.write_str(" ")?;
+            write_ggp(state, ggp)
+        }
+        GroupGraphPatternSub::Bind(kwd, _, bind, _) => {
+            write_kwd(state, kwd)?;
+            state.write_str("(")?;
+            write_bind(state, bind)?;
+            state.write_str(")")
+        }
+        GroupGraphPatternSub::Values(data) => write_inline_data(state, data),
+    }
+}
+
+/// Writes `{`, every element on its own indented line, and `}`.
+fn write_block<T>(
+    state: &mut FormatState,
+    items: &[Spanned<T>],
+    mut write: impl FnMut(&mut FormatState, &T) -> io::Result<()>,
+) -> io::Result<()> {
+    if items.is_empty() {
+        return state.write_str("{ }");
+    }
+
+    state.write_str("{")?;
+    state.inc();
+    for item in items {
+        state.new_line()?;
+        state.check_comments(item.span())?;
+        write(state, item.value())?;
+    }
+    state.decr();
+    state.new_line()?;
+    state.write_str("}")
+}
+
+fn write_ggp(state: &mut FormatState, ggp: &GroupGraphPattern) -> io::Result<()> {
+    match ggp {
+        GroupGraphPattern::GroupGraph(subs) => write_block(state, subs, write_sub),
+        GroupGraphPattern::SubSelect(sub) => {
+            state.write_str("{")?;
+            state.inc();
+            state.new_line()?;
+            write_sub_select(state, sub)?;
+            state.decr();
+            state.new_line()?;
+            state.write_str("}")
+        }
+        GroupGraphPattern::Invalid => Err(invalid("invalid group graph patterns")),
+    }
+}
+
+fn write_select(state: &mut FormatState, select: &SelectClause) -> io::Result<()> {
+    write_kwd(state, &select.kwd)?;
+    if let Some(modifier) = &select.modifier {
+        state.write_str(" ")?;
+        write_kwd(state, modifier)?;
+    }
+    write_wrapped(state, &select.solutions, |state, solution| match solution {
+        Solution::All => state.write_str("*"),
+        Solution::Var(var) => state.write_str(&var.0),
+        Solution::VarAs(bind) => {
+            state.write_str("(")?;
+            write_bind(state, bind)?;
+            state.write_str(")")
+        }
+    })
+}
+
+fn write_where(state: &mut FormatState, where_clause: &WhereClause) -> io::Result<()> {
+    write_kwd(state, &SparqlKeyword::Where)?;
+    state.write_str(" ")?;
+    write_ggp(state, &where_clause.ggp)
+}
+
+fn write_modifier(state: &mut FormatState, modifier: &Modifier) -> io::Result<()> {
+    match modifier {
+        Modifier::GroupBy(_, _, conditions) => {
+            state.write_str("GROUP BY")?;
+            write_wrapped(state, conditions, |state, condition| match condition {
+                GroupCondition::Expression(expr) => write_expr(state, expr),
+                GroupCondition::Bind(bind) => {
+                    state.write_str("(")?;
+                    write_bind(state, bind)?;
+                    state.write_str(")")
+                }
+            })
+        }
+        Modifier::Having(kwd, constraints) => {
+            write_kwd(state, kwd)?;
+            write_wrapped(state, constraints, write_expr)
+        }
+        Modifier::OrderBy(_, _, conditions) => {
+            state.write_str("ORDER BY")?;
+            write_wrapped(state, conditions, |state, condition| {
+                if let Some(direction) = &condition.direction {
+                    write_kwd(state, direction)?;
+                }
+                write_expr(state, &condition.expr)
+            })
+        }
+        Modifier::LimitOffset(kwd, value) => {
+            write_kwd(state, kwd)?;
+            state.write_str(&format!(" {}", value.value()))
+        }
+    }
+}
+
+/// Writes the modifiers and VALUES block that follow a where clause, each on their own line.
+fn write_solution_modifiers(
+    state: &mut FormatState,
+    modifiers: &[Spanned<Modifier>],
+    values: Option<&Spanned<InlineData>>,
+) -> io::Result<()> {
+    for modifier in modifiers {
+        state.new_line()?;
+        state.check_comments(modifier.span())?;
+        write_modifier(state, modifier)?;
+    }
+    if let Some(values) = values {
+        state.new_line()?;
+        state.check_comments(values.span())?;
+        write_inline_data(state, values)?;
+    }
+    Ok(())
+}
+
+fn write_sub_select(state: &mut FormatState, sub: &SubSelect) -> io::Result<()> {
+    write_select(state, &sub.select)?;
+    state.new_line()?;
+    write_where(state, &sub.where_clause)?;
+    write_solution_modifiers(state, &sub.modifier, sub.values.as_ref())
+}
+
+fn write_construct(state: &mut FormatState, construct: &ConstructClause) -> io::Result<()> {
+    write_kwd(state, &construct.kwd)?;
+    if let Some((_, subs, _)) = &construct.template {
+        state.write_str(" ")?;
+        write_block(state, subs, write_sub)?;
+    }
+    Ok(())
+}
+
+fn write_describe(state: &mut FormatState, describe: &DescribeClause) -> io::Result<()> {
+    write_kwd(state, &describe.kwd)?;
+    write_wrapped(state, &describe.targets, |state, target| match target {
+        DescribeTarget::All => state.write_str("*"),
+        DescribeTarget::Var(var) => state.write_str(&var.0),
+        DescribeTarget::NamedNode(node) => state.write_str(&node.to_string()),
+    })
+}
+
+fn write_graph_target(state: &mut FormatState, target: &GraphTarget) -> io::Result<()> {
+    match target {
+        GraphTarget::Default => write_kwd(state, &SparqlKeyword::Default),
+        GraphTarget::Named => write_kwd(state, &SparqlKeyword::Named),
+        GraphTarget::All => write_kwd(state, &SparqlKeyword::All),
+        GraphTarget::Graph(node) => state.write_str(&format!("GRAPH {}", node.value())),
+    }
+}
+
+fn write_quads(state: &mut FormatState, quads: &Quads) -> io::Result<()> {
+    write_block(state, quads, |state, quad| match quad {
+        QuadPattern::Triple(triple) => state.write_triple(triple),
+        QuadPattern::Graph(kwd, name, triples) => {
+            write_kwd(state, kwd)?;
+            state.write_str(" ")?;
+            state.write_term(name)?;
+            state.write_str(" ")?;
+            write_block(state, triples, |state, triple| state.write_triple(triple))
+        }
+    })
+}
+
+fn write_modify(state: &mut FormatState, modify: &Modify) -> io::Result<()> {
+    if let Some((kwd, iri)) = &modify.with {
+        write_kwd(state, kwd)?;
+        state.write_str(&format!(" {}", iri.value()))?;
+        state.new_line()?;
+    }
+    for (kwd, quads) in modify.delete.iter().chain(modify.insert.iter()) {
+        write_kwd(state, kwd)?;
+        state.write_str(" ")?;
+        write_quads(state, quads)?;
+        state.new_line()?;
+    }
+    for using in &modify.using {
+        write_kwd(state, &using.from)?;
+        if let Some(named) = &using.named {
+            state.write_str(" ")?;
+            write_kwd(state, named)?;
+        }
+        state.write_str(&format!(" {}", using.iri.value()))?;
+        state.new_line()?;
+    }
+    write_where(state, &modify.where_clause)
+}
+
+fn write_update(state: &mut FormatState, update: &Update) -> io::Result<()> {
+    match update {
+        Update::Load {
+            kwd,
+            silent,
+            iri,
+            into,
+        } => {
+            write_kwd(state, kwd)?;
+            if silent.is_some() {
+                state.write_str(" SILENT")?;
+            }
+            state.write_str(&format!(" {}", iri.value()))?;
+            if let Some((kwd, target)) = into {
+                state.write_str(" ")?;
+                write_kwd(state, kwd)?;
+                state.write_str(" ")?;
+                write_graph_target(state, target)?;
+            }
+            Ok(())
+        }
+        Update::Graph {
+            kwd,
+            silent,
+            target,
+        } => {
+            write_kwd(state, kwd)?;
+            if silent.is_some() {
+                state.write_str(" SILENT")?;
+            }
+            state.write_str(" ")?;
+            write_graph_target(state, target)
+        }
+        Update::Transfer {
+            kwd,
+            silent,
+            from,
+            to,
+            target,
+        } => {
+            write_kwd(state, kwd)?;
+            if silent.is_some() {
+                state.write_str(" SILENT")?;
+            }
+            state.write_str(" ")?;
+            write_graph_target(state, from)?;
+            state.write_str(" ")?;
+            write_kwd(state, to)?;
+            state.write_str(" ")?;
+            write_graph_target(state, target)
+        }
+        Update::Quads { kwd, second, quads } => {
+            write_kwd(state, kwd)?;
+            state.write_str(" ")?;
+            write_kwd(state, second)?;
+            state.write_str(" ")?;
+            write_quads(state, quads)
+        }
+        Update::Modify(modify) => write_modify(state, modify),
+    }
+}
+
+fn write_query(state: &mut FormatState, query: &Query) -> io::Result<()> {
+    if let Some(base) = &query.base_statement {
+        state.check_comments(base.span())?;
+        state.write_str(&format!("BASE {}", base.iri.value()))?;
+        state.new_line()?;
+    }
+    for prefix in &query.prefixes {
+        state.check_comments(prefix.span())?;
+        state.write_str(&format!(
+            "PREFIX {}: {}",
+            prefix.prefix.value(),
+            prefix.value.value()
+        ))?;
+        state.new_line()?;
+    }
+    if query.base_statement.is_some() || !query.prefixes.is_empty() {
+        state.new_line()?;
+    }
+
+    match &query.kwds {
+        QueryClause::Select(select) => write_select(state, select)?,
+        QueryClause::Construct(construct) => write_construct(state, construct)?,
+        QueryClause::Describe(describe) => write_describe(state, describe)?,
+        QueryClause::Ask(kwd) => write_kwd(state, kwd)?,
+        QueryClause::Update(updates) => {
+            for (i, update) in updates.iter().enumerate() {
+                if i > 0 {
+                    state.write_str(";")?;
+                    state.new_line()?;
+                }
+                state.check_comments(update.span())?;
+                write_update(state, update)?;
+            }
+        }
+        QueryClause::Invalid => return Err(invalid("an invalid query form")),
+    }
+
+    for dataset in &query.datasets {
+        state.new_line()?;
+        state.check_comments(dataset.span())?;
+        write_kwd(state, &dataset.from)?;
+        if let Some(named) = &dataset.named {
+            state.write_str(" ")?;
+            write_kwd(state, named)?;
+        }
+        state.write_str(&format!(" {}", dataset.iri.value()))?;
+    }
+
+    if let Some(where_clause) = &query.where_clause {
+        state.new_line()?;
+        state.check_comments(where_clause.span())?;
+        write_where(state, where_clause)?;
+    }
+
+    write_solution_modifiers(state, &query.modifier, query.values.as_ref())?;
+    state.new_line()?;
+    state.write_tail()
+}
+
+pub fn format_sparql(
+    query: &Query,
+    config: FormattingOptions,
+    comments: &[Spanned<String>],
+    source: &Rope,
+) -> Option<String> {
+    let tabs = !config.insert_spaces;
+    let mut state = FormatState::new(config, comments, source).with_variables();
+    if tabs {
+        state = state.with_tabs();
+    }
+    match write_query(&mut state, query) {
+        Ok(_) => info!("Format succesful"),
+        Err(e) => {
+            info!("Format unsuccesful {:?}", e);
+            return None;
+        }
+    }
+    state.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use lang_turtle::lang::context::Context;
+    use ropey::Rope;
+
+    use super::format_sparql;
+    use crate::lang::{parsing::parse, tokenizer::parse_tokens_str};
+
+    fn format(inp: &str) -> String {
+        format_with(
+            inp,
+            lsp_types::FormattingOptions {
+                tab_size: 2,
+                insert_spaces: true,
+                ..Default::default()
+            },
+        )
+    }
+
+    fn format_with(inp: &str, options: lsp_types::FormattingOptions) -> String {
+        let context = Context::new();
+        let (tokens, _) = parse_tokens_str(inp);
+        let comments: Vec<_> = tokens
+            .iter()
+            .filter(|x| x.is_comment())
+            .cloned()
+            .map(|x| lsp_core::prelude::spanned(x.0.to_comment(), x.1))
+            .collect();
+        let (query, errors) = parse(
+            inp,
+            lsp_types::Url::parse("memory::myFile.sq").unwrap(),
+            tokens,
+            context.ctx(),
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        format_sparql(&query.0, options, &comments, &Rope::from_str(inp)).expect("formatted")
+    }
+
+    #[test]
+    fn format_uses_the_indentation_of_the_client() {
+        let txt = "select * where { ?x ?p ?o }";
+        let options = |tab_size, insert_spaces| lsp_types::FormattingOptions {
+            tab_size,
+            insert_spaces,
+            ..Default::default()
+        };
+
+        assert!(format_with(txt, options(4, true)).contains("\n    ?x ?p ?o."));
+        assert!(format_with(txt, options(4, false)).contains("\n\t?x ?p ?o."));
+    }
+
+    #[test]
+    fn format_select() {
+        let txt = "prefix foaf: <http://xmlns.com/foaf/0.1/>
+select distinct ?name (count(?x) as ?c) where { ?x foaf:name ?name.
+# only people with mail
+optional { ?x foaf:mbox ?mbox } filter(bound(?mbox) && ?name != \"\") }
+group by ?name order by desc(?c) limit 10";
+
+        let expected = "PREFIX foaf: <http://xmlns.com/foaf/0.1/>
+
+SELECT DISTINCT ?name (COUNT(?x) AS ?c)
+WHERE {
+  ?x foaf:name ?name.
+  # only people with mail
+  OPTIONAL {
+    ?x foaf:mbox ?mbox.
+  }
+  FILTER (BOUND(?mbox) && ?name != \"\")
+}
+GROUP BY ?name
+ORDER BY DESC(?c)
+LIMIT 10
+
+";
+        assert_eq!(format(txt), expected);
+    }
+
+    #[test]
+    fn format_keeps_separator_quotes() {
+        let txt = "select (group_concat(?n; separator='\"') as ?ns) where { ?x ?p ?n }";
+
+        let expected = "SELECT (GROUP_CONCAT(?n; SEPARATOR = '\"') AS ?ns)
+WHERE {
+  ?x ?p ?n.
+}
+
+";
+        assert_eq!(format(txt), expected);
+    }
+
+    #[test]
+    fn format_long_projection() {
+        let txt = "SELECT ?aaaaaaaaaaaa ?bbbbbbbbbbbbbbb ?cccccccccccccccc ?dddddddddddddddd ?eeeeeeeeeeeeeeeeee WHERE { ?aaaaaaaaaaaa ?bbbbbbbbbbbbbbb ?cccccccccccccccc. ?dddddddddddddddd ?eeeeeeeeeeeeeeeeee ?cccccccccccccccc }";
+
+        let expected = "SELECT
+  ?aaaaaaaaaaaa
+  ?bbbbbbbbbbbbbbb
+  ?cccccccccccccccc
+  ?dddddddddddddddd
+  ?eeeeeeeeeeeeeeeeee
+WHERE {
+  ?aaaaaaaaaaaa ?bbbbbbbbbbbbbbb ?cccccccccccccccc.
+  ?dddddddddddddddd ?eeeeeeeeeeeeeeeeee ?cccccccccccccccc.
+}
+
+";
+        assert_eq!(format(txt), expected);
+    }
+}
@@ -1,3 +1,5 @@
+pub mod eval;
+pub mod formatter;
 pub mod model;
 pub mod parsing;
 pub mod tokenizer;
@@ -1,8 +1,16 @@
+use std::ops::RangeInclusive;
+
 use lang_turtle::lang::{
     context::Context,
-    model::{Based, NamedNode, Triple, TriplesBuilder, TurtlePrefix, TurtleSimpleError},
+    model::{
+        BlankNode, Based, Literal, NamedNode, Term, Triple, TriplesBuilder, TurtlePrefix,
+        TurtleSimpleError, Variable as TurtleVariable, PO,
+    },
+};
+use lsp_core::prelude::{
+    Membered as _, Spanned, SparqlAggregate, SparqlCall, SparqlExpr, SparqlKeyword, StringStyle,
+    Token,
 };
-use lsp_core::prelude::{Spanned, SparqlKeyword, Token};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Base {
@@ -62,6 +70,23 @@ impl GroupGraphPattern {
         }
         Ok(())
     }
+
+    /// Variables that are in-scope after this pattern, see section 18.2.1 of SPARQL 1.1.
+    pub fn in_scope(&self) -> Vec<Spanned<&str>> {
+        match self {
+            GroupGraphPattern::SubSelect(sub_select) => sub_select.projected(),
+            GroupGraphPattern::GroupGraph(xs) => xs.iter().flat_map(|x| x.in_scope()).collect(),
+            GroupGraphPattern::Invalid => Vec::new(),
+        }
+    }
+
+    fn visit<'a>(&'a self, f: &mut dyn FnMut(Visit<'a>)) {
+        match self {
+            GroupGraphPattern::SubSelect(sub_select) => sub_select.visit(f),
+            GroupGraphPattern::GroupGraph(xs) => xs.iter().for_each(|x| x.visit(f)),
+            GroupGraphPattern::Invalid => {}
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -81,13 +106,210 @@ impl WhereClause {
     fn add_to_context(&self, ctx: &mut Context) {
         self.ggp.value().add_to_context(ctx);
     }
+
+    fn visit<'a>(&'a self, f: &mut dyn FnMut(Visit<'a>)) {
+        self.ggp.visit(f);
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Variable(pub String);
 
+impl Variable {
+    /// Name of the variable without the leading '?' or '$', `?x` and `$x` are the same variable.
+    pub fn name(&self) -> &str {
+        variable_name(&self.0)
+    }
+}
+
+pub fn variable_name(var: &str) -> &str {
+    var.get(1..).unwrap_or_default()
+}
+
+/// Parts of a [`Query`] that are found while walking it with [`Query::visit`].
+#[derive(Clone, Debug)]
+pub enum Visit<'a> {
+    Variable(Spanned<&'a str>),
+    Expression(&'a Spanned<Expression>),
+}
+
+/// The function that is called in a [`Expression::Call`].
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub struct Expression {}
+pub enum Function {
+    Call(SparqlCall),
+    /// `REGEX`, `SUBSTR` and `REPLACE` are keywords
+    Keyword(SparqlKeyword),
+    /// Custom function, `iri(args)`
+    Iri(NamedNode),
+}
+impl std::fmt::Display for Function {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Function::Call(call) => write!(f, "{}", call.complete()),
+            Function::Keyword(kwd) => write!(f, "{}", kwd.complete()),
+            Function::Iri(iri) => write!(f, "{}", iri),
+        }
+    }
+}
+impl Function {
+    /// Number of arguments the built-in function accepts, `None` for custom functions.
+    pub fn arity(&self) -> Option<RangeInclusive<usize>> {
+        use SparqlCall::*;
+        let out = match self {
+            Function::Call(Rand | Now | Uuid | StrUuid) => 0..=0,
+            Function::Call(Bnode) => 0..=1,
+            Function::Call(Coalesce | Concat) => 0..=usize::MAX,
+            Function::Call(
+                LangMatches | Contains | StrStarts | StrEnds | StrBefore | StrAfter | StrLang
+                | StrDt | SameTerm,
+            ) => 2..=2,
+            Function::Call(If | StrLangDir | SparqlCall::Triple) => 3..=3,
+            Function::Call(_) => 1..=1,
+            Function::Keyword(SparqlKeyword::Replace) => 3..=4,
+            Function::Keyword(_) => 2..=3,
+            Function::Iri(_) => return None,
+        };
+        Some(out)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expression {
+    Variable(Variable),
+    NamedNode(NamedNode),
+    Literal(Literal),
+    /// Expression between '(' and ')'
+    Bracketted(Box<Spanned<Expression>>),
+    Unary(Spanned<SparqlExpr>, Box<Spanned<Expression>>),
+    Binary(
+        Box<Spanned<Expression>>,
+        Spanned<SparqlExpr>,
+        Box<Spanned<Expression>>,
+    ),
+    /// `expr IN (..)` and `expr NOT IN (..)`
+    In {
+        expr: Box<Spanned<Expression>>,
+        not: Option<Spanned<SparqlExpr>>,
+        list: Spanned<Vec<Spanned<Expression>>>,
+    },
+    /// Built-in or custom function call, `args` spans the brackets.
+    Call {
+        function: Spanned<Function>,
+        distinct: Option<Spanned<SparqlKeyword>>,
+        args: Spanned<Vec<Spanned<Expression>>>,
+    },
+    /// Aggregate, `args` spans the brackets and is empty for `COUNT(*)`.
+    Aggregate {
+        name: Spanned<SparqlAggregate>,
+        distinct: Option<Spanned<SparqlKeyword>>,
+        star: bool,
+        args: Spanned<Vec<Spanned<Expression>>>,
+        /// Separator of `GROUP_CONCAT`, as written with its quotes.
+        separator: Option<Spanned<(String, StringStyle)>>,
+    },
+    /// `EXISTS { }` and `NOT EXISTS { }`
+    Exists {
+        not: Option<Spanned<SparqlExpr>>,
+        kwd: Spanned<SparqlKeyword>,
+        ggp: Box<Spanned<GroupGraphPattern>>,
+    },
+    Invalid,
+}
+
+impl Expression {
+    /// Direct sub expressions, patterns of `EXISTS` are not included.
+    pub fn children(&self) -> Vec<&Spanned<Expression>> {
+        match self {
+            Expression::Bracketted(x) | Expression::Unary(_, x) => vec![x.as_ref()],
+            Expression::Binary(a, _, b) => vec![a.as_ref(), b.as_ref()],
+            Expression::In { expr, list, .. } => {
+                let mut out = vec![expr.as_ref()];
+                out.extend(list.iter());
+                out
+            }
+            Expression::Call { args, .. } | Expression::Aggregate { args, .. } => {
+                args.iter().collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Calls `f` for this expression and all nested expressions.
+    pub fn walk<'a>(this: &'a Spanned<Expression>, f: &mut impl FnMut(&'a Spanned<Expression>)) {
+        f(this);
+        for child in this.value().children() {
+            Expression::walk(child, f);
+        }
+    }
+
+    fn visit<'a>(this: &'a Spanned<Expression>, f: &mut dyn FnMut(Visit<'a>)) {
+        Expression::walk(this, &mut |expr| {
+            f(Visit::Expression(expr));
+            match expr.value() {
+                Expression::Variable(var) => {
+                    f(Visit::Variable(Spanned(&var.0, expr.span().clone())))
+                }
+                Expression::Exists { ggp, .. } => ggp.visit(f),
+                _ => {}
+            }
+        });
+    }
+
+    fn add_to_context(this: &Spanned<Expression>, ctx: &mut Context) {
+        Expression::walk(this, &mut |expr| {
+            if let Expression::Exists { ggp, .. } = expr.value() {
+                ggp.add_to_context(ctx);
+            }
+        });
+    }
+
+    fn ingest_triples<'a>(
+        this: &'a Spanned<Expression>,
+        builder: &mut TriplesBuilder<'a, Query>,
+    ) -> Result<(), TurtleSimpleError> {
+        let mut patterns = Vec::new();
+        Expression::walk(this, &mut |expr| {
+            if let Expression::Exists { ggp, .. } = expr.value() {
+                patterns.push(ggp.as_ref());
+            }
+        });
+        for ggp in patterns {
+            ggp.ingest_triples(builder)?;
+        }
+        Ok(())
+    }
+}
+
+fn term_visit<'a>(term: &'a Spanned<Term>, f: &mut dyn FnMut(Visit<'a>)) {
+    match term.value() {
+        Term::Variable(TurtleVariable(var, _)) => {
+            f(Visit::Variable(Spanned(var, term.span().clone())))
+        }
+        Term::BlankNode(BlankNode::Unnamed(pos, _, _)) => po_visit(pos, f),
+        Term::Collection(terms) => terms.iter().for_each(|t| term_visit(t, f)),
+        Term::Quoted(quoted) => {
+            term_visit(&quoted.subject, f);
+            term_visit(&quoted.predicate, f);
+            term_visit(&quoted.object, f);
+        }
+        _ => {}
+    }
+}
+
+fn po_visit<'a>(pos: &'a [Spanned<PO>], f: &mut dyn FnMut(Visit<'a>)) {
+    for po in pos {
+        term_visit(&po.predicate, f);
+        po.object.iter().for_each(|o| term_visit(o, f));
+        for annotation in &po.annotations {
+            po_visit(&annotation.po, f);
+        }
+    }
+}
+
+fn triple_visit<'a>(triple: &'a Triple, f: &mut dyn FnMut(Visit<'a>)) {
+    term_visit(&triple.subject, f);
+    po_visit(&triple.po, f);
+}
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Bind {
@@ -95,6 +317,12 @@ pub struct Bind {
     pub kwd: Spanned<SparqlKeyword>,
     pub var: Spanned<Variable>,
 }
+impl Bind {
+    fn visit<'a>(&'a self, f: &mut dyn FnMut(Visit<'a>)) {
+        Expression::visit(&self.expr, f);
+        f(Visit::Variable(Spanned(&self.var.0 .0, self.var.span().clone())));
+    }
+}
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Solution {
@@ -109,41 +337,330 @@ pub struct SelectClause {
     pub modifier: Option<Spanned<SparqlKeyword>>,
     pub solutions: Vec<Spanned<Solution>>,
 }
+impl SelectClause {
+    /// Projected variables, `None` for `SELECT *`.
+    pub fn projected(&self) -> Option<Vec<Spanned<&str>>> {
+        let mut out = Vec::new();
+        for solution in &self.solutions {
+            match solution.value() {
+                Solution::All => return None,
+                Solution::Var(var) => out.push(Spanned(var.0.as_str(), solution.span().clone())),
+                Solution::VarAs(bind) => {
+                    out.push(Spanned(bind.var.0 .0.as_str(), bind.var.span().clone()))
+                }
+            }
+        }
+        Some(out)
+    }
+
+    fn visit<'a>(&'a self, f: &mut dyn FnMut(Visit<'a>)) {
+        for solution in &self.solutions {
+            match solution.value() {
+                Solution::All => {}
+                Solution::Var(var) => {
+                    f(Visit::Variable(Spanned(&var.0, solution.span().clone())))
+                }
+                Solution::VarAs(bind) => bind.visit(f),
+            }
+        }
+    }
+}
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ConstructClause {
-    kwd: Spanned<SparqlKeyword>,
-    template: Option<(
+    pub kwd: Spanned<SparqlKeyword>,
+    /// The construct template, `None` for the short `CONSTRUCT WHERE { }` form where the
+    /// where clause is the template.
+    pub template: Option<(
         Spanned<Token>,
         Vec<Spanned<GroupGraphPatternSub>>,
         Spanned<Token>,
     )>,
 }
+impl ConstructClause {
+    fn add_to_context(&self, ctx: &mut Context) {
+        if let Some((_, subs, _)) = &self.template {
+            for s in subs {
+                s.value().add_to_context(ctx);
+            }
+        }
+    }
+
+    pub fn ingest_triples<'a>(
+        &'a self,
+        builder: &mut TriplesBuilder<'a, Query>,
+    ) -> Result<(), TurtleSimpleError> {
+        if let Some((_, subs, _)) = &self.template {
+            for s in subs {
+                s.ingest_triples(builder)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn visit<'a>(&'a self, f: &mut dyn FnMut(Visit<'a>)) {
+        if let Some((_, subs, _)) = &self.template {
+            subs.iter().for_each(|s| s.visit(f));
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DescribeTarget {
+    All,
+    Var(Variable),
+    NamedNode(NamedNode),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DescribeClause {
+    pub kwd: Spanned<SparqlKeyword>,
+    pub targets: Vec<Spanned<DescribeTarget>>,
+}
+impl DescribeClause {
+    fn visit<'a>(&'a self, f: &mut dyn FnMut(Visit<'a>)) {
+        for target in &self.targets {
+            if let DescribeTarget::Var(var) = target.value() {
+                f(Visit::Variable(Spanned(&var.0, target.span().clone())));
+            }
+        }
+    }
+}
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum QueryClause {
     Select(SelectClause),
     Construct(ConstructClause),
+    Describe(DescribeClause),
+    Ask(Spanned<SparqlKeyword>),
+    /// An update request, operations are separated by ';'.
+    Update(Vec<Spanned<Update>>),
     Invalid,
 }
+impl QueryClause {
+    fn add_to_context(&self, ctx: &mut Context) {
+        match self {
+            QueryClause::Construct(construct) => construct.add_to_context(ctx),
+            QueryClause::Update(updates) => {
+                for update in updates {
+                    update.add_to_context(ctx);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn ingest_triples<'a>(
+        &'a self,
+        builder: &mut TriplesBuilder<'a, Query>,
+    ) -> Result<(), TurtleSimpleError> {
+        match self {
+            QueryClause::Construct(construct) => construct.ingest_triples(builder),
+            QueryClause::Update(updates) => {
+                for update in updates {
+                    update.ingest_triples(builder)?;
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn visit<'a>(&'a self, f: &mut dyn FnMut(Visit<'a>)) {
+        match self {
+            QueryClause::Select(select) => select.visit(f),
+            QueryClause::Construct(construct) => construct.visit(f),
+            QueryClause::Describe(describe) => describe.visit(f),
+            QueryClause::Update(updates) => updates.iter().for_each(|x| x.visit(f)),
+            QueryClause::Ask(_) | QueryClause::Invalid => {}
+        }
+    }
+}
+
+/// The graph an update operation works on, `GraphRefAll` and `GraphOrDefault` in the grammar.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GraphTarget {
+    Default,
+    Named,
+    All,
+    Graph(Spanned<NamedNode>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum QuadPattern {
+    Triple(Spanned<Triple>),
+    Graph(Spanned<SparqlKeyword>, Spanned<Term>, Vec<Spanned<Triple>>),
+}
+impl QuadPattern {
+    fn add_to_context(&self, ctx: &mut Context) {
+        match self {
+            QuadPattern::Triple(t) => t.value().set_context(ctx),
+            QuadPattern::Graph(_, _, ts) => {
+                for t in ts {
+                    t.value().set_context(ctx);
+                }
+            }
+        }
+    }
+
+    pub fn ingest_triples<'a>(
+        &'a self,
+        builder: &mut TriplesBuilder<'a, Query>,
+    ) -> Result<(), TurtleSimpleError> {
+        match self {
+            QuadPattern::Triple(triple) => builder.ingest(triple),
+            QuadPattern::Graph(_, name, triples) => builder.ingest_graph(Some(name), triples),
+        }
+    }
+
+    fn visit<'a>(&'a self, f: &mut dyn FnMut(Visit<'a>)) {
+        match self {
+            QuadPattern::Triple(t) => triple_visit(t, f),
+            QuadPattern::Graph(_, name, ts) => {
+                term_visit(name, f);
+                ts.iter().for_each(|t| triple_visit(t, f));
+            }
+        }
+    }
+}
+
+/// Quads between '{' and '}', used as data, template or pattern.
+pub type Quads = Spanned<Vec<Spanned<QuadPattern>>>;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Modify {
+    pub with: Option<(Spanned<SparqlKeyword>, Spanned<NamedNode>)>,
+    pub delete: Option<(Spanned<SparqlKeyword>, Quads)>,
+    pub insert: Option<(Spanned<SparqlKeyword>, Quads)>,
+    pub using: Vec<Spanned<DatasetClause>>,
+    pub where_clause: Spanned<WhereClause>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Update {
+    /// `LOAD SILENT? iri (INTO GRAPH iri)?`
+    Load {
+        kwd: Spanned<SparqlKeyword>,
+        silent: Option<Spanned<SparqlKeyword>>,
+        iri: Spanned<NamedNode>,
+        into: Option<(Spanned<SparqlKeyword>, Spanned<GraphTarget>)>,
+    },
+    /// `CLEAR`, `DROP` and `CREATE`
+    Graph {
+        kwd: Spanned<SparqlKeyword>,
+        silent: Option<Spanned<SparqlKeyword>>,
+        target: Spanned<GraphTarget>,
+    },
+    /// `ADD`, `MOVE` and `COPY`
+    Transfer {
+        kwd: Spanned<SparqlKeyword>,
+        silent: Option<Spanned<SparqlKeyword>>,
+        from: Spanned<GraphTarget>,
+        to: Spanned<SparqlKeyword>,
+        target: Spanned<GraphTarget>,
+    },
+    /// `INSERT DATA`, `DELETE DATA` and `DELETE WHERE`
+    Quads {
+        kwd: Spanned<SparqlKeyword>,
+        second: Spanned<SparqlKeyword>,
+        quads: Quads,
+    },
+    Modify(Modify),
+}
+impl Update {
+    fn add_to_context(&self, ctx: &mut Context) {
+        match self {
+            Update::Quads { quads, .. } => {
+                for q in &quads.0 {
+                    q.add_to_context(ctx);
+                }
+            }
+            Update::Modify(modify) => {
+                let quads = modify.delete.iter().chain(modify.insert.iter());
+                for q in quads.flat_map(|(_, qs)| qs.iter()) {
+                    q.add_to_context(ctx);
+                }
+                modify.where_clause.add_to_context(ctx);
+            }
+            _ => {}
+        }
+    }
+
+    pub fn ingest_triples<'a>(
+        &'a self,
+        builder: &mut TriplesBuilder<'a, Query>,
+    ) -> Result<(), TurtleSimpleError> {
+        match self {
+            Update::Quads { quads, .. } => {
+                for q in &quads.0 {
+                    q.ingest_triples(builder)?;
+                }
+            }
+            Update::Modify(modify) => {
+                let quads = modify.delete.iter().chain(modify.insert.iter());
+                for q in quads.flat_map(|(_, qs)| qs.iter()) {
+                    q.ingest_triples(builder)?;
+                }
+                modify.where_clause.ingest_triples(builder)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn visit<'a>(&'a self, f: &mut dyn FnMut(Visit<'a>)) {
+        match self {
+            Update::Quads { quads, .. } => quads.iter().for_each(|q| q.visit(f)),
+            Update::Modify(modify) => {
+                let quads = modify.delete.iter().chain(modify.insert.iter());
+                quads
+                    .flat_map(|(_, qs)| qs.iter())
+                    .for_each(|q| q.visit(f));
+                modify.where_clause.visit(f);
+            }
+            _ => {}
+        }
+    }
+}
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SubSelect {
     pub select: SelectClause,
     pub where_clause: WhereClause,
     pub modifier: Vec<Spanned<Modifier>>,
-    // TODO values
+    pub values: Option<Spanned<InlineData>>,
 }
 impl SubSelect {
+    /// Variables that are in-scope outside of the sub select.
+    pub fn projected(&self) -> Vec<Spanned<&str>> {
+        self.select
+            .projected()
+            .unwrap_or_else(|| self.where_clause.ggp.in_scope())
+    }
+
     fn add_to_context(&self, ctx: &mut Context) {
         self.where_clause.add_to_context(ctx);
+        self.modifier.iter().for_each(|m| m.add_to_context(ctx));
     }
 
     pub fn ingest_triples<'a>(
         &'a self,
         builder: &mut TriplesBuilder<'a, Query>,
     ) -> Result<(), TurtleSimpleError> {
-        self.where_clause.ingest_triples(builder)
+        self.where_clause.ingest_triples(builder)?;
+        for modifier in &self.modifier {
+            modifier.ingest_triples(builder)?;
+        }
+        Ok(())
+    }
+
+    fn visit<'a>(&'a self, f: &mut dyn FnMut(Visit<'a>)) {
+        self.select.visit(f);
+        self.where_clause.visit(f);
+        self.modifier.iter().for_each(|m| m.visit(f));
+        if let Some(values) = &self.values {
+            values.visit(f);
+        }
     }
 }
 
@@ -153,16 +670,24 @@ pub enum GraphPatternNotTriples {}
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum GroupGraphPatternSub {
     Triple(Spanned<Triple>),
-    Kwd(Spanned<SparqlKeyword>, Spanned<GroupGraphPattern>),
-    Filter(Spanned<SparqlKeyword>, ()),
+    Optional(Spanned<SparqlKeyword>, Spanned<GroupGraphPattern>),
+    Minus(Spanned<SparqlKeyword>, Spanned<GroupGraphPattern>),
+    Filter(Spanned<SparqlKeyword>, Spanned<Expression>),
     Union(
         Spanned<GroupGraphPattern>,
         Vec<(Spanned<SparqlKeyword>, Spanned<GroupGraphPattern>)>,
     ),
-    GraphOrService(
+    /// `GRAPH varOrIri { }`
+    Graph(
+        Spanned<SparqlKeyword>,
+        Spanned<Term>,
+        Spanned<GroupGraphPattern>,
+    ),
+    /// `SERVICE SILENT? varOrIri { }`
+    Service(
         Spanned<SparqlKeyword>,
         Option<Spanned<SparqlKeyword>>,
-        Spanned<NamedNode>,
+        Spanned<Term>,
         Spanned<GroupGraphPattern>,
     ),
     Bind(
@@ -171,21 +696,76 @@ pub enum GroupGraphPatternSub {
         Spanned<Bind>,
         Spanned<Token>,
     ),
-    Inline(Spanned<()>),
+    Values(InlineData),
 }
 impl GroupGraphPatternSub {
+    /// Variables that this element brings in scope, variables in FILTER and MINUS are not
+    /// bound.
+    pub fn in_scope(&self) -> Vec<Spanned<&str>> {
+        let mut out = Vec::new();
+        let mut add = |x| {
+            if let Visit::Variable(var) = x {
+                out.push(var);
+            }
+        };
+        match self {
+            GroupGraphPatternSub::Triple(t) => triple_visit(t, &mut add),
+            GroupGraphPatternSub::Optional(_, ggp) => return ggp.in_scope(),
+            GroupGraphPatternSub::Union(x, xs) => {
+                let mut out = x.in_scope();
+                xs.iter().for_each(|(_, x)| out.extend(x.in_scope()));
+                return out;
+            }
+            GroupGraphPatternSub::Graph(_, name, ggp)
+            | GroupGraphPatternSub::Service(_, _, name, ggp) => {
+                term_visit(name, &mut add);
+                out.extend(ggp.in_scope());
+            }
+            GroupGraphPatternSub::Bind(_, _, bind, _) => {
+                out.push(Spanned(bind.var.0 .0.as_str(), bind.var.span().clone()))
+            }
+            GroupGraphPatternSub::Values(values) => values.visit(&mut add),
+            GroupGraphPatternSub::Minus(_, _) | GroupGraphPatternSub::Filter(_, _) => {}
+        }
+        out
+    }
+
+    /// Nested group graph patterns, patterns inside expressions are not included.
+    pub fn patterns(&self) -> Vec<&Spanned<GroupGraphPattern>> {
+        match self {
+            GroupGraphPatternSub::Optional(_, ggp)
+            | GroupGraphPatternSub::Minus(_, ggp)
+            | GroupGraphPatternSub::Graph(_, _, ggp)
+            | GroupGraphPatternSub::Service(_, _, _, ggp) => vec![ggp],
+            GroupGraphPatternSub::Union(x, xs) => {
+                let mut out = vec![x];
+                out.extend(xs.iter().map(|(_, x)| x));
+                out
+            }
+            _ => Vec::new(),
+        }
+    }
+
     fn add_to_context(&self, ctx: &mut Context) {
         match self {
             GroupGraphPatternSub::Triple(t) => t.value().set_context(ctx),
-            GroupGraphPatternSub::Kwd(_, beta) => beta.add_to_context(ctx),
+            GroupGraphPatternSub::Optional(_, ggp) | GroupGraphPatternSub::Minus(_, ggp) => {
+                ggp.add_to_context(ctx)
+            }
             GroupGraphPatternSub::Union(a, b) => {
                 a.add_to_context(ctx);
                 for (_, b) in b {
                     b.add_to_context(ctx);
                 }
             }
-            GroupGraphPatternSub::GraphOrService(_, _, _, a) => a.add_to_context(ctx),
-            _ => {}
+            GroupGraphPatternSub::Graph(_, _, ggp) | GroupGraphPatternSub::Service(_, _, _, ggp) => {
+                ggp.add_to_context(ctx)
+            }
+            GroupGraphPatternSub::Filter(_, expr) => Expression::add_to_context(expr, ctx),
+            GroupGraphPatternSub::Bind(_, _, bind, _) => {
+                Expression::add_to_context(&bind.expr, ctx)
+            }
+            GroupGraphPatternSub::Values(_) => {}
         }
     }
 
@@ -195,27 +775,158 @@ impl GroupGraphPatternSub {
     ) -> Result<(), TurtleSimpleError> {
         match self {
             GroupGraphPatternSub::Triple(triple) => builder.ingest(&triple)?,
-            GroupGraphPatternSub::Kwd(_, x) => x.ingest_triples(builder)?,
+            GroupGraphPatternSub::Optional(_, x) | GroupGraphPatternSub::Minus(_, x) => {
+                x.ingest_triples(builder)?
+            }
             GroupGraphPatternSub::Union(x, xs) => {
                 x.ingest_triples(builder)?;
                 for (_, x) in xs {
                     x.ingest_triples(builder)?;
                 }
             }
-            GroupGraphPatternSub::GraphOrService(_, _, _, x) => x.ingest_triples(builder)?,
-            _ => {}
+            GroupGraphPatternSub::Graph(_, _, x) | GroupGraphPatternSub::Service(_, _, _, x) => {
+                x.ingest_triples(builder)?
+            }
+            GroupGraphPatternSub::Filter(_, expr) => Expression::ingest_triples(expr, builder)?,
+            GroupGraphPatternSub::Bind(_, _, bind, _) => {
+                Expression::ingest_triples(&bind.expr, builder)?
+            }
+            GroupGraphPatternSub::Values(_) => {}
         }
         Ok(())
     }
+
+    fn visit<'a>(&'a self, f: &mut dyn FnMut(Visit<'a>)) {
+        match self {
+            GroupGraphPatternSub::Triple(t) => triple_visit(t, f),
+            GroupGraphPatternSub::Optional(_, x) | GroupGraphPatternSub::Minus(_, x) => x.visit(f),
+            GroupGraphPatternSub::Union(x, xs) => {
+                x.visit(f);
+                xs.iter().for_each(|(_, x)| x.visit(f));
+            }
+            GroupGraphPatternSub::Graph(_, name, x) | GroupGraphPatternSub::Service(_, _, name, x) => {
+                term_visit(name, f);
+                x.visit(f);
+            }
+            GroupGraphPatternSub::Filter(_, expr) => Expression::visit(expr, f),
+            GroupGraphPatternSub::Bind(_, _, bind, _) => bind.visit(f),
+            GroupGraphPatternSub::Values(values) => values.visit(f),
+        }
+    }
+}
+
+/// Value in a row of a VALUES block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DataValue {
+    /// IRI or literal
+    Term(Term),
+    Undef,
+}
+
+/// Inline data, `VALUES ?x { 1 2 }` or `VALUES (?x ?y) { (1 2) (UNDEF 3) }`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InlineData {
+    pub kwd: Spanned<SparqlKeyword>,
+    pub vars: Vec<Spanned<Variable>>,
+    /// Variables and rows are written between brackets, always the case with multiple
+    /// variables.
+    pub bracketted: bool,
+    pub rows: Vec<Spanned<Vec<Spanned<DataValue>>>>,
+}
+impl InlineData {
+    fn visit<'a>(&'a self, f: &mut dyn FnMut(Visit<'a>)) {
+        for var in &self.vars {
+            f(Visit::Variable(Spanned(&var.0, var.span().clone())));
+        }
+    }
+}
+
+/// Condition of a GROUP BY clause.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GroupCondition {
+    /// Variable, function call or bracketted expression
+    Expression(Spanned<Expression>),
+    /// `(expr AS ?var)`
+    Bind(Spanned<Bind>),
+}
+impl GroupCondition {
+    fn visit<'a>(&'a self, f: &mut dyn FnMut(Visit<'a>)) {
+        match self {
+            GroupCondition::Expression(expr) => Expression::visit(expr, f),
+            GroupCondition::Bind(bind) => bind.visit(f),
+        }
+    }
+
+    fn expression(&self) -> &Spanned<Expression> {
+        match self {
+            GroupCondition::Expression(expr) => expr,
+            GroupCondition::Bind(bind) => &bind.expr,
+        }
+    }
+}
+
+/// Condition of an ORDER BY clause, `ASC(..)`, `DESC(..)` or a plain expression.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OrderCondition {
+    pub direction: Option<Spanned<SparqlKeyword>>,
+    pub expr: Spanned<Expression>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Modifier {
-    GroupBy(Spanned<SparqlKeyword>, Spanned<SparqlKeyword>, Spanned<()>),
-    Having(Spanned<SparqlKeyword>, Spanned<()>),
-    OrderBy(Spanned<SparqlKeyword>, Spanned<SparqlKeyword>, Spanned<()>),
+    GroupBy(
+        Spanned<SparqlKeyword>,
+        Spanned<SparqlKeyword>,
+        Vec<Spanned<GroupCondition>>,
+    ),
+    Having(Spanned<SparqlKeyword>, Vec<Spanned<Expression>>),
+    OrderBy(
+        Spanned<SparqlKeyword>,
+        Spanned<SparqlKeyword>,
+        Vec<Spanned<OrderCondition>>,
+    ),
     LimitOffset(Spanned<SparqlKeyword>, Spanned<String>),
 }
+impl Modifier {
+    fn add_to_context(&self, ctx: &mut Context) {
+        self.expressions()
+            .into_iter()
+            .for_each(|expr| Expression::add_to_context(expr, ctx));
+    }
+
+    fn ingest_triples<'a>(
+        &'a self,
+        builder: &mut TriplesBuilder<'a, Query>,
+    ) -> Result<(), TurtleSimpleError> {
+        for expr in self.expressions() {
+            Expression::ingest_triples(expr, builder)?;
+        }
+        Ok(())
+    }
+
+    /// The top level expressions of this modifier.
+    fn expressions(&self) -> Vec<&Spanned<Expression>> {
+        match self {
+            Modifier::GroupBy(_, _, conditions) => {
+                conditions.iter().map(|c| c.expression()).collect()
+            }
+            Modifier::Having(_, exprs) => exprs.iter().collect(),
+            Modifier::OrderBy(_, _, conditions) => conditions.iter().map(|c| &c.expr).collect(),
+            Modifier::LimitOffset(_, _) => Vec::new(),
+        }
+    }
+
+    fn visit<'a>(&'a self, f: &mut dyn FnMut(Visit<'a>)) {
+        match self {
+            Modifier::GroupBy(_, _, conditions) => conditions.iter().for_each(|c| c.visit(f)),
+            Modifier::Having(_, exprs) => exprs.iter().for_each(|e| Expression::visit(e, f)),
+            Modifier::OrderBy(_, _, conditions) => conditions
+                .iter()
+                .for_each(|c| Expression::visit(&c.expr, f)),
+            Modifier::LimitOffset(_, _) => {}
+        }
+    }
+}
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Query {
@@ -224,22 +935,68 @@ pub struct Query {
     pub base_statement: Option<Spanned<Base>>,
     pub kwds: QueryClause,
     pub datasets: Vec<Spanned<DatasetClause>>,
-    pub where_clause: Spanned<WhereClause>,
+    /// Only optional for DESCRIBE queries.
+    pub where_clause: Option<Spanned<WhereClause>>,
     pub modifier: Vec<Spanned<Modifier>>,
+    pub values: Option<Spanned<InlineData>>,
 }
 impl Query {
     pub fn add_to_context(&self, ctx: &mut Context) {
-        self.where_clause.add_to_context(ctx);
+        self.kwds.add_to_context(ctx);
+        if let Some(where_clause) = &self.where_clause {
+            where_clause.add_to_context(ctx);
+        }
+        self.modifier.iter().for_each(|m| m.add_to_context(ctx));
     }
 
     pub fn ingest_triples<'a>(
         &'a self,
         builder: &mut TriplesBuilder<'a, Query>,
     ) -> Result<(), TurtleSimpleError> {
-        self.where_clause.ingest_triples(builder)?;
+        self.kwds.ingest_triples(builder)?;
+        if let Some(where_clause) = &self.where_clause {
+            where_clause.ingest_triples(builder)?;
+        }
+        for modifier in &self.modifier {
+            modifier.ingest_triples(builder)?;
+        }
         Ok(())
     }
 
+    /// All variable occurrences in the query, in order of appearance.
+    pub fn variables(&self) -> Vec<Spanned<&str>> {
+        let mut out = Vec::new();
+        self.visit(&mut |x| {
+            if let Visit::Variable(var) = x {
+                out.push(var);
+            }
+        });
+        out
+    }
+
+    /// All expressions in the query, nested expressions included.
+    pub fn expressions(&self) -> Vec<&Spanned<Expression>> {
+        let mut out = Vec::new();
+        self.visit(&mut |x| {
+            if let Visit::Expression(expr) = x {
+                out.push(expr);
+            }
+        });
+        out
+    }
+
+    /// Walks the query in order of appearance, see [`Visit`].
+    pub fn visit<'a>(&'a self, f: &mut dyn FnMut(Visit<'a>)) {
+        self.kwds.visit(f);
+        if let Some(where_clause) = &self.where_clause {
+            where_clause.visit(f);
+        }
+        self.modifier.iter().for_each(|m| m.visit(f));
+        if let Some(values) = &self.values {
+            values.visit(f);
+        }
+    }
+
     pub fn set_base(&mut self, base: lsp_types::Url) {
         self.base = self
             .base_statement
@@ -275,14 +1032,15 @@ impl Default for Query {
             prefixes: vec![],
             kwds: QueryClause::Invalid,
             datasets: vec![],
-            where_clause: Spanned(
+            where_clause: Some(Spanned(
                 WhereClause {
                     kwd: None,
                     ggp: Spanned(GroupGraphPattern::Invalid, 0..0),
                 },
                 0..0,
-            ),
+            )),
             modifier: vec![],
+            values: None,
         }
     }
 }
@@ -371,4 +1129,111 @@ SELECT  ?title ?price
 
         assert_eq!(builder.triples.len(), 4);
     }
+
+    #[test]
+    fn construct_template_triples() {
+        let st = r#"
+PREFIX  ns:  <http://example.org/ns#>
+
+CONSTRUCT { ?x ns:cost ?p . ?x ns:currency "EUR" . }
+WHERE { ?x ns:price ?p . }
+        "#;
+
+        let query = parse_sparql(st);
+        let base = BaseIri::new(query.base.to_string()).unwrap();
+        let mut builder = TriplesBuilder::new(&query, base);
+        query.ingest_triples(&mut builder).expect("builds fine");
+
+        assert_eq!(builder.triples.len(), 3);
+    }
+
+    #[test]
+    fn update_quads_are_triples() {
+        let st = r#"
+PREFIX  ex:  <http://example.org/>
+INSERT DATA { ex:a ex:p ex:b . GRAPH ex:g { ex:a ex:q ex:c } } ;
+DELETE { ?s ex:p ?o } WHERE { ?s ex:r ?o }
+        "#;
+
+        let query = parse_sparql(st);
+        let base = BaseIri::new(query.base.to_string()).unwrap();
+        let mut builder = TriplesBuilder::new(&query, base);
+        query.ingest_triples(&mut builder).expect("builds fine");
+
+        assert_eq!(builder.triples.len(), 4);
+        let graphs: Vec<_> = builder
+            .triples
+            .iter()
+            .map(|q| q.graph.as_ref().map(|g| g.as_str()))
+            .collect();
+        assert_eq!(graphs[1], Some("http://example.org/g"));
+    }
+
+    #[test]
+    fn expression_variables_and_exists_triples() {
+        let st = r#"
+PREFIX  ns:  <http://example.org/ns#>
+
+SELECT ?x (SUM(?p * 2) AS ?total)
+WHERE {
+  ?x ns:price ?p .
+  FILTER (?p > 10 && $x NOT IN (ns:a, ns:b))
+  FILTER NOT EXISTS { ?x ns:discount ?d }
+  BIND (STRLEN(?x) AS ?len)
+}
+        "#;
+
+        let query = parse_sparql(st);
+        let names: Vec<_> = query
+            .variables()
+            .into_iter()
+            .map(|x| variable_name(x.value()))
+            .collect();
+        assert_eq!(names.iter().filter(|x| **x == "x").count(), 5);
+        assert!(names.contains(&"total"));
+        assert!(names.contains(&"len"));
+        assert!(names.contains(&"d"));
+
+        let calls = query
+            .expressions()
+            .into_iter()
+            .filter(|x| matches!(x.value(), Expression::Call { .. }))
+            .count();
+        assert_eq!(calls, 1);
+
+        let base = BaseIri::new(query.base.to_string()).unwrap();
+        let mut builder = TriplesBuilder::new(&query, base);
+        query.ingest_triples(&mut builder).expect("builds fine");
+
+        assert_eq!(builder.triples.len(), 2);
+    }
+
+    #[test]
+    fn property_path_iris_are_predicates() {
+        let st = r#"
+PREFIX  ex:  <http://example.org/>
+
+SELECT * WHERE { ?x ex:a/ex:b ?y . ?y ^ex:c|ex:d* ?z . }
+        "#;
+
+        let query = parse_sparql(st);
+        let base = BaseIri::new(query.base.to_string()).unwrap();
+        let mut builder = TriplesBuilder::new(&query, base);
+        query.ingest_triples(&mut builder).expect("builds fine");
+
+        let triples: Vec<_> = builder
+            .triples
+            .iter()
+            .map(|q| (q.subject.as_str(), q.predicate.as_str(), q.object.as_str()))
+            .collect();
+
+        assert_eq!(triples.len(), 4);
+        assert_eq!(triples[0].0, "?x");
+        assert_eq!(triples[0].1, "http://example.org/a");
+        assert_eq!(triples[0].2, triples[1].0);
+        assert_eq!(triples[1].1, "http://example.org/b");
+        assert_eq!(triples[1].2, "?y");
+        assert_eq!(triples[2], ("?z", "http://example.org/c", "?y"));
+        assert_eq!(triples[3], ("?y", "http://example.org/d", "?z"));
+    }
 }
@@ -1,16 +1,22 @@
 use chumsky::{prelude::*, Error};
 use lang_turtle::lang::{
     context::Ctx,
-    model::TurtlePrefix,
-    parser::{named_node, not, triple},
+    model::{Literal, Term, Triple, TurtlePrefix, Variable as TurtleVariable},
+    parser::{expect_token, literal, named_node, not, triple, triple_with_stop},
+};
+use lsp_core::prelude::{
+    spanned, Membered as _, PToken, Spanned, SparqlAggregate, SparqlExpr, SparqlKeyword, Token,
 };
-use lsp_core::prelude::{spanned, PToken, Spanned, SparqlExpr, SparqlKeyword, Token};
 
 use crate::lang::model::{
-    Base, Bind, DatasetClause, Expression, GroupGraphPattern, GroupGraphPatternSub, Modifier,
-    Prologue, Query, QueryClause, SelectClause, Solution, SubSelect, Variable, WhereClause,
+    Base, Bind, ConstructClause, DataValue, DatasetClause, DescribeClause, DescribeTarget,
+    Expression, Function, GraphTarget, GroupCondition, GroupGraphPattern, GroupGraphPatternSub,
+    InlineData, Modifier, Modify, OrderCondition, Prologue, QuadPattern, Quads, Query, QueryClause,
+    SelectClause, Solution, SubSelect, Update, Variable, WhereClause,
 };
 
+type S = std::ops::Range<usize>;
+
 fn j(token: Token) -> impl Parser<PToken, Token, Error = Simple<PToken>> + Clone {
     just(PToken(token, 0)).map(|x| x.0)
 }
@@ -53,14 +59,258 @@ fn dataset_clause() -> impl Parser<PToken, DatasetClause, Error = Simple<PToken>
         .map(|((from, named), iri)| DatasetClause { from, named, iri })
 }
 
-fn expression() -> impl Parser<PToken, Expression, Error = Simple<PToken>> + Clone {
-    todo()
+fn op(
+    expr: SparqlExpr,
+) -> impl Parser<PToken, Spanned<SparqlExpr>, Error = Simple<PToken>> + Clone {
+    j(Token::SparqlExpr(expr.clone()))
+        .to(expr)
+        .map_with_span(spanned)
 }
 
-// DONE
-fn bind() -> impl Parser<PToken, Bind, Error = Simple<PToken>> + Clone {
-    sparql_kwd(SparqlKeyword::Bind)
-        .ignore_then(expression())
+fn binary(
+    (a, op, b): (
+        Spanned<Expression>,
+        Spanned<SparqlExpr>,
+        Spanned<Expression>,
+    ),
+) -> Spanned<Expression> {
+    let span = a.span().start..b.span().end;
+    Spanned(Expression::Binary(Box::new(a), op, Box::new(b)), span)
+}
+
+/// Numbers like `-1` are a single token, in `?x -1` they are the operator and the operand.
+fn signed_number(
+) -> impl Parser<PToken, (Spanned<SparqlExpr>, Spanned<Expression>), Error = Simple<PToken>> + Clone
+{
+    filter_map(|span: S, PToken(t, idx)| match t {
+        Token::Number(x) if x.starts_with('+') || x.starts_with('-') => {
+            let op = if x.starts_with('-') {
+                SparqlExpr::Minus
+            } else {
+                SparqlExpr::Plus
+            };
+            let literal = Expression::Literal(Literal::Numeric(x[1..].to_string()));
+            Ok((
+                Spanned(op, span.start..span.start + 1),
+                Spanned(literal, span.start + 1..span.end),
+            ))
+        }
+        t => Err(Simple::expected_input_found(
+            span,
+            None,
+            Some(PToken(t, idx)),
+        )),
+    })
+}
+
+fn expression<G: Parser<PToken, GroupGraphPattern, Error = Simple<PToken>> + Clone>(
+    ggp: G,
+) -> impl Parser<PToken, Expression, Error = Simple<PToken>> + Clone + use<G> {
+    recursive(|expr| {
+        let expr = expr.map_with_span(spanned);
+        let open = j(Token::BracketOpen);
+        let close = expect_it(Token::BracketClose, "close");
+        let distinct = sparql_kwd(SparqlKeyword::Distinct).or_not();
+
+        let args = open
+            .clone()
+            .ignore_then(expr.clone().separated_by(j(Token::Comma)))
+            .then_ignore(close.clone())
+            .map_with_span(spanned);
+        let distinct_args = open
+            .clone()
+            .ignore_then(distinct.clone())
+            .then(expr.clone().separated_by(j(Token::Comma)))
+            .then_ignore(close.clone())
+            .map_with_span(|(distinct, args), span| (distinct, Spanned(args, span)));
+
+        let bracketted = expr
+            .clone()
+            .delimited_by(open.clone(), close.clone())
+            .map(|x| Expression::Bracketted(Box::new(x)));
+
+        let builtin = select! {
+            PToken(Token::SparqlCall(call), _) => Function::Call(call),
+            PToken(Token::SparqlKeyword(SparqlKeyword::Regex), _) => Function::Keyword(SparqlKeyword::Regex),
+            PToken(Token::SparqlKeyword(SparqlKeyword::Substr), _) => Function::Keyword(SparqlKeyword::Substr),
+            PToken(Token::SparqlKeyword(SparqlKeyword::Replace), _) => Function::Keyword(SparqlKeyword::Replace),
+        }
+        .map_with_span(spanned)
+        .then(args.clone())
+        .map(|(function, args)| Expression::Call {
+            function,
+            distinct: None,
+            args,
+        });
+
+        let function = named_node()
+            .map_with_span(spanned)
+            .then(distinct_args.or_not())
+            .map(|(Spanned(iri, span), args)| match args {
+                Some((distinct, args)) => Expression::Call {
+                    function: Spanned(Function::Iri(iri), span),
+                    distinct,
+                    args,
+                },
+                None => Expression::NamedNode(iri),
+            });
+
+        let separator = j(Token::PredicateSplit)
+            .ignore_then(sparql_kwd(SparqlKeyword::Separator))
+            .ignore_then(op(SparqlExpr::Equal))
+            .ignore_then(
+                select! { PToken(Token::Str(x, style), _) => (x, style) }.map_with_span(spanned),
+            );
+        let aggregate = select! { PToken(Token::SparqlAggregate(x), _) => x }
+            .map_with_span(spanned)
+            .then(
+                open.clone()
+                    .ignore_then(distinct)
+                    .then(op(SparqlExpr::Times).to(None).or(expr.clone().map(Some)))
+                    .then(separator.or_not())
+                    .then_ignore(close.clone())
+                    .map_with_span(spanned),
+            )
+            .validate(
+                |(name, Spanned(((distinct, arg), separator), span)), _, emit| {
+                    if arg.is_none() && name.value() != &SparqlAggregate::Count {
+                        emit(Simple::custom(
+                            span.clone(),
+                            format!("Only COUNT accepts '*', not {}", name.complete()),
+                        ));
+                    }
+                    if separator.is_some() && name.value() != &SparqlAggregate::GroupConcat {
+                        emit(Simple::custom(
+                            span.clone(),
+                            format!(
+                                "Only GROUP_CONCAT accepts a separator, not {}",
+                                name.complete()
+                            ),
+                        ));
+                    }
+                    Expression::Aggregate {
+                        name,
+                        distinct,
+                        star: arg.is_none(),
+                        args: Spanned(arg.into_iter().collect(), span),
+                        separator,
+                    }
+                },
+            );
+
+        let exists = op(SparqlExpr::Not)
+            .or_not()
+            .then(sparql_kwd(SparqlKeyword::Exists))
+            .then(ggp.map_with_span(spanned))
+            .map(|((not, kwd), ggp)| Expression::Exists {
+                not,
+                kwd,
+                ggp: Box::new(ggp),
+            });
+
+        let primary = bracketted
+            .or(builtin)
+            .or(aggregate)
+            .or(exists)
+            .or(function)
+            .or(literal().map(Expression::Literal))
+            .or(variable().map(Expression::Variable))
+            .map_with_span(spanned)
+            .labelled("expression");
+
+        let unary = op(SparqlExpr::Exclamation)
+            .or(op(SparqlExpr::Plus))
+            .or(op(SparqlExpr::Minus))
+            .then(primary.clone())
+            .map_with_span(|(op, x), span| Spanned(Expression::Unary(op, Box::new(x)), span))
+            .or(primary);
+
+        let multiplicative = unary
+            .clone()
+            .then(
+                op(SparqlExpr::Times)
+                    .or(op(SparqlExpr::Divide))
+                    .then(unary)
+                    .repeated(),
+            )
+            .foldl(|a, (op, b)| binary((a, op, b)));
+
+        let additive = multiplicative
+            .clone()
+            .then(
+                op(SparqlExpr::Plus)
+                    .or(op(SparqlExpr::Minus))
+                    .then(multiplicative)
+                    .or(signed_number())
+                    .repeated(),
+            )
+            .foldl(|a, (op, b)| binary((a, op, b)));
+
+        let compare = op(SparqlExpr::Equal)
+            .or(op(SparqlExpr::NotEqual))
+            .or(op(SparqlExpr::Lte))
+            .or(op(SparqlExpr::Gte))
+            .or(op(SparqlExpr::Lt))
+            .or(op(SparqlExpr::Gt))
+            .then(additive.clone())
+            .map(Ok);
+        let in_list = op(SparqlExpr::Not)
+            .or_not()
+            .then_ignore(op(SparqlExpr::In))
+            .then(args)
+            .map(Err);
+        let relational = additive
+            .then(compare.or(in_list).or_not())
+            .map(|(a, rest)| match rest {
+                Some(Ok((op, b))) => binary((a, op, b)),
+                Some(Err((not, list))) => {
+                    let span = a.span().start..list.span().end;
+                    let expr = Expression::In {
+                        expr: Box::new(a),
+                        not,
+                        list,
+                    };
+                    Spanned(expr, span)
+                }
+                None => a,
+            });
+
+        let and = relational
+            .clone()
+            .then(op(SparqlExpr::And).then(relational).repeated())
+            .foldl(|a, (op, b)| binary((a, op, b)));
+
+        and.clone()
+            .then(op(SparqlExpr::Or).then(and).repeated())
+            .foldl(|a, (op, b)| binary((a, op, b)))
+            .map(|Spanned(x, _)| x)
+    })
+}
+
+/// Constraint of a FILTER, a bracketted expression or a function call.
+fn constraint<G: Parser<PToken, GroupGraphPattern, Error = Simple<PToken>> + Clone>(
+    ggp: G,
+) -> impl Parser<PToken, Expression, Error = Simple<PToken>> + Clone + use<G> {
+    expression(ggp).validate(|expr, span, emit| {
+        match &expr {
+            Expression::Bracketted(_)
+            | Expression::Call { .. }
+            | Expression::Exists { .. }
+            | Expression::Invalid => {}
+            _ => emit(Simple::custom(
+                span,
+                "Expected a bracketted expression or a function call",
+            )),
+        }
+        expr
+    })
+}
+
+/// `expression AS ?var`, used in BIND and in the SELECT clause.
+fn bind<G: Parser<PToken, GroupGraphPattern, Error = Simple<PToken>> + Clone>(
+    ggp: G,
+) -> impl Parser<PToken, Bind, Error = Simple<PToken>> + Clone + use<G> {
+    expression(ggp)
         .map_with_span(spanned)
         .then(sparql_kwd(SparqlKeyword::As))
         .then(variable().map_with_span(spanned))
@@ -74,21 +324,24 @@ fn variable() -> impl Parser<PToken, Variable, Error = Simple<PToken>> + Clone {
 }
 
 // DONE
-fn select_clause() -> impl Parser<PToken, SelectClause, Error = Simple<PToken>> + Clone {
+fn select_clause<G: Parser<PToken, GroupGraphPattern, Error = Simple<PToken>> + Clone>(
+    ggp: G,
+) -> impl Parser<PToken, SelectClause, Error = Simple<PToken>> + Clone + use<G> {
     let star = j(Token::SparqlExpr(SparqlExpr::Times))
         .to(Solution::All)
         .map_with_span(spanned)
         .map(|x| vec![x]);
 
-    let others = bind()
-        .delimited_by(j(Token::CurlOpen), j(Token::CurlClose))
+    let others = bind(ggp)
+        .delimited_by(
+            j(Token::BracketOpen),
+            expect_it(Token::BracketClose, "close"),
+        )
         .map(Solution::VarAs)
         .or(variable().map(Solution::Var))
         .map_with_span(spanned)
         .repeated();
 
-    // This might be different to support ask and describe
-
     sparql_kwd(SparqlKeyword::Select)
         .then(
             sparql_kwd(SparqlKeyword::Distinct)
@@ -103,19 +356,72 @@ fn select_clause() -> impl Parser<PToken, SelectClause, Error = Simple<PToken>>
         })
 }
 
+/// Triples in a template, the last triple doesn't need a '.' before the closing '}'.
+fn template_triple<'a>(
+    ctx: Ctx<'a>,
+) -> impl Parser<PToken, Triple, Error = Simple<PToken>> + Clone + use<'a> {
+    let stop = j(Token::Stop)
+        .ignored()
+        .or(j(Token::CurlClose).rewind().ignored())
+        .or(expect_token(Token::Stop, |_| true).ignored());
+
+    triple_with_stop(ctx, stop)
+}
+
+fn construct_clause<'a>(
+    ctx: Ctx<'a>,
+) -> impl Parser<PToken, ConstructClause, Error = Simple<PToken>> + Clone + use<'a> {
+    let template = j(Token::CurlOpen)
+        .map_with_span(spanned)
+        .then(
+            template_triple(ctx)
+                .map_with_span(spanned)
+                .map(GroupGraphPatternSub::Triple)
+                .map_with_span(spanned)
+                .repeated(),
+        )
+        .then(expect_it(Token::CurlClose, "close").map_with_span(spanned))
+        .map(|((open, triples), close)| (open, triples, close));
+
+    sparql_kwd(SparqlKeyword::Construct)
+        .then(template.or_not())
+        .map(|(kwd, template)| ConstructClause { kwd, template })
+}
+
+fn describe_clause() -> impl Parser<PToken, DescribeClause, Error = Simple<PToken>> + Clone {
+    let star = j(Token::SparqlExpr(SparqlExpr::Times))
+        .to(DescribeTarget::All)
+        .map_with_span(spanned)
+        .map(|x| vec![x]);
+
+    let others = variable()
+        .map(DescribeTarget::Var)
+        .or(named_node().map(DescribeTarget::NamedNode))
+        .map_with_span(spanned)
+        .repeated()
+        .at_least(1);
+
+    sparql_kwd(SparqlKeyword::Describe)
+        .then(star.or(others))
+        .map(|(kwd, targets)| DescribeClause { kwd, targets })
+}
+
 // DONE
 fn sub_select<'a>(
     ctx: Ctx<'a>,
 ) -> impl Parser<PToken, SubSelect, Error = Simple<PToken>> + Clone + use<'a> {
     recursive(|sub_select| {
-        let modi = modifier().map_with_span(spanned).repeated();
-        select_clause()
+        let ggp = group_graph_pattern(sub_select.clone(), ctx);
+        let modi = modifier(ggp.clone()).map_with_span(spanned).repeated();
+        select_clause(ggp)
             .then(where_clause(sub_select, ctx))
             .then(modi)
-            .map(|((select, where_clause), modifier)| SubSelect {
+            .then(inline_data().map_with_span(spanned).or_not())
+            .map(|(((select, where_clause), modifier), values)| SubSelect {
                 modifier,
                 where_clause,
                 select,
+                values,
             })
     })
 }
@@ -135,12 +441,30 @@ fn group_graph_pattern_sub<
         .map(GroupGraphPatternSub::Triple)
         .labelled("triple");
 
-    let kwd = sparql_kwd(SparqlKeyword::Minus)
-        .or(sparql_kwd(SparqlKeyword::Optional))
-        .clone()
-        .then_ignore(j(Token::CurlOpen).rewind())
-        .then(ggp.clone().map_with_span(spanned))
-        .map(|(kwd, ggp)| GroupGraphPatternSub::Kwd(kwd, ggp));
+    let nested = j(Token::CurlOpen)
+        .rewind()
+        .ignore_then(ggp.clone().map_with_span(spanned));
+
+    let optional = sparql_kwd(SparqlKeyword::Optional)
+        .then(nested.clone())
+        .map(|(kwd, ggp)| GroupGraphPatternSub::Optional(kwd, ggp));
+
+    let minus = sparql_kwd(SparqlKeyword::Minus)
+        .then(nested.clone())
+        .map(|(kwd, ggp)| GroupGraphPatternSub::Minus(kwd, ggp));
+
+    let graph = sparql_kwd(SparqlKeyword::Graph)
+        .then(var_or_iri().map_with_span(spanned))
+        .then(nested.clone())
+        .map(|((kwd, name), ggp)| GroupGraphPatternSub::Graph(kwd, name, ggp));
+
+    let service = sparql_kwd(SparqlKeyword::Service)
+        .then(sparql_kwd(SparqlKeyword::Silent).or_not())
+        .then(var_or_iri().map_with_span(spanned))
+        .then(nested)
+        .map(|(((kwd, silent), name), ggp)| GroupGraphPatternSub::Service(kwd, silent, name, ggp));
+
+    let values = inline_data().map(GroupGraphPatternSub::Values);
 
     let union = j(Token::CurlOpen)
         .rewind()
@@ -148,15 +472,32 @@ fn group_graph_pattern_sub<
         .map_with_span(spanned)
         .then(
             sparql_kwd(SparqlKeyword::Union)
-                .then(ggp.map_with_span(spanned))
+                .then(ggp.clone().map_with_span(spanned))
                 .repeated(),
         )
         .map(|(start, rest)| GroupGraphPatternSub::Union(start, rest));
 
-    // TODO add the others
-    //
+    let filter = sparql_kwd(SparqlKeyword::Filter)
+        .then(constraint(ggp.clone()).map_with_span(spanned))
+        .map(|(kwd, expr)| GroupGraphPatternSub::Filter(kwd, expr));
+
+    let bind = sparql_kwd(SparqlKeyword::Bind)
+        .then(j(Token::BracketOpen).map_with_span(spanned))
+        .then(bind(ggp).map_with_span(spanned))
+        .then(expect_it(Token::BracketClose, "close").map_with_span(spanned))
+        .map(|(((kwd, open), bind), close)| GroupGraphPatternSub::Bind(kwd, open, bind, close));
 
-    next_check.ignore_then(trip.or(kwd).or(union).labelled("group_graph_pattern_sub"))
+    next_check.ignore_then(
+        trip.or(optional)
+            .or(minus)
+            .or(graph)
+            .or(service)
+            .or(union)
+            .or(filter)
+            .or(bind)
+            .or(values)
+            .labelled("group_graph_pattern_sub"),
+    )
 }
 
 fn expect_it(
@@ -212,17 +553,268 @@ fn where_clause<'a, T: Parser<PToken, SubSelect, Error = Simple<PToken>> + Clone
 }
 
 // DONE
-fn modifier() -> impl Parser<PToken, Modifier, Error = Simple<PToken>> + Clone {
+fn modifier<G: Parser<PToken, GroupGraphPattern, Error = Simple<PToken>> + Clone>(
+    ggp: G,
+) -> impl Parser<PToken, Modifier, Error = Simple<PToken>> + Clone + use<G> {
     let num = select!(
         PToken(Token::Number(x), _) => x,
     )
     .map_with_span(spanned);
+    let expr = expression(ggp.clone()).map_with_span(spanned);
+
+    let group_condition = bind(ggp.clone())
+        .delimited_by(
+            j(Token::BracketOpen),
+            expect_it(Token::BracketClose, "close"),
+        )
+        .map_with_span(spanned)
+        .map(GroupCondition::Bind)
+        .or(expr.clone().map(GroupCondition::Expression))
+        .map_with_span(spanned);
+    let group_by = sparql_kwd(SparqlKeyword::Group)
+        .then(sparql_kwd(SparqlKeyword::By))
+        .then(group_condition.repeated().at_least(1))
+        .map(|((group, by), conditions)| Modifier::GroupBy(group, by, conditions));
+
+    let having = sparql_kwd(SparqlKeyword::Having)
+        .then(
+            constraint(ggp)
+                .map_with_span(spanned)
+                .repeated()
+                .at_least(1),
+        )
+        .map(|(kwd, constraints)| Modifier::Having(kwd, constraints));
+
+    let order_condition = sparql_kwd(SparqlKeyword::Asc)
+        .or(sparql_kwd(SparqlKeyword::Desc))
+        .or_not()
+        .then(expr)
+        .validate(|(direction, expr), span, emit| {
+            if direction.is_some() && !matches!(expr.value(), Expression::Bracketted(_)) {
+                emit(Simple::custom(
+                    span,
+                    "Expected a bracketted expression after ASC or DESC",
+                ));
+            }
+            OrderCondition { direction, expr }
+        })
+        .map_with_span(spanned);
+    let order_by = sparql_kwd(SparqlKeyword::Order)
+        .then(sparql_kwd(SparqlKeyword::By))
+        .then(order_condition.repeated().at_least(1))
+        .map(|((order, by), conditions)| Modifier::OrderBy(order, by, conditions));
 
     let limit_offset = sparql_kwd(SparqlKeyword::Limit)
         .or(sparql_kwd(SparqlKeyword::Offset))
         .then(num)
         .map(|(kwd, num)| Modifier::LimitOffset(kwd, num));
-    limit_offset
+
+    group_by.or(having).or(order_by).or(limit_offset)
+}
+
+fn var_or_iri() -> impl Parser<PToken, Term, Error = Simple<PToken>> + Clone {
+    named_node().map(Term::NamedNode).or(select! {
+        PToken(Token::Variable(x), idx) => Term::Variable(TurtleVariable(x, idx)),
+    })
+}
+
+/// VALUES block, with a single variable or a bracketted list of variables.
+fn inline_data() -> impl Parser<PToken, InlineData, Error = Simple<PToken>> + Clone {
+    let value = sparql_kwd(SparqlKeyword::Undef)
+        .to(DataValue::Undef)
+        .or(named_node().map(|x| DataValue::Term(Term::NamedNode(x))))
+        .or(literal().map(|x| DataValue::Term(Term::Literal(x))))
+        .map_with_span(spanned);
+    let var = variable().map_with_span(spanned);
+    let open = j(Token::BracketOpen);
+    let close = expect_it(Token::BracketClose, "close");
+
+    let single = var
+        .clone()
+        .then(
+            value
+                .clone()
+                .map(|x| {
+                    let span = x.span().clone();
+                    Spanned(vec![x], span)
+                })
+                .repeated()
+                .delimited_by(j(Token::CurlOpen), expect_it(Token::CurlClose, "close")),
+        )
+        .map(|(var, rows)| (vec![var], false, rows));
+
+    let multi = var
+        .repeated()
+        .delimited_by(open.clone(), close.clone())
+        .then(
+            value
+                .repeated()
+                .delimited_by(open, close)
+                .map_with_span(spanned)
+                .repeated()
+                .delimited_by(j(Token::CurlOpen), expect_it(Token::CurlClose, "close")),
+        )
+        .map(|(vars, rows)| (vars, true, rows));
+
+    sparql_kwd(SparqlKeyword::Values)
+        .then(single.or(multi))
+        .validate(|(kwd, (vars, bracketted, rows)), _, emit| {
+            for row in &rows {
+                if row.len() != vars.len() {
+                    emit(Simple::custom(
+                        row.span().clone(),
+                        format!("Expected {} values, found {}", vars.len(), row.len()),
+                    ));
+                }
+            }
+            InlineData {
+                kwd,
+                vars,
+                bracketted,
+                rows,
+            }
+        })
+}
+
+fn graph_target() -> impl Parser<PToken, GraphTarget, Error = Simple<PToken>> + Clone {
+    let graph = sparql_kwd(SparqlKeyword::Graph)
+        .or_not()
+        .ignore_then(named_node().map_with_span(spanned))
+        .map(GraphTarget::Graph);
+
+    sparql_kwd(SparqlKeyword::Default)
+        .to(GraphTarget::Default)
+        .or(sparql_kwd(SparqlKeyword::Named).to(GraphTarget::Named))
+        .or(sparql_kwd(SparqlKeyword::All).to(GraphTarget::All))
+        .or(graph)
+}
+
+fn quads<'a>(ctx: Ctx<'a>) -> impl Parser<PToken, Quads, Error = Simple<PToken>> + Clone + use<'a> {
+    let graph = sparql_kwd(SparqlKeyword::Graph)
+        .then(var_or_iri().map_with_span(spanned))
+        .then(
+            template_triple(ctx)
+                .map_with_span(spanned)
+                .repeated()
+                .delimited_by(j(Token::CurlOpen), expect_it(Token::CurlClose, "close")),
+        )
+        .map(|((kwd, name), triples)| QuadPattern::Graph(kwd, name, triples));
+
+    let triple = template_triple(ctx)
+        .map_with_span(spanned)
+        .map(QuadPattern::Triple);
+
+    graph
+        .or(triple)
+        .map_with_span(spanned)
+        .repeated()
+        .delimited_by(j(Token::CurlOpen), expect_it(Token::CurlClose, "close"))
+        .map_with_span(spanned)
+}
+
+fn update_operation<'a>(
+    ctx: Ctx<'a>,
+) -> impl Parser<PToken, Update, Error = Simple<PToken>> + Clone + use<'a> {
+    let silent = sparql_kwd(SparqlKeyword::Silent).or_not();
+    let target = graph_target().map_with_span(spanned);
+
+    let load = sparql_kwd(SparqlKeyword::Load)
+        .then(silent.clone())
+        .then(named_node().map_with_span(spanned))
+        .then(
+            sparql_kwd(SparqlKeyword::Into)
+                .then(target.clone())
+                .or_not(),
+        )
+        .map(|(((kwd, silent), iri), into)| Update::Load {
+            kwd,
+            silent,
+            iri,
+            into,
+        });
+
+    let graph = sparql_kwd(SparqlKeyword::Clear)
+        .or(sparql_kwd(SparqlKeyword::Drop))
+        .or(sparql_kwd(SparqlKeyword::Create))
+        .then(silent.clone())
+        .then(target.clone())
+        .map(|((kwd, silent), target)| Update::Graph {
+            kwd,
+            silent,
+            target,
+        });
+
+    let transfer = sparql_kwd(SparqlKeyword::Add)
+        .or(sparql_kwd(SparqlKeyword::Move))
+        .or(sparql_kwd(SparqlKeyword::Copy))
+        .then(silent)
+        .then(target.clone())
+        .then(sparql_kwd(SparqlKeyword::To))
+        .then(target)
+        .map(|((((kwd, silent), from), to), target)| Update::Transfer {
+            kwd,
+            silent,
+            from,
+            to,
+            target,
+        });
+
+    let data = sparql_kwd(SparqlKeyword::Insert)
+        .or(sparql_kwd(SparqlKeyword::Delete))
+        .then(sparql_kwd(SparqlKeyword::Data).or(sparql_kwd(SparqlKeyword::Where)))
+        .then(quads(ctx))
+        .validate(|((kwd, second), quads), _, emit| {
+            if kwd.value() == &SparqlKeyword::Insert && second.value() == &SparqlKeyword::Where {
+                emit(Simple::custom(
+                    second.span().clone(),
+                    "INSERT WHERE is not allowed, use INSERT { } WHERE { }",
+                ));
+            }
+            Update::Quads { kwd, second, quads }
+        });
+
+    let with = sparql_kwd(SparqlKeyword::With)
+        .then(named_node().map_with_span(spanned))
+        .or_not();
+    let insert = sparql_kwd(SparqlKeyword::Insert).then(quads(ctx));
+    let delete_insert = sparql_kwd(SparqlKeyword::Delete)
+        .then(quads(ctx))
+        .then(insert.clone().or_not())
+        .map(|(delete, insert)| (Some(delete), insert))
+        .or(insert.map(|insert| (None, Some(insert))));
+    let using = sparql_kwd(SparqlKeyword::Using)
+        .then(sparql_kwd(SparqlKeyword::Named).or_not())
+        .then(named_node().map_with_span(spanned))
+        .map(|((from, named), iri)| DatasetClause { from, named, iri })
+        .map_with_span(spanned)
+        .repeated();
+
+    let modify = with
+        .then(delete_insert)
+        .then(using)
+        .then(where_clause(sub_select(ctx), ctx).map_with_span(spanned))
+        .map(|(((with, (delete, insert)), using), where_clause)| {
+            Update::Modify(Modify {
+                with,
+                delete,
+                insert,
+                using,
+                where_clause,
+            })
+        });
+
+    data.or(load).or(graph).or(transfer).or(modify)
+}
+
+fn add_prologue(
+    Spanned(prologue, span): Spanned<Prologue>,
+    base: &mut Option<Spanned<Base>>,
+    prefixes: &mut Vec<Spanned<TurtlePrefix>>,
+) {
+    match prologue {
+        Prologue::Base { token, iri } => *base = Some(Spanned(Base { token, iri }, span)),
+        Prologue::Prefix(prefix) => prefixes.push(Spanned(prefix, span)),
+    }
 }
 
 pub fn query<'a>(
@@ -232,53 +824,169 @@ pub fn query<'a>(
     let prologues = prologue().map_with_span(spanned).repeated().map(|xs| {
         let mut base = None;
         let mut prefixes = vec![];
-        xs.into_iter().for_each(|Spanned(x, span)| match x {
-            Prologue::Base { token, iri } => base = Some(Spanned(Base { token, iri }, span)),
-            Prologue::Prefix(prefix) => prefixes.push(Spanned(prefix, span)),
-        });
+        xs.into_iter()
+            .for_each(|x| add_prologue(x, &mut base, &mut prefixes));
         (base, prefixes)
     });
-    let kwds = select_clause().map(QueryClause::Select);
     let datasets = dataset_clause().map_with_span(spanned).repeated();
     let where_clause = where_clause(sub_select(ctx), ctx).map_with_span(spanned);
-    let modifiers = modifier().map_with_span(spanned).repeated();
+    let modifiers = modifier(group_graph_pattern(sub_select(ctx), ctx))
+        .map_with_span(spanned)
+        .repeated();
+    let values = inline_data().map_with_span(spanned).or_not();
 
-    prologues
-        .then(kwds)
+    let select = select_clause(group_graph_pattern(sub_select(ctx), ctx)).map(QueryClause::Select);
+    let construct = construct_clause(ctx).map(QueryClause::Construct);
+    let ask = sparql_kwd(SparqlKeyword::Ask).map(QueryClause::Ask);
+    let with_where = select
+        .or(construct)
+        .or(ask)
+        .then(datasets.clone())
+        .then(where_clause.clone().map(Some));
+
+    // The where clause is optional for DESCRIBE, `DESCRIBE <x>` is a complete query
+    let describe = describe_clause()
+        .map(QueryClause::Describe)
         .then(datasets)
-        .then(where_clause)
+        .then(where_clause.or_not());
+
+    prologues
+        .then(with_where.or(describe))
         .then(modifiers)
+        .then(values)
         .map(
-            move |(((((base_statement, prefixes), kwds), datasets), where_clause), modifier)| {
-                Query {
-                    base_statement,
-                    prefixes,
-                    base: base.clone(),
-                    modifier,
-                    where_clause,
-                    datasets,
-                    kwds,
-                }
+            move |(
+                (((base_statement, prefixes), ((kwds, datasets), where_clause)), modifier),
+                values,
+            )| Query {
+                base_statement,
+                prefixes,
+                base: base.clone(),
+                modifier,
+                where_clause,
+                datasets,
+                kwds,
+                values,
             },
         )
 }
 
+fn is_update(tokens: &[(PToken, S)]) -> bool {
+    tokens
+        .iter()
+        .find_map(|(PToken(t, _), _)| match t {
+            Token::SparqlKeyword(kwd) => Some(kwd),
+            _ => None,
+        })
+        .map(|kwd| {
+            matches!(
+                kwd,
+                SparqlKeyword::Insert
+                    | SparqlKeyword::Delete
+                    | SparqlKeyword::Load
+                    | SparqlKeyword::Clear
+                    | SparqlKeyword::Drop
+                    | SparqlKeyword::Create
+                    | SparqlKeyword::Add
+                    | SparqlKeyword::Move
+                    | SparqlKeyword::Copy
+                    | SparqlKeyword::With
+            )
+        })
+        .unwrap_or(false)
+}
+
+/// Splits the tokens on the ';' between update operations, a ';' inside brackets belongs to the
+/// operation.
+fn split_operations(tokens: Vec<(PToken, S)>) -> Vec<Vec<(PToken, S)>> {
+    let mut out = vec![Vec::new()];
+    let mut depth = 0usize;
+    for (token, span) in tokens {
+        match &token.0 {
+            Token::CurlOpen | Token::BracketOpen | Token::SqOpen => depth += 1,
+            Token::CurlClose | Token::BracketClose | Token::SqClose => {
+                depth = depth.saturating_sub(1)
+            }
+            Token::PredicateSplit if depth == 0 => {
+                out.push(Vec::new());
+                continue;
+            }
+            _ => {}
+        }
+        out.last_mut().unwrap().push((token, span));
+    }
+    out
+}
+
+/// Parses an update request, every operation is parsed on its own so an error in one operation
+/// never hides the others.
+fn parse_update(
+    source: &str,
+    base: lsp_types::Url,
+    tokens: Vec<(PToken, S)>,
+    ctx: Ctx<'_>,
+) -> (Spanned<Query>, Vec<Simple<PToken>>) {
+    let mut base_statement = None;
+    let mut prefixes = Vec::new();
+    let mut updates = Vec::new();
+    let mut errors = Vec::new();
+
+    for operation in split_operations(tokens) {
+        let Some(eoi) = operation.last().map(|x| x.1.end) else {
+            continue;
+        };
+
+        let stream = chumsky::Stream::from_iter(eoi..eoi, operation.into_iter());
+        let parser = prologue()
+            .map_with_span(spanned)
+            .repeated()
+            .then(update_operation(ctx).map_with_span(spanned).or_not())
+            .then_ignore(end().recover_with(skip_then_retry_until([])));
+        let (operation, es) = parser.parse_recovery(stream);
+        errors.extend(es);
+
+        if let Some((prologues, update)) = operation {
+            prologues
+                .into_iter()
+                .for_each(|x| add_prologue(x, &mut base_statement, &mut prefixes));
+            updates.extend(update);
+        }
+    }
+
+    let query = Query {
+        base,
+        prefixes,
+        base_statement,
+        kwds: QueryClause::Update(updates),
+        datasets: Vec::new(),
+        where_clause: None,
+        modifier: Vec::new(),
+        values: None,
+    };
+    (Spanned(query, 0..source.len()), errors)
+}
+
 pub fn parse(
     source: &str,
     base: lsp_types::Url,
     tokens: Vec<Spanned<Token>>,
     ctx: Ctx<'_>,
 ) -> (Spanned<Query>, Vec<Simple<PToken>>) {
+    let ctx = ctx.with_paths();
     let len = source.len();
-    let stream = chumsky::Stream::from_iter(
-        0..len,
-        tokens
-            .into_iter()
-            .enumerate()
-            .filter(|(_, x)| !x.is_comment())
-            .map(|(i, t)| t.map(|x| PToken(x, i)))
-            .map(|Spanned(x, s)| (x, s)),
-    );
+    let tokens: Vec<_> = tokens
+        .into_iter()
+        .enumerate()
+        .filter(|(_, x)| !x.is_comment())
+        .map(|(i, t)| t.map(|x| PToken(x, i)))
+        .map(|Spanned(x, s)| (x, s))
+        .collect();
+
+    if is_update(&tokens) {
+        return parse_update(source, base, tokens, ctx);
+    }
+
+    let stream = chumsky::Stream::from_iter(0..len, tokens.into_iter());
 
     let parser = query(base, ctx)
         .map_with_span(spanned)
@@ -297,10 +1005,7 @@ mod tests {
     use lang_turtle::lang::context::Context;
 
     use super::*;
-    use crate::lang::{
-        parsing::select_clause,
-        tokenizer::{parse_tokens_str},
-    };
+    use crate::lang::{parsing::select_clause, tokenizer::parse_tokens_str};
     pub fn parse_it<T, P: Parser<PToken, T, Error = Simple<PToken>>>(
         turtle: &str,
         parser: P,
@@ -342,7 +1047,12 @@ PREFIX  dc:  <http://purl.org/dc/elements/1.1/>
 SELECT  ?title ?price
         "#;
 
-        let (q, tok) = parse_it(inp, select_clause());
+        let context = Context::new();
+        let ctx = context.ctx();
+        let (q, tok) = parse_it(
+            inp,
+            select_clause(group_graph_pattern(sub_select(ctx), ctx)),
+        );
 
         println!("q {:?}", q);
 
@@ -368,6 +1078,55 @@ SELECT  ?title ?price
         assert!(q.is_some());
     }
 
+    #[test]
+    fn parse_sparql_star_triple() {
+        let context = Context::new();
+        let ctx = context.ctx();
+        let inp = r#"
+  << ?x ns:price ?p >> ns:source ?src ; ns:at ?x {| ns:certainty ?c |} .
+        "#;
+
+        let (q, tok) = parse_it(inp, triple(ctx));
+
+        assert_eq!(tok, vec![]);
+        let q = q.expect("triple");
+        assert!(matches!(
+            q.subject.value(),
+            lang_turtle::lang::model::Term::Quoted(_)
+        ));
+        assert_eq!(q.po[1].annotations.len(), 1);
+    }
+
+    #[test]
+    fn parse_property_paths() {
+        let context = Context::new();
+        let ctx = context.ctx().with_paths();
+        let inp = r#"
+  ?x foaf:knows+/foaf:name ?n ; ^ex:p ?y ; (ex:a|ex:b)* ?z ; !(a|^ex:c) ?w ; ex:plain ?v .
+        "#;
+
+        let (q, tok) = parse_it(inp, triple(ctx));
+
+        assert_eq!(tok, vec![]);
+        let q = q.expect("triple");
+        let predicates: Vec<_> =
+            q.po.iter()
+                .map(|po| po.predicate.value().to_string())
+                .collect();
+        assert_eq!(
+            predicates,
+            vec![
+                "foaf:knows+/foaf:name",
+                "^ex:p",
+                "(ex:a|ex:b)*",
+                "!(a|^ex:c)",
+                "ex:plain"
+            ]
+        );
+        assert!(matches!(q.po[0].predicate.value(), Term::Path(_)));
+        assert!(matches!(q.po[4].predicate.value(), Term::NamedNode(_)));
+    }
+
     #[test]
     fn parse_group_graph_pattern_sub() {
         let context = Context::new();
@@ -420,4 +1179,234 @@ SELECT  ?title ?price
         assert_eq!(tok, vec![]);
         assert!(q.is_some());
     }
+
+    #[test]
+    fn parse_other_query_forms() {
+        let context = Context::new();
+        let ctx = context.ctx();
+        let base = lsp_types::Url::parse("memory://myFile.sq").unwrap();
+        let inputs = [
+            "ASK { ?x <http://example.org/ns#price> ?p . }",
+            "DESCRIBE ?x <http://example.org/a> WHERE { ?x ?p ?o . }",
+            "DESCRIBE <http://example.org/a>",
+            "CONSTRUCT { ?x <http://example.org/ns#cost> ?p . } WHERE { ?x <http://example.org/ns#price> ?p . }",
+            "CONSTRUCT WHERE { ?x <http://example.org/ns#price> ?p . }",
+        ];
+
+        for inp in inputs {
+            let (q, tok) = parse_it(inp, query(base.clone(), ctx));
+            assert_eq!(tok, vec![], "{}", inp);
+            assert!(q.is_some(), "{}", inp);
+        }
+
+        let (q, _) = parse_it(inputs[2], query(base.clone(), ctx));
+        let q = q.unwrap();
+        assert!(q.where_clause.is_none());
+        let QueryClause::Describe(describe) = q.kwds else {
+            panic!("expected describe");
+        };
+        assert_eq!(describe.targets.len(), 1);
+
+        let (q, _) = parse_it(inputs[4], query(base, ctx));
+        let QueryClause::Construct(construct) = q.unwrap().kwds else {
+            panic!("expected construct");
+        };
+        assert!(construct.template.is_none());
+    }
+
+    #[test]
+    fn parse_expressions() {
+        let context = Context::new();
+        let ctx = context.ctx();
+        let ggp = || group_graph_pattern(sub_select(ctx), ctx);
+        let inputs = [
+            "?a + ?b * 2 > 10 || !BOUND(?c)",
+            "?x -1",
+            "?x NOT IN (1, 2, <http://example.org/a>)",
+            "regex(str(?x), \"^a\", \"i\")",
+            "NOT EXISTS { ?x ?p ?o }",
+            "GROUP_CONCAT(DISTINCT ?x ; SEPARATOR = \", \")",
+            "COUNT(*)",
+            "<http://example.org/fn>(?x, 1)",
+            "RAND()",
+        ];
+
+        for inp in inputs {
+            let (q, tok) = parse_it(inp, expression(ggp()));
+            assert_eq!(tok, vec![], "{}", inp);
+            assert!(q.is_some(), "{}", inp);
+        }
+
+        let (q, _) = parse_it(inputs[0], expression(ggp()));
+        let Some(Expression::Binary(_, op, _)) = q else {
+            panic!("expected binary expression");
+        };
+        assert_eq!(op.value(), &SparqlExpr::Or);
+
+        let (q, _) = parse_it(inputs[1], expression(ggp()));
+        assert!(matches!(
+            q,
+            Some(Expression::Binary(_, Spanned(SparqlExpr::Minus, _), _))
+        ));
+
+        let (_, tok) = parse_it("SUM(*)", expression(ggp()));
+        assert_eq!(tok.len(), 1);
+    }
+
+    #[test]
+    fn parse_filter_bind_and_projection() {
+        let context = Context::new();
+        let ctx = context.ctx();
+        let base = lsp_types::Url::parse("memory://myFile.sq").unwrap();
+        let inp = r#"SELECT ?x (COUNT(DISTINCT ?p) AS ?count) WHERE {
+    ?x <http://example.org/ns#price> ?p .
+    FILTER (?p >= 10)
+    FILTER regex(?x, "a")
+    BIND (?p * 2 AS ?double)
+}"#;
+
+        let (q, tok) = parse_it(inp, query(base.clone(), ctx));
+        assert_eq!(tok, vec![]);
+        let q = q.unwrap();
+        let QueryClause::Select(select) = &q.kwds else {
+            panic!("expected select");
+        };
+        assert!(matches!(select.solutions[1].value(), Solution::VarAs(_)));
+        let ggp = &q.where_clause.as_ref().unwrap().ggp;
+        let GroupGraphPattern::GroupGraph(subs) = ggp.value() else {
+            panic!("expected group graph");
+        };
+        assert!(matches!(subs[1].value(), GroupGraphPatternSub::Filter(..)));
+        assert!(matches!(subs[2].value(), GroupGraphPatternSub::Filter(..)));
+        assert!(matches!(subs[3].value(), GroupGraphPatternSub::Bind(..)));
+
+        let (_, tok) = parse_it("SELECT * WHERE { ?x ?p ?o . FILTER ?o }", query(base, ctx));
+        assert_eq!(tok.len(), 1);
+    }
+
+    #[test]
+    fn parse_solution_modifiers_and_values() {
+        let context = Context::new();
+        let ctx = context.ctx();
+        let base = lsp_types::Url::parse("memory://myFile.sq").unwrap();
+        let inp = r#"SELECT ?x (SUM(?p) AS ?total) WHERE {
+    ?x <http://example.org/ns#price> ?p .
+}
+GROUP BY ?x (STR(?p) AS ?s)
+HAVING (SUM(?p) > 10)
+ORDER BY DESC(?total) ?x
+LIMIT 10 OFFSET 5
+VALUES (?x ?p) { (<http://example.org/a> 1) (UNDEF 2) }"#;
+
+        let (q, tok) = parse_it(inp, query(base.clone(), ctx));
+        assert_eq!(tok, vec![]);
+        let q = q.unwrap();
+        assert_eq!(q.modifier.len(), 5);
+        let Modifier::GroupBy(_, _, conditions) = q.modifier[0].value() else {
+            panic!("expected group by");
+        };
+        assert!(matches!(conditions[1].value(), GroupCondition::Bind(_)));
+        let Modifier::OrderBy(_, _, conditions) = q.modifier[2].value() else {
+            panic!("expected order by");
+        };
+        assert_eq!(conditions.len(), 2);
+        assert!(conditions[0].direction.is_some());
+        let values = q.values.expect("values");
+        assert_eq!(values.vars.len(), 2);
+        assert_eq!(values.rows.len(), 2);
+        assert_eq!(values.rows[1][0].value(), &DataValue::Undef);
+
+        let (_, tok) = parse_it(
+            "SELECT * WHERE { ?x ?p ?o } VALUES (?x ?p) { (1) }",
+            query(base, ctx),
+        );
+        assert_eq!(tok.len(), 1);
+    }
+
+    #[test]
+    fn parse_group_graph_pattern_kinds() {
+        let context = Context::new();
+        let ctx = context.ctx();
+        let inp = r#"{
+    ?x <http://example.org/p> ?y .
+    OPTIONAL { ?y <http://example.org/q> ?z }
+    MINUS { ?x <http://example.org/r> ?z }
+    GRAPH ?g { ?x ?p ?o }
+    SERVICE SILENT <http://example.org/sparql> { ?x ?p ?o }
+    VALUES ?z { 1 2 UNDEF }
+}"#;
+
+        let (q, tok) = parse_it(inp, group_graph_pattern(sub_select(ctx), ctx));
+
+        assert_eq!(tok, vec![]);
+        let Some(GroupGraphPattern::GroupGraph(subs)) = q else {
+            panic!("expected group graph");
+        };
+        assert!(matches!(
+            subs[1].value(),
+            GroupGraphPatternSub::Optional(..)
+        ));
+        assert!(matches!(subs[2].value(), GroupGraphPatternSub::Minus(..)));
+        assert!(matches!(subs[3].value(), GroupGraphPatternSub::Graph(..)));
+        assert!(matches!(
+            subs[4].value(),
+            GroupGraphPatternSub::Service(_, Some(_), _, _)
+        ));
+        let GroupGraphPatternSub::Values(values) = subs[5].value() else {
+            panic!("expected values");
+        };
+        assert!(!values.bracketted);
+        assert_eq!(values.rows.len(), 3);
+    }
+
+    #[test]
+    fn parse_update_operations() {
+        let context = Context::new();
+        let ctx = context.ctx();
+        let inp = r#"PREFIX ex: <http://example.org/>
+INSERT DATA { ex:a ex:p ex:b . GRAPH ex:g { ex:a ex:q "x" } } ;
+DELETE WHERE { ?s ex:p ?o } ;
+LOAD <http://example.org/data.ttl> INTO GRAPH ex:g ;
+CLEAR SILENT DEFAULT ;
+PREFIX foaf: <http://xmlns.com/foaf/0.1/>
+WITH ex:g DELETE { ?s foaf:name ?o } INSERT { ?s ex:name ?o } WHERE { ?s foaf:name ?o }
+"#;
+        let (tokens, _) = parse_tokens_str(inp);
+        let url = lsp_types::Url::parse("memory://myFile.ru").unwrap();
+        let (q, errors) = parse(inp, url, tokens, ctx);
+
+        assert_eq!(errors, vec![]);
+        assert_eq!(q.prefixes.len(), 2);
+        let QueryClause::Update(updates) = &q.kwds else {
+            panic!("expected an update");
+        };
+        assert_eq!(updates.len(), 5);
+        assert!(matches!(updates[4].value(), Update::Modify(_)));
+    }
+
+    #[test]
+    fn update_errors_stay_in_their_operation() {
+        let context = Context::new();
+        let ctx = context.ctx();
+        let inp = r#"INSERT DATA { <http://example.org/a> <http://example.org/p> } ;
+DROP GRAPH <http://example.org/g> ;
+CLEAR ALL
+"#;
+        let (tokens, _) = parse_tokens_str(inp);
+        let url = lsp_types::Url::parse("memory://myFile.ru").unwrap();
+        let (q, errors) = parse(inp, url, tokens, ctx);
+
+        assert!(!errors.is_empty());
+        assert!(errors
+            .iter()
+            .all(|e| e.span().end <= inp.find(';').unwrap()));
+        let QueryClause::Update(updates) = &q.kwds else {
+            panic!("expected an update");
+        };
+        let graph_ops = updates
+            .iter()
+            .filter(|x| matches!(x.value(), Update::Graph { .. }))
+            .count();
+        assert_eq!(graph_ops, 2);
+    }
 }
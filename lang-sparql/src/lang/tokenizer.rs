@@ -41,6 +41,8 @@ enum SparqlToken {
     #[token("ADD", |_| SparqlKeyword::Add, ignore(case))]
     #[token("MOVE", |_| SparqlKeyword::Move, ignore(case))]
     #[token("COPY", |_| SparqlKeyword::Copy, ignore(case))]
+    #[token("INTO", |_| SparqlKeyword::Into, ignore(case))]
+    #[token("TO", |_| SparqlKeyword::To, ignore(case))]
     #[token("INSERT", |_| SparqlKeyword::Insert, ignore(case))]
     #[token("DATA", |_| SparqlKeyword::Data, ignore(case))]
     #[token("DELETE", |_| SparqlKeyword::Delete, ignore(case))]
@@ -54,6 +56,7 @@ enum SparqlToken {
     #[token("UNDEF", |_| SparqlKeyword::Undef, ignore(case))]
     #[token("MINUS", |_| SparqlKeyword::Minus, ignore(case))]
     #[token("FILTER", |_| SparqlKeyword::Filter, ignore(case))]
+    #[token("SEPARATOR", |_| SparqlKeyword::Separator, ignore(case))]
     Kwd(SparqlKeyword),
 
     #[token("COUNT", |_| SparqlAggregate::Count, ignore(case))]
@@ -140,6 +143,9 @@ enum SparqlToken {
     #[token("*", |_| SparqlExpr::Times, ignore(case))]
     #[token("/", |_| SparqlExpr::Divide, ignore(case))]
     #[token("!", |_| SparqlExpr::Exclamation, ignore(case))]
+    #[token("|", |_| SparqlExpr::Pipe, ignore(case))]
+    #[token("^", |_| SparqlExpr::Hat, ignore(case))]
+    #[token("?", |_| SparqlExpr::Question, ignore(case))]
     Expr(SparqlExpr),
 
     #[token("prefix", ignore(case))]
@@ -186,6 +192,18 @@ enum SparqlToken {
     #[token("}")]
     CurlClose,
 
+    #[token("<<")]
+    TripleOpen,
+
+    #[token(">>")]
+    TripleClose,
+
+    #[token("{|")]
+    AnnotationOpen,
+
+    #[token("|}")]
+    AnnotationClose,
+
     #[regex(r#"(_:((([A-Z]|[a-z]|[\u00C0-\u00D6\u00D8-\u00F6\u00F8-\u02FF\u0370-\u037D\u037F-\u1FFF\u200C-\u200D\u2070-\u218F\u2C00-\u2FEF\u3001-\uD7FF\uF900-\uFDCF\uFDF0-\uFFFD\U00010000-\U000EFFFF])|_)|[0-9])((([A-Z]|[a-z]|[\u00C0-\u00D6\u00D8-\u00F6\u00F8-\u02FF\u0370-\u037D\u037F-\u1FFF\u200C-\u200D\u2070-\u218F\u2C00-\u2FEF\u3001-\uD7FF\uF900-\uFDCF\uFDF0-\uFFFD\U00010000-\U000EFFFF])|_)|\-|[0-9]|\u00B7|[\u0300-\u036F]|[\u203F-\u2040])*(\.*((([A-Z]|[a-z]|[\u00C0-\u00D6\u00D8-\u00F6\u00F8-\u02FF\u0370-\u037D\u037F-\u1FFF\u200C-\u200D\u2070-\u218F\u2C00-\u2FEF\u3001-\uD7FF\uF900-\uFDCF\uFDF0-\uFFFD\U00010000-\U000EFFFF])|_)|\-|[0-9]|\u00B7|[\u0300-\u036F]|[\u203F-\u2040])((([A-Z]|[a-z]|[\u00C0-\u00D6\u00D8-\u00F6\u00F8-\u02FF\u0370-\u037D\u037F-\u1FFF\u200C-\u200D\u2070-\u218F\u2C00-\u2FEF\u3001-\uD7FF\uF900-\uFDCF\uFDF0-\uFFFD\U00010000-\U000EFFFF])|_)|\-|[0-9]|\u00B7|[\u0300-\u036F]|[\u203F-\u2040])*)*)"#)]
     BLANK_NODE_LABEL,
 
@@ -257,6 +275,10 @@ pub fn parse_tokens_str<'a>(text: &'a str) -> (Vec<Spanned<Token>>, Vec<Simple<c
                     SparqlToken::TypeTag => Token::PredType,
                     SparqlToken::CurlOpen => Token::CurlOpen,
                     SparqlToken::CurlClose => Token::CurlClose,
+                    SparqlToken::TripleOpen => Token::TripleOpen,
+                    SparqlToken::TripleClose => Token::TripleClose,
+                    SparqlToken::AnnotationOpen => Token::AnnotationOpen,
+                    SparqlToken::AnnotationClose => Token::AnnotationClose,
                     SparqlToken::Semi => Token::PredicateSplit,
                     SparqlToken::Comma => Token::Comma,
                     SparqlToken::Stop => Token::Stop,
@@ -353,6 +375,8 @@ pub fn parse_tokens_str_safe(text: &str) -> Result<Vec<Spanned<Token>>, Vec<Simp
 
 #[cfg(test)]
 mod tests {
+    use lsp_core::prelude::Token;
+
     use super::parse_tokens_str;
 
     #[test]
@@ -475,6 +499,18 @@ CONSTRUCT {
         assert_eq!(er, vec![]);
     }
 
+    #[test]
+    fn parse_sparql_star_tokens() {
+        let inp = r#"SELECT ?s { <<?s ?p ?o>> ?q ?v . ?s ?p ?o {| ?q ?v |} }"#;
+
+        let (tok, er) = parse_tokens_str(inp);
+        assert_eq!(er, vec![]);
+        assert_eq!(tok[3].value(), &Token::TripleOpen);
+        assert_eq!(tok[7].value(), &Token::TripleClose);
+        assert_eq!(tok[14].value(), &Token::AnnotationOpen);
+        assert_eq!(tok[17].value(), &Token::AnnotationClose);
+    }
+
     #[test]
     fn parse_random_tokens_8() {
         let inp = r#"
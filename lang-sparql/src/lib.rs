@@ -7,7 +7,11 @@ use lsp_core::prelude::*;
 use lsp_types::SemanticTokenType;
 
 pub mod ecs;
-use crate::ecs::{setup_completion, setup_parse};
+use crate::ecs::{
+    setup_code_action, setup_commands, setup_completion, setup_diagnostics, setup_formatting,
+    setup_hover, setup_parse, setup_references, setup_semantic, setup_signature_help,
+    setup_symbols,
+};
 pub mod lang;
 // pub mod model;
 // use crate::model::Query;
@@ -16,12 +20,14 @@ pub mod lang;
 
 pub fn setup_world(world: &mut World) {
     let mut semantic_token_dict = world.resource_mut::<SemanticTokensDict>();
-    [SemanticTokenType::VARIABLE].iter().for_each(|lt| {
-        if !semantic_token_dict.contains_key(lt) {
-            let l = semantic_token_dict.0.len();
-            semantic_token_dict.insert(lt.clone(), l);
-        }
-    });
+    [SemanticTokenType::VARIABLE, SemanticTokenType::FUNCTION]
+        .iter()
+        .for_each(|lt| {
+            if !semantic_token_dict.contains_key(lt) {
+                let l = semantic_token_dict.0.len();
+                semantic_token_dict.insert(lt.clone(), l);
+            }
+        });
     world.observe(|trigger: Trigger<CreateEvent>, mut commands: Commands| {
         match &trigger.event().language_id {
             Some(x) if x == "sparql" => {
@@ -35,7 +41,8 @@ pub fn setup_world(world: &mut World) {
             _ => {}
         }
 
-        if trigger.event().url.as_str().ends_with(".sq") {
+        let url = trigger.event().url.as_str();
+        if url.ends_with(".sq") || url.ends_with(".rq") || url.ends_with(".ru") {
             info!("Found sparql documnet!");
             commands
                 .entity(trigger.entity())
@@ -52,6 +59,15 @@ pub fn setup_world(world: &mut World) {
     setup_parse(world);
 
     setup_completion(world);
+    setup_code_action(world);
+    setup_symbols(world);
+    setup_references(world);
+    setup_semantic(world);
+    setup_diagnostics(world);
+    setup_formatting(world);
+    setup_commands(world);
+    setup_hover(world);
+    setup_signature_help(world);
 }
 
 #[derive(Debug, Component)]
@@ -69,7 +85,7 @@ impl Lang for Sparql {
     const PATTERN: Option<&'static str> = None;
 
     const LANG: &'static str = "sparql";
-    const CODE_ACTION: bool = false;
+    const CODE_ACTION: bool = true;
     const HOVER: bool = true;
 
     const TRIGGERS: &'static [&'static str] = &[];
@@ -80,6 +96,8 @@ impl Lang for Sparql {
         SemanticTokenType::KEYWORD,
         SemanticTokenType::PROPERTY,
         SemanticTokenType::ENUM_MEMBER,
+        SemanticTokenType::OPERATOR,
+        SemanticTokenType::FUNCTION,
     ];
 }
 
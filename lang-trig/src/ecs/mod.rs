@@ -0,0 +1,161 @@
+use bevy_ecs::{prelude::*, world::World};
+use lang_turtle::ecs::{
+    format_turtle_system,
+    parse::{derive_prefixes, derive_triples, parse_source, parse_turtle_system},
+    subject_completion, turtle_lov_undefined_prefix_completion,
+    turtle_undefined_prefix_code_action,
+};
+use lsp_core::prelude::*;
+use symbol::trig_document_symbols;
+
+use crate::TrigLang;
+
+mod parse;
+mod symbol;
+
+pub fn setup_parsing(world: &mut World) {
+    use lsp_core::feature::parse::*;
+    world.schedule_scope(ParseLabel, |_, schedule| {
+        schedule.add_systems((
+            parse_source::<TrigLang>,
+            parse_turtle_system::<TrigLang>.after(parse_source::<TrigLang>),
+            derive_prefixes::<TrigLang>
+                .after(parse_turtle_system::<TrigLang>)
+                .before(prefixes),
+            derive_triples::<TrigLang>
+                .after(parse_turtle_system::<TrigLang>)
+                .before(triples),
+        ));
+    });
+}
+
+pub fn setup_formatting(world: &mut World) {
+    world.schedule_scope(FormatLabel, |_, schedule| {
+        schedule.add_systems(format_turtle_system::<TrigLang>);
+    });
+}
+
+pub fn setup_code_action(world: &mut World) {
+    world.schedule_scope(CodeActionLabel, |_, schedule| {
+        schedule.add_systems(turtle_undefined_prefix_code_action::<TrigLang>);
+    });
+}
+
+pub fn setup_symbols(world: &mut World) {
+    world.schedule_scope(DocumentSymbolLabel, |_, schedule| {
+        schedule.add_systems(trig_document_symbols);
+    });
+}
+
+pub fn setup_completion(world: &mut World) {
+    use lsp_core::feature::completion::*;
+    world.schedule_scope(CompletionLabel, |_, schedule| {
+        schedule.add_systems((
+            turtle_lov_undefined_prefix_completion::<TrigLang>.after(get_current_token),
+            subject_completion::<TrigLang>.after(get_current_token),
+        ));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_core::{
+        components::*,
+        prelude::{diagnostics::DiagnosticItem, *},
+    };
+    use test_log::test;
+    use test_utils::{create_file, setup_world, TestClient};
+
+    use crate::TrigLang;
+
+    #[test]
+    fn trig_documents_have_quads() {
+        let (mut world, mut rx) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = "
+@prefix ex: <http://example.com/>.
+ex:a ex:p ex:b.
+GRAPH ex:g { ex:c ex:p ex:d }
+";
+
+        let entity = create_file(&mut world, t1, "http://example.com/data.trig", "", Open);
+        world.run_schedule(ParseLabel);
+        world.run_schedule(DiagnosticsLabel);
+
+        assert!(world.entity(entity).contains::<TrigLang>());
+
+        let triples = world.entity(entity).get::<Triples>().expect("triples");
+        assert_eq!(triples.0.len(), 2);
+        assert!(triples.0[0].graph.is_none());
+        assert_eq!(
+            triples.0[1].graph.as_ref().map(|g| g.as_str()),
+            Some("http://example.com/g")
+        );
+
+        let mut items: Vec<DiagnosticItem> = Vec::new();
+        while let Ok(Some(x)) = rx.try_next() {
+            items.push(x);
+        }
+        assert!(items[0].diagnostics.is_empty());
+    }
+
+    #[test]
+    fn undefined_prefix_code_action() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = "GRAPH <g> { <a> foaf:name \"Arthur\" }";
+        let entity = create_file(&mut world, t1, "http://example.com/data.trig", "trig", Open);
+
+        let range = lsp_types::Range::new(
+            lsp_types::Position::new(0, 0),
+            lsp_types::Position::new(0, 20),
+        );
+        world
+            .entity_mut(entity)
+            .insert(CodeActionRequest::new(range, vec![]));
+        world.run_schedule(CodeActionLabel);
+
+        let req = world
+            .entity_mut(entity)
+            .take::<CodeActionRequest>()
+            .expect("code action request");
+        let edits: Vec<_> = req
+            .actions
+            .iter()
+            .filter_map(|x| match x {
+                lsp_types::CodeActionOrCommand::CodeAction(a) => a.edit.as_ref()?.changes.clone(),
+                _ => None,
+            })
+            .flat_map(|x| x.into_values().flatten())
+            .collect();
+        assert_eq!(edits.len(), 1);
+        assert_eq!(
+            edits[0].new_text,
+            "@prefix foaf: <http://xmlns.com/foaf/0.1/>.\n"
+        );
+    }
+
+    #[test]
+    fn format_trig_document() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = "@prefix ex: <http://example.com/>.
+ex:g {ex:c ex:p ex:d}";
+
+        let entity = create_file(&mut world, t1, "http://example.com/data.trig", "trig", Open);
+        world.run_schedule(ParseLabel);
+
+        world.entity_mut(entity).insert(FormatRequest(None));
+        world.run_schedule(FormatLabel);
+        let formatted = world
+            .entity_mut(entity)
+            .take::<FormatRequest>()
+            .and_then(|x| x.0)
+            .expect("formatted");
+
+        assert_eq!(
+            formatted[0].new_text,
+            "@prefix ex: <http://example.com/>.\nex:g {\n  ex:c ex:p ex:d.\n}\n\n"
+        );
+    }
+}
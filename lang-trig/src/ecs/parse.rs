@@ -0,0 +1,49 @@
+use chumsky::prelude::Simple;
+use lang_turtle::{
+    ecs::parse::TurtleDocument,
+    lang::{
+        context::{Context, Ctx},
+        model::Base,
+    },
+};
+use lsp_core::prelude::*;
+
+use crate::lang::{formatter::format_trig, model::TriG, parser::parse_trig};
+
+/// TriG is parsed from Turtle tokens, so it reuses the Turtle parse systems.
+impl TurtleDocument for TriG {
+    fn parse(
+        location: &lsp_types::Url,
+        tokens: Vec<Spanned<Token>>,
+        len: usize,
+        ctx: Ctx<'_>,
+    ) -> (Spanned<Self>, Vec<Simple<PToken>>) {
+        parse_trig(location, tokens, len, ctx)
+    }
+
+    fn enabled(config: &Config) -> bool {
+        config.trig.unwrap_or(true)
+    }
+
+    fn base(&self) -> Option<&Spanned<Base>> {
+        self.base.as_ref()
+    }
+
+    fn set_context(&self, ctx: &mut Context) {
+        TriG::set_context(self, ctx)
+    }
+
+    fn triples(&self) -> Option<Vec<MyQuad<'static>>> {
+        let triples = self.get_simple_triples().ok()?;
+        Some(triples.iter().map(|x| x.to_owned()).collect())
+    }
+
+    fn format(
+        &self,
+        config: lsp_types::FormattingOptions,
+        comments: &[Spanned<String>],
+        source: &ropey::Rope,
+    ) -> Option<String> {
+        format_trig(self, config, comments, source)
+    }
+}
@@ -0,0 +1,49 @@
+use bevy_ecs::prelude::*;
+use lang_turtle::lang::symbol::{prefixes_symbol, term_name, triple_symbol};
+use lsp_core::{components::*, feature::symbol::document_symbol, prelude::*};
+use lsp_types::SymbolKind;
+
+use crate::{lang::model::Block, TrigLang};
+
+pub fn trig_document_symbols(
+    mut query: Query<(&Element<TrigLang>, &RopeC, &mut DocumentSymbolRequest)>,
+) {
+    for (trig, rope, mut req) in &mut query {
+        let trig = trig.0.value();
+        req.0.extend(prefixes_symbol(&trig.prefixes, &rope.0));
+
+        for block in &trig.blocks {
+            match block.value() {
+                Block::Triple(t) => req.0.extend(triple_symbol(t, trig, &rope.0)),
+                Block::Graph(g) => {
+                    let children = g
+                        .triples
+                        .iter()
+                        .flat_map(|t| triple_symbol(t, trig, &rope.0))
+                        .collect();
+                    let name = g
+                        .name
+                        .as_ref()
+                        .map(|n| term_name(n.value()))
+                        .unwrap_or_else(|| String::from("default graph"));
+                    let selection = g
+                        .name
+                        .as_ref()
+                        .map(|n| n.span())
+                        .or(g.kwd.as_ref())
+                        .unwrap_or(block.span());
+
+                    req.0.extend(document_symbol(
+                        name,
+                        None,
+                        SymbolKind::NAMESPACE,
+                        block.span(),
+                        selection,
+                        children,
+                        &rope.0,
+                    ));
+                }
+            }
+        }
+    }
+}
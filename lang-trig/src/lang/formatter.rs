@@ -0,0 +1,118 @@
+use std::io;
+
+use lang_turtle::lang::formatter::FormatState;
+use lsp_core::prelude::*;
+use lsp_types::FormattingOptions;
+use ropey::Rope;
+use tracing::info;
+
+use crate::lang::model::{Block, GraphBlock, TriG};
+
+fn write_graph(state: &mut FormatState, graph: &GraphBlock) -> io::Result<()> {
+    if graph.kwd.is_some() {
+        state.write_str("GRAPH ")?;
+    }
+    if let Some(name) = &graph.name {
+        state.write_term(name)?;
+        state.write_str(" ")?;
+    }
+
+    if graph.triples.is_empty() {
+        return state.write_str("{ }");
+    }
+
+    state.write_str("{")?;
+    state.inc();
+    for t in &graph.triples {
+        state.new_line()?;
+        state.check_comments(t.span())?;
+        state.write_triple(t)?;
+    }
+    state.decr();
+    state.new_line()?;
+    state.write_str("}")
+}
+
+fn write_trig(state: &mut FormatState, trig: &TriG) -> io::Result<()> {
+    state.write_directives(trig.base.as_ref(), &trig.prefixes)?;
+
+    let mut prev_line = 0;
+    for block in &trig.blocks {
+        if prev_line + 1 < state.line_count() {
+            state.new_line()?;
+        }
+        prev_line = state.line_count();
+        state.check_comments(block.span())?;
+        match block.value() {
+            Block::Triple(t) => state.write_triple(t)?,
+            Block::Graph(g) => write_graph(state, g)?,
+        }
+        state.new_line()?;
+    }
+
+    state.write_tail()
+}
+
+pub fn format_trig(
+    trig: &TriG,
+    config: FormattingOptions,
+    comments: &[Spanned<String>],
+    source: &Rope,
+) -> Option<String> {
+    let mut state = FormatState::new(config, comments, source);
+    match write_trig(&mut state, trig) {
+        Ok(_) => info!("Format succesful"),
+        Err(e) => {
+            info!("Format unsuccesful {:?}", e);
+            return None;
+        }
+    }
+    state.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use lang_turtle::lang::{context::Context, tokenizer::parse_tokens_str_safe};
+    use ropey::Rope;
+
+    use super::format_trig;
+    use crate::lang::parser::parse_trig;
+
+    #[test]
+    fn format_graph_blocks() {
+        let txt = "@prefix ex: <http://example.com/>.
+ex:a ex:p ex:b.
+GRAPH ex:g { ex:c ex:p ex:d. ex:e ex:p ex:f }
+{ }";
+        let url = lsp_types::Url::from_str("http://example.com/ns#").unwrap();
+        let tokens = parse_tokens_str_safe(txt).unwrap();
+        let context = Context::new();
+        let (trig, errors) = parse_trig(&url, tokens, txt.len(), context.ctx());
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let output = format_trig(
+            &trig,
+            lsp_types::FormattingOptions {
+                tab_size: 2,
+                ..Default::default()
+            },
+            &[],
+            &Rope::from_str(txt),
+        )
+        .expect("formatted");
+
+        let expected = "@prefix ex: <http://example.com/>.
+ex:a ex:p ex:b.
+GRAPH ex:g {
+  ex:c ex:p ex:d.
+  ex:e ex:p ex:f.
+}
+
+{ }
+
+";
+        assert_eq!(output, expected);
+    }
+}
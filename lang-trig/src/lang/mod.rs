@@ -0,0 +1,3 @@
+pub mod formatter;
+pub mod model;
+pub mod parser;
@@ -0,0 +1,210 @@
+use std::{collections::HashSet, fmt::Display, ops::Range};
+
+use lang_turtle::lang::{
+    context::Context,
+    model::{Base, Based, Term, Triple, TriplesBuilder, TurtlePrefix, TurtleSimpleError},
+};
+use lsp_core::prelude::{MyTerm, Spanned, Triples2};
+use sophia_iri::resolve::BaseIri;
+
+/// A block of triples wrapped in `{ }`, optionally preceded by `GRAPH` and a graph name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GraphBlock {
+    /// Span of the `GRAPH` keyword, if present.
+    pub kwd: Option<Range<usize>>,
+    /// Name of the graph, `None` is the default graph.
+    pub name: Option<Spanned<Term>>,
+    pub triples: Vec<Spanned<Triple>>,
+}
+
+impl Display for GraphBlock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.kwd.is_some() {
+            write!(f, "GRAPH ")?;
+        }
+        if let Some(name) = &self.name {
+            write!(f, "{} ", name.value())?;
+        }
+        writeln!(f, "{{")?;
+        self.triples
+            .iter()
+            .try_for_each(|x| writeln!(f, "  {}", x.value()))?;
+        write!(f, "}}")
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Block {
+    /// Triple in the default graph, outside of any `{ }`.
+    Triple(Spanned<Triple>),
+    Graph(GraphBlock),
+}
+
+impl Display for Block {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Block::Triple(t) => write!(f, "{}", t.value()),
+            Block::Graph(g) => write!(f, "{}", g),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TriG {
+    pub base: Option<Spanned<Base>>,
+    pub set_base: lsp_types::Url,
+    pub prefixes: Vec<Spanned<TurtlePrefix>>,
+    pub blocks: Vec<Spanned<Block>>,
+}
+
+impl Based for TriG {
+    fn get_base(&self) -> &lsp_types::Url {
+        &self.set_base
+    }
+
+    fn prefixes(&self) -> &[Spanned<TurtlePrefix>] {
+        &self.prefixes
+    }
+}
+
+impl TriG {
+    pub fn new(
+        mut base: Option<Spanned<Base>>,
+        prefixes: Vec<Spanned<TurtlePrefix>>,
+        blocks: Vec<Spanned<Block>>,
+        location: &lsp_types::Url,
+    ) -> Self {
+        if let Some(b) = base.as_mut() {
+            b.resolve_location(location);
+        }
+        Self {
+            base,
+            prefixes,
+            blocks,
+            set_base: location.clone(),
+        }
+    }
+
+    pub fn empty(location: &lsp_types::Url) -> Self {
+        Self::new(None, Vec::new(), Vec::new(), location)
+    }
+
+    /// All triples in the document, regardless of their graph.
+    pub fn triples(&self) -> impl Iterator<Item = &Spanned<Triple>> {
+        self.blocks.iter().flat_map(|block| match block.value() {
+            Block::Triple(t) => std::slice::from_ref(t),
+            Block::Graph(g) => g.triples.as_slice(),
+        })
+    }
+
+    pub fn set_context(&self, ctx: &mut Context) {
+        for t in self.triples() {
+            t.set_context(ctx);
+        }
+    }
+
+    pub fn shorten(&self, url: &str) -> Option<String> {
+        self.prefixes
+            .iter()
+            .flat_map(|pref| pref.shorten(self, url))
+            .next()
+    }
+
+    /// Creates quads for all triples, triples in a named graph block get that graph name.
+    pub fn get_simple_triples<'a>(&'a self) -> Result<Triples2<'a>, TurtleSimpleError> {
+        let base = match &self.base {
+            Some(Spanned(Base(_, Spanned(named_node, _)), _)) => {
+                let nn = named_node.expand_step(self, HashSet::new()).ok_or(
+                    TurtleSimpleError::UnexpectedBase("Expected valid named node base"),
+                )?;
+                BaseIri::new(nn).map_err(TurtleSimpleError::Parse)?
+            }
+            None => BaseIri::new(self.set_base.as_str().to_string())
+                .map_err(TurtleSimpleError::Parse)?,
+        };
+
+        let mut builder = TriplesBuilder::new(self, base);
+
+        for block in &self.blocks {
+            match block.value() {
+                Block::Triple(t) => builder.ingest(t)?,
+                Block::Graph(g) => builder.ingest_graph(g.name.as_ref(), &g.triples)?,
+            }
+        }
+
+        let base = match &self.base {
+            Some(Spanned(Base(_, Spanned(named_node, span)), _)) => named_node
+                .expand_step(self, HashSet::new())
+                .map(|st| MyTerm::named_node(st, span.clone())),
+            None => Some(MyTerm::named_node(self.set_base.as_str().to_string(), 0..0)),
+        };
+
+        Ok(Triples2 {
+            triples: builder.triples,
+            base,
+            base_url: self.set_base.to_string(),
+        })
+    }
+}
+
+impl Display for TriG {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(b) = &self.base {
+            writeln!(f, "{}", b.value())?;
+        }
+
+        self.prefixes
+            .iter()
+            .map(|x| x.value())
+            .try_for_each(|x| writeln!(f, "{}", x))?;
+
+        self.blocks
+            .iter()
+            .map(|x| x.value())
+            .try_for_each(|x| writeln!(f, "{}", x))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use lang_turtle::lang::{context::Context, tokenizer::parse_tokens_str_safe};
+
+    use crate::lang::parser::parse_trig;
+
+    #[test]
+    fn quads_get_their_graph_name() {
+        let txt = "
+@prefix ex: <http://example.com/>.
+ex:a ex:p ex:b.
+ex:g { ex:c ex:p ex:d }
+GRAPH ex:h { ex:e ex:p ex:f. ex:e ex:q ex:f. }
+{ ex:x ex:p ex:y }
+";
+        let url = lsp_types::Url::from_str("http://example.com/ns#").unwrap();
+        let tokens = parse_tokens_str_safe(txt).unwrap();
+        let context = Context::new();
+        let (trig, errors) = parse_trig(&url, tokens, txt.len(), context.ctx());
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let triples = trig.get_simple_triples().expect("triples");
+        let graphs: Vec<_> = triples
+            .iter()
+            .map(|q| q.graph.as_ref().map(|g| g.as_str()))
+            .collect();
+
+        assert_eq!(
+            graphs,
+            vec![
+                None,
+                Some("http://example.com/g"),
+                Some("http://example.com/h"),
+                Some("http://example.com/h"),
+                None,
+            ]
+        );
+    }
+}
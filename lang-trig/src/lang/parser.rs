@@ -0,0 +1,180 @@
+use chumsky::prelude::*;
+use lang_turtle::lang::{
+    context::Ctx,
+    model::{Base, BlankNode, Term, Triple, TurtlePrefix},
+    parser::{base, expect_token, named_node, prefix, triple, triple_with_stop},
+};
+use lsp_core::prelude::*;
+use tracing::info;
+
+use crate::lang::model::{Block, GraphBlock, TriG};
+
+/// The name of a graph, either an IRI or a blank node label.
+fn graph_name() -> impl Parser<PToken, Term, Error = Simple<PToken>> + Clone {
+    named_node().map(Term::NamedNode).or(select! {
+        PToken(Token::BlankNodeLabel(x), idx) => Term::BlankNode(BlankNode::Named(x, idx)),
+    })
+}
+
+/// Triples in a graph block, the last triple doesn't need a '.' before the closing '}'.
+fn block_triple(
+    ctx: Ctx<'_>,
+) -> impl Parser<PToken, Triple, Error = Simple<PToken>> + Clone + use<'_> {
+    let stop = just(PToken(Token::Stop, 0))
+        .ignored()
+        .or(just(PToken(Token::CurlClose, 0)).rewind().ignored())
+        .or(expect_token(Token::Stop, |_| true).ignored());
+
+    triple_with_stop(ctx, stop)
+}
+
+fn graph_block(
+    ctx: Ctx<'_>,
+) -> impl Parser<PToken, GraphBlock, Error = Simple<PToken>> + Clone + use<'_> {
+    let kwd = just(PToken(Token::SparqlKeyword(SparqlKeyword::Graph), 0))
+        .map_with_span(|_, span| span)
+        .or_not();
+
+    let close = just(PToken(Token::CurlClose, 0))
+        .ignored()
+        .recover_with(skip_parser(empty()));
+
+    kwd.then(graph_name().map_with_span(spanned).or_not())
+        .then_ignore(just(PToken(Token::CurlOpen, 0)))
+        .then(block_triple(ctx).map_with_span(spanned).repeated())
+        .then_ignore(close)
+        .validate(
+            |((kwd, name), triples), span: std::ops::Range<usize>, emit| {
+                if kwd.is_some() && name.is_none() {
+                    emit(Simple::custom(span, "Expected a graph name after GRAPH"));
+                }
+                GraphBlock { kwd, name, triples }
+            },
+        )
+}
+
+// Makes it easier to handle parts that are not ordered
+enum Statement {
+    Base(Spanned<Base>),
+    Prefix(Spanned<TurtlePrefix>),
+    Block(Spanned<Block>),
+}
+
+pub fn trig<'a>(
+    location: &'a lsp_types::Url,
+    ctx: Ctx<'a>,
+) -> impl Parser<PToken, TriG, Error = Simple<PToken>> + 'a {
+    let base = base().map_with_span(spanned).map(Statement::Base);
+    let prefix = prefix().map_with_span(spanned).map(Statement::Prefix);
+    let graph = graph_block(ctx)
+        .map(Block::Graph)
+        .map_with_span(spanned)
+        .map(Statement::Block);
+    let triple = triple(ctx)
+        .map_with_span(spanned)
+        .map(Block::Triple)
+        .map_with_span(spanned)
+        .map(Statement::Block);
+
+    let statement = base.or(prefix).or(graph).or(triple);
+    statement
+        .repeated()
+        .map(|statements| {
+            let mut base = None;
+            let mut prefixes = Vec::new();
+            let mut blocks = Vec::new();
+            for statement in statements {
+                match statement {
+                    Statement::Base(b) => base = Some(b),
+                    Statement::Prefix(p) => prefixes.push(p),
+                    Statement::Block(b) => blocks.push(b),
+                }
+            }
+
+            TriG::new(base, prefixes, blocks, location)
+        })
+        .then_ignore(end())
+}
+
+pub fn parse_trig(
+    location: &lsp_types::Url,
+    tokens: Vec<Spanned<Token>>,
+    len: usize,
+    ctx: Ctx<'_>,
+) -> (Spanned<TriG>, Vec<Simple<PToken>>) {
+    let stream = chumsky::Stream::from_iter(
+        0..len,
+        tokens
+            .into_iter()
+            .enumerate()
+            .filter(|(_, x)| !x.is_comment())
+            .map(|(i, t)| t.map(|x| PToken(x, i)))
+            .map(|Spanned(x, s)| (x, s)),
+    );
+
+    let parser = trig(location, ctx)
+        .map_with_span(spanned)
+        .then_ignore(end().recover_with(skip_then_retry_until([])));
+
+    info!("Parsing {}", location.as_str());
+    let (trig, errors) = parser.parse_recovery(stream);
+
+    (
+        trig.unwrap_or(Spanned(TriG::empty(location), 0..len)),
+        errors,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use lang_turtle::lang::{context::Context, tokenizer::parse_tokens_str_safe};
+
+    use super::*;
+
+    fn parse(txt: &str) -> (Spanned<TriG>, Vec<Simple<PToken>>) {
+        let url = lsp_types::Url::from_str("http://example.com/ns#").unwrap();
+        let tokens = parse_tokens_str_safe(txt).unwrap();
+        let context = Context::new();
+        parse_trig(&url, tokens, txt.len(), context.ctx())
+    }
+
+    #[test]
+    fn parse_graph_blocks() {
+        let (trig, errors) = parse(
+            "@prefix ex: <http://example.com/>.
+ex:a ex:p ex:b.
+GRAPH ex:g { ex:c ex:p ex:d. ex:e ex:p ex:f }
+_:g { ex:c ex:p ex:d. }
+{ ex:x ex:p ex:y }",
+        );
+
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(trig.prefixes.len(), 1);
+        assert_eq!(trig.blocks.len(), 4);
+
+        let Block::Graph(ref graph) = trig.blocks[1].0 else {
+            panic!("expected a graph block");
+        };
+        assert!(graph.kwd.is_some());
+        assert_eq!(graph.triples.len(), 2);
+
+        let Block::Graph(ref graph) = trig.blocks[3].0 else {
+            panic!("expected a graph block");
+        };
+        assert!(graph.name.is_none());
+        assert_eq!(trig.triples().count(), 5);
+    }
+
+    #[test]
+    fn unclosed_graph_block_is_recovered() {
+        let (trig, errors) = parse(
+            "@prefix ex: <http://example.com/>.
+ex:g { ex:c ex:p ex:d.",
+        );
+
+        assert!(!errors.is_empty());
+        assert_eq!(trig.triples().count(), 1);
+    }
+}
@@ -0,0 +1,93 @@
+#![doc(
+    html_logo_url = "https://ajuvercr.github.io/semantic-web-lsp/assets/icons/favicon.png",
+    html_favicon_url = "https://ajuvercr.github.io/semantic-web-lsp/assets/icons/favicon.ico"
+)]
+//! [TriG](https://www.w3.org/TR/trig/) support, built on top of [`lang_turtle`].
+//!
+//! TriG documents are tokenized with the Turtle tokenizer, triples are parsed with the Turtle
+//! parser and wrapped in graph blocks. All quads in a graph block get that graph as name.
+use bevy_ecs::{component::Component, observer::Trigger, system::Commands, world::World};
+use chumsky::prelude::Simple;
+use lang_turtle::TurtleLang;
+use lsp_core::{
+    feature::diagnostics::publish_diagnostics,
+    lang::{Lang, LangHelper},
+    prelude::*,
+    CreateEvent,
+};
+
+pub mod ecs;
+pub mod lang;
+
+use crate::ecs::{
+    setup_code_action, setup_completion, setup_formatting, setup_parsing, setup_symbols,
+};
+
+#[derive(Component)]
+pub struct TrigLang;
+
+#[derive(Debug)]
+pub struct TrigHelper;
+impl LangHelper for TrigHelper {
+    fn keyword(&self) -> &[&'static str] {
+        &["@prefix", "@base", "a", "GRAPH"]
+    }
+}
+
+pub fn setup_world(world: &mut World) {
+    let mut semantic_token_dict = world.resource_mut::<SemanticTokensDict>();
+    TrigLang::LEGEND_TYPES.iter().for_each(|lt| {
+        if !semantic_token_dict.contains_key(lt) {
+            let l = semantic_token_dict.0.len();
+            semantic_token_dict.insert(lt.clone(), l);
+        }
+    });
+
+    world.observe(|trigger: Trigger<CreateEvent>, mut commands: Commands| {
+        match &trigger.event().language_id {
+            Some(x) if x == "trig" => {
+                commands
+                    .entity(trigger.entity())
+                    .insert((TrigLang, DynLang(Box::new(TrigHelper))));
+                return;
+            }
+            _ => {}
+        }
+        if trigger.event().url.as_str().ends_with(".trig") {
+            commands
+                .entity(trigger.entity())
+                .insert((TrigLang, DynLang(Box::new(TrigHelper))));
+            return;
+        }
+    });
+
+    world.schedule_scope(lsp_core::feature::DiagnosticsLabel, |_, schedule| {
+        schedule.add_systems(publish_diagnostics::<TrigLang>);
+    });
+
+    setup_parsing(world);
+    setup_code_action(world);
+    setup_completion(world);
+    setup_formatting(world);
+    setup_symbols(world);
+}
+
+impl Lang for TrigLang {
+    type Token = Token;
+
+    type TokenError = Simple<char>;
+
+    type Element = crate::lang::model::TriG;
+
+    type ElementError = Simple<Token>;
+
+    const LANG: &'static str = "trig";
+
+    const TRIGGERS: &'static [&'static str] = &[":"];
+    const CODE_ACTION: bool = true;
+    const HOVER: bool = true;
+
+    const LEGEND_TYPES: &'static [lsp_types::SemanticTokenType] = TurtleLang::LEGEND_TYPES;
+
+    const PATTERN: Option<&'static str> = None;
+}
@@ -0,0 +1,31 @@
+use bevy_ecs::prelude::*;
+use lsp_core::{
+    components::*,
+    prelude::*,
+    systems::prefix::{prefix_code_action_helper, prefix_insert_edit, undefined_prefixes},
+};
+
+use super::parse::TurtleDocument;
+
+/// Offers to define undefined prefixes, the `@prefix` statement goes after the base statement.
+pub fn turtle_undefined_prefix_code_action<L: Lang + Component>(
+    mut query: Query<(
+        &Tokens,
+        &Element<L>,
+        &Prefixes,
+        &Label,
+        &RopeC,
+        &mut CodeActionRequest,
+    )>,
+) where
+    L::Element: TurtleDocument,
+{
+    for (tokens, turtle, prefixes, label, rope, mut req) in &mut query {
+        let base_end = turtle.0.value().base().map(|base| base.span().end);
+        let undefined = undefined_prefixes(&tokens.0, prefixes);
+        prefix_code_action_helper(undefined, &mut req, label, rope, |name, location| {
+            let statement = format!("@prefix {}: <{}>.\n", name, location);
+            Some(vec![prefix_insert_edit(statement, base_end, rope)?])
+        });
+    }
+}
@@ -1,44 +1,52 @@
 use bevy_ecs::prelude::*;
 use completion::{CompletionRequest, SimpleCompletion};
-use lsp_core::{components::*, prelude::*, systems::prefix::prefix_completion_helper};
+use lsp_core::{
+    components::*,
+    prelude::*,
+    systems::prefix::{prefix_completion_helper, prefix_insert_edit},
+};
 use lsp_types::CompletionItemKind;
 use tracing::debug;
 
-use crate::{lang::model::NamedNode, TurtleLang};
+use super::parse::TurtleDocument;
+use crate::lang::model::{Based, NamedNode};
 
-pub fn turtle_lov_undefined_prefix_completion(
+pub fn turtle_lov_undefined_prefix_completion<L: Lang + Component>(
     mut query: Query<(
         &TokenComponent,
-        &Element<TurtleLang>,
+        &Element<L>,
         &Prefixes,
+        &RopeC,
         &mut CompletionRequest,
     )>,
-) {
-    for (word, turtle, prefixes, mut req) in &mut query {
-        let mut start = Position::new(0, 0);
-
-        if turtle.base.is_some() {
-            start = Position::new(1, 0);
-        }
-
-        use lsp_types::{Position, Range};
+) where
+    L::Element: TurtleDocument,
+{
+    for (word, turtle, prefixes, rope, mut req) in &mut query {
+        let base_end = turtle.value().base().map(|base| base.span().end);
         prefix_completion_helper(word, prefixes, &mut req.0, |name, location| {
-            Some(vec![lsp_types::TextEdit {
-                range: Range::new(start.clone(), start),
-                new_text: format!("@prefix {}: <{}>.\n", name, location),
-            }])
+            let statement = format!("@prefix {}: <{}>.\n", name, location);
+            Some(vec![prefix_insert_edit(statement, base_end, rope)?])
         });
     }
 }
 
-pub fn subject_completion(
-    mut query: Query<(
-        &TokenComponent,
-        &Element<TurtleLang>,
-        &mut CompletionRequest,
-    )>,
+/// The IRI written with the first prefix of the document that matches it.
+fn shorten<T: Based>(document: &T, url: &str) -> Option<String> {
+    document
+        .prefixes()
+        .iter()
+        .flat_map(|prefix| prefix.shorten(document, url))
+        .next()
+}
+
+/// Completes prefixed names with the subjects of open documents.
+pub fn subject_completion<L: Lang + Component>(
+    mut query: Query<(&TokenComponent, &Element<L>, &mut CompletionRequest)>,
     triples: Query<(&Triples, &Label), With<Open>>,
-) {
+) where
+    L::Element: TurtleDocument,
+{
     for (word, turtle, mut req) in &mut query {
         let m_expaned = match word.token.value() {
             Token::PNameLN(pref, value) => NamedNode::Prefixed {
@@ -56,7 +64,8 @@ pub fn subject_completion(
                 debug!("Triple {} start with {}", triple.subject.as_str(), expanded);
                 let subj = triple.subject.as_str();
                 if subj.starts_with(&expanded) {
-                    let new_text = turtle.0.shorten(subj).unwrap_or_else(|| String::from(subj));
+                    let new_text =
+                        shorten(turtle.0.value(), subj).unwrap_or_else(|| String::from(subj));
 
                     if new_text != word.text {
                         req.push(
@@ -3,11 +3,13 @@ use lsp_core::{components::*, prelude::*};
 use lsp_types::{Position, Range};
 use tracing::info;
 
-use crate::{lang::formatter::format_turtle, TurtleLang};
+use super::parse::TurtleDocument;
 
-pub fn format_turtle_system(
-    mut query: Query<(&RopeC, &Element<TurtleLang>, &Tokens, &mut FormatRequest), Without<Dirty>>,
-) {
+pub fn format_turtle_system<L: Lang + Component>(
+    mut query: Query<(&RopeC, &Element<L>, &Tokens, &mut FormatRequest), Without<Dirty>>,
+) where
+    L::Element: TurtleDocument,
+{
     info!("Format turtle system");
 
     for (source, turtle, tokens, mut request) in &mut query {
@@ -23,8 +25,7 @@ pub fn format_turtle_system(
             .map(|Spanned(x, span)| Spanned(x.into_comment(), span))
             .collect();
 
-        let formatted = format_turtle(
-            &turtle.0,
+        let formatted = turtle.value().format(
             lsp_types::FormattingOptions {
                 tab_size: 2,
                 ..Default::default()
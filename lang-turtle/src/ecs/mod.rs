@@ -1,30 +1,50 @@
 use bevy_ecs::{prelude::*, system::Query, world::World};
-use completion::{subject_completion, turtle_lov_undefined_prefix_completion};
-use format::format_turtle_system;
+pub use code_action::turtle_undefined_prefix_code_action;
+pub use completion::{subject_completion, turtle_lov_undefined_prefix_completion};
+pub use format::format_turtle_system;
 use lsp_core::prelude::*;
-use parse::{derive_triples, parse_source, parse_turtle_system};
+use parse::{derive_prefixes, derive_triples, parse_source, parse_turtle_system};
+use symbol::turtle_document_symbols;
 
 use crate::TurtleLang;
 
+mod code_action;
 mod completion;
 mod format;
-mod parse;
+pub mod parse;
+mod symbol;
 
 pub fn setup_parsing(world: &mut World) {
     use lsp_core::feature::parse::*;
     world.schedule_scope(ParseLabel, |_, schedule| {
         schedule.add_systems((
-            parse_source,
-            parse_turtle_system.after(parse_source),
-            derive_prefixes.after(parse_turtle_system).before(prefixes),
-            derive_triples.after(parse_turtle_system).before(triples),
+            parse_source::<TurtleLang>,
+            parse_turtle_system::<TurtleLang>.after(parse_source::<TurtleLang>),
+            derive_prefixes::<TurtleLang>
+                .after(parse_turtle_system::<TurtleLang>)
+                .before(prefixes),
+            derive_triples::<TurtleLang>
+                .after(parse_turtle_system::<TurtleLang>)
+                .before(triples),
         ));
     });
 }
 
 pub fn setup_formatting(world: &mut World) {
     world.schedule_scope(FormatLabel, |_, schedule| {
-        schedule.add_systems(format_turtle_system);
+        schedule.add_systems(format_turtle_system::<TurtleLang>);
+    });
+}
+
+pub fn setup_code_action(world: &mut World) {
+    world.schedule_scope(CodeActionLabel, |_, schedule| {
+        schedule.add_systems(turtle_undefined_prefix_code_action::<TurtleLang>);
+    });
+}
+
+pub fn setup_symbols(world: &mut World) {
+    world.schedule_scope(DocumentSymbolLabel, |_, schedule| {
+        schedule.add_systems(turtle_document_symbols);
     });
 }
 
@@ -32,51 +52,21 @@ pub fn setup_completion(world: &mut World) {
     use lsp_core::feature::completion::*;
     world.schedule_scope(CompletionLabel, |_, schedule| {
         schedule.add_systems((
-            turtle_lov_undefined_prefix_completion.after(get_current_token),
-            subject_completion.after(get_current_token),
+            turtle_lov_undefined_prefix_completion::<TurtleLang>.after(get_current_token),
+            subject_completion::<TurtleLang>.after(get_current_token),
         ));
     });
 }
 
-fn derive_prefixes(
-    query: Query<(Entity, &Label, &Element<TurtleLang>), Changed<Element<TurtleLang>>>,
-    mut commands: Commands,
-) {
-    for (entity, url, turtle) in &query {
-        let prefixes: Vec<_> = turtle
-            .prefixes
-            .iter()
-            .flat_map(|prefix| {
-                let url = prefix.value.expand(turtle.value())?;
-                let url = lsp_types::Url::parse(&url).ok()?;
-                Some(Prefix {
-                    url,
-                    prefix: prefix.prefix.value().clone(),
-                })
-            })
-            .collect();
-
-        let base = turtle
-            .base
-            .as_ref()
-            .and_then(|b| {
-                b.0 .1
-                    .expand(turtle.value())
-                    .and_then(|x| lsp_types::Url::parse(&x).ok())
-            })
-            .unwrap_or(url.0.clone());
-
-        commands.entity(entity).insert(Prefixes(prefixes, base));
-    }
-}
-
 #[cfg(test)]
 mod tests {
+    use bevy_ecs::query::{With, Without};
     use chumsky::chain::Chain;
     use futures::executor::block_on;
     use lsp_core::{
         components::*,
         prelude::{diagnostics::DiagnosticItem, *},
+        Tasks,
     };
     use ropey::Rope;
     use test_log::test;
@@ -164,4 +154,256 @@ foa
         assert_eq!(links[0].0.as_str(), "file:///tmp/swls/test/foaf.ttl");
         assert_eq!(links[0].1, "prefix import");
     }
+
+    #[test]
+    fn undefined_prefix_code_action() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = "<a> foaf:name \"Arthur\".";
+        let entity = create_file(&mut world, t1, "http://example.com/ns#", "turtle", Open);
+
+        let range = lsp_types::Range::new(
+            lsp_types::Position::new(0, 0),
+            lsp_types::Position::new(0, 10),
+        );
+        world
+            .entity_mut(entity)
+            .insert(CodeActionRequest::new(range, vec![]));
+        world.run_schedule(CodeActionLabel);
+
+        let req = world
+            .entity_mut(entity)
+            .take::<CodeActionRequest>()
+            .expect("code action request");
+        // Conversions to the other formats are commands, they are offered as well
+        let actions: Vec<_> = req
+            .actions
+            .iter()
+            .filter(|x| !matches!(x, lsp_types::CodeActionOrCommand::CodeAction(a) if a.command.is_some()))
+            .collect();
+        assert_eq!(actions.len(), 1);
+
+        let lsp_types::CodeActionOrCommand::CodeAction(action) = actions[0] else {
+            panic!("expected a code action");
+        };
+        let edits = action
+            .edit
+            .as_ref()
+            .and_then(|x| x.changes.as_ref())
+            .and_then(|x| x.values().next())
+            .expect("edits");
+        assert_eq!(
+            edits[0].new_text,
+            "@prefix foaf: <http://xmlns.com/foaf/0.1/>.\n"
+        );
+    }
+
+    #[test]
+    fn convert_to_jsonld_command() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = "@prefix foaf: <http://xmlns.com/foaf/0.1/>.
+<http://example.com/me> a foaf:Person; foaf:name \"Arthur\".
+";
+        let entity = create_file(
+            &mut world,
+            t1,
+            "http://example.com/data.ttl",
+            "turtle",
+            Open,
+        );
+
+        let range = lsp_types::Range::new(
+            lsp_types::Position::new(0, 0),
+            lsp_types::Position::new(0, 0),
+        );
+        world
+            .entity_mut(entity)
+            .insert(CodeActionRequest::new(range, vec![]));
+        world.run_schedule(CodeActionLabel);
+        let req = world
+            .entity_mut(entity)
+            .take::<CodeActionRequest>()
+            .expect("code action request");
+        let titles: Vec<_> = req
+            .actions
+            .iter()
+            .filter_map(|x| match x {
+                lsp_types::CodeActionOrCommand::CodeAction(a) if a.command.is_some() => {
+                    Some(a.title.as_str())
+                }
+                _ => None,
+            })
+            .collect();
+        assert!(titles.contains(&"Convert to JSON-LD in a new document"));
+        assert!(titles.contains(&"Replace with N-Triples"));
+        assert!(!titles.iter().any(|x| x.contains("Turtle")));
+
+        let arguments = vec![
+            serde_json::Value::from("http://example.com/data.ttl"),
+            serde_json::Value::from("jsonld"),
+            serde_json::Value::from("new"),
+        ];
+        world.entity_mut(entity).insert(ExecuteCommandRequest::new(
+            feature::command::CONVERT.to_string(),
+            arguments,
+        ));
+        world.run_schedule(ExecuteCommandLabel);
+        let req = world
+            .entity_mut(entity)
+            .take::<ExecuteCommandRequest>()
+            .expect("execute command request");
+        assert_eq!(req.error, None);
+
+        let conversion: feature::command::Conversion =
+            serde_json::from_value(req.result.expect("result")).expect("conversion");
+        assert_eq!(conversion.uri.as_str(), "untitled:data.jsonld");
+        assert!(!conversion.replace);
+        assert!(conversion
+            .text
+            .contains("\"foaf\": \"http://xmlns.com/foaf/0.1/\""));
+        assert!(conversion.text.contains("\"@type\": \"foaf:Person\""));
+        assert!(conversion.text.contains("\"foaf:name\": \"Arthur\""));
+    }
+
+    #[test]
+    fn document_symbols() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = "@prefix foaf: <http://xmlns.com/foaf/0.1/>.
+<a> a foaf:Person;
+    foaf:name \"Arthur\";
+    foaf:knows [ foaf:name \"Jos\" ].
+<b> foaf:name \"Bert\".
+";
+        let entity = create_file(&mut world, t1, "http://example.com/ns#", "turtle", Open);
+
+        world
+            .entity_mut(entity)
+            .insert(DocumentSymbolRequest::default());
+        world.run_schedule(DocumentSymbolLabel);
+
+        let symbols = world
+            .entity_mut(entity)
+            .take::<DocumentSymbolRequest>()
+            .expect("document symbol request")
+            .0;
+
+        assert_eq!(symbols.len(), 3);
+        assert_eq!(symbols[0].name, "prefixes");
+        assert_eq!(symbols[0].children.as_ref().map(|x| x.len()), Some(1));
+
+        assert_eq!(symbols[1].name, "<a>");
+        assert_eq!(symbols[1].detail.as_deref(), Some("foaf:Person"));
+        let children = symbols[1].children.as_ref().expect("predicates");
+        assert_eq!(children.len(), 3);
+        assert_eq!(children[2].name, "foaf:knows");
+        assert_eq!(children[2].children.as_ref().map(|x| x.len()), Some(1));
+
+        assert_eq!(symbols[2].name, "<b>");
+        assert_eq!(symbols[2].detail, None);
+    }
+
+    #[test]
+    fn workspace_symbols() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = "@prefix me: <http://example.com/me#>.
+me:me <http://www.w3.org/2000/01/rdf-schema#label> \"Arthur\".
+";
+        let t2 = "@prefix ex: <http://example.com/ns#>.
+ex:you ex:knows ex:them.
+";
+        create_file(&mut world, t1, "http://example.com/one", "turtle", Open);
+        create_file(&mut world, t2, "http://example.com/two", "turtle", Open);
+
+        let mut find = |query: &str| {
+            let entity = world.spawn(WorkspaceSymbolRequest::new(query)).id();
+            world.run_schedule(WorkspaceSymbolLabel);
+            let mut names: Vec<_> = world
+                .entity_mut(entity)
+                .take::<WorkspaceSymbolRequest>()
+                .expect("workspace symbol request")
+                .symbols
+                .into_iter()
+                .map(|x| x.name)
+                .collect();
+            names.sort();
+            names
+        };
+
+        assert_eq!(find("arth"), vec![String::from("me:me")]);
+        assert_eq!(find("ex:"), vec![String::from("ex:you")]);
+        assert_eq!(find("").len(), 2);
+    }
+
+    #[test]
+    fn index_workspace_folders() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let mut root = std::env::temp_dir();
+        root.push("swls");
+        root.push("index-test");
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("shapes")).unwrap();
+        std::fs::create_dir_all(root.join("node_modules")).unwrap();
+        std::fs::write(root.join("shapes/a.ttl"), "<a> <b> <c>.").unwrap();
+        std::fs::write(root.join("node_modules/b.ttl"), "<a> <b> <c>.").unwrap();
+        std::fs::write(root.join("notes.txt"), "not rdf").unwrap();
+
+        let folder = lsp_types::WorkspaceFolder {
+            uri: lsp_types::Url::from_directory_path(&root).unwrap(),
+            name: String::from("index-test"),
+        };
+        world.trigger(systems::IndexWorkspace(vec![folder]));
+
+        let c = world.resource::<TestClient>().clone();
+        block_on(c.await_futures(|| world.run_schedule(Tasks)));
+
+        let mut indexed: Vec<_> = world
+            .query_filtered::<&Label, (With<Triples>, Without<Open>)>()
+            .iter(&world)
+            .filter(|l| l.0.as_str().contains("index-test"))
+            .map(|l| l.0.path().to_string())
+            .collect();
+        indexed.sort();
+
+        assert_eq!(indexed.len(), 1);
+        assert!(indexed[0].ends_with("shapes/a.ttl"));
+    }
+
+    #[test]
+    fn refresh_watched_documents() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let url = lsp_types::Url::parse("file:///workspace/vocab.ttl").unwrap();
+        let open = create_file(
+            &mut world,
+            "<a> <b> <c>.",
+            "file:///workspace/open.ttl",
+            "turtle",
+            Open,
+        );
+        let entity = create_file(&mut world, "<a> <b> <c>.", url.as_str(), "turtle", ());
+
+        let changed = systems::refresh_document(
+            &mut world,
+            url.clone(),
+            Some(String::from("<a> <b> <c>, <d>.")),
+        );
+        assert!(changed);
+        world.run_schedule(ParseLabel);
+        assert_eq!(
+            world.entity(entity).get::<Triples>().map(|x| x.len()),
+            Some(2)
+        );
+
+        // Open documents follow the editor, not the disk
+        let open_url = lsp_types::Url::parse("file:///workspace/open.ttl").unwrap();
+        assert!(!systems::refresh_document(&mut world, open_url, None));
+        assert!(world.get_entity(open).is_some());
+
+        assert!(systems::refresh_document(&mut world, url, None));
+        assert!(world.get_entity(entity).is_none());
+    }
 }
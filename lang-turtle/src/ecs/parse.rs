@@ -1,42 +1,117 @@
 use std::collections::HashMap;
 
 use bevy_ecs::prelude::*;
+use chumsky::prelude::Simple;
 use lsp_core::prelude::*;
 use tracing::{info, instrument};
 
-use crate::{
-    lang::{
-        context::{Context, TokenIdx},
-        parser::parse_turtle,
-        tokenizer::parse_tokens_str,
-    },
-    TurtleLang,
+use crate::lang::{
+    context::{Context, Ctx, TokenIdx},
+    formatter::format_turtle,
+    model::{Base, Based, Turtle},
+    parser::parse_turtle,
+    tokenizer::parse_tokens_str,
 };
 
+/// Model of a language that is parsed from Turtle tokens (i.e. Turtle and TriG).
+///
+/// The parse systems in this module are generic over the language marker, so these languages
+/// share tokenizing, parsing with context and deriving prefixes and triples.
+pub trait TurtleDocument: Based + Sized + Send + Sync + 'static {
+    fn parse(
+        location: &lsp_types::Url,
+        tokens: Vec<Spanned<Token>>,
+        len: usize,
+        ctx: Ctx<'_>,
+    ) -> (Spanned<Self>, Vec<Simple<PToken>>);
+
+    /// Whether parsing this language is enabled in the config.
+    fn enabled(config: &Config) -> bool;
+
+    fn base(&self) -> Option<&Spanned<Base>>;
+
+    fn set_context(&self, ctx: &mut Context);
+
+    fn triples(&self) -> Option<Vec<MyQuad<'static>>>;
+
+    fn format(
+        &self,
+        config: lsp_types::FormattingOptions,
+        comments: &[Spanned<String>],
+        source: &ropey::Rope,
+    ) -> Option<String>;
+}
+
+impl TurtleDocument for Turtle {
+    fn parse(
+        location: &lsp_types::Url,
+        tokens: Vec<Spanned<Token>>,
+        len: usize,
+        ctx: Ctx<'_>,
+    ) -> (Spanned<Self>, Vec<Simple<PToken>>) {
+        parse_turtle(location, tokens, len, ctx)
+    }
+
+    fn enabled(config: &Config) -> bool {
+        config.turtle.unwrap_or(true)
+    }
+
+    fn base(&self) -> Option<&Spanned<Base>> {
+        self.base.as_ref()
+    }
+
+    fn set_context(&self, ctx: &mut Context) {
+        Turtle::set_context(self, ctx)
+    }
+
+    fn triples(&self) -> Option<Vec<MyQuad<'static>>> {
+        let triples = self.get_simple_triples().ok()?;
+        Some(triples.iter().map(|x| x.to_owned()).collect())
+    }
+
+    fn format(
+        &self,
+        config: lsp_types::FormattingOptions,
+        comments: &[Spanned<String>],
+        source: &ropey::Rope,
+    ) -> Option<String> {
+        format_turtle(self, config, comments, source)
+    }
+}
+
 #[instrument(skip(query, commands), name = "parse_source")]
-pub fn parse_source(
-    query: Query<(Entity, &Source), (Changed<Source>, With<TurtleLang>)>,
+pub fn parse_source<L: Lang + Component>(
+    query: Query<
+        (
+            Entity,
+            &Source,
+            Option<&SourceChange>,
+            Option<&Tokens>,
+            Option<&Errors<Simple<char>>>,
+        ),
+        (Changed<Source>, With<L>),
+    >,
     mut commands: Commands,
 ) {
-    for (entity, source) in &query {
-        let (tok, es) = parse_tokens_str(source.0.as_str());
-        let t = Tokens(tok);
-        commands.entity(entity).insert(t);
-        commands.entity(entity).insert(Errors(es));
+    for (entity, source, change, old_tokens, old_errors) in &query {
+        commands.entity(entity).remove::<SourceChange>();
+        let old = old_tokens.zip(old_errors);
+        let (tok, es) = tokenize_source(&source.0, change, old, parse_tokens_str);
+        info!("tokenized  {} tokens ({} errors)", tok.len(), es.len());
+        commands.entity(entity).insert((Tokens(tok), Errors(es)));
     }
 }
 
 #[instrument(skip(query, commands, old), name = "parse_turtle")]
-pub fn parse_turtle_system(
-    query: Query<
-        (Entity, &Source, &Tokens, &Label, Option<&Open>),
-        (Changed<Tokens>, With<TurtleLang>),
-    >,
+pub fn parse_turtle_system<L: Lang + Component>(
+    query: Query<(Entity, &Source, &Tokens, &Label, Option<&Open>), (Changed<Tokens>, With<L>)>,
     mut commands: Commands,
     mut old: Local<HashMap<String, (Vec<Spanned<Token>>, Context)>>,
     config: Res<ServerConfig>,
-) {
-    if !config.config.turtle.unwrap_or(true) {
+) where
+    L::Element: TurtleDocument,
+{
+    if !L::Element::enabled(&config.config) {
         return;
     }
     for (entity, source, tokens, label, open) in &query {
@@ -52,19 +127,16 @@ pub fn parse_turtle_system(
         // First parse it without context
         // This assures that if the model is correct, the parser will parse it correctly
         let empty = Context::new();
-        let (turtle, es) = parse_turtle(&label.0, tokens.0.clone(), source.0.len(), empty.ctx());
+        let (turtle, es) =
+            L::Element::parse(&label.0, tokens.0.clone(), source.0.len(), empty.ctx());
         // If that didn't work, retry with the context
         let (turtle, es) = es.is_empty().then_some((turtle, es)).unwrap_or_else(|| {
-            parse_turtle(&label.0, tokens.0.clone(), source.0.len(), context.ctx())
+            L::Element::parse(&label.0, tokens.0.clone(), source.0.len(), context.ctx())
         });
 
         let es: Vec<_> = es.into_iter().map(|e| (e.map(|PToken(t, _)| t))).collect();
 
-        info!(
-            "{} triples ({} errors)",
-            turtle.value().triples.len(),
-            es.len()
-        );
+        info!("parsed {} ({} errors)", label.0.as_str(), es.len());
         if open.is_some() {
             for e in &es {
                 info!("Error {:?}", e);
@@ -75,28 +147,62 @@ pub fn parse_turtle_system(
         context.clear();
         turtle.set_context(context);
 
+        let element = Element::<L>(turtle);
         if es.is_empty() {
-            let element = Element::<TurtleLang>(turtle);
             commands
                 .entity(entity)
                 .insert((element, Errors(es)))
                 .remove::<Dirty>();
         } else {
-            let element = Element::<TurtleLang>(turtle);
             commands.entity(entity).insert((Errors(es), element, Dirty));
         }
     }
 }
 
 // #[instrument(skip(query, commands), name = "derive_triples")]
-pub fn derive_triples(
-    query: Query<(Entity, &Element<TurtleLang>), (Changed<Element<TurtleLang>>, With<TurtleLang>)>,
+pub fn derive_triples<L: Lang + Component>(
+    query: Query<(Entity, &Element<L>), (Changed<Element<L>>, With<L>)>,
     mut commands: Commands,
-) {
+) where
+    L::Element: TurtleDocument,
+{
     for (entity, turtle) in &query {
-        if let Ok(tripl) = turtle.0.get_simple_triples() {
-            let triples: Vec<_> = tripl.iter().map(|x| x.to_owned()).collect();
+        if let Some(triples) = turtle.value().triples() {
             commands.entity(entity).insert(Triples(triples));
         }
     }
 }
+
+pub fn derive_prefixes<L: Lang + Component>(
+    query: Query<(Entity, &Label, &Element<L>), Changed<Element<L>>>,
+    mut commands: Commands,
+) where
+    L::Element: TurtleDocument,
+{
+    for (entity, url, turtle) in &query {
+        let turtle = turtle.value();
+        let prefixes: Vec<_> = turtle
+            .prefixes()
+            .iter()
+            .flat_map(|prefix| {
+                let url = prefix.value.expand(turtle)?;
+                let url = lsp_types::Url::parse(&url).ok()?;
+                Some(Prefix {
+                    url,
+                    prefix: prefix.prefix.value().clone(),
+                })
+            })
+            .collect();
+
+        let base = turtle
+            .base()
+            .and_then(|b| {
+                b.0 .1
+                    .expand(turtle)
+                    .and_then(|x| lsp_types::Url::parse(&x).ok())
+            })
+            .unwrap_or(url.0.clone());
+
+        commands.entity(entity).insert(Prefixes(prefixes, base));
+    }
+}
@@ -0,0 +1,22 @@
+use bevy_ecs::prelude::*;
+use lsp_core::{components::*, prelude::*};
+
+use crate::{
+    lang::symbol::{prefixes_symbol, triple_symbol},
+    TurtleLang,
+};
+
+pub fn turtle_document_symbols(
+    mut query: Query<(&Element<TurtleLang>, &RopeC, &mut DocumentSymbolRequest)>,
+) {
+    for (turtle, rope, mut req) in &mut query {
+        let turtle = turtle.0.value();
+        req.0.extend(prefixes_symbol(&turtle.prefixes, &rope.0));
+        req.0.extend(
+            turtle
+                .triples
+                .iter()
+                .flat_map(|triple| triple_symbol(triple, turtle, &rope.0)),
+        );
+    }
+}
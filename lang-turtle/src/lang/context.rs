@@ -20,9 +20,22 @@ impl<'a> Index<usize> for TokenIdx<'a> {
 #[derive(Clone, Copy)]
 pub struct Ctx<'a> {
     context: &'a Context,
+    paths: bool,
 }
 
 impl<'a> Ctx<'a> {
+    /// Allows SPARQL property paths in predicate position, Turtle predicates are plain terms.
+    pub fn with_paths(self) -> Self {
+        Self {
+            paths: true,
+            ..self
+        }
+    }
+
+    pub fn paths(&self) -> bool {
+        self.paths
+    }
+
     pub fn find_was(&self, idx: usize) -> Option<ContextKind> {
         if let Some(idx) = self.context.current_to_prev.get(&idx) {
             if self.context.subjects.contains(idx) {
@@ -161,6 +174,9 @@ impl Context {
     }
 
     pub fn ctx<'a>(&'a self) -> Ctx<'a> {
-        Ctx { context: self }
+        Ctx {
+            context: self,
+            paths: false,
+        }
     }
 }
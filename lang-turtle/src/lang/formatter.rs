@@ -8,7 +8,9 @@ use lsp_types::FormattingOptions;
 use ropey::Rope;
 use tracing::info;
 
-use crate::lang::model::{Base, BlankNode, Term, Triple, Turtle, TurtlePrefix, PO};
+use crate::lang::model::{
+    Annotation, Base, BlankNode, QuotedTriple, Term, Triple, Turtle, TurtlePrefix, PO,
+};
 
 #[allow(unused)]
 pub fn format(tokens: &[&Token], options: FormattingOptions) -> String {
@@ -85,6 +87,10 @@ pub fn format(tokens: &[&Token], options: FormattingOptions) -> String {
             }
             Token::BracketOpen => line += "(",
             Token::BracketClose => line += ")",
+            Token::TripleOpen => line += "<<",
+            Token::TripleClose => line += ">>",
+            Token::AnnotationOpen => line += "{|",
+            Token::AnnotationClose => line += "|}",
             Token::DataTypeDelim => {
                 line += "^^";
             }
@@ -152,7 +158,20 @@ pub fn format(tokens: &[&Token], options: FormattingOptions) -> String {
 }
 
 type Buf = Cursor<Vec<u8>>;
-struct FormatState<'a> {
+
+/// Position in a [`FormatState`], see [`FormatState::checkpoint`].
+#[derive(Clone, Copy, Debug)]
+pub struct Checkpoint {
+    position: u64,
+    line_start: u64,
+    line_count: usize,
+    comments_idx: usize,
+}
+
+/// Formatting state that writes Turtle statements while keeping comments in place.
+///
+/// Exposed so that languages built on Turtle, like TriG, can format their own blocks.
+pub struct FormatState<'a> {
     indent_level: usize,
     indent: String,
     buf: Buf,
@@ -161,15 +180,16 @@ struct FormatState<'a> {
     comments_idx: usize,
     tail: Spanned<String>,
     line_count: usize,
+    variables: bool,
 }
 
 impl<'a> FormatState<'a> {
-    fn new(
+    pub fn new(
         options: FormattingOptions,
-        buf: Buf,
         comments: &'a [Spanned<String>],
         source: &'a Rope,
     ) -> Self {
+        let buf: Buf = Cursor::new(Vec::new());
         let mut indent = String::new();
         for _ in 0..options.tab_size {
             indent.push(' ');
@@ -188,10 +208,23 @@ impl<'a> FormatState<'a> {
             comments,
             comments_idx: 0,
             line_count: 0,
+            variables: false,
         }
     }
 
-    fn check_comments(&mut self, span: &Range<usize>) -> io::Result<bool> {
+    /// Allows writing variables, for SPARQL patterns that are written as Turtle triples.
+    pub fn with_variables(mut self) -> Self {
+        self.variables = true;
+        self
+    }
+
+    /// Indents with tabs instead of `tab_size` spaces.
+    pub fn with_tabs(mut self) -> Self {
+        self.indent = String::from("\t");
+        self
+    }
+
+    pub fn check_comments(&mut self, span: &Range<usize>) -> io::Result<bool> {
         println!("Checking comments with span {:?}", span);
         let mut first = true;
         loop {
@@ -208,10 +241,33 @@ impl<'a> FormatState<'a> {
         }
         Ok(!first)
     }
-    fn current_line_length(&self) -> u64 {
+    pub fn current_line_length(&self) -> u64 {
         self.buf.position() - self.line_start
     }
-    fn new_line(&mut self) -> io::Result<()> {
+    /// Remembers the current position, to retry writing with a different layout.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            position: self.buf.position(),
+            line_start: self.line_start,
+            line_count: self.line_count,
+            comments_idx: self.comments_idx,
+        }
+    }
+    /// Throws away everything written since `checkpoint`.
+    pub fn rewind(&mut self, checkpoint: Checkpoint) {
+        self.buf.get_mut().truncate(checkpoint.position as usize);
+        self.buf.set_position(checkpoint.position);
+        self.line_start = checkpoint.line_start;
+        self.line_count = checkpoint.line_count;
+        self.comments_idx = checkpoint.comments_idx;
+    }
+    pub fn line_count(&self) -> usize {
+        self.line_count
+    }
+    pub fn write_str(&mut self, st: &str) -> io::Result<()> {
+        write!(self.buf, "{}", st)
+    }
+    pub fn new_line(&mut self) -> io::Result<()> {
         self.line_count += 1;
         write!(self.buf, "\n")?;
         self.line_start = self.buf.position();
@@ -220,26 +276,20 @@ impl<'a> FormatState<'a> {
         }
         Ok(())
     }
-    fn inc(&mut self) {
+    pub fn inc(&mut self) {
         self.indent_level += 1;
     }
-    fn decr(&mut self) {
+    pub fn decr(&mut self) {
         self.indent_level -= 1;
     }
+    pub fn finish(self) -> Option<String> {
+        String::from_utf8(self.buf.into_inner()).ok()
+    }
 }
 
 impl FormatState<'_> {
     fn write_turtle(&mut self, turtle: &Turtle) -> io::Result<()> {
-        if let Some(ref b) = turtle.base {
-            self.check_comments(&b.1)?;
-            self.write_base(b)?;
-            self.new_line()?;
-        }
-        for p in &turtle.prefixes {
-            self.check_comments(&p.1)?;
-            self.write_prefix(p)?;
-            self.new_line()?;
-        }
+        self.write_directives(turtle.base.as_ref(), &turtle.prefixes)?;
 
         let mut prev_line = 0;
 
@@ -253,6 +303,30 @@ impl FormatState<'_> {
             self.new_line()?;
             // request_newline = t.0.po.len() > 1 || t.0.po[0].0.object.len() > 1;
         }
+
+        self.write_tail()
+    }
+
+    pub fn write_directives(
+        &mut self,
+        base: Option<&Spanned<Base>>,
+        prefixes: &[Spanned<TurtlePrefix>],
+    ) -> io::Result<()> {
+        if let Some(b) = base {
+            self.check_comments(&b.1)?;
+            self.write_base(b)?;
+            self.new_line()?;
+        }
+        for p in prefixes {
+            self.check_comments(&p.1)?;
+            self.write_prefix(p)?;
+            self.new_line()?;
+        }
+        Ok(())
+    }
+
+    /// Ends the document, writing all comments that are not yet written.
+    pub fn write_tail(&mut self) -> io::Result<()> {
         self.new_line()?;
 
         for i in self.comments_idx..self.comments.len() {
@@ -352,18 +426,21 @@ impl FormatState<'_> {
         Ok(())
     }
 
-    fn write_term(&mut self, term: &Term) -> io::Result<()> {
+    pub fn write_term(&mut self, term: &Term) -> io::Result<()> {
         match term {
             Term::Literal(s) => write!(self.buf, "{}", s)?,
             Term::BlankNode(b) => self.write_bnode(b)?,
             Term::NamedNode(n) => write!(self.buf, "{}", n)?,
             Term::Collection(ts) => self.write_collection(ts)?,
+            Term::Quoted(q) => self.write_quoted(q)?,
+            Term::Path(p) => write!(self.buf, "{}", p)?,
             Term::Invalid => {
                 return Err(io::Error::new(
                     io::ErrorKind::Other,
                     "cannot format turtle with invalid terms",
                 ))
             }
+            Term::Variable(x) if self.variables => write!(self.buf, "{}", x.0)?,
             Term::Variable(_) => {
                 return Err(io::Error::new(
                     io::ErrorKind::Other,
@@ -374,16 +451,46 @@ impl FormatState<'_> {
         Ok(())
     }
 
+    fn write_quoted(&mut self, quoted: &QuotedTriple) -> io::Result<()> {
+        write!(self.buf, "<< ")?;
+        self.write_term(&quoted.subject)?;
+        write!(self.buf, " ")?;
+        self.write_term(&quoted.predicate)?;
+        write!(self.buf, " ")?;
+        self.write_term(&quoted.object)?;
+        write!(self.buf, " >>")
+    }
+
+    fn write_annotation(&mut self, annotation: &Annotation) -> io::Result<()> {
+        write!(self.buf, " {{| ")?;
+        for (i, po) in annotation.po.iter().enumerate() {
+            if i > 0 {
+                write!(self.buf, "; ")?;
+            }
+            self.write_po(po)?;
+        }
+        write!(self.buf, " |}}")
+    }
+
+    /// Writes the object at index `i` followed by its annotation, if any.
+    fn write_object(&mut self, po: &PO, i: usize) -> io::Result<()> {
+        self.write_term(&po.object[i])?;
+        if let Some(annotation) = po.annotation(i) {
+            self.write_annotation(annotation)?;
+        }
+        Ok(())
+    }
+
     fn write_po(&mut self, po: &PO) -> io::Result<()> {
         write!(self.buf, "{} ", po.predicate.0)?;
-        self.write_term(&po.object[0])?;
+        self.write_object(po, 0)?;
         let mut should_indent = false;
 
         let start = self.buf.position();
         let current_line = self.line_count;
         for i in 1..po.object.len() {
             write!(self.buf, ", ")?;
-            self.write_term(&po.object[i])?;
+            self.write_object(po, i)?;
 
             if self.current_line_length() > 80 {
                 should_indent = true;
@@ -399,7 +506,7 @@ impl FormatState<'_> {
                 write!(self.buf, ",")?;
                 self.new_line()?;
                 self.check_comments(&po.object[i].1)?;
-                self.write_term(&po.object[i])?;
+                self.write_object(po, i)?;
             }
             self.decr();
         }
@@ -407,10 +514,12 @@ impl FormatState<'_> {
         Ok(())
     }
 
-    fn write_triple(&mut self, triple: &Triple) -> io::Result<()> {
+    pub fn write_triple(&mut self, triple: &Triple) -> io::Result<()> {
         match &triple.subject.0 {
             Term::BlankNode(bn) => self.write_bnode(bn)?,
             Term::NamedNode(n) => write!(self.buf, "{}", n)?,
+            Term::Quoted(q) => self.write_quoted(q)?,
+            Term::Variable(x) if self.variables => write!(self.buf, "{}", x.0)?,
             _ => write!(self.buf, "invalid")?,
         }
         write!(self.buf, " ")?;
@@ -451,8 +560,7 @@ pub fn format_turtle(
     comments: &[Spanned<String>],
     source: &Rope,
 ) -> Option<String> {
-    let buf: Buf = Cursor::new(Vec::new());
-    let mut state = FormatState::new(config, buf, comments, source);
+    let mut state = FormatState::new(config, comments, source);
     match state.write_turtle(turtle) {
         Ok(_) => info!("Format succesful"),
         Err(e) => {
@@ -460,7 +568,7 @@ pub fn format_turtle(
             return None;
         }
     }
-    String::from_utf8(state.buf.into_inner()).ok()
+    state.finish()
 }
 
 #[cfg(test)]
@@ -527,6 +635,32 @@ mod tests {
 [ ] a foaf:Name;
   foaf:knows <abc>.
 
+"#;
+
+        let url = lsp_types::Url::from_str("http://example.com/ns#").unwrap();
+        let (output, comments) = parse_turtle(txt, &url).expect("Simple");
+        let formatted = format_turtle(
+            &output,
+            lsp_types::FormattingOptions {
+                tab_size: 2,
+                ..Default::default()
+            },
+            &comments,
+            &Rope::from_str(txt),
+        )
+        .expect("formatting");
+        assert_eq!(formatted, expected);
+    }
+
+    #[test]
+    fn format_quoted_triples() {
+        let txt = r#"<<<a> <b> <c>>> <p> <o>.
+<a> <b> <c>   {|<src>   <d>|}.
+"#;
+
+        let expected = r#"<< <a> <b> <c> >> <p> <o>.
+<a> <b> <c> {| <src> <d> |}.
+
 "#;
 
         let url = lsp_types::Url::from_str("http://example.com/ns#").unwrap();
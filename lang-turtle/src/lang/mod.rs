@@ -11,6 +11,7 @@ pub mod model;
 // pub mod model2;
 pub mod parser;
 // pub mod parser2;
+pub mod symbol;
 pub mod tokenizer;
 
 pub fn parse_source(url: &Url, string: &str) -> (Option<Turtle>, Vec<String>) {
@@ -21,6 +21,8 @@ pub enum Literal {
     Numeric(String),
 }
 
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+
 impl Literal {
     pub fn plain_string(&self) -> String {
         match self {
@@ -29,6 +31,18 @@ impl Literal {
             Literal::Numeric(x) => x.clone(),
         }
     }
+
+    /// Datatype of the unquoted boolean and number shorthands.
+    pub fn shorthand_datatype(&self) -> Option<String> {
+        let ty = match self {
+            Literal::RDF(_) => return None,
+            Literal::Boolean(_) => "boolean",
+            Literal::Numeric(x) if x.contains(['e', 'E']) => "double",
+            Literal::Numeric(x) if x.contains('.') => "decimal",
+            Literal::Numeric(_) => "integer",
+        };
+        Some(format!("{}{}", XSD, ty))
+    }
 }
 impl Display for Literal {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -61,7 +75,7 @@ impl Display for RDFLiteral {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let quote = match self.quote_style {
             StringStyle::DoubleLong => "\"\"\"",
-            StringStyle::Double => "\"",
+            StringStyle::Double | StringStyle::Plain => "\"",
             StringStyle::SingleLong => "'''",
             StringStyle::Single => "'",
         };
@@ -188,6 +202,167 @@ impl Display for BlankNode {
     }
 }
 
+/// Quoted triple `<< s p o >>` from RDF-star.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QuotedTriple {
+    pub subject: Spanned<Term>,
+    pub predicate: Spanned<Term>,
+    pub object: Spanned<Term>,
+}
+
+impl QuotedTriple {
+    pub fn fix_spans(&mut self, len: usize) {
+        for term in [&mut self.subject, &mut self.predicate, &mut self.object] {
+            term.1 = rev_range(&term.1, len);
+            term.0.fix_spans(len);
+        }
+    }
+
+    pub fn set_context(&self, ctx: &mut Context) {
+        self.subject.set_context(ctx, ContextKind::Subject);
+        self.predicate.set_context(ctx, ContextKind::Predicate);
+        self.object.set_context(ctx, ContextKind::Object);
+    }
+}
+
+impl Display for QuotedTriple {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "<< {} {} {} >>",
+            self.subject.value(),
+            self.predicate.value(),
+            self.object.value()
+        )
+    }
+}
+
+/// Modifier after a path element, `*`, `+` or `?`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PathModifier {
+    ZeroOrMore,
+    OneOrMore,
+    ZeroOrOne,
+}
+
+impl Display for PathModifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PathModifier::ZeroOrMore => write!(f, "*"),
+            PathModifier::OneOrMore => write!(f, "+"),
+            PathModifier::ZeroOrOne => write!(f, "?"),
+        }
+    }
+}
+
+/// Property path from SPARQL, like `foaf:knows+/foaf:name` or `^ex:p`.
+///
+/// Only produced when parsing SPARQL, a path that is a single IRI stays a [`Term::NamedNode`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PropertyPath {
+    Iri(Spanned<NamedNode>),
+    /// `^path`
+    Inverse(Box<Spanned<PropertyPath>>),
+    /// `path / path`
+    Sequence(Vec<Spanned<PropertyPath>>),
+    /// `path | path`
+    Alternative(Vec<Spanned<PropertyPath>>),
+    Modified(Box<Spanned<PropertyPath>>, Spanned<PathModifier>),
+    /// `!iri` or `!(iri | ^iri)`, the elements are IRIs or inverse IRIs.
+    Negated(Vec<Spanned<PropertyPath>>),
+}
+
+impl PropertyPath {
+    /// All IRIs in this path, in order of appearance.
+    pub fn iris(&self) -> Vec<&Spanned<NamedNode>> {
+        let mut out = Vec::new();
+        self.collect_iris(&mut out);
+        out
+    }
+
+    fn collect_iris<'a>(&'a self, out: &mut Vec<&'a Spanned<NamedNode>>) {
+        match self {
+            PropertyPath::Iri(iri) => out.push(iri),
+            PropertyPath::Inverse(path) | PropertyPath::Modified(path, _) => path.collect_iris(out),
+            PropertyPath::Sequence(paths)
+            | PropertyPath::Alternative(paths)
+            | PropertyPath::Negated(paths) => paths.iter().for_each(|p| p.collect_iris(out)),
+        }
+    }
+
+    pub fn fix_spans(&mut self, len: usize) {
+        match self {
+            PropertyPath::Iri(iri) => iri.1 = rev_range(&iri.1, len),
+            PropertyPath::Inverse(path) => {
+                path.1 = rev_range(&path.1, len);
+                path.0.fix_spans(len);
+            }
+            PropertyPath::Modified(path, modifier) => {
+                path.1 = rev_range(&path.1, len);
+                path.0.fix_spans(len);
+                modifier.1 = rev_range(&modifier.1, len);
+            }
+            PropertyPath::Sequence(paths)
+            | PropertyPath::Alternative(paths)
+            | PropertyPath::Negated(paths) => paths.iter_mut().for_each(|path| {
+                path.1 = rev_range(&path.1, len);
+                path.0.fix_spans(len);
+            }),
+        }
+    }
+
+    pub fn set_context(&self, ctx: &mut Context) {
+        for iri in self.iris() {
+            match iri.value() {
+                NamedNode::Full(_, idx) | NamedNode::A(idx) | NamedNode::Prefixed { idx, .. } => {
+                    ctx.add(*idx, ContextKind::Predicate)
+                }
+                NamedNode::Invalid => {}
+            }
+        }
+    }
+}
+
+impl Display for PropertyPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn write_all(
+            f: &mut std::fmt::Formatter<'_>,
+            paths: &[Spanned<PropertyPath>],
+            sep: &str,
+        ) -> std::fmt::Result {
+            for (i, path) in paths.iter().enumerate() {
+                if i > 0 {
+                    write!(f, "{}", sep)?;
+                }
+                write!(f, "{}", path.value())?;
+            }
+            Ok(())
+        }
+        match self {
+            PropertyPath::Iri(iri) => write!(f, "{}", iri.value()),
+            PropertyPath::Inverse(path) => write!(f, "^{}", path.value()),
+            PropertyPath::Sequence(paths) => write_all(f, paths, "/"),
+            PropertyPath::Alternative(paths) => {
+                write!(f, "(")?;
+                write_all(f, paths, "|")?;
+                write!(f, ")")
+            }
+            PropertyPath::Modified(path, modifier) => match path.value() {
+                PropertyPath::Iri(_) | PropertyPath::Alternative(_) | PropertyPath::Negated(_) => {
+                    write!(f, "{}{}", path.value(), modifier.value())
+                }
+                _ => write!(f, "({}){}", path.value(), modifier.value()),
+            },
+            PropertyPath::Negated(paths) if paths.len() == 1 => write!(f, "!{}", paths[0].value()),
+            PropertyPath::Negated(paths) => {
+                write!(f, "!(")?;
+                write_all(f, paths, "|")?;
+                write!(f, ")")
+            }
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Term {
     Literal(Literal),
@@ -195,6 +370,8 @@ pub enum Term {
     NamedNode(NamedNode),
     Collection(Vec<Spanned<Term>>),
     Variable(Variable),
+    Quoted(Box<QuotedTriple>),
+    Path(Box<PropertyPath>),
     Invalid,
 }
 
@@ -214,6 +391,8 @@ impl Term {
                     po.set_context(ctx);
                 }
             }
+            Term::Quoted(quoted) => quoted.set_context(ctx),
+            Term::Path(path) => path.set_context(ctx),
             Term::BlankNode(BlankNode::Named(_, idx))
             | Term::Variable(Variable(_, idx))
             | Term::NamedNode(NamedNode::Full(_, idx))
@@ -231,6 +410,8 @@ impl Term {
                     span.0.fix_spans(len);
                 });
             }
+            Term::Quoted(quoted) => quoted.fix_spans(len),
+            Term::Path(path) => path.fix_spans(len),
             _ => {}
         }
     }
@@ -250,6 +431,7 @@ impl Term {
             Term::NamedNode(_) => true,
             Term::Invalid => true,
             Term::Collection(_) => true,
+            Term::Quoted(_) => true,
             _ => false,
         }
     }
@@ -257,6 +439,7 @@ impl Term {
         match self {
             Term::NamedNode(_) => true,
             Term::Variable(_) => true,
+            Term::Path(_) => true,
             Term::Invalid => true,
             _ => false,
         }
@@ -265,6 +448,7 @@ impl Term {
     pub fn is_object(&self) -> bool {
         match self {
             Term::NamedNode(NamedNode::A(_)) => false,
+            Term::Path(_) => false,
             Term::Variable(_) => true,
             Term::Invalid => true,
             Term::Collection(_) => true,
@@ -286,6 +470,8 @@ impl Term {
             Term::Collection(_) => "collection",
             Term::Invalid => "invalid",
             Term::Variable(_) => "variable",
+            Term::Quoted(_) => "quoted triple",
+            Term::Path(_) => "property path",
         }
     }
     pub fn expand<T: Based>(&self, turtle: &T) -> Option<String> {
@@ -316,6 +502,8 @@ impl Display for Term {
             }
             Term::Invalid => write!(f, "invalid"),
             Term::Variable(x) => write!(f, "{}", x.0),
+            Term::Quoted(x) => x.fmt(f),
+            Term::Path(x) => x.fmt(f),
         }
     }
 }
@@ -358,12 +546,47 @@ impl Display for Triple {
     }
 }
 
+/// Annotation `{| ... |}` from RDF-star, annotating one object of a [`PO`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Annotation {
+    /// Index of the annotated object in [`PO::object`].
+    pub object: usize,
+    pub po: Vec<Spanned<PO>>,
+}
+
+impl Display for Annotation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{{| ")?;
+        for (i, po) in self.po.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", po.value())?;
+        }
+        write!(f, " |}}")
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct PO {
     pub predicate: Spanned<Term>,
     pub object: Vec<Spanned<Term>>,
+    pub annotations: Vec<Spanned<Annotation>>,
 }
 impl PO {
+    pub fn new(predicate: Spanned<Term>, object: Vec<Spanned<Term>>) -> Self {
+        Self {
+            predicate,
+            object,
+            annotations: Vec::new(),
+        }
+    }
+
+    /// The annotation of the object at index `object`, if any.
+    pub fn annotation(&self, object: usize) -> Option<&Spanned<Annotation>> {
+        self.annotations.iter().find(|a| a.object == object)
+    }
+
     pub fn fix_spans(&mut self, len: usize) {
         self.predicate.1 = rev_range(&self.predicate.1, len);
         self.predicate.0.fix_spans(len);
@@ -372,6 +595,14 @@ impl PO {
             span.1 = rev_range(&span.1, len);
             span.0.fix_spans(len);
         });
+
+        self.annotations.iter_mut().for_each(|span| {
+            span.1 = rev_range(&span.1, len);
+            span.0.po.iter_mut().for_each(|po| {
+                po.1 = rev_range(&po.1, len);
+                po.0.fix_spans(len);
+            });
+        });
     }
 
     pub fn set_context(&self, ctx: &mut Context) {
@@ -380,15 +611,23 @@ impl PO {
         for o in &self.object {
             o.set_context(ctx, ContextKind::Object);
         }
+
+        for po in self.annotations.iter().flat_map(|a| &a.po) {
+            po.set_context(ctx);
+        }
     }
 }
 
 impl Display for PO {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} {}", self.predicate.value(), self.object[0].value())?;
+        write!(f, "{}", self.predicate.value())?;
 
-        for po in &self.object[1..] {
-            write!(f, ", {}", po.value())?;
+        for (i, o) in self.object.iter().enumerate() {
+            let sep = if i == 0 { " " } else { ", " };
+            write!(f, "{}{}", sep, o.value())?;
+            if let Some(annotation) = self.annotation(i) {
+                write!(f, " {}", annotation.value())?;
+            }
         }
 
         Ok(())
@@ -424,7 +663,7 @@ pub struct TurtlePrefix {
     pub value: Spanned<NamedNode>,
 }
 impl TurtlePrefix {
-    fn shorten<T: Based>(&self, turtle: &T, url: &str) -> Option<String> {
+    pub fn shorten<T: Based>(&self, turtle: &T, url: &str) -> Option<String> {
         let prefix_url = self.value.expand(turtle)?;
         let short = url.strip_prefix(&prefix_url)?;
         Some(format!("{}:{}", self.prefix.value(), short))
@@ -506,9 +745,14 @@ impl Turtle {
 
 pub struct TriplesBuilder<'a, T> {
     pub triples: Vec<MyQuad<'a>>,
+    /// Property paths between subject and object, only collected with
+    /// [`TriplesBuilder::keep_paths`].
+    pub paths: Vec<(MyTerm<'a>, &'a PropertyPath, MyTerm<'a>)>,
+    keep_paths: bool,
     blank_node: Box<dyn FnMut(std::ops::Range<usize>) -> MyTerm<'a>>,
     base: BaseIri<String>,
     based: &'a T,
+    graph: Option<MyTerm<'a>>,
 }
 
 impl<'a, T: Based> TriplesBuilder<'a, T> {
@@ -520,9 +764,45 @@ impl<'a, T: Based> TriplesBuilder<'a, T> {
         });
         Self {
             triples: vec![],
+            paths: vec![],
+            keep_paths: false,
             blank_node,
             base,
             based,
+            graph: None,
+        }
+    }
+
+    /// Property paths are collected in [`TriplesBuilder::paths`] instead of being approximated
+    /// with triples.
+    pub fn keep_paths(mut self) -> Self {
+        self.keep_paths = true;
+        self
+    }
+
+    /// Expands and resolves `node` against the prefixes and base.
+    pub fn resolve(&self, node: &NamedNode) -> Option<String> {
+        node.expand_step(self.based, HashSet::new())
+            .and_then(|n| self.base.resolve(n.as_str()).ok())
+            .map(|x| x.unwrap())
+    }
+
+    /// Literal term with its language tag or resolved datatype.
+    pub fn literal(&self, literal: &Literal, span: std::ops::Range<usize>) -> MyTerm<'a> {
+        let term = MyTerm::literal(literal.plain_string(), span);
+        match literal {
+            Literal::RDF(RDFLiteral {
+                lang: Some(lang), ..
+            }) => term.with_language(lang.clone()),
+            Literal::RDF(RDFLiteral { ty: Some(ty), .. }) => match self.resolve(ty) {
+                Some(ty) => term.with_datatype(ty),
+                None => term,
+            },
+            Literal::RDF(_) => term,
+            _ => match literal.shorthand_datatype() {
+                Some(ty) => term.with_datatype(ty),
+                None => term,
+            },
         }
     }
 
@@ -540,6 +820,7 @@ impl<'a, T: Based> TriplesBuilder<'a, T> {
                 subject: subject.clone(),
                 predicate: predicate.clone(),
                 object,
+                graph: self.graph.clone(),
                 span: span.clone(),
             };
 
@@ -547,7 +828,15 @@ impl<'a, T: Based> TriplesBuilder<'a, T> {
         }
         let mut first = true;
 
-        for Spanned(PO { predicate, object }, span2) in pos.iter() {
+        for Spanned(
+            PO {
+                predicate,
+                object,
+                annotations,
+            },
+            span2,
+        ) in pos.iter()
+        {
             let this_span = if first {
                 first = false;
                 span.clone()
@@ -555,6 +844,11 @@ impl<'a, T: Based> TriplesBuilder<'a, T> {
                 span2.clone()
             };
 
+            let path = match predicate.value() {
+                Term::Path(path) => Some(path.as_ref()),
+                _ => None,
+            };
+
             let predicate = if let Ok(node) = predicate
                 .value()
                 .expand_step(self.based, HashSet::new())
@@ -574,7 +868,7 @@ impl<'a, T: Based> TriplesBuilder<'a, T> {
             };
 
             let mut first_object = true;
-            for o in object.iter() {
+            for (i, o) in object.iter().enumerate() {
                 let this_span = if first_object {
                     first_object = false;
                     this_span.clone()
@@ -583,14 +877,84 @@ impl<'a, T: Based> TriplesBuilder<'a, T> {
                 };
                 let object = self.term_to_my_term(Ok(o.as_ref()))?;
 
+                if let Some(path) = path {
+                    if self.keep_paths {
+                        self.paths.push((subject.clone(), path, object));
+                    } else {
+                        self.handle_path(path, subject.clone(), object, this_span)?;
+                    }
+                    continue;
+                }
+
                 let quad = MyQuad {
                     subject: subject.clone(),
                     predicate: predicate.clone(),
-                    object,
+                    object: object.clone(),
+                    graph: self.graph.clone(),
                     span: this_span,
                 };
 
                 self.triples.push(quad);
+
+                for annotation in annotations.iter().filter(|a| a.object == i) {
+                    let reifier = self.reify(
+                        subject.clone(),
+                        predicate.clone(),
+                        object.clone(),
+                        annotation.span().clone(),
+                    );
+                    self.handle_po(&annotation.po, annotation.span().clone(), reifier)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// [`MyTerm`] has no paths, every IRI of the path becomes a predicate between `subject` and
+    /// `object`. Inverse paths swap both ends, the steps of a sequence are chained with fresh
+    /// blank nodes and negated property sets are skipped.
+    fn handle_path(
+        &mut self,
+        path: &'a PropertyPath,
+        subject: MyTerm<'a>,
+        object: MyTerm<'a>,
+        span: std::ops::Range<usize>,
+    ) -> Result<(), TurtleSimpleError> {
+        match path {
+            PropertyPath::Iri(iri) => {
+                let predicate = match self.resolve(iri) {
+                    Some(node) => MyTerm::named_node(node, iri.span().clone()),
+                    None => MyTerm::invalid(iri.span().clone()),
+                };
+
+                self.triples.push(MyQuad {
+                    subject,
+                    predicate,
+                    object,
+                    graph: self.graph.clone(),
+                    span,
+                });
+            }
+            PropertyPath::Inverse(path) => self.handle_path(path, object, subject, span)?,
+            PropertyPath::Modified(path, _) => self.handle_path(path, subject, object, span)?,
+            // A negated property set matches every other predicate, there is no triple to add
+            PropertyPath::Negated(_) => {}
+            PropertyPath::Alternative(paths) => {
+                for path in paths {
+                    self.handle_path(path, subject.clone(), object.clone(), span.clone())?;
+                }
+            }
+            PropertyPath::Sequence(paths) => {
+                let mut current = subject;
+                for (i, path) in paths.iter().enumerate() {
+                    let next = if i + 1 == paths.len() {
+                        object.clone()
+                    } else {
+                        (self.blank_node)(path.span().clone())
+                    };
+                    self.handle_path(path, current, next.clone(), span.clone())?;
+                    current = next;
+                }
             }
         }
         Ok(())
@@ -616,9 +980,7 @@ impl<'a, T: Based> TriplesBuilder<'a, T> {
                     .map(|x| x.unwrap())?,
                 span,
             ),
-            Ok(Spanned(Term::Literal(literal), span)) => {
-                MyTerm::literal(literal.plain_string(), span)
-            }
+            Ok(Spanned(Term::Literal(literal), span)) => self.literal(literal, span),
             Ok(Spanned(Term::BlankNode(bn), span)) => match bn {
                 BlankNode::Named(v, _) => MyTerm::blank_node(v, span),
                 BlankNode::Unnamed(v, _, _) => {
@@ -633,13 +995,61 @@ impl<'a, T: Based> TriplesBuilder<'a, T> {
                 }
             },
             Ok(Spanned(Term::Collection(terms), span)) => self.handle_collection(&terms, span)?,
+            Ok(Spanned(Term::Quoted(quoted), span)) => self.handle_quoted(quoted, span)?,
             Ok(Spanned(Term::Invalid, span)) => MyTerm::invalid(span),
+            Ok(Spanned(Term::Path(_), span)) => MyTerm::invalid(span),
             Err(x) => x,
         };
 
         Ok(object)
     }
 
+    /// [`MyTerm`] has no triple terms, so a quoted triple is reified on a fresh blank node with
+    /// `rdf:subject`, `rdf:predicate` and `rdf:object`.
+    fn reify(
+        &mut self,
+        subject: MyTerm<'a>,
+        predicate: MyTerm<'a>,
+        object: MyTerm<'a>,
+        span: std::ops::Range<usize>,
+    ) -> MyTerm<'a> {
+        let node = (self.blank_node)(span);
+
+        for (p, o) in [
+            (
+                "http://www.w3.org/1999/02/22-rdf-syntax-ns#subject",
+                subject,
+            ),
+            (
+                "http://www.w3.org/1999/02/22-rdf-syntax-ns#predicate",
+                predicate,
+            ),
+            ("http://www.w3.org/1999/02/22-rdf-syntax-ns#object", object),
+        ] {
+            let quad = MyQuad {
+                subject: node.clone(),
+                predicate: MyTerm::named_node(p, o.span.start..o.span.start),
+                span: o.span.clone(),
+                object: o,
+                graph: self.graph.clone(),
+            };
+            self.triples.push(quad);
+        }
+
+        node
+    }
+
+    fn handle_quoted(
+        &mut self,
+        quoted: &'a QuotedTriple,
+        span: std::ops::Range<usize>,
+    ) -> Result<MyTerm<'a>, TurtleSimpleError> {
+        let subject = self.term_to_my_term(Ok(quoted.subject.as_ref()))?;
+        let predicate = self.term_to_my_term(Ok(quoted.predicate.as_ref()))?;
+        let object = self.term_to_my_term(Ok(quoted.object.as_ref()))?;
+        Ok(self.reify(subject, predicate, object, span))
+    }
+
     fn handle_collection(
         &mut self,
         collection: &'a [Spanned<Term>],
@@ -660,6 +1070,7 @@ impl<'a, T: Based> TriplesBuilder<'a, T> {
                     prev.span.start..prev.span.start,
                 ),
                 object: self.term_to_my_term(Ok(Spanned(term, s.clone())))?,
+                graph: self.graph.clone(),
                 span: span.clone(),
             };
 
@@ -672,6 +1083,7 @@ impl<'a, T: Based> TriplesBuilder<'a, T> {
                     s.start..s.start,
                 ),
                 object: prev,
+                graph: self.graph.clone(),
                 span: s.clone(),
             };
 
@@ -711,6 +1123,7 @@ impl<'a, T: Based> TriplesBuilder<'a, T> {
             ),
             Term::Invalid => MyTerm::invalid(triple.subject.span().clone()),
             Term::Variable(var) => MyTerm::variable(&var.0, triple.subject.span().clone()),
+            Term::Quoted(quoted) => self.handle_quoted(quoted, triple.subject.span().clone())?,
 
             x => {
                 info!("Failed, unexpected {}", x.ty());
@@ -725,6 +1138,23 @@ impl<'a, T: Based> TriplesBuilder<'a, T> {
 
         Ok(())
     }
+
+    /// Ingests triples that belong to the graph `name`, `None` is the default graph.
+    pub fn ingest_graph(
+        &mut self,
+        name: Option<&'a Spanned<Term>>,
+        triples: &'a [Spanned<Triple>],
+    ) -> Result<(), TurtleSimpleError> {
+        let graph = name
+            .map(|name| self.term_to_my_term(Ok(name.as_ref())))
+            .transpose()?;
+        let previous = std::mem::replace(&mut self.graph, graph);
+
+        let out = triples.iter().try_for_each(|t| self.ingest(t));
+
+        self.graph = previous;
+        out
+    }
 }
 
 impl Turtle {
@@ -924,6 +1354,35 @@ _:internal_bnode_1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> <http://ex
         assert_eq!(triples.triples.len(), 7);
     }
 
+    #[test]
+    fn quoted_triples_are_reified() {
+        let txt = r#"
+<< <a> <b> <c> >> <src> <d>.
+<e> <f> <g> {| <src> <h> |}.
+"#;
+
+        let url = lsp_types::Url::from_str("http://example.com/ns#").unwrap();
+        let (output, _) = parse_turtle(txt, &url).expect("Quoted triples");
+        let triples = output.get_simple_triples().expect("Triples found");
+
+        let quads: HashSet<String> = triples
+            .iter()
+            .map(|triple| format!("{} {} {}.", triple.subject, triple.predicate, triple.object))
+            .collect();
+
+        let expected_quads: HashSet<String> = "_:internal_bnode_1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#subject> <http://example.com/a>.
+_:internal_bnode_1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#predicate> <http://example.com/b>.
+_:internal_bnode_1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#object> <http://example.com/c>.
+_:internal_bnode_1 <http://example.com/src> <http://example.com/d>.
+<http://example.com/e> <http://example.com/f> <http://example.com/g>.
+_:internal_bnode_2 <http://www.w3.org/1999/02/22-rdf-syntax-ns#subject> <http://example.com/e>.
+_:internal_bnode_2 <http://www.w3.org/1999/02/22-rdf-syntax-ns#predicate> <http://example.com/f>.
+_:internal_bnode_2 <http://www.w3.org/1999/02/22-rdf-syntax-ns#object> <http://example.com/g>.
+_:internal_bnode_2 <http://example.com/src> <http://example.com/h>.".split("\n").map(|x|x.trim()).map(String::from).collect();
+
+        assert_eq!(quads, expected_quads);
+    }
+
     #[test]
     fn owl_is_valid() {
         let txt = include_str!("../../../lov/prefixes/owl.ttl");
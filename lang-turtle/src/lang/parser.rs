@@ -4,8 +4,8 @@ use tracing::info;
 
 use super::context::{ContextKind, Ctx};
 use crate::lang::model::{
-    Base, BlankNode, Literal, NamedNode, RDFLiteral, Term, Triple, Turtle, TurtlePrefix, Variable,
-    PO,
+    Annotation, Base, BlankNode, Literal, NamedNode, PathModifier, PropertyPath, QuotedTriple,
+    RDFLiteral, Term, Triple, Turtle, TurtlePrefix, Variable, PO,
 };
 
 type S = std::ops::Range<usize>;
@@ -92,7 +92,7 @@ impl LiteralHelper {
     }
 }
 
-fn literal() -> impl Parser<PToken, Literal, Error = Simple<PToken, S>> + Clone {
+pub fn literal() -> impl Parser<PToken, Literal, Error = Simple<PToken, S>> + Clone {
     let lt = select! { PToken(Token::LangTag(x), _) => LiteralHelper::LangTag(x)};
 
     let dt = just(PToken(Token::DataTypeDelim, 0))
@@ -261,6 +261,7 @@ fn term<'a, const C: usize, T: 'a + Clone + Parser<PToken, BlankNode, Error = Si
     // .recover_with(skip_parser(empty().map(|_| Term::Invalid)))
     .ignore_then(recursive(|term| {
         let collection = term
+            .clone()
             .map_with_span(spanned)
             .repeated()
             .delimited_by(
@@ -269,20 +270,156 @@ fn term<'a, const C: usize, T: 'a + Clone + Parser<PToken, BlankNode, Error = Si
             )
             .map(|x| Term::Collection(x));
 
+        let quoted = term
+            .clone()
+            .map_with_span(spanned)
+            .then(term.clone().map_with_span(spanned))
+            .then(term.map_with_span(spanned))
+            .delimited_by(
+                just(PToken(Token::TripleOpen, 0)),
+                just(PToken(Token::TripleClose, 0)),
+            )
+            .map(|((subject, predicate), object)| {
+                Term::Quoted(Box::new(QuotedTriple {
+                    subject,
+                    predicate,
+                    object,
+                }))
+            });
+
         let nn = named_node().map(|x| Term::NamedNode(x));
         let blank = bn.map(|x| Term::BlankNode(x));
         let literal = literal().map(|x| Term::Literal(x));
         let variable = variable().map(|x| Term::Variable(x));
 
-        choice((collection, literal, nn, blank, variable))
+        choice((collection, quoted, literal, nn, blank, variable))
     }))
 }
 
-fn po<'a, T: Clone + Parser<PToken, BlankNode, Error = Simple<PToken>> + 'a>(
+fn path_op(expr: SparqlExpr) -> impl Parser<PToken, (), Error = Simple<PToken>> + Clone {
+    just(PToken(Token::SparqlExpr(expr), 0)).ignored()
+}
+
+/// SPARQL property path, the tokens are only produced by the SPARQL tokenizer.
+fn property_path() -> impl Parser<PToken, PropertyPath, Error = Simple<PToken>> + Clone {
+    let iri = select! {
+        PToken(Token::PredType, idx) => NamedNode::A(idx),
+        PToken(Token::IRIRef(x), idx) => NamedNode::Full(x, idx),
+        PToken(Token::PNameLN(x, b), idx) => NamedNode::Prefixed { prefix: x.unwrap_or_default(), value: b, idx },
+    }
+    .map_with_span(spanned)
+    .map(PropertyPath::Iri);
+
+    recursive(|path| {
+        let open = just(PToken(Token::BracketOpen, 0));
+        let close = just(PToken(Token::BracketClose, 0));
+
+        let negated_one = path_op(SparqlExpr::Hat)
+            .ignore_then(iri.clone().map_with_span(spanned))
+            .map(|x| PropertyPath::Inverse(Box::new(x)))
+            .or(iri.clone())
+            .map_with_span(spanned);
+        let negated = path_op(SparqlExpr::Exclamation)
+            .ignore_then(
+                negated_one.clone().map(|x| vec![x]).or(negated_one
+                    .separated_by(path_op(SparqlExpr::Pipe))
+                    .at_least(1)
+                    .delimited_by(open.clone(), close.clone())),
+            )
+            .map(PropertyPath::Negated);
+
+        let primary = iri
+            .or(negated)
+            .or(path.delimited_by(open, close))
+            .map_with_span(spanned);
+
+        let modifier = select! {
+            PToken(Token::SparqlExpr(SparqlExpr::Times), _) => PathModifier::ZeroOrMore,
+            PToken(Token::SparqlExpr(SparqlExpr::Plus), _) => PathModifier::OneOrMore,
+            PToken(Token::SparqlExpr(SparqlExpr::Question), _) => PathModifier::ZeroOrOne,
+        }
+        .map_with_span(spanned);
+
+        let elt = primary
+            .then(modifier.or_not())
+            .map_with_span(|(path, modifier), span| match modifier {
+                Some(modifier) => spanned(PropertyPath::Modified(Box::new(path), modifier), span),
+                None => path,
+            });
+
+        let inverse = path_op(SparqlExpr::Hat)
+            .ignore_then(elt.clone())
+            .map_with_span(|path, span| spanned(PropertyPath::Inverse(Box::new(path)), span))
+            .or(elt);
+
+        let sequence = inverse
+            .separated_by(path_op(SparqlExpr::Divide))
+            .at_least(1)
+            .map_with_span(|mut paths, span| {
+                if paths.len() == 1 {
+                    paths.pop().unwrap()
+                } else {
+                    spanned(PropertyPath::Sequence(paths), span)
+                }
+            });
+
+        sequence
+            .separated_by(path_op(SparqlExpr::Pipe))
+            .at_least(1)
+            .map(|mut paths| {
+                if paths.len() == 1 {
+                    paths.pop().unwrap().into_value()
+                } else {
+                    PropertyPath::Alternative(paths)
+                }
+            })
+    })
+}
+
+/// Predicate of a [`PO`], a plain [`Term`] or a SPARQL property path that is more than one IRI.
+///
+/// Property paths are only tried when `ctx` allows them, see [`Ctx::with_paths`].
+fn predicate<'a, T: Clone + Parser<PToken, BlankNode, Error = Simple<PToken>> + 'a>(
+    bn: T,
+    ctx: Ctx<'a>,
+) -> impl Parser<PToken, Term, Error = Simple<PToken>> + Clone + use<'a, T> {
+    let term = term(bn, ctx, [ContextKind::Subject]);
+    if !ctx.paths() {
+        return term.boxed();
+    }
+
+    property_path()
+        .try_map(|path, span: S| match path {
+            PropertyPath::Iri(_) => Err(Simple::custom(span, "Expected a property path")),
+            path => Ok(Term::Path(Box::new(path))),
+        })
+        .or(term)
+        .boxed()
+}
+
+/// Annotation `{| ... |}` after an object, `pos` parses the annotating predicate objects.
+fn annotation<'a, P: Clone + Parser<PToken, Vec<Spanned<PO>>, Error = Simple<PToken>> + 'a>(
+    pos: P,
+) -> impl Parser<PToken, Vec<Spanned<PO>>, Error = Simple<PToken>> + Clone + use<'a, P> {
+    let close = just(PToken(Token::AnnotationClose, 0))
+        .ignored()
+        .recover_with(skip_parser(empty()));
+
+    just(PToken(Token::AnnotationOpen, 0))
+        .ignore_then(pos)
+        .then_ignore(close)
+}
+
+fn po<
+    'a,
+    T: Clone + Parser<PToken, BlankNode, Error = Simple<PToken>> + 'a,
+    P: Clone + Parser<PToken, Vec<Spanned<PO>>, Error = Simple<PToken>> + 'a,
+>(
     bn: T,
     ctx: Ctx<'a>,
-) -> impl Parser<PToken, PO, Error = Simple<PToken>> + Clone + use<'a, T> {
-    term(bn.clone(), ctx, [ContextKind::Subject])
+    pos: P,
+) -> impl Parser<PToken, PO, Error = Simple<PToken>> + Clone + use<'a, T, P> {
+    predicate(bn.clone(), ctx)
         .labelled("predicate")
         .map_with_span(spanned)
         .then(
@@ -294,26 +431,44 @@ fn po<'a, T: Clone + Parser<PToken, BlankNode, Error = Simple<PToken>> + 'a>(
             .recover_with(skip_parser(empty().map(|_| Term::Invalid)))
             .labelled("object")
             .map_with_span(spanned)
+            .then(annotation(pos).map_with_span(spanned).or_not())
             .separated_by(just(PToken(Token::Comma, 0)).labelled("comma")), // .at_least(1),
         )
-        .map(|(predicate, object)| PO { predicate, object })
+        .map(|(predicate, objects)| {
+            let mut po = PO::new(predicate, Vec::with_capacity(objects.len()));
+            for (i, (object, annotation)) in objects.into_iter().enumerate() {
+                po.object.push(object);
+                if let Some(Spanned(po_list, span)) = annotation {
+                    po.annotations.push(spanned(
+                        Annotation {
+                            object: i,
+                            po: po_list,
+                        },
+                        span,
+                    ));
+                }
+            }
+            po
+        })
 }
 
 fn po_list<'a, T: Clone + Parser<PToken, BlankNode, Error = Simple<PToken>> + 'a>(
     bn: T,
     ctx: Ctx<'a>,
 ) -> impl Parser<PToken, Vec<Spanned<PO>>, Error = Simple<PToken>> + Clone + use<'a, T> {
-    po(bn, ctx)
-        .labelled("po")
-        .map_with_span(spanned)
-        .separated_by(
-            expect_token(Token::PredicateSplit, move |t| {
-                t.map(|t| ctx.was_predicate(t.1) || t.0.is_invalid())
-                    .unwrap_or_default()
-            })
-            .ignore_then(just([Token::PredicateSplit.into()]).repeated()),
-        )
-        .allow_trailing()
+    recursive(move |pos| {
+        po(bn, ctx, pos)
+            .labelled("po")
+            .map_with_span(spanned)
+            .separated_by(
+                expect_token(Token::PredicateSplit, move |t| {
+                    t.map(|t| ctx.was_predicate(t.1) || t.0.is_invalid())
+                        .unwrap_or_default()
+                })
+                .ignore_then(just([Token::PredicateSplit.into()]).repeated()),
+            )
+            .allow_trailing()
+    })
 }
 
 fn po_list_recovery<'a>(
@@ -321,18 +476,19 @@ fn po_list_recovery<'a>(
 ) -> impl Parser<PToken, Vec<Spanned<PO>>, Error = Simple<PToken>> + Clone + use<'a> {
     po_list(blank_node(ctx), ctx).recover_with(skip_parser(empty().map_with_span(|_, span: S| {
         vec![spanned(
-            PO {
-                predicate: spanned(Term::Invalid, span.clone()),
-                object: vec![spanned(Term::Invalid, span.clone())],
-            },
+            PO::new(
+                spanned(Term::Invalid, span.clone()),
+                vec![spanned(Term::Invalid, span.clone())],
+            ),
             span,
         )]
     })))
 }
 
-fn bn_triple(
-    ctx: Ctx<'_>,
-) -> impl Parser<PToken, Triple, Error = Simple<PToken>> + Clone + use<'_> {
+fn bn_triple<'a, E: Parser<PToken, (), Error = Simple<PToken>> + Clone + 'a>(
+    ctx: Ctx<'a>,
+    stop: E,
+) -> impl Parser<PToken, Triple, Error = Simple<PToken>> + Clone + use<'a, E> {
     let pos = po_list_recovery(ctx).validate(|po, span, emit| {
         if po.is_empty() {
             emit(Simple::custom(
@@ -340,10 +496,10 @@ fn bn_triple(
                 format!("Expected at least one predicate object."),
             ));
             vec![spanned(
-                PO {
-                    predicate: spanned(Term::Invalid, span.clone()),
-                    object: vec![spanned(Term::Invalid, span.clone())],
-                },
+                PO::new(
+                    spanned(Term::Invalid, span.clone()),
+                    vec![spanned(Term::Invalid, span.clone())],
+                ),
                 span,
             )]
         } else {
@@ -353,7 +509,7 @@ fn bn_triple(
     just([Token::SqOpen.into()])
         .ignore_then(pos)
         .then_ignore(just([Token::SqClose.into()]))
-        .then_ignore(expect_token(Token::Stop, |_| true))
+        .then_ignore(stop)
         .map_with_span(|pos, span| Triple {
             subject: spanned(Term::BlankNode(BlankNode::Unnamed(pos, 0, 0)), span),
             po: Vec::new(),
@@ -363,6 +519,14 @@ fn bn_triple(
 pub fn triple(
     ctx: Ctx<'_>,
 ) -> impl Parser<PToken, Triple, Error = Simple<PToken>> + Clone + use<'_> {
+    triple_with_stop(ctx, expect_token(Token::Stop, |_| true).ignored())
+}
+
+/// Parses a triple that is terminated by `stop`, instead of a required '.'.
+pub fn triple_with_stop<'a, E: Parser<PToken, (), Error = Simple<PToken>> + Clone + 'a>(
+    ctx: Ctx<'a>,
+    stop: E,
+) -> impl Parser<PToken, Triple, Error = Simple<PToken>> + Clone + use<'a, E> {
     let pos = po_list_recovery(ctx)
         .validate(|po, span, emit| {
             if po.is_empty() {
@@ -371,10 +535,10 @@ pub fn triple(
                     format!("Expected at least one predicate object."),
                 ));
                 vec![spanned(
-                    PO {
-                        predicate: spanned(Term::Invalid, span.clone()),
-                        object: vec![spanned(Term::Invalid, span.clone())],
-                    },
+                    PO::new(
+                        spanned(Term::Invalid, span.clone()),
+                        vec![spanned(Term::Invalid, span.clone())],
+                    ),
                     span,
                 )]
             } else {
@@ -387,7 +551,7 @@ pub fn triple(
         .labelled("subject")
         .map_with_span(spanned)
         .then(pos)
-        .then_ignore(expect_token(Token::Stop, |_| true))
+        .then_ignore(stop.clone())
         .map(|(subject, po)| Triple { subject, po })
         .validate(|this: Triple, _, emit| {
             for po in &this.po {
@@ -417,7 +581,7 @@ pub fn triple(
 
             this
         })
-        .or(bn_triple(ctx))
+        .or(bn_triple(ctx, stop))
 
     // expect_token(Token::Stop, |_| true)
     //     .ignore_then(po_list())
@@ -485,7 +649,7 @@ pub fn triple(
     //     .or(bn_triple())
 }
 
-fn base() -> impl Parser<PToken, Base, Error = Simple<PToken>> + Clone {
+pub fn base() -> impl Parser<PToken, Base, Error = Simple<PToken>> + Clone {
     let turtle_base = just([Token::BaseTag.into()])
         .map_with_span(|_, s| s)
         .then(named_node().map_with_span(spanned))
@@ -500,7 +664,7 @@ fn base() -> impl Parser<PToken, Base, Error = Simple<PToken>> + Clone {
     turtle_base.or(sparql_base)
 }
 
-fn prefix() -> impl Parser<PToken, TurtlePrefix, Error = Simple<PToken>> {
+pub fn prefix() -> impl Parser<PToken, TurtlePrefix, Error = Simple<PToken>> {
     let turtle_prefix = just([Token::PrefixTag.into()])
         .map_with_span(|_, s| s)
         .then(select! { |span| PToken(Token::PNameLN(x, _), _) => Spanned(x.unwrap_or_default(), span)})
@@ -751,6 +915,31 @@ pub mod turtle_tests {
         assert_eq!(output.to_string(), "<a> <b> [ ].");
     }
 
+    #[test]
+    fn parse_quoted_triples_and_annotations() {
+        let context = Context::new();
+        let ctx = context.ctx();
+
+        let turtle = "<< <a> <b> <c> >> <p> <o> .";
+        let output = parse_it(turtle, triple(ctx)).0.expect("quoted subject");
+        assert_eq!(output.to_string(), "<< <a> <b> <c> >> <p> <o>.");
+
+        let turtle = "<a> <b> << _:x <p> \"o\" >> .";
+        let output = parse_it(turtle, triple(ctx)).0.expect("quoted object");
+        assert_eq!(output.to_string(), "<a> <b> << _:x <p> \"o\" >>.");
+
+        let turtle = "<a> <b> <c> {| <src> <d>; <at> 5 |}, <e> .";
+        let (output, errors) = parse_it(turtle, triple(ctx));
+        assert!(errors.is_empty(), "{:?}", errors);
+        let output = output.expect("annotation");
+        assert_eq!(output.po[0].annotations.len(), 1);
+        assert_eq!(output.po[0].annotations[0].object, 0);
+        assert_eq!(
+            output.to_string(),
+            "<a> <b> <c> {| <src> <d>; <at> 5 |}, <e>."
+        );
+    }
+
     #[test]
     fn parse_triple_with_recovery_no_end() {
         let context = Context::new();
@@ -0,0 +1,113 @@
+use lsp_core::{feature::symbol::document_symbol, prelude::Spanned};
+use lsp_types::{DocumentSymbol, SymbolKind};
+use ropey::Rope;
+
+use super::model::{Based, BlankNode, Term, Triple, TurtlePrefix, PO};
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+
+/// Short name of a term, nested blank nodes and collections are not written out.
+pub fn term_name(term: &Term) -> String {
+    match term {
+        Term::BlankNode(BlankNode::Unnamed(_, _, _)) => String::from("[ ]"),
+        Term::Collection(_) => String::from("( )"),
+        x => x.to_string(),
+    }
+}
+
+fn is_type<T: Based>(po: &PO, based: &T) -> bool {
+    po.predicate.expand(based).as_deref() == Some(RDF_TYPE)
+}
+
+fn po_symbol<T: Based>(po: &Spanned<PO>, based: &T, rope: &Rope) -> Option<DocumentSymbol> {
+    let objects: Vec<_> = po.object.iter().map(|o| term_name(o.value())).collect();
+
+    // Nested blank nodes are shown as children of the predicate
+    let children = po
+        .object
+        .iter()
+        .flat_map(|o| match o.value() {
+            Term::BlankNode(BlankNode::Unnamed(pos, _, _)) => pos.as_slice(),
+            _ => &[],
+        })
+        .flat_map(|po| po_symbol(po, based, rope))
+        .collect();
+
+    document_symbol(
+        po.predicate.value().to_string(),
+        Some(objects.join(", ")),
+        SymbolKind::PROPERTY,
+        po.span(),
+        po.predicate.span(),
+        children,
+        rope,
+    )
+}
+
+/// Creates a symbol for a subject, with its types as detail and its predicates as children.
+pub fn triple_symbol<T: Based>(
+    triple: &Spanned<Triple>,
+    based: &T,
+    rope: &Rope,
+) -> Option<DocumentSymbol> {
+    let types: Vec<_> = triple
+        .po
+        .iter()
+        .filter(|po| is_type(po, based))
+        .flat_map(|po| po.object.iter().map(|o| term_name(o.value())))
+        .collect();
+
+    let children = triple
+        .po
+        .iter()
+        .flat_map(|po| po_symbol(po, based, rope))
+        .collect();
+
+    let kind = match triple.subject.value() {
+        Term::BlankNode(_) => SymbolKind::OBJECT,
+        Term::Variable(_) => SymbolKind::VARIABLE,
+        _ => SymbolKind::CLASS,
+    };
+
+    document_symbol(
+        term_name(triple.subject.value()),
+        (!types.is_empty()).then(|| types.join(", ")),
+        kind,
+        triple.span(),
+        triple.subject.span(),
+        children,
+        rope,
+    )
+}
+
+/// Groups all prefix declarations in one symbol.
+pub fn prefixes_symbol(prefixes: &[Spanned<TurtlePrefix>], rope: &Rope) -> Option<DocumentSymbol> {
+    let first = prefixes.first()?;
+    let last = prefixes.last()?;
+    let range = first.span().start..last.span().end;
+
+    let children = prefixes
+        .iter()
+        .flat_map(|prefix| {
+            document_symbol(
+                format!("{}:", prefix.prefix.value()),
+                Some(prefix.value.value().to_string()),
+                SymbolKind::NAMESPACE,
+                prefix.span(),
+                prefix.prefix.span(),
+                Vec::new(),
+                rope,
+            )
+        })
+        .collect();
+
+    document_symbol(
+        "prefixes",
+        None,
+        SymbolKind::NAMESPACE,
+        &range,
+        &range,
+        children,
+        rope,
+    )
+}
@@ -1,6 +1,6 @@
 use chumsky::prelude::*;
 use logos::Logos;
-use lsp_core::prelude::{spanned, Spanned, StringStyle, Token};
+use lsp_core::prelude::{spanned, Spanned, SparqlKeyword, StringStyle, Token};
 use token_helpers::*;
 
 #[allow(non_camel_case_types)]
@@ -31,6 +31,27 @@ enum TurtleToken {
     #[token(")")]
     BraceClose,
 
+    #[token("{")]
+    CurlOpen,
+
+    #[token("}")]
+    CurlClose,
+
+    #[token("<<")]
+    TripleOpen,
+
+    #[token(">>")]
+    TripleClose,
+
+    #[token("{|")]
+    AnnotationOpen,
+
+    #[token("|}")]
+    AnnotationClose,
+
+    #[token("GRAPH", ignore(case))]
+    Graph,
+
     #[token("a")]
     TypeTag,
 
@@ -118,6 +139,13 @@ pub fn parse_tokens_str<'a>(text: &'a str) -> (Vec<Spanned<Token>>, Vec<Simple<c
                     TurtleToken::SqClose => Token::SqClose,
                     TurtleToken::BraceOpen => Token::BracketOpen,
                     TurtleToken::BraceClose => Token::BracketClose,
+                    TurtleToken::CurlOpen => Token::CurlOpen,
+                    TurtleToken::CurlClose => Token::CurlClose,
+                    TurtleToken::TripleOpen => Token::TripleOpen,
+                    TurtleToken::TripleClose => Token::TripleClose,
+                    TurtleToken::AnnotationOpen => Token::AnnotationOpen,
+                    TurtleToken::AnnotationClose => Token::AnnotationClose,
+                    TurtleToken::Graph => Token::SparqlKeyword(SparqlKeyword::Graph),
                     TurtleToken::TypeTag => Token::PredType,
                     TurtleToken::Semi => Token::PredicateSplit,
                     TurtleToken::Comma => Token::Comma,
@@ -15,7 +15,9 @@ use lsp_types::SemanticTokenType;
 pub mod ecs;
 pub mod lang;
 
-use crate::ecs::{setup_completion, setup_formatting, setup_parsing};
+use crate::ecs::{
+    setup_code_action, setup_completion, setup_formatting, setup_parsing, setup_symbols,
+};
 
 #[derive(Component)]
 pub struct TurtleLang;
@@ -63,6 +65,8 @@ pub fn setup_world(world: &mut World) {
     setup_parsing(world);
     setup_completion(world);
     setup_formatting(world);
+    setup_code_action(world);
+    setup_symbols(world);
 }
 
 impl Lang for TurtleLang {
@@ -89,6 +93,7 @@ impl Lang for TurtleLang {
         SemanticTokenType::KEYWORD,
         SemanticTokenType::NAMESPACE,
         SemanticTokenType::NUMBER,
+        SemanticTokenType::OPERATOR,
         SemanticTokenType::PROPERTY,
         SemanticTokenType::STRING,
         SemanticTokenType::VARIABLE,
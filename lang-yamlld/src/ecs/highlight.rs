@@ -0,0 +1,38 @@
+use bevy_ecs::prelude::*;
+use lang_jsonld::lang::parser::Json;
+use lsp_core::{components::Element, feature::semantic::TokenTypesComponent, prelude::*};
+use lsp_types::SemanticTokenType;
+
+use crate::YamlLd;
+
+fn walk_json(json: &Spanned<Json>, ttc: &mut Vec<Spanned<SemanticTokenType>>) {
+    match json.value() {
+        Json::Token(Token::Str(x, _)) if x.starts_with('@') => {
+            ttc.push(Spanned(SemanticTokenType::KEYWORD, json.span().clone()));
+        }
+        Json::Array(xs) => xs.iter().for_each(|x| walk_json(x, ttc)),
+        Json::Object(ms) => {
+            for m in ms {
+                let field = m.field();
+                let ty = match field.value() {
+                    Token::Str(x, _) if x.starts_with('@') => SemanticTokenType::KEYWORD,
+                    _ => SemanticTokenType::PROPERTY,
+                };
+                ttc.push(Spanned(ty, field.span().clone()));
+                if let Some(value) = m.json_value() {
+                    walk_json(value, ttc);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Keys are highlighted as properties and keywords, unlike the other scalars.
+pub fn highlight_keys(
+    mut query: Query<(&Element<YamlLd>, &mut TokenTypesComponent), With<HighlightRequest>>,
+) {
+    for (yaml, mut ttc) in &mut query {
+        walk_json(&yaml.0, &mut ttc.0);
+    }
+}
@@ -0,0 +1,133 @@
+use bevy_ecs::{schedule::IntoSystemConfigs as _, world::World};
+use lang_jsonld::ecs::{derive_triples, load_remote_contexts, setup_remote_contexts};
+use lsp_core::prelude::*;
+
+use crate::YamlLd;
+
+mod highlight;
+pub use highlight::highlight_keys;
+
+mod parse;
+pub use parse::{parse_source, parse_yamlld_system};
+
+pub fn setup_parse(world: &mut World) {
+    use lsp_core::prelude::parse::*;
+    setup_remote_contexts(world);
+    world.schedule_scope(ParseLabel, |_, schedule| {
+        schedule.add_systems((
+            parse_source,
+            parse_yamlld_system.after(parse_source),
+            derive_triples::<YamlLd>
+                .after(parse_yamlld_system)
+                .after(load_remote_contexts)
+                .before(triples)
+                .before(prefixes),
+        ));
+    });
+}
+
+pub fn setup_semantic(world: &mut World) {
+    use lsp_core::prelude::semantic::*;
+    world.schedule_scope(SemanticLabel, |_, schedule| {
+        schedule.add_systems(
+            highlight_keys
+                .after(basic_semantic_tokens)
+                .before(semantic_tokens_system),
+        );
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use chumsky::prelude::Simple;
+    use lsp_core::{components::*, prelude::*};
+    use test_utils::{create_file, setup_world, TestClient};
+
+    use crate::YamlLd;
+
+    #[test_log::test]
+    fn parse_works() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = r#"
+"@context":
+  foaf: http://xmlns.com/foaf/0.1/
+"@id": http://example.com/ns#me
+foaf:name: Arthur
+foaf:knows:
+  - "@id": http://example.com/ns#you
+"#;
+        let entity = create_file(&mut world, t1, "http://example.com/ns#", "yamlld", Open);
+
+        assert!(world.entity(entity).get::<Element<YamlLd>>().is_some());
+        let errors = world
+            .entity(entity)
+            .get::<Errors<Simple<Token>>>()
+            .expect("errors exists");
+        assert!(errors.0.is_empty());
+
+        let triples = world
+            .entity(entity)
+            .get::<Triples>()
+            .expect("triples exists");
+        assert_eq!(triples.0.len(), 2);
+
+        let prefixes = world
+            .entity(entity)
+            .get::<Prefixes>()
+            .expect("prefixes exists");
+        assert!(prefixes.iter().any(|x| x.prefix == "foaf"));
+    }
+
+    #[test_log::test]
+    fn contexts_are_shared_with_jsonld() {
+        let (mut world, _) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = r#""@context":
+  name: http://xmlns.com/foaf/0.1/name
+"@tpye": foaf:Agent
+name: Arthur
+"#;
+        let entity = create_file(&mut world, t1, "http://example.com/ns#", "yamlld", Open);
+
+        let warnings = world
+            .entity(entity)
+            .get::<Errors<SimpleDiagnostic>>()
+            .expect("context diagnostics");
+        assert!(warnings
+            .0
+            .iter()
+            .any(|x| x.msg == "Unknown keyword @tpye, the entry is ignored"));
+
+        world.entity_mut(entity).insert((
+            HoverRequest::default(),
+            PositionComponent(lsp_types::Position::new(3, 2)),
+        ));
+        world.run_schedule(HoverLabel);
+        let hover = world
+            .entity_mut(entity)
+            .take::<HoverRequest>()
+            .expect("hover request");
+        assert!(hover
+            .0
+            .contains(&String::from("name: http://xmlns.com/foaf/0.1/name")));
+    }
+
+    #[test_log::test]
+    fn syntax_errors_are_published() {
+        let (mut world, mut rx) = setup_world(TestClient::new(), crate::setup_world);
+
+        let t1 = "\"@id\": http://example.com/ns#me\nfoaf:name\n";
+        create_file(&mut world, t1, "http://example.com/ns#", "yamlld", Open);
+        world.run_schedule(DiagnosticsLabel);
+
+        let mut diagnostics = Vec::new();
+        while let Ok(Some(item)) = rx.try_next() {
+            diagnostics = item.diagnostics;
+        }
+
+        assert!(diagnostics
+            .iter()
+            .any(|x| x.message == "Expected ':' after the key"));
+    }
+}
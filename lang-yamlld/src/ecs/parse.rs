@@ -0,0 +1,44 @@
+use bevy_ecs::prelude::*;
+use lsp_core::{components::*, prelude::Tokens};
+use tracing::{info, instrument};
+
+use crate::{
+    lang::{parser::parse, tokenizer::tokenize},
+    YamlLd,
+};
+
+#[instrument(skip(query, commands))]
+pub fn parse_source(
+    query: Query<(Entity, &Source), (Changed<Source>, With<YamlLd>)>,
+    mut commands: Commands,
+) {
+    for (entity, source) in &query {
+        let (tok, es) = tokenize(source.0.as_str());
+        info!("tokenized  {} tokens ({} errors)", tok.len(), es.len());
+        commands.entity(entity).insert((Tokens(tok), Errors(es)));
+    }
+}
+
+#[instrument(skip(query, commands))]
+pub fn parse_yamlld_system(
+    query: Query<(Entity, &Source, &Tokens, &Label), (Changed<Tokens>, With<YamlLd>)>,
+    mut commands: Commands,
+    config: Res<ServerConfig>,
+) {
+    if !config.config.yamlld.unwrap_or(true) {
+        return;
+    }
+    for (entity, source, tokens, label) in &query {
+        let (yamlld, es) = parse(source.as_str(), tokens.0.clone());
+        info!("{} parsed ({} errors)", label.0, es.len());
+        let element = Element::<YamlLd>(yamlld);
+        if es.is_empty() {
+            commands
+                .entity(entity)
+                .insert((element, Errors(es)))
+                .remove::<Dirty>();
+        } else {
+            commands.entity(entity).insert((Errors(es), element, Dirty));
+        }
+    }
+}
@@ -0,0 +1,471 @@
+//! Builds the [`Json`] tree of `lang_jsonld` from YAML tokens, so YAML-LD documents are
+//! expanded like JSON-LD documents.
+//!
+//! Block collections are delimited by the column of their first token, flow collections by
+//! their brackets. Keys that are not followed by a `:` yet become [`ObjectMember::Partial`].
+use std::ops::Range;
+
+use chumsky::prelude::Simple;
+use lang_jsonld::lang::parser::{Json, ObjectMember};
+use lsp_core::prelude::{Spanned, StringStyle, Token};
+
+/// A token together with the line and column it starts at.
+struct Tok {
+    token: Spanned<Token>,
+    line: usize,
+    col: usize,
+}
+
+fn is_scalar(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Str(_, _) | Token::Number(_) | Token::True | Token::False | Token::Null
+    )
+}
+
+pub fn parse(source: &str, tokens: Vec<Spanned<Token>>) -> (Spanned<Json>, Vec<Simple<Token>>) {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(
+            source
+                .chars()
+                .enumerate()
+                .filter(|(_, c)| *c == '\n')
+                .map(|(i, _)| i + 1),
+        )
+        .collect();
+
+    let tokens = tokens
+        .into_iter()
+        .filter(|t| !t.is_comment())
+        .map(|token| {
+            let line = line_starts.partition_point(|x| *x <= token.span().start) - 1;
+            let col = token.span().start - line_starts[line];
+            Tok { token, line, col }
+        })
+        .collect();
+
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        errors: Vec::new(),
+        end: source.chars().count(),
+    };
+    let json = parser.document();
+    (json, parser.errors)
+}
+
+struct Parser {
+    tokens: Vec<Tok>,
+    pos: usize,
+    errors: Vec<Simple<Token>>,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Tok> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_token(&self) -> Option<Token> {
+        self.peek().map(|t| t.token.value().clone())
+    }
+
+    fn next(&mut self) -> Option<Spanned<Token>> {
+        let token = self.tokens.get(self.pos)?.token.clone();
+        self.pos += 1;
+        Some(token)
+    }
+
+    fn null(&self, at: usize) -> Spanned<Json> {
+        Spanned(Json::Token(Token::Null), at..at)
+    }
+
+    fn unexpected(&mut self, token: &Spanned<Token>) {
+        self.errors.push(Simple::expected_input_found(
+            token.span().clone(),
+            [],
+            Some(token.value().clone()),
+        ));
+    }
+
+    /// Reports the next token as unexpected and skips the rest of its line.
+    fn skip_line(&mut self) {
+        let Some(line) = self.peek().map(|t| t.line) else {
+            return;
+        };
+        if let Some(token) = self.next() {
+            self.unexpected(&token);
+        }
+        while self.peek().is_some_and(|t| t.line == line) {
+            self.pos += 1;
+        }
+    }
+
+    /// Whether the token at `pos` is the key of a block mapping entry, a scalar followed by `:`
+    /// on the same line.
+    fn is_key(&self, pos: usize) -> bool {
+        let (Some(key), Some(colon)) = (self.tokens.get(pos), self.tokens.get(pos + 1)) else {
+            return false;
+        };
+        is_scalar(key.token.value())
+            && colon.token.value() == &Token::Colon
+            && colon.line == key.line
+    }
+
+    /// Keys are strings, whatever scalar they are written as.
+    fn key(&mut self) -> Option<Spanned<Token>> {
+        Some(self.next()?.map(|x| match x {
+            Token::Number(x) => Token::Str(x, StringStyle::Plain),
+            Token::True => Token::Str(String::from("true"), StringStyle::Plain),
+            Token::False => Token::Str(String::from("false"), StringStyle::Plain),
+            Token::Null => Token::Str(String::from("null"), StringStyle::Plain),
+            x => x,
+        }))
+    }
+
+    fn document(&mut self) -> Spanned<Json> {
+        let json = if self.peek().is_some() {
+            self.node()
+        } else {
+            self.null(0)
+        };
+        while self.peek().is_some() {
+            self.skip_line();
+        }
+        json
+    }
+
+    /// Parses the node that starts at the next token, block collections are indented at its
+    /// column.
+    fn node(&mut self) -> Spanned<Json> {
+        let Some(t) = self.peek() else {
+            return self.null(self.end);
+        };
+        let col = t.col;
+        if t.token.value() == &Token::Dash {
+            self.block_sequence(col)
+        } else if self.is_key(self.pos) {
+            self.block_mapping(col)
+        } else {
+            self.flow_node()
+        }
+    }
+
+    /// Parses the value of the entry at column `col` on `line`, after its `indicator` (`:` or
+    /// `-`). `in_mapping` is set for mapping values, these can be sequences at the same column
+    /// but can't be block collections on the line of their key.
+    fn value(
+        &mut self,
+        col: usize,
+        line: usize,
+        indicator: &Range<usize>,
+        in_mapping: bool,
+    ) -> Spanned<Json> {
+        let Some(t) = self.peek() else {
+            return self.null(indicator.end);
+        };
+
+        if t.line == line {
+            if in_mapping && (t.token.value() == &Token::Dash || self.is_key(self.pos)) {
+                let span = t.token.span().clone();
+                self.errors.push(Simple::custom(
+                    span,
+                    "Block collections cannot start on the line of their key",
+                ));
+            }
+            return self.node();
+        }
+
+        if t.col > col || (in_mapping && t.col == col && t.token.value() == &Token::Dash) {
+            return self.node();
+        }
+
+        self.null(indicator.end)
+    }
+
+    fn block_mapping(&mut self, col: usize) -> Spanned<Json> {
+        let start = self
+            .peek()
+            .map(|t| t.token.span().start)
+            .unwrap_or(self.end);
+        let mut end = start;
+        let mut members = Vec::new();
+
+        while let Some(t) = self.peek() {
+            if t.col < col {
+                break;
+            }
+            if t.col > col {
+                self.skip_line();
+                continue;
+            }
+
+            let line = t.line;
+            if self.is_key(self.pos) {
+                let Some(key) = self.key() else { break };
+                let Some(colon) = self.next() else { break };
+                let value = self.value(col, line, colon.span(), true);
+                end = value.span().end.max(colon.span().end);
+                let span = key.span().start..end;
+                members.push(Spanned(ObjectMember::Full(key, value), span));
+            } else if is_scalar(t.token.value()) {
+                // A key that is still being typed
+                let Some(key) = self.key() else { break };
+                self.errors.push(Simple::custom(
+                    key.span().clone(),
+                    "Expected ':' after the key",
+                ));
+                end = key.span().end;
+                let span = key.span().clone();
+                members.push(Spanned(ObjectMember::Partial(key, None, None), span));
+            } else if t.token.value() == &Token::Dash {
+                // A sequence at the column of the keys ends the mapping
+                break;
+            } else {
+                self.skip_line();
+            }
+        }
+
+        Spanned(Json::Object(members), start..end)
+    }
+
+    fn block_sequence(&mut self, col: usize) -> Spanned<Json> {
+        let start = self
+            .peek()
+            .map(|t| t.token.span().start)
+            .unwrap_or(self.end);
+        let mut end = start;
+        let mut items = Vec::new();
+
+        while let Some(t) = self.peek() {
+            if t.col < col || (t.col == col && t.token.value() != &Token::Dash) {
+                break;
+            }
+            if t.col > col {
+                self.skip_line();
+                continue;
+            }
+
+            let line = t.line;
+            let Some(dash) = self.next() else { break };
+            let item = self.value(col, line, dash.span(), false);
+            end = item.span().end.max(dash.span().end);
+            items.push(item);
+        }
+
+        Spanned(Json::Array(items), start..end)
+    }
+
+    fn flow_node(&mut self) -> Spanned<Json> {
+        match self.peek_token() {
+            None => self.null(self.end),
+            Some(Token::SqOpen) => self.flow_sequence(),
+            Some(Token::CurlOpen) => self.flow_mapping(),
+            Some(x) if is_scalar(&x) => self
+                .next()
+                .map(|x| x.map(Json::Token))
+                .unwrap_or_else(|| self.null(self.end)),
+            Some(_) => match self.next() {
+                Some(token) => {
+                    self.unexpected(&token);
+                    Spanned(Json::Invalid, token.span().clone())
+                }
+                None => self.null(self.end),
+            },
+        }
+    }
+
+    /// Reports an unclosed flow collection that was opened at `open`.
+    fn unclosed(&mut self, open: &Spanned<Token>, close: Token) {
+        self.errors.push(Simple::expected_input_found(
+            open.span().start..self.end,
+            [Some(close)],
+            None,
+        ));
+    }
+
+    /// After an entry of a flow collection, expects a `,` or the `close` token.
+    fn flow_separator(&mut self, close: &Token) {
+        match self.peek_token() {
+            Some(Token::Comma) => {
+                self.pos += 1;
+            }
+            Some(x) if &x == close => {}
+            None => {}
+            Some(_) => {
+                let token = self.peek().map(|t| t.token.clone()).expect("token");
+                self.errors.push(Simple::expected_input_found(
+                    token.span().clone(),
+                    [Some(Token::Comma), Some(close.clone())],
+                    Some(token.value().clone()),
+                ));
+            }
+        }
+    }
+
+    fn flow_sequence(&mut self) -> Spanned<Json> {
+        let Some(open) = self.next() else {
+            return self.null(self.end);
+        };
+        let mut items = Vec::new();
+        loop {
+            match self.peek_token() {
+                None => {
+                    self.unclosed(&open, Token::SqClose);
+                    return Spanned(Json::Array(items), open.span().start..self.end);
+                }
+                Some(Token::SqClose) => {
+                    let end = self.next().map(|x| x.span().end).unwrap_or(self.end);
+                    return Spanned(Json::Array(items), open.span().start..end);
+                }
+                Some(Token::Comma) => {
+                    if let Some(comma) = self.next() {
+                        self.unexpected(&comma);
+                    }
+                }
+                Some(_) => {
+                    items.push(self.flow_node());
+                    self.flow_separator(&Token::SqClose);
+                }
+            }
+        }
+    }
+
+    fn flow_mapping(&mut self) -> Spanned<Json> {
+        let Some(open) = self.next() else {
+            return self.null(self.end);
+        };
+        let mut members = Vec::new();
+        loop {
+            match self.peek_token() {
+                None => {
+                    self.unclosed(&open, Token::CurlClose);
+                    return Spanned(Json::Object(members), open.span().start..self.end);
+                }
+                Some(Token::CurlClose) => {
+                    let end = self.next().map(|x| x.span().end).unwrap_or(self.end);
+                    return Spanned(Json::Object(members), open.span().start..end);
+                }
+                Some(x) if is_scalar(&x) => {
+                    let Some(key) = self.key() else { continue };
+                    if self.peek_token() != Some(Token::Colon) {
+                        self.errors.push(Simple::custom(
+                            key.span().clone(),
+                            "Expected ':' after the key",
+                        ));
+                        let span = key.span().clone();
+                        members.push(Spanned(ObjectMember::Partial(key, None, None), span));
+                        self.flow_separator(&Token::CurlClose);
+                        continue;
+                    }
+
+                    let colon = self.next().expect("colon");
+                    let value = match self.peek_token() {
+                        Some(Token::Comma | Token::CurlClose) | None => self.null(colon.span().end),
+                        Some(_) => self.flow_node(),
+                    };
+                    let span = key.span().start..value.span().end.max(colon.span().end);
+                    members.push(Spanned(ObjectMember::Full(key, value), span));
+                    self.flow_separator(&Token::CurlClose);
+                }
+                Some(_) => {
+                    if let Some(token) = self.next() {
+                        self.unexpected(&token);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lang_jsonld::lang::parser::{Json, ObjectMember};
+    use lsp_core::prelude::{Spanned, Token};
+
+    use super::parse;
+    use crate::lang::tokenizer::tokenize;
+
+    fn parse_yaml(st: &str) -> (Spanned<Json>, usize) {
+        let (tokens, token_errors) = tokenize(st);
+        assert!(token_errors.is_empty(), "{:?}", token_errors);
+        let (json, errors) = parse(st, tokens);
+        (json, errors.len())
+    }
+
+    fn keys(json: &Json) -> Vec<String> {
+        let Json::Object(ms) = json else {
+            panic!("expected an object, found {:?}", json);
+        };
+        ms.iter()
+            .map(|m| match m.field().value() {
+                Token::Str(x, _) => x.clone(),
+                x => panic!("expected a string key, found {:?}", x),
+            })
+            .collect()
+    }
+
+    fn get<'a>(json: &'a Json, key: &str) -> &'a Json {
+        let Json::Object(ms) = json else {
+            panic!("expected an object");
+        };
+        ms.iter()
+            .find(|m| matches!(m.field().value(), Token::Str(x, _) if x == key))
+            .and_then(|m| m.json_value())
+            .map(|x| x.value())
+            .expect("member")
+    }
+
+    #[test]
+    fn nested_block_collections() {
+        let st = r#""@context":
+  foaf: http://xmlns.com/foaf/0.1/
+"@id": http://example.com/me
+foaf:knows:
+- foaf:name: Julian
+  foaf:age: 12
+- [a, {b: c}]
+empty:
+"#;
+        let (json, errors) = parse_yaml(st);
+        assert_eq!(errors, 0);
+        assert_eq!(keys(&json), vec!["@context", "@id", "foaf:knows", "empty"]);
+        assert_eq!(keys(get(&json, "@context")), vec!["foaf"]);
+        assert_eq!(get(&json, "empty"), &Json::Token(Token::Null));
+
+        let Json::Array(items) = get(&json, "foaf:knows") else {
+            panic!("expected an array");
+        };
+        assert_eq!(items.len(), 2);
+        assert_eq!(keys(items[0].value()), vec!["foaf:name", "foaf:age"]);
+        let Json::Array(flow) = items[1].value() else {
+            panic!("expected a flow sequence");
+        };
+        assert_eq!(flow.len(), 2);
+        assert_eq!(keys(flow[1].value()), vec!["b"]);
+    }
+
+    #[test]
+    fn partial_keys() {
+        let st = "foaf:name: Arthur\nfoaf:kno\n";
+        let (tokens, _) = tokenize(st);
+        let (json, errors) = parse(st, tokens);
+        assert_eq!(errors.len(), 1);
+
+        let Json::Object(ms) = json.value() else {
+            panic!("expected an object");
+        };
+        assert_eq!(ms.len(), 2);
+        assert!(matches!(
+            ms[1].value(),
+            ObjectMember::Partial(_, None, None)
+        ));
+    }
+
+    #[test]
+    fn invalid_indentation() {
+        let (json, errors) = parse_yaml("a: 1\n   b: 2\nc: 3\n");
+        assert_eq!(errors, 1);
+        assert_eq!(keys(json.value()), vec!["a", "c"]);
+    }
+}
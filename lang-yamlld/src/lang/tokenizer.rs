@@ -0,0 +1,587 @@
+//! Tokenizer for the YAML subset used by YAML-LD documents.
+//!
+//! Scalars become [`Token::Str`], [`Token::Number`], [`Token::True`], [`Token::False`] or
+//! [`Token::Null`] following the YAML core schema, plain scalars use [`StringStyle::Plain`].
+//! Block scalars (`|` and `>`) are read completely, their indentation is relative to the line of
+//! their header. Anchors, aliases, tags and complex keys are reported and skipped.
+use std::ops::Range;
+
+use chumsky::prelude::Simple;
+use lsp_core::prelude::{spanned, Spanned, StringStyle, Token};
+
+pub fn tokenize(st: &str) -> (Vec<Spanned<Token>>, Vec<Simple<char>>) {
+    let mut tokenizer = Tokenizer {
+        chars: st.chars().collect(),
+        pos: 0,
+        flow: 0,
+        tokens: Vec::new(),
+        errors: Vec::new(),
+    };
+    tokenizer.tokenize();
+    (tokenizer.tokens, tokenizer.errors)
+}
+
+fn is_blank(c: Option<char>) -> bool {
+    c.map_or(true, char::is_whitespace)
+}
+
+fn is_flow_indicator(c: char) -> bool {
+    matches!(c, ',' | '[' | ']' | '{' | '}')
+}
+
+fn is_number(st: &str) -> bool {
+    let digits = st.strip_prefix(['-', '+']).unwrap_or(st);
+    let (mantissa, exponent) = match digits.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+        None => (digits, None),
+    };
+    let exponent_valid = exponent.map_or(true, |x| {
+        let x = x.strip_prefix(['-', '+']).unwrap_or(x);
+        !x.is_empty() && x.chars().all(|c| c.is_ascii_digit())
+    });
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    exponent_valid
+        && !(int.is_empty() && frac.is_empty())
+        && int.chars().all(|c| c.is_ascii_digit())
+        && frac.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Resolves a plain scalar with the YAML core schema.
+fn plain_token(st: &str) -> Token {
+    match st {
+        "null" | "Null" | "NULL" | "~" => Token::Null,
+        "true" | "True" | "TRUE" => Token::True,
+        "false" | "False" | "FALSE" => Token::False,
+        x if is_number(x) => Token::Number(x.trim_start_matches('+').to_string()),
+        x => Token::Str(x.to_string(), StringStyle::Plain),
+    }
+}
+
+struct Tokenizer {
+    chars: Vec<char>,
+    pos: usize,
+    /// Depth of the flow collections (`[...]` and `{...}`) around the current position
+    flow: usize,
+    tokens: Vec<Spanned<Token>>,
+    errors: Vec<Simple<char>>,
+}
+
+impl Tokenizer {
+    fn peek_at(&self, idx: usize) -> Option<char> {
+        self.chars.get(idx).copied()
+    }
+
+    fn line_start(&self, idx: usize) -> usize {
+        self.chars[..idx]
+            .iter()
+            .rposition(|c| *c == '\n')
+            .map_or(0, |x| x + 1)
+    }
+
+    fn line_end(&self, idx: usize) -> usize {
+        self.chars[idx..]
+            .iter()
+            .position(|c| *c == '\n')
+            .map_or(self.chars.len(), |x| idx + x)
+    }
+
+    fn indent_of_line(&self, idx: usize) -> usize {
+        self.chars[self.line_start(idx)..]
+            .iter()
+            .take_while(|c| **c == ' ')
+            .count()
+    }
+
+    fn error(&mut self, span: Range<usize>, msg: &str) {
+        self.errors.push(Simple::custom(span, msg));
+    }
+
+    fn push(&mut self, token: Token, span: Range<usize>) {
+        self.tokens.push(spanned(token, span));
+    }
+
+    /// A `:` is an indicator when it is followed by a space, or directly follows a quoted scalar
+    /// or flow collection like in JSON.
+    fn is_colon_indicator(&self) -> bool {
+        let next = self.peek_at(self.pos + 1);
+        if is_blank(next) || (self.flow > 0 && next.is_some_and(is_flow_indicator)) {
+            return true;
+        }
+        self.tokens.last().is_some_and(|t| {
+            t.span().end == self.pos
+                && matches!(
+                    t.value(),
+                    Token::Str(_, StringStyle::Double | StringStyle::Single)
+                        | Token::SqClose
+                        | Token::CurlClose
+                )
+        })
+    }
+
+    fn tokenize(&mut self) {
+        while let Some(c) = self.peek_at(self.pos) {
+            let start = self.pos;
+            let at_line_start = self.line_start(start) == start;
+            let next = self.peek_at(start + 1);
+            match c {
+                c if c.is_whitespace() => self.pos += 1,
+                '#' => {
+                    let end = self.line_end(start);
+                    let comment = self.chars[start..end].iter().collect();
+                    self.push(Token::Comment(comment), start..end);
+                    self.pos = end;
+                }
+                '-' | '.' if at_line_start && self.is_document_marker(c) => {
+                    if c == '-' && self.tokens.iter().any(|t| !t.is_comment()) {
+                        self.error(start..start + 3, "Only a single YAML document is supported");
+                    }
+                    self.pos += 3;
+                }
+                '%' if at_line_start => self.pos = self.line_end(start),
+                '-' if is_blank(next) => {
+                    self.push(Token::Dash, start..start + 1);
+                    self.pos += 1;
+                }
+                '?' if is_blank(next) => {
+                    self.error(start..start + 1, "Complex mapping keys are not supported");
+                    self.pos += 1;
+                }
+                ':' if self.is_colon_indicator() => {
+                    self.push(Token::Colon, start..start + 1);
+                    self.pos += 1;
+                }
+                '[' | '{' => {
+                    self.flow += 1;
+                    let token = if c == '[' {
+                        Token::SqOpen
+                    } else {
+                        Token::CurlOpen
+                    };
+                    self.push(token, start..start + 1);
+                    self.pos += 1;
+                }
+                ']' | '}' => {
+                    self.flow = self.flow.saturating_sub(1);
+                    let token = if c == ']' {
+                        Token::SqClose
+                    } else {
+                        Token::CurlClose
+                    };
+                    self.push(token, start..start + 1);
+                    self.pos += 1;
+                }
+                ',' if self.flow > 0 => {
+                    self.push(Token::Comma, start..start + 1);
+                    self.pos += 1;
+                }
+                '"' => self.double_quoted(),
+                '\'' => self.single_quoted(),
+                '|' | '>' if self.flow == 0 => self.block_scalar(c == '|'),
+                '&' | '*' | '!' => {
+                    let mut end = start + 1;
+                    while let Some(c) = self.peek_at(end) {
+                        if c.is_whitespace() || (self.flow > 0 && is_flow_indicator(c)) {
+                            break;
+                        }
+                        end += 1;
+                    }
+                    let msg = match c {
+                        '&' => "Anchors are not supported",
+                        '*' => "Aliases are not supported",
+                        _ => "Tags are not supported",
+                    };
+                    self.error(start..end, msg);
+                    self.pos = end;
+                }
+                '@' | '`' => {
+                    self.error(
+                        start..start + 1,
+                        "Plain scalars cannot start with a reserved indicator, quote the scalar",
+                    );
+                    self.plain();
+                }
+                _ => self.plain(),
+            }
+        }
+    }
+
+    /// `---` starts and `...` ends a document.
+    fn is_document_marker(&self, c: char) -> bool {
+        self.peek_at(self.pos + 1) == Some(c)
+            && self.peek_at(self.pos + 2) == Some(c)
+            && is_blank(self.peek_at(self.pos + 3))
+    }
+
+    fn plain(&mut self) {
+        let start = self.pos;
+        let mut end = start;
+        let mut idx = start;
+        while let Some(c) = self.peek_at(idx) {
+            if c == '\n' || c == '\r' {
+                break;
+            }
+            if c == ':' {
+                let next = self.peek_at(idx + 1);
+                if is_blank(next) || (self.flow > 0 && next.is_some_and(is_flow_indicator)) {
+                    break;
+                }
+            }
+            if c == '#' && idx > start && self.chars[idx - 1].is_whitespace() {
+                break;
+            }
+            if self.flow > 0 && is_flow_indicator(c) {
+                break;
+            }
+            idx += 1;
+            if !c.is_whitespace() {
+                end = idx;
+            }
+        }
+
+        let text: String = self.chars[start..end].iter().collect();
+        self.push(plain_token(&text), start..end);
+        self.pos = end;
+    }
+
+    /// Folds the line breaks of a quoted scalar, a single break becomes a space.
+    fn fold(&mut self, out: &mut String) {
+        while out.ends_with([' ', '\t']) {
+            out.pop();
+        }
+        let mut breaks = 0;
+        while let Some(c) = self.peek_at(self.pos) {
+            match c {
+                '\n' => breaks += 1,
+                ' ' | '\t' | '\r' => {}
+                _ => break,
+            }
+            self.pos += 1;
+        }
+        if breaks == 1 {
+            out.push(' ');
+        }
+        for _ in 1..breaks {
+            out.push('\n');
+        }
+    }
+
+    fn single_quoted(&mut self) {
+        let start = self.pos;
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            match self.peek_at(self.pos) {
+                None => {
+                    self.error(start..self.pos, "Unterminated string, expected '");
+                    break;
+                }
+                Some('\'') if self.peek_at(self.pos + 1) == Some('\'') => {
+                    out.push('\'');
+                    self.pos += 2;
+                }
+                Some('\'') => {
+                    self.pos += 1;
+                    break;
+                }
+                Some('\n') => self.fold(&mut out),
+                Some(c) => {
+                    out.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+        self.push(Token::Str(out, StringStyle::Single), start..self.pos);
+    }
+
+    fn double_quoted(&mut self) {
+        let start = self.pos;
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            match self.peek_at(self.pos) {
+                None => {
+                    self.error(start..self.pos, "Unterminated string, expected \"");
+                    break;
+                }
+                Some('"') => {
+                    self.pos += 1;
+                    break;
+                }
+                Some('\\') => self.escape(&mut out),
+                Some('\n') => self.fold(&mut out),
+                Some(c) => {
+                    out.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+        self.push(Token::Str(out, StringStyle::Double), start..self.pos);
+    }
+
+    fn escape(&mut self, out: &mut String) {
+        let start = self.pos;
+        self.pos += 2;
+        let digits = match self.peek_at(start + 1) {
+            Some('x') => 2,
+            Some('u') => 4,
+            Some('U') => 8,
+            Some('\n') => {
+                // An escaped line break joins the lines
+                while self
+                    .peek_at(self.pos)
+                    .is_some_and(|c| c == ' ' || c == '\t')
+                {
+                    self.pos += 1;
+                }
+                return;
+            }
+            Some(c) => {
+                let escaped = match c {
+                    '0' => '\0',
+                    'a' => '\x07',
+                    'b' => '\x08',
+                    't' | '\t' => '\t',
+                    'n' => '\n',
+                    'v' => '\x0B',
+                    'f' => '\x0C',
+                    'r' => '\r',
+                    'e' => '\x1B',
+                    'N' => '\u{85}',
+                    '_' => '\u{A0}',
+                    'L' => '\u{2028}',
+                    'P' => '\u{2029}',
+                    ' ' | '"' | '/' | '\\' => c,
+                    _ => {
+                        self.error(start..self.pos, "Invalid escape sequence");
+                        c
+                    }
+                };
+                out.push(escaped);
+                return;
+            }
+            None => {
+                self.pos = start + 1;
+                return;
+            }
+        };
+
+        let mut end = self.pos;
+        while end < self.pos + digits && self.peek_at(end).is_some_and(|c| c.is_ascii_hexdigit()) {
+            end += 1;
+        }
+        let hex: String = self.chars[self.pos..end].iter().collect();
+        self.pos = end;
+        match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+            Some(c) if hex.len() == digits => out.push(c),
+            _ => {
+                self.error(start..end, "Invalid escape sequence");
+                out.push('\u{FFFD}');
+            }
+        }
+    }
+
+    /// Reads a literal (`|`) or folded (`>`) block scalar, with its chomping (`-`, `+`) and
+    /// indentation indicators.
+    fn block_scalar(&mut self, literal: bool) {
+        let start = self.pos;
+        let parent = self.indent_of_line(start);
+        self.pos += 1;
+
+        let mut chomp = None;
+        let mut explicit = None;
+        while let Some(c) = self.peek_at(self.pos) {
+            match c {
+                '-' | '+' if chomp.is_none() => chomp = Some(c),
+                '1'..='9' if explicit.is_none() => explicit = c.to_digit(10),
+                _ => break,
+            }
+            self.pos += 1;
+        }
+
+        let header_end = self.line_end(self.pos);
+        let mut comment = None;
+        if let Some(offset) = self.chars[self.pos..header_end]
+            .iter()
+            .position(|c| !c.is_whitespace())
+        {
+            let idx = self.pos + offset;
+            if self.chars[idx] == '#' {
+                let text = self.chars[idx..header_end].iter().collect();
+                comment = Some(spanned(Token::Comment(text), idx..header_end));
+            } else {
+                self.error(
+                    idx..header_end,
+                    "Unexpected content after the block scalar header",
+                );
+            }
+        }
+
+        let mut indent = explicit.map(|x| parent + x as usize);
+        let mut lines: Vec<String> = Vec::new();
+        let mut end = header_end;
+        let mut idx = header_end;
+        while idx < self.chars.len() {
+            let line_start = idx + 1;
+            let line_end = self.line_end(line_start.min(self.chars.len()));
+            let line = &self.chars[line_start.min(line_end)..line_end];
+            if line.iter().all(|c| c.is_whitespace()) {
+                lines.push(String::new());
+                idx = line_end;
+                continue;
+            }
+
+            let spaces = line.iter().take_while(|c| **c == ' ').count();
+            let indent = *indent.get_or_insert(spaces);
+            if spaces < indent || indent <= parent {
+                break;
+            }
+            lines.push(line[indent..].iter().collect());
+            end = line_end;
+            idx = line_end;
+        }
+
+        let trailing = lines.iter().rev().take_while(|x| x.is_empty()).count();
+        lines.truncate(lines.len() - trailing);
+
+        let mut value = String::new();
+        for (i, line) in lines.iter().enumerate() {
+            if i > 0 {
+                let previous = &lines[i - 1];
+                if literal || line.is_empty() {
+                    value.push('\n');
+                } else if !previous.is_empty() {
+                    let more_indented = line.starts_with(char::is_whitespace)
+                        || previous.starts_with(char::is_whitespace);
+                    value.push(if more_indented { '\n' } else { ' ' });
+                }
+            } else if line.is_empty() {
+                value.push('\n');
+            }
+            value += line;
+        }
+        match chomp {
+            Some('-') => {}
+            Some(_) => {
+                value.push('\n');
+                for _ in 0..trailing {
+                    value.push('\n');
+                }
+            }
+            None if !lines.is_empty() => value.push('\n'),
+            None => {}
+        }
+
+        self.push(Token::Str(value, StringStyle::Plain), start..end);
+        self.tokens.extend(comment);
+        self.pos = end;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_core::prelude::{StringStyle, Token};
+
+    use super::tokenize;
+
+    fn tokens(st: &str) -> Vec<Token> {
+        let (tokens, errors) = tokenize(st);
+        assert!(errors.is_empty(), "{:?}", errors);
+        tokens.into_iter().map(|x| x.0).collect()
+    }
+
+    fn plain(st: &str) -> Token {
+        Token::Str(st.to_string(), StringStyle::Plain)
+    }
+
+    #[test]
+    fn block_collections() {
+        let st = "# a comment
+\"@id\": http://example.com/me
+knows:
+  - name: Julian # friend
+    age: 12
+";
+        assert_eq!(
+            tokens(st),
+            vec![
+                Token::Comment(String::from("# a comment")),
+                Token::Str(String::from("@id"), StringStyle::Double),
+                Token::Colon,
+                plain("http://example.com/me"),
+                plain("knows"),
+                Token::Colon,
+                Token::Dash,
+                plain("name"),
+                Token::Colon,
+                plain("Julian"),
+                Token::Comment(String::from("# friend")),
+                plain("age"),
+                Token::Colon,
+                Token::Number(String::from("12")),
+            ]
+        );
+    }
+
+    #[test]
+    fn flow_collections_and_scalars() {
+        let st = r#"{"a":[1.5, true, ~, 'it''s', "tab\tbed"], b: c:d}"#;
+        assert_eq!(
+            tokens(st),
+            vec![
+                Token::CurlOpen,
+                Token::Str(String::from("a"), StringStyle::Double),
+                Token::Colon,
+                Token::SqOpen,
+                Token::Number(String::from("1.5")),
+                Token::Comma,
+                Token::True,
+                Token::Comma,
+                Token::Null,
+                Token::Comma,
+                Token::Str(String::from("it's"), StringStyle::Single),
+                Token::Comma,
+                Token::Str(String::from("tab\tbed"), StringStyle::Double),
+                Token::SqClose,
+                Token::Comma,
+                plain("b"),
+                Token::Colon,
+                plain("c:d"),
+                Token::CurlClose,
+            ]
+        );
+    }
+
+    #[test]
+    fn block_scalars() {
+        let st = "literal: |
+  first
+   second
+
+folded: >-
+  first
+  second
+next: 1
+";
+        assert_eq!(
+            tokens(st),
+            vec![
+                plain("literal"),
+                Token::Colon,
+                plain("first\n second\n"),
+                plain("folded"),
+                Token::Colon,
+                plain("first second"),
+                plain("next"),
+                Token::Colon,
+                Token::Number(String::from("1")),
+            ]
+        );
+    }
+
+    #[test]
+    fn unsupported_features_are_reported() {
+        let (tokens, errors) = tokenize("a: &anchor b\nc: *anchor\n@id: x\n");
+        assert_eq!(errors.len(), 3);
+        assert!(tokens.iter().any(|x| x.value() == &plain("b")));
+    }
+}
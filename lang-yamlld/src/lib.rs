@@ -0,0 +1,112 @@
+#![doc(
+    html_logo_url = "https://ajuvercr.github.io/semantic-web-lsp/assets/icons/favicon.png",
+    html_favicon_url = "https://ajuvercr.github.io/semantic-web-lsp/assets/icons/favicon.ico"
+)]
+//! [YAML-LD](https://json-ld.github.io/yaml-ld/spec/) support, built on top of [`lang_jsonld`].
+//!
+//! Documents are parsed into the same [`Json`] tree as JSON-LD documents, so contexts, triples,
+//! diagnostics and term completion, hover and goto definition are handled by [`lang_jsonld`].
+//! Only a single document with plain YAML is supported, anchors, aliases and tags are reported as
+//! errors.
+use bevy_ecs::prelude::*;
+use chumsky::prelude::Simple;
+use lang_jsonld::{
+    ecs::{setup_completion, setup_goto_definition, setup_hover},
+    lang::parser::Json,
+};
+use lsp_core::{
+    components::DynLang,
+    lang::{Lang, LangHelper},
+    prelude::*,
+    CreateEvent,
+};
+use lsp_types::SemanticTokenType;
+use ropey::Rope;
+
+pub mod ecs;
+pub mod lang;
+
+use crate::ecs::{setup_parse, setup_semantic};
+
+pub fn setup_world(world: &mut World) {
+    let mut semantic_token_dict = world.resource_mut::<SemanticTokensDict>();
+    YamlLd::LEGEND_TYPES.iter().for_each(|lt| {
+        if !semantic_token_dict.contains_key(lt) {
+            let l = semantic_token_dict.0.len();
+            semantic_token_dict.insert(lt.clone(), l);
+        }
+    });
+    world.observe(|trigger: Trigger<CreateEvent>, mut commands: Commands| {
+        let is_yamlld = match &trigger.event().language_id {
+            Some(x) => x == "yamlld",
+            None => false,
+        };
+        if is_yamlld || trigger.event().url.as_str().ends_with(".yamlld") {
+            commands
+                .entity(trigger.entity())
+                .insert(YamlLd)
+                .insert(DynLang(Box::new(YamlLdHelper)));
+        }
+    });
+
+    world.schedule_scope(DiagnosticsLabel, |_, schedule| {
+        use diagnostics::*;
+        schedule.add_systems(publish_diagnostics::<YamlLd>);
+    });
+
+    setup_parse(world);
+    setup_semantic(world);
+    setup_completion::<YamlLd>(world);
+    setup_hover::<YamlLd>(world);
+    setup_goto_definition::<YamlLd>(world);
+}
+
+#[derive(Debug, Component)]
+pub struct YamlLd;
+
+impl Lang for YamlLd {
+    type Token = Token;
+
+    type TokenError = Simple<char>;
+
+    type Element = Json;
+
+    type ElementError = Simple<Token>;
+
+    const PATTERN: Option<&'static str> = None;
+
+    const LANG: &'static str = "yamlld";
+    const CODE_ACTION: bool = false;
+    const HOVER: bool = true;
+
+    const TRIGGERS: &'static [&'static str] = &["@", "\"", "'"];
+    const LEGEND_TYPES: &'static [SemanticTokenType] = &[
+        SemanticTokenType::VARIABLE,
+        SemanticTokenType::STRING,
+        SemanticTokenType::NUMBER,
+        SemanticTokenType::KEYWORD,
+        SemanticTokenType::PROPERTY,
+        SemanticTokenType::ENUM_MEMBER,
+    ];
+}
+
+#[derive(Debug)]
+pub struct YamlLdHelper;
+impl LangHelper for YamlLdHelper {
+    fn get_relevant_text(
+        &self,
+        token: &Spanned<Token>,
+        rope: &Rope,
+    ) -> (String, std::ops::Range<usize>) {
+        let r = token.span();
+        match token.value() {
+            Token::Str(st, StringStyle::Plain) => (st.clone(), r.clone()),
+            Token::Str(st, _) => (st.clone(), r.start + 1..r.end - 1),
+            _ => (self._get_relevant_text(token, rope), r.clone()),
+        }
+    }
+
+    fn keyword(&self) -> &[&'static str] {
+        &[]
+    }
+}
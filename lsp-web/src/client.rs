@@ -13,7 +13,12 @@ use lsp_core::{
     client::{Client, ClientSync, Resp},
     prelude::{Fs, FsTrait},
 };
-use lsp_types::{request::Request, Diagnostic, MessageType, TextEdit, Url, WorkspaceEdit};
+use lsp_types::{
+    notification::Progress,
+    request::{Request, WorkDoneProgressCreate},
+    Diagnostic, MessageType, NumberOrString, ProgressParams, ProgressParamsValue, TextEdit, Url,
+    WorkDoneProgress, WorkDoneProgressCreateParams, WorkspaceEdit,
+};
 use serde::{Deserialize, Serialize};
 use tracing::info;
 
@@ -174,4 +179,23 @@ impl Client for WebClient {
         );
         self.client.publish_diagnostics(uri, diags, version).await;
     }
+    async fn create_progress(&self, token: NumberOrString) -> () {
+        let params = WorkDoneProgressCreateParams { token };
+        if let Err(e) = self
+            .client
+            .send_request::<WorkDoneProgressCreate>(params)
+            .await
+        {
+            tracing::error!("Failed to create progress {:?}", e);
+        }
+    }
+
+    async fn progress(&self, token: NumberOrString, value: WorkDoneProgress) -> () {
+        self.client
+            .send_notification::<Progress>(ProgressParams {
+                token,
+                value: ProgressParamsValue::WorkDone(value),
+            })
+            .await;
+    }
 }
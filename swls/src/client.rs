@@ -6,7 +6,11 @@ use lsp_core::{
     client::{Client, ClientSync, Resp},
     prelude::FsTrait,
 };
-use lsp_types::{Diagnostic, MessageType, Url};
+use lsp_types::{
+    notification::Progress, request::WorkDoneProgressCreate, Diagnostic, MessageType,
+    NumberOrString, ProgressParams, ProgressParamsValue, Url, WorkDoneProgress,
+    WorkDoneProgressCreateParams,
+};
 use tokio::fs::{self, read_to_string, write};
 use tracing::info;
 
@@ -60,6 +64,22 @@ impl FsTrait for BinFs {
         }
         write(fp, content.as_bytes()).await.ok()
     }
+
+    async fn read_dir(&self, url: &lsp_types::Url) -> Option<Vec<lsp_types::Url>> {
+        let fp = url.to_file_path().ok()?;
+        let mut entries = fs::read_dir(fp).await.ok()?;
+        let mut out = Vec::new();
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let is_dir = entry.file_type().await.map(|x| x.is_dir()).unwrap_or(false);
+            let url = if is_dir {
+                lsp_types::Url::from_directory_path(entry.path())
+            } else {
+                lsp_types::Url::from_file_path(entry.path())
+            };
+            out.extend(url.ok());
+        }
+        Some(out)
+    }
 }
 
 #[derive(Resource, Clone)]
@@ -169,4 +189,23 @@ impl Client for TowerClient {
     ) -> () {
         self.client.publish_diagnostics(uri, diags, version).await;
     }
+    async fn create_progress(&self, token: NumberOrString) -> () {
+        let params = WorkDoneProgressCreateParams { token };
+        if let Err(e) = self
+            .client
+            .send_request::<WorkDoneProgressCreate>(params)
+            .await
+        {
+            tracing::error!("Failed to create progress {:?}", e);
+        }
+    }
+
+    async fn progress(&self, token: NumberOrString, value: WorkDoneProgress) -> () {
+        self.client
+            .send_notification::<Progress>(ProgressParams {
+                token,
+                value: ProgressParamsValue::WorkDone(value),
+            })
+            .await;
+    }
 }
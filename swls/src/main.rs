@@ -32,6 +32,9 @@ fn setup_world<C: Client + ClientSync + Resource + Clone>(
     lang_turtle::setup_world(&mut world);
     lang_jsonld::setup_world(&mut world);
     lang_sparql::setup_world(&mut world);
+    lang_trig::setup_world(&mut world);
+    lang_ntriples::setup_world(&mut world);
+    lang_yamlld::setup_world(&mut world);
 
     let (tx, mut rx) = unbounded();
     let sender = CommandSender(tx);
@@ -23,7 +23,7 @@ use bevy_ecs::prelude::*;
 use futures::{
     channel::mpsc::{unbounded, UnboundedReceiver},
     lock::Mutex,
-    FutureExt as _,
+    FutureExt as _, StreamExt as _,
 };
 use lsp_core::{
     client::{Client, ClientSync, Resp},
@@ -36,7 +36,7 @@ use lsp_core::{
     systems::{handle_tasks, spawn_or_insert},
     Startup,
 };
-use lsp_types::{Diagnostic, MessageType, TextDocumentItem, Url};
+use lsp_types::{Diagnostic, MessageType, NumberOrString, TextDocumentItem, Url, WorkDoneProgress};
 
 #[derive(Resource, Debug, Clone)]
 pub struct TestClient {
@@ -97,6 +97,10 @@ impl Client for TestClient {
         let mut lock = self.diagnostics.lock().await;
         lock.push((uri, diags));
     }
+
+    async fn create_progress(&self, _token: NumberOrString) -> () {}
+
+    async fn progress(&self, _token: NumberOrString, _value: WorkDoneProgress) -> () {}
 }
 
 struct Sendable<T>(pub T);
@@ -192,6 +196,22 @@ impl FsTrait for TestFs {
         }
         fs::write(fp, content.as_bytes()).await.ok()
     }
+
+    async fn read_dir(&self, url: &lsp_types::Url) -> Option<Vec<lsp_types::Url>> {
+        let fp = url.to_file_path().ok()?;
+        let mut entries = fs::read_dir(fp).await.ok()?;
+        let mut out = Vec::new();
+        while let Some(Ok(entry)) = entries.next().await {
+            let is_dir = entry.file_type().await.map(|x| x.is_dir()).unwrap_or(false);
+            let url = if is_dir {
+                lsp_types::Url::from_directory_path(entry.path())
+            } else {
+                lsp_types::Url::from_file_path(entry.path())
+            };
+            out.extend(url.ok());
+        }
+        Some(out)
+    }
 }
 
 pub fn setup_world(